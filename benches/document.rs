@@ -0,0 +1,60 @@
+// regenerate a comparable document by hand with
+// `uat_editor gen-stress --steps 5000 -o big.json`, the benches build the same one in memory
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use uat_editor::bench;
+
+const STEPS: usize = 5000;
+
+fn compile(c: &mut Criterion) {
+    let items = bench::generate(STEPS, bench::DEFAULT_SEED);
+    c.bench_function("gen_html", |b| {
+        b.iter(|| bench::gen_html(black_box(&items)).unwrap())
+    });
+}
+
+fn embedding(c: &mut Criterion) {
+    let items = bench::generate(STEPS, bench::DEFAULT_SEED);
+    let serialized = bench::serialize_items(&items).unwrap();
+    println!("embedding for {} steps: {} bytes", STEPS, serialized.len());
+    c.bench_function("serialize_items", |b| {
+        b.iter(|| bench::serialize_items(black_box(&items)).unwrap())
+    });
+    c.bench_function("deserialize_items", |b| {
+        b.iter(|| bench::deserialize_items(black_box(&serialized)).unwrap())
+    });
+}
+
+fn parse_markdown(c: &mut Criterion) {
+    let markdown = bench::markdown(&bench::generate(STEPS, bench::DEFAULT_SEED));
+    c.bench_function("parse_markdown", |b| {
+        b.iter(|| {
+            for step in &markdown {
+                bench::parse_markdown(black_box(step)).unwrap();
+            }
+        })
+    });
+}
+
+const IMPORT_STEPS: usize = 2000;
+
+fn bulk_import(c: &mut Criterion) {
+    let document = bench::document(&bench::generate(IMPORT_STEPS, bench::DEFAULT_SEED));
+    c.bench_function("parse_document", |b| {
+        b.iter(|| bench::parse_document(black_box(&document)).unwrap())
+    });
+}
+
+fn rows(c: &mut Criterion) {
+    let rows = bench::Rows::new(bench::generate(STEPS, bench::DEFAULT_SEED)).unwrap();
+    c.bench_function("build_rows", |b| b.iter(|| black_box(rows.build())));
+}
+
+criterion_group!(
+    benches,
+    compile,
+    embedding,
+    parse_markdown,
+    bulk_import,
+    rows
+);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+#!/usr/bin/env python3
+"""Example uat_editor exporter: turns the document into a markdown checklist.
+
+uat_editor writes the document to stdin as json, {"version": 2, "items": [...]}, and
+saves whatever is printed to stdout. Anything on stderr ends up in :messages, a
+non-zero exit is reported as a failure. Enable it in ~/.config/uat_editor/config.toml:
+
+    [exporters.checklist]
+    command = "python3 /path/to/examples/exporters/checklist.py"
+    # clipboard = true  copies the output instead of asking for a path
+
+then run :export checklist
+"""
+import json
+import sys
+
+
+def first_line(text):
+    for line in text.splitlines():
+        if line.strip():
+            return line.strip()
+    return ""
+
+
+def main():
+    document = json.load(sys.stdin)
+    if document.get("version") != 2:
+        print("unsupported document version %r" % document.get("version"), file=sys.stderr)
+        return 1
+    out = []
+    for item in document["items"]:
+        if item.get("is_new_section"):
+            out.append("\n## %s\n" % (first_line(item["instructions"]) or "Section"))
+        elif item.get("is_stepless_comment"):
+            out.append("> %s" % first_line(item["instructions"]))
+        else:
+            done = "x" if item.get("status") == "pass" else " "
+            line = "- [%s] %s" % (done, first_line(item["instructions"]))
+            expected = first_line(item.get("expected_results", ""))
+            if expected:
+                line += " (expect: %s)" % expected
+            out.append(line)
+    print("\n".join(out).strip())
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
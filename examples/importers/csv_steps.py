@@ -0,0 +1,41 @@
+#!/usr/bin/env python3
+"""Example uat_editor importer: reads a csv with action,expected columns.
+
+uat_editor runs the command with the file on stdin (or substitutes {path}) and
+expects the document json on stdout, {"version": 2, "items": [...]}. Enable it in
+~/.config/uat_editor/config.toml:
+
+    [importers.csv]
+    command = "python3 /path/to/examples/importers/csv_steps.py"
+
+then run :import csv steps.csv
+"""
+import csv
+import json
+import sys
+
+
+def step(instructions, expected):
+    return {
+        "is_stepless_comment": False,
+        "is_new_section": False,
+        "instructions": instructions,
+        "test_data": "",
+        "expected_results": expected,
+        "ac": "",
+    }
+
+
+def main():
+    reader = csv.DictReader(sys.stdin)
+    missing = {"action", "expected"} - set(reader.fieldnames or [])
+    if missing:
+        print("missing columns: %s" % ", ".join(sorted(missing)), file=sys.stderr)
+        return 1
+    items = [step(row["action"], row["expected"]) for row in reader]
+    json.dump({"version": 2, "items": items}, sys.stdout)
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
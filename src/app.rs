@@ -1,43 +1,116 @@
-use crate::{colors::Colors, config::Config, err_msg::WithErrMsg, test_step::TestStep};
-use arboard::Clipboard;
-use base64::prelude::*;
+use self::{history::History, overlay::Overlay};
+use crate::{
+    backup,
+    colors::{self, Colors},
+    config::{self, Config},
+    edit_session::{self, EditSession, Target},
+    embedding,
+    err_msg::WithErrMsg,
+    export::html::{self, HtmlOptions},
+    filter::Filter,
+    locale::Key,
+    query::Field,
+    scaffold,
+    signals::Signals,
+    test_step::{self, StepKind, TestStep},
+    text_input::TextInput,
+    timestamp, validate, words,
+};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, HashMap, VecDeque},
     fs::File,
-    io::{Read, Write},
+    io::Write,
+    path::Path,
     process::{Command, Stdio},
+    time::Duration,
 };
 
-use pulldown_cmark::{Options, Parser};
 use ratatui::{
     DefaultTerminal, Frame,
     crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     layout::{Constraint, Layout, Margin, Rect},
     style::Stylize,
-    text::Text,
+    text::{Line, Span, Text},
     widgets::{
         Cell, HighlightSpacing, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
-        Table, TableState,
+        Table, TableState, Wrap,
     },
 };
 
+mod bench;
+mod buffer;
+mod builtin;
+mod cell_edit;
+mod clipboard;
+mod columns;
+mod command;
+mod document;
+mod empty;
+mod execution;
+mod exit;
+mod export;
+mod exporters;
+mod footer;
+mod gdiff;
+mod highlight;
+mod history;
+mod hooks;
+mod import;
+mod jumps;
+mod keyboard;
+mod marks;
+mod messages;
+mod minimap;
+mod normalize;
+mod outline;
+mod overlay;
+mod paste;
+mod peek;
+mod pipe;
+mod project;
+mod readonly;
+mod reload;
+mod reorder;
+mod reverse;
+mod review;
+mod search;
+mod serve;
+mod session;
+mod setfield;
+mod snippets;
+mod sort;
+mod split;
+mod stats;
+mod title;
+mod transform;
+mod trash;
+mod viewport;
+mod windows;
+mod wizard;
+
 const ITEM_HEIGHT: usize = 4;
-const MDEMBEDDING: &'static str = "MDEMBEDDING";
+const SIGNAL_POLL: Duration = Duration::from_millis(250);
+// keys that wait for the next key before doing anything: g (gg, gJ, gr, gs, gp, go),
+// m{a-z}, '{a-z}, ]], [[, zz/zt/zb, is and r0-r3
+const PENDING_KEYS: &[char] = &['g', 'm', '\'', ']', '[', 'z', 'i', 'r'];
 
-enum RowType {
-    SectionStart,
-    SectionMid,
-    SectionEnd,
-    Comment,
-}
-enum ColType {
-    SectionStart,
-    SectionMid,
-    SectionEnd,
-}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Window {
-    UAT,
+    Uat,
     Template,
+    Snippets,
+}
+
+// how the tui was started, from the command line
+#[derive(Debug, Default, Clone)]
+pub struct AppOptions {
+    pub readonly: bool,
+    pub no_wizard: bool,
+    // where to write the json summary on exit
+    pub exit_summary: Option<String>,
+    pub no_color: bool,
 }
 
 enum InsertDirection {
@@ -45,6 +118,24 @@ enum InsertDirection {
     Down,
 }
 
+// inserts `new` above or below the selected row (appending when nothing is selected or
+// the list is empty) and selects the first inserted row, returning its index
+fn insert_at_selection<T>(
+    items: &mut Vec<T>,
+    state: &mut TableState,
+    direction: InsertDirection,
+    new: impl IntoIterator<Item = T>,
+) -> usize {
+    let at = match (state.selected().filter(|_| !items.is_empty()), direction) {
+        (Some(idx), InsertDirection::Up) => idx.min(items.len()),
+        (Some(idx), InsertDirection::Down) => (idx + 1).min(items.len()),
+        (None, _) => items.len(),
+    };
+    items.splice(at..at, new);
+    state.select(Some(at));
+    at
+}
+
 enum MsgState {
     Default,
     Compile,
@@ -71,248 +162,287 @@ impl MsgState {
 enum InputMode {
     Normal,
     Prefix(String),
+    Command(TextInput),
 }
 pub struct App {
-    clipboard: Clipboard,
+    // opened on first use, so the app starts (and rows can be built) without a display
+    clipboard: Option<Box<dyn clipboard::SystemClipboard>>,
     config: Config,
     template_list: Vec<TestStep>,
+    snippets: snippets::SnippetList,
+    picker: Option<snippets::Picker>,
+    export_picker: Option<exporters::ExportPicker>,
+    column_mapping: Option<import::ColumnMapping>,
+    // what ctrl+shift can be told apart on, known once the terminal is up
+    modifiers: keyboard::Modifiers,
     window: Window,
     msg_state: MsgState,
     state: TableState,
     items: Vec<TestStep>,
-    col_constraints: (u16, u16, u16, u16), // order is (name, instructions, expected_results)
     colors: Colors,
     scroll_state: ScrollbarState,
-    internal_clipboard: Option<TestStep>,
+    // what y, d and gJ last took, pasted back together
+    internal_clipboard: Vec<TestStep>,
     input_mode: InputMode,
+    filter: Option<Filter>,
+    search: Option<Filter>,
+    show_detail: bool,
+    view_state: TableState,
+    // where the selection was in each window that isn't showing
+    positions: HashMap<Window, windows::Position>,
+    // how many rows fit in the table when it was last drawn
+    table_rows: usize,
+    overlay: Option<Overlay>,
+    history: History,
+    sorted_by: Option<sort::ActiveSort>,
+    cell_edit: Option<cell_edit::CellEdit>,
+    trash: trash::Trash,
+    // the external editor round trip in progress, only one at a time
+    edit_session: Option<EditSession>,
+    // an editor further down the chain that worked after the configured one was missing
+    editor_fallback: Option<String>,
+    alternate: Option<buffer::Buffer>,
+    jumps: jumps::JumpList,
+    marks: BTreeMap<char, u64>,
+    visual_anchor: Option<usize>,
+    reorder: Option<reorder::Reorder>,
+    signals: Signals,
+    readonly: bool,
+    // where the document was last read from or written to, and whether it has changed since
+    document: Option<String>,
+    dirty: bool,
+    last_export: Option<exit::LastExport>,
+    // what `$$` runs again, by name
+    last_exporter: Option<String>,
+    // what --exit-summary reports besides the table itself
+    exit_summary_path: Option<String>,
+    quitting: bool,
+    compiled: bool,
+    backup_written: bool,
+    // the encrypted backup didn't load, so it isn't written over this session
+    backup_locked: bool,
+    // the items as loaded, kept only while read-only mode has been on since startup
+    loaded_readonly: Option<Vec<TestStep>>,
+    messages: messages::MessageLog,
+    hooks: hooks::Hooks,
+    server: Option<crate::serve::Server>,
+    config_error: Option<String>,
+    needs_setup: bool,
 }
 
-impl App {
-    pub fn new() -> Result<Self, String> {
-        let clipboard = Clipboard::new().with_err_msg(&"Failed to grab system clipboard")?;
+// how many lines of a failed editor's stderr make it into the message
+const EDITOR_STDERR_LINES: usize = 3;
+
+// a missing editor can be swapped for the next one in the chain, other failures can't
+#[derive(Debug, PartialEq)]
+enum EditorError {
+    NotFound(String),
+    Failed(String),
+}
+
+impl From<EditorError> for String {
+    fn from(err: EditorError) -> String {
+        match err {
+            EditorError::NotFound(msg) | EditorError::Failed(msg) => msg,
+        }
+    }
+}
+
+// blocks until the editor exits, failures name the exact command that was run
+fn run_editor(editor: &str, path: &Path, capture_stderr: bool) -> Result<(), EditorError> {
+    let cmdline = format!("{} {}", editor, path.display());
+    let child = Command::new(editor)
+        .arg(path)
+        .stdout(Stdio::inherit())
+        .stdin(Stdio::inherit())
+        .stderr(if capture_stderr {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .spawn()
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => EditorError::NotFound(format!(
+                "Editor not found: `{}` ({}), check PATH or the editor in the config",
+                cmdline, err
+            )),
+            _ => EditorError::Failed(format!("Failed to start editor `{}`: {}", cmdline, err)),
+        })?;
+    let output = child.wait_with_output().map_err(|err| {
+        EditorError::Failed(format!("Failed to wait on editor `{}`: {}", cmdline, err))
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(EDITOR_STDERR_LINES)
+        .collect();
+    let mut msg = format!("Editor `{}` failed ({})", cmdline, output.status);
+    if !lines.is_empty() {
+        msg.push_str(&format!(": {}", lines.join(" | ")));
+    }
+    Err(EditorError::Failed(msg))
+}
+
+// asks on the plain terminal, the tui has to be restored first
+fn read_answer(msg: &str) -> Result<String, String> {
+    println!();
+    print!("{}: ", msg);
+    let _ = std::io::stdout().flush();
+
+    let mut input = String::new();
+
+    std::io::stdin()
+        .read_line(&mut input)
+        .with_err_msg(&"Failed to read user input from prompt")?;
 
-        let config = Config::load_config()?;
+    input.pop();
+    Ok(input)
+}
+
+impl App {
+    pub fn new(signals: Signals, options: AppOptions) -> Result<Self, String> {
+        // a broken config opens read-only on defaults rather than risk saving over it
+        let (config, config_error) = match Config::load_config() {
+            Ok(config) => (config, None),
+            Err(err) => (Config::default(), Some(err)),
+        };
+        // a bad theme shouldn't keep the editor from starting, it's reported instead
+        let plain = colors::no_color(options.no_color);
+        let (colors, msg_state) = match Colors::load(&config.theme, plain) {
+            Ok(colors) => (colors, MsgState::Default),
+            Err(err) if plain => (Colors::monochrome(), MsgState::DynamicMsg(err)),
+            Err(err) => (Colors::new(), MsgState::DynamicMsg(err)),
+        };
+        let msg_state = match &config_error {
+            Some(err) => MsgState::DynamicMsg(err.clone()),
+            None => msg_state,
+        };
+        let readonly = options.readonly || config_error.is_some();
+        let needs_setup = config_error.is_none() && config.editor().trim().is_empty();
+        if needs_setup && options.no_wizard {
+            return Err(format!(
+                "No editor found: set $VISUAL or $EDITOR, pass --editor <cmd> or add editor to {}",
+                Config::path().unwrap_or_default()
+            ));
+        }
         let data_vec = Vec::new();
 
-        let idx = if data_vec.len() > 0 {
+        let idx = if !data_vec.is_empty() {
             data_vec.len() - 1
         } else {
             0
         };
 
-        let template_list = config
-            .templates
-            .keys()
-            .map(|name| {
-                let mut data = TestStep::new(false, false);
-                data.instructions = name.clone();
-                data
-            })
-            .collect();
+        let template_list = reload::template_list(&config);
+        let snippets = snippets::snippet_list(&config);
 
         Ok(Self {
-            clipboard,
+            clipboard: None,
             template_list,
+            snippets,
+            picker: None,
+            export_picker: None,
+            column_mapping: None,
+            modifiers: keyboard::Modifiers::Ambiguous,
             config,
-            window: Window::UAT,
-            msg_state: MsgState::Default,
+            window: Window::Uat,
+            msg_state,
             state: TableState::default().with_selected(0),
-            col_constraints: (4, 20, 20, 10),
             scroll_state: ScrollbarState::new(idx * ITEM_HEIGHT),
-            colors: Colors::new(),
+            colors,
             items: data_vec,
-            internal_clipboard: None,
+            internal_clipboard: Vec::new(),
             input_mode: InputMode::Normal,
+            filter: None,
+            search: None,
+            show_detail: false,
+            view_state: TableState::default(),
+            positions: HashMap::new(),
+            table_rows: 1,
+            overlay: None,
+            history: History::default(),
+            sorted_by: None,
+            cell_edit: None,
+            trash: trash::Trash::default(),
+            edit_session: None,
+            editor_fallback: None,
+            alternate: None,
+            jumps: jumps::JumpList::default(),
+            marks: BTreeMap::new(),
+            visual_anchor: None,
+            reorder: None,
+            signals,
+            readonly,
+            document: None,
+            dirty: false,
+            last_export: None,
+            last_exporter: None,
+            exit_summary_path: options.exit_summary,
+            quitting: false,
+            compiled: false,
+            backup_written: false,
+            backup_locked: false,
+            loaded_readonly: None,
+            messages: messages::MessageLog::default(),
+            hooks: hooks::Hooks::default(),
+            server: None,
+            config_error,
+            needs_setup,
         })
     }
 
-    fn serialize_items(&self) -> Result<String, String> {
-        let items_json =
-            serde_json::to_string(&self.items).with_err_msg(&"Failed to serialize items")?;
-        Ok(BASE64_STANDARD.encode(items_json))
-    }
-
-    fn deserialize_items(&mut self, serialized_items: &str) -> Result<(), String> {
-        let err_msg = format!(
-            "Failed to deserialize base64 items, found: {}",
-            serialized_items
-        );
-
-        let items_json = BASE64_STANDARD
-            .decode(serialized_items)
-            .with_err_msg(&err_msg)?;
-
-        let items_json = String::from_utf8(items_json)
-            .with_err_msg(&"Failed to convert byte string to String")?;
-
-        self.items = serde_json::from_str(&items_json)
-            .with_err_msg(&"Failed to convert json to items data")?;
-
+    fn load_items(&mut self, items: Vec<TestStep>) {
+        self.push_undo();
+        self.items = items;
+        self.sorted_by = None;
+        self.dirty = false;
         self.msg_state = MsgState::Loaded;
-        Ok(())
-    }
-
-    fn build_td(class: &str, val: &str, row_type: &RowType, col_type: &ColType) -> String {
-        let mut added_borders = match col_type {
-            &ColType::SectionStart => String::from("border-left: 2px solid black;"),
-            &ColType::SectionMid => String::new(),
-            &ColType::SectionEnd => String::from("border-right: 2px solid black;"),
-        };
-        match row_type {
-            &RowType::SectionStart => {
-                added_borders.push_str("border-top: 2px solid black;");
-            }
-            &RowType::SectionEnd => {
-                added_borders.push_str("border-bottom: 2px solid black;");
-            }
-            &RowType::Comment => {
-                added_borders
-                    .push_str("border-top: 2px solid black; border-bottom: 2px solid black;");
-            }
-            _ => {}
-        }
-        format!(
-            "<td class=\"{}\" style=\"border: 1px solid black;{}\">{}</td>",
-            class, added_borders, val
-        )
-    }
 
-    fn parse_td(
-        options: Options,
-        class: &str,
-        s: String,
-        row_type: &RowType,
-        col_type: &ColType,
-    ) -> String {
-        let parser = Parser::new_ext(&s, options);
-        let mut html_output = String::new();
-        pulldown_cmark::html::push_html(&mut html_output, parser);
-        Self::build_td(class, html_output.as_str(), row_type, col_type)
-    }
-
-    fn last_was_comment(&self, current_idx: usize) -> bool {
-        if current_idx == 0 {
-            false
-        } else {
-            self.items
-                .get(current_idx - 1)
-                .map(|i| i.is_stepless_comment)
-                .unwrap_or(false)
+        let hidden_ac = self
+            .items
+            .iter()
+            .filter(|i| !i.ac.trim().is_empty())
+            .count();
+        if !self.config.use_ac && hidden_ac > 0 {
+            self.msg_state = MsgState::DynamicMsg(format!(
+                "Loaded, {} step(s) have AC content but the AC column is hidden (use_ac = false)",
+                hidden_ac
+            ));
         }
     }
 
-    fn is_end_of_section(&self, current_idx: usize) -> bool {
-        self.items
-            .get(current_idx + 1)
-            .map(|i| i.is_new_section)
-            .unwrap_or(true)
-    }
-
     fn gen_html(&self) -> Result<String, String> {
-        let mut table = String::new();
-
-        let options = Options::empty();
-        let mut section_idx = 1;
-        let mut step_idx = 1;
-        for (idx, item) in self.items.iter().enumerate() {
-            let row_type = if item.is_new_section {
-                if !self.last_was_comment(idx) && idx != 0 && idx != 1 {
-                    section_idx += 1;
-                    step_idx = 1;
-                }
-                RowType::SectionStart
-            } else if item.is_stepless_comment {
-                if self.is_end_of_section(idx) && idx != 0 && idx != 1 {
-                    section_idx += 1;
-                    step_idx = 1;
-                }
-                RowType::Comment
-            } else if idx == 0 {
-                RowType::SectionStart
-            } else if idx == 1 {
-                if self.last_was_comment(idx) {
-                    RowType::SectionStart
-                } else {
-                    RowType::SectionMid
-                }
-            } else {
-                if self.is_end_of_section(idx) {
-                    RowType::SectionEnd
-                } else {
-                    RowType::SectionMid
-                }
-            };
-            let i = if item.is_stepless_comment && idx == 0 {
-                String::new()
-            } else if item.is_stepless_comment {
-                format!("{}", section_idx)
-            } else {
-                format!("{}.{}", section_idx, step_idx)
-            };
-            table.push_str("<tr>");
-            table.push_str(&Self::build_td(
-                "step-td",
-                i.as_str(),
-                &row_type,
-                &ColType::SectionStart,
-            ));
-            table.push_str(&Self::build_td(
-                "pass-td",
-                "",
-                &row_type,
-                &ColType::SectionMid,
-            ));
-            table.push_str(&Self::parse_td(
-                options,
-                "action-td",
-                item.instructions(),
-                &row_type,
-                &ColType::SectionMid,
-            ));
-            table.push_str(&Self::parse_td(
-                options,
-                "expected-result-td",
-                item.expected_results(),
-                &row_type,
-                &ColType::SectionMid,
-            ));
-            table.push_str(&Self::build_td(
-                "comments-td",
-                "",
-                &row_type,
-                &ColType::SectionMid,
-            ));
-            table.push_str(&Self::parse_td(
-                options,
-                "ac-td",
-                item.ac(),
-                &row_type,
-                &ColType::SectionEnd,
-            ));
-            table.push_str("</tr>");
-            if !item.is_stepless_comment {
-                step_idx += 1;
-            }
-        }
-
-        Ok(format!(
-            include_str!("./template.html"),
-            include_str!("./style.css"),
-            table,
-            MDEMBEDDING,
-            self.serialize_items()?
-        ))
+        html::gen_html(&self.items, &HtmlOptions::from_config(&self.config))
     }
 
     fn length_constraint(&self) -> usize {
         match self.window {
-            Window::UAT => self.items.len(),
+            Window::Uat => self.items.len(),
             Window::Template => self.template_list.len(),
+            Window::Snippets => self.snippets.steps.len(),
+        }
+    }
+
+    // indices of the rows currently shown, honoring the active filter
+    fn visible_indices(&self) -> Vec<usize> {
+        match (&self.window, &self.filter) {
+            (Window::Uat, Some(filter)) => filter.matching(&self.items),
+            _ => (0..self.length_constraint()).collect(),
         }
     }
 
     fn delta_selection(&self, i: usize, delta: isize) -> usize {
-        (i as isize + delta).rem_euclid(self.length_constraint() as isize) as usize
+        let visible = self.visible_indices();
+        if visible.is_empty() {
+            return i;
+        }
+        let pos = visible.iter().position(|v| *v >= i).unwrap_or(0) as isize;
+        visible[(pos + delta).rem_euclid(visible.len() as isize) as usize]
     }
 
     fn delta_row_impl(&mut self, delta: isize) {
@@ -325,6 +455,37 @@ impl App {
         self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
     }
 
+    // the visual selection if one is active, otherwise just the selected row
+    fn selected_range(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        let selected = self.state.selected()?;
+        if selected >= self.items.len() {
+            return None;
+        }
+        Some(match self.visual_anchor {
+            Some(anchor) => {
+                let anchor = anchor.min(self.items.len() - 1);
+                anchor.min(selected)..=anchor.max(selected)
+            }
+            None => selected..=selected,
+        })
+    }
+
+    // moves the selection onto a visible row if the filter hid it
+    fn clamp_to_visible(&mut self) {
+        let visible = self.visible_indices();
+        let selected = self.state.selected().unwrap_or(0);
+        if !visible.contains(&selected) {
+            let i = visible
+                .iter()
+                .find(|v| **v >= selected)
+                .or(visible.last())
+                .copied()
+                .unwrap_or(0);
+            self.state.select(Some(i));
+            self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        }
+    }
+
     pub fn next_row(&mut self) {
         self.delta_row_impl(1);
     }
@@ -333,34 +494,74 @@ impl App {
         self.delta_row_impl(-1);
     }
 
+    // None when the file was closed without changes, there's nothing to parse then
     fn open_editor(
-        editor: &str,
+        &mut self,
+        target: Target,
         md: String,
         terminal: &mut DefaultTerminal,
-    ) -> Result<String, String> {
-        let mut file = File::create("/tmp/uat_editor.md")
-            .with_err_msg(&"Failed to open /tmp/uat_editor.md for editing")?;
-
-        file.write_all(md.as_bytes())
-            .with_err_msg(&"Failed to populate /tmp/uat_editor.md")?;
-
-        ratatui::restore();
-
-        let mut child = Command::new(editor)
-            .arg("/tmp/uat_editor.md")
-            .stdout(Stdio::inherit())
-            .stdin(Stdio::inherit())
-            .spawn()
-            .with_err_msg(&"Failed to spawn child editor process")?;
-
-        child
-            .wait()
-            .with_err_msg(&"Failed to wait on child editor process")?;
-
-        *terminal = ratatui::init();
+    ) -> Result<Option<String>, String> {
+        if let Some(session) = &self.edit_session {
+            return Err(format!(
+                "Already editing {} in {}, finish that first",
+                session.target().describe(),
+                session.path().display()
+            ));
+        }
+        let session = EditSession::create(&std::env::temp_dir(), target, &md)?;
+        let path = session.path().to_path_buf();
+        self.edit_session = Some(session);
+
+        self.suspend_terminal();
+        let edited = self.run_editor_chain(&path);
+        *terminal = self.resume_terminal();
+
+        // dropping the session removes its file
+        let session = self.edit_session.take();
+        edited?;
+        match session {
+            Some(session) => session.read_back(),
+            None => Err("Edit session ended early".to_string()),
+        }
+    }
 
-        std::fs::read_to_string("/tmp/uat_editor.md")
-            .with_err_msg(&"Failed to grab edits to uat_editor.md")
+    // runs the configured editor, offering the next one in the chain whenever the one
+    // tried isn't installed. one picked that way is used first for the rest of the session
+    fn run_editor_chain(&mut self, path: &Path) -> Result<(), String> {
+        let mut editors = self.config.editors();
+        if let Some(fallback) = &self.editor_fallback
+            && let Some(pos) = editors.iter().position(|(editor, _)| editor == fallback)
+        {
+            let picked = editors.remove(pos);
+            editors.insert(0, picked);
+        }
+        let mut missing = format!(
+            "No editor configured: set $VISUAL or $EDITOR or add editor to {}",
+            Config::path().unwrap_or_default()
+        );
+        for (n, (editor, source)) in editors.iter().enumerate() {
+            if n > 0 {
+                let answer = read_answer(&format!(
+                    "{}\nTry {} ({}) instead? [Y/n]",
+                    missing,
+                    editor,
+                    source.describe()
+                ))?;
+                if !matches!(answer.trim(), "" | "y" | "Y" | "yes") {
+                    break;
+                }
+            }
+            match run_editor(editor, path, self.config.capture_editor_stderr) {
+                Err(EditorError::NotFound(msg)) => missing = msg,
+                edited => {
+                    if n > 0 && edited.is_ok() {
+                        self.editor_fallback = Some(editor.clone());
+                    }
+                    return edited.map_err(String::from);
+                }
+            }
+        }
+        Err(missing)
     }
 
     fn grab_selection_as_mut(&mut self) -> Result<(usize, &mut TestStep), String> {
@@ -376,32 +577,52 @@ impl App {
         Ok((idx, data))
     }
 
-    fn grab_selection_as_markdown(&mut self) -> Result<(&mut TestStep, String), String> {
-        let (_, data) = self.grab_selection_as_mut()?;
-        let md = data.gen_markdown();
-        Ok((data, md))
-    }
-
-    fn edit_existing(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
-        let editor = self.config.editor.clone();
-        let (item, item_md) = self.grab_selection_as_markdown()?;
-        let content = App::open_editor(editor.as_str(), item_md, terminal)?;
-        let new_data = TestStep::parse_markdown(&content)?;
+    fn edit_existing(&mut self, terminal: &mut DefaultTerminal) -> Result<MsgState, String> {
+        let author = self.config.author();
+        let snapshot = self.items.clone();
+        let (use_ac, locale) = (self.config.use_ac, self.config.locale);
+        let (idx, item) = self.grab_selection_as_mut()?;
+        let item_md = item.gen_markdown_in(use_ac, locale);
+        let Some(content) = self.open_editor(Target::Step(idx), item_md, terminal)? else {
+            return Ok(MsgState::DynamicMsg("No changes".to_string()));
+        };
+        let mut new_data = TestStep::parse_markdown_with(&content, &self.config.heading_aliases)?;
+        let item = self
+            .items
+            .get_mut(idx)
+            .with_err_msg(&"Items vec did not contain index")?;
+        new_data.inherit_metadata(item, author);
         *item = new_data;
-        Ok(())
+        self.push_undo_snapshot(snapshot);
+        Ok(MsgState::Default)
     }
 
-    fn compile_to_clipboard(&mut self) -> Result<MsgState, String> {
-        self.clipboard
-            .set_text(self.gen_html()?)
-            .with_err_msg(&"Failed to set clipboard content")?;
-
-        Ok(MsgState::Compile)
+    fn compile_to_clipboard(&mut self, force: bool) -> Result<MsgState, String> {
+        self.check_exportable(force)?;
+        self.normalize_before_export();
+        let html = self.gen_html()?;
+        let read_back = self.copy_compiled_html(&html)?;
+        self.dirty = false;
+        self.compiled = true;
+        self.start_post_compile(html);
+
+        if let Some(warning) = read_back {
+            return Ok(MsgState::DynamicMsg(warning));
+        }
+        let issues = validate::validate(&self.items, &self.config);
+        if issues.is_empty() {
+            Ok(MsgState::Compile)
+        } else {
+            Ok(MsgState::DynamicMsg(format!(
+                "Compiled with {} warning(s), see :check",
+                issues.len()
+            )))
+        }
     }
 
     fn yank(&mut self) -> Result<MsgState, String> {
         let (_, item) = self.grab_selection_as_mut()?;
-        self.internal_clipboard = Some(item.clone());
+        self.internal_clipboard = vec![item.clone()];
         Ok(MsgState::Yanked)
     }
 
@@ -410,110 +631,257 @@ impl App {
             .state
             .selected()
             .with_err_msg(&"No row selected to delete")?;
-        self.internal_clipboard = Some(self.items.remove(idx));
+        if idx >= self.items.len() {
+            return Err("Items vec did not contain index".to_string());
+        }
+        self.push_undo();
+        let removed = self.items.remove(idx);
+        self.trash(&trash::describe(&removed), vec![removed.clone()]);
+        self.internal_clipboard = vec![removed];
         Ok(())
     }
 
     fn paste(&mut self, direction: InsertDirection) -> Result<(), String> {
-        let idx = self
-            .state
-            .selected()
-            .with_err_msg(&"No row selected to paste")?;
-
-        let item = self
-            .internal_clipboard
-            .as_ref()
-            .with_err_msg(&"No step in internal register")?
-            .clone();
-
-        match direction {
-            InsertDirection::Up => {
-                self.items.insert(idx, item);
-            }
-            InsertDirection::Down => {
-                self.items.insert(idx + 1, item);
+        if self.internal_clipboard.is_empty() {
+            return Err("No step in internal register".to_string());
+        }
+        let mut items = self.internal_clipboard.clone();
+
+        // a moved step keeps its id, a copy gets a fresh one
+        let mut next_id = TestStep::next_id(&self.items).max(TestStep::next_id(&items));
+        for item in &mut items {
+            if item.id == 0 || self.items.iter().any(|i| i.id == item.id) {
+                item.id = next_id;
+                next_id += 1;
             }
         }
 
+        self.push_undo();
+        self.insert_items(direction, items);
+
         Ok(())
     }
 
+    // every paste and insert goes through here so the selection (and the scrollbar)
+    // always lands on the first new row, whichever direction it went in
+    fn insert_items(&mut self, direction: InsertDirection, items: Vec<TestStep>) {
+        let at = insert_at_selection(&mut self.items, &mut self.state, direction, items);
+        self.scroll_state = self.scroll_state.position(at * ITEM_HEIGHT);
+    }
+
     fn insert_step(
         &mut self,
         terminal: &mut DefaultTerminal,
         direction: InsertDirection,
-        is_new_section: bool,
-        is_stepless_comment: bool,
-    ) -> Result<(), String> {
-        let data = TestStep::new(is_stepless_comment, is_new_section);
-        let item_md = data.gen_markdown();
-        let editor = self.config.editor.clone();
-        let content = App::open_editor(editor.as_str(), item_md, terminal)?;
+        kind: StepKind,
+    ) -> Result<MsgState, String> {
+        let data = TestStep::blank(kind);
+        let mut item_md = data.gen_markdown_in(self.config.use_ac, self.config.locale);
+        if kind == StepKind::Step && self.config.scaffold_lines > 0 {
+            let project = self
+                .config
+                .project
+                .iter()
+                .flat_map(|p| p.templates.values());
+            let steps = self
+                .items
+                .iter()
+                .chain(self.config.templates.values().chain(project).flatten());
+            let lines = scaffold::frequent_lines(steps, self.config.scaffold_lines);
+            item_md = scaffold::insert(&item_md, &lines);
+        }
+        let Some(content) = self.open_editor(Target::NewStep, item_md, terminal)? else {
+            return Ok(MsgState::DynamicMsg(
+                "Closed without changes, nothing inserted".to_string(),
+            ));
+        };
 
-        let new_data = TestStep::parse_markdown(&content)?;
+        let mut new_data = TestStep::parse_markdown_with(&content, &self.config.heading_aliases)?;
+        new_data.id = TestStep::next_id(&self.items);
+        new_data.created_at = Some(timestamp::now());
+        new_data.updated_at = new_data.created_at;
+        new_data.author = self.config.author();
 
-        if let Some(idx) = self.state.selected() {
-            match direction {
-                InsertDirection::Up => {
-                    self.items.insert(idx, new_data);
-                }
-                InsertDirection::Down => {
-                    self.items.insert(idx + 1, new_data);
-                    self.state.select(Some(idx + 1));
-                }
-            }
-        } else {
-            self.items.push(new_data);
-            self.state.select(Some(0));
-        }
+        self.push_undo();
+        self.insert_items(direction, vec![new_data]);
 
-        Ok(())
+        Ok(MsgState::Default)
     }
 
-    fn parse_clipboard_context(&mut self, context: String) -> Result<(), String> {
-        let marker = format!("{}:", MDEMBEDDING);
-        let idx = context
-            .find(&marker)
-            .with_err_msg(&"Could not find MDEMBEDDING marker")?;
+    fn insert_blank(
+        &mut self,
+        direction: InsertDirection,
+        count: usize,
+    ) -> Result<MsgState, String> {
+        let now = timestamp::now();
+        let author = self.config.author();
+        let first_id = TestStep::next_id(&self.items);
+        let blanks = (0..count as u64)
+            .map(|offset| TestStep {
+                id: first_id + offset,
+                created_at: Some(now),
+                updated_at: Some(now),
+                author: author.clone(),
+                ..TestStep::new()
+            })
+            .collect();
 
-        let context = context.split_at(idx + marker.len()).1;
+        self.push_undo();
+        self.insert_items(direction, blanks);
+        Ok(MsgState::DynamicMsg(format!(
+            "Inserted {} blank step{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        )))
+    }
 
-        let idx = context
-            .find("\"")
-            .with_err_msg(&"Could not find ending quote for MDEMBEDDING")?;
+    fn join_below(&mut self, count: usize) -> Result<MsgState, String> {
+        let idx = self
+            .state
+            .selected()
+            .filter(|idx| *idx < self.items.len())
+            .with_err_msg(&"No item is currently selected")?;
+        let mut merged = self.items[idx].clone();
+        let mut removed = Vec::new();
+        for lower in self.items.iter().skip(idx + 1).take(count) {
+            if lower.is_new_section {
+                return Err("Cannot join across a section boundary".to_string());
+            }
+            merged.merge(lower);
+            removed.push(lower.clone());
+        }
+        if removed.is_empty() {
+            return Err("No step below to join".to_string());
+        }
 
-        let context = context.split_at(idx).0;
-        self.deserialize_items(context)
+        self.push_undo();
+        merged.updated_at = Some(timestamp::now());
+        self.items.drain(idx + 1..=idx + removed.len());
+        self.items[idx] = merged;
+        self.internal_clipboard = removed;
+        self.state.select(Some(idx));
+        Ok(MsgState::Default)
+    }
+
+    fn parse_clipboard_context(&mut self, context: String) -> Result<(), String> {
+        let items = embedding::extract(&context)?;
+        self.load_items(items);
+        Ok(())
     }
 
     fn load_from_clipboard(&mut self) -> Result<(), String> {
         let text = self
-            .clipboard
+            .clipboard()?
             .get_text()
             .with_err_msg(&"Failed to get text from system clipboard")?;
 
         self.parse_clipboard_context(text)
     }
 
-    fn handle_deletion(&mut self, ctrl: bool, shift: bool) -> Result<(), String> {
-        if ctrl && shift {
-            self.items = Vec::new();
-            Ok(())
-        } else {
-            self.delete_yank()
+    fn handle_deletion(&mut self, ctrl: bool, shift: bool) -> Result<MsgState, String> {
+        match (ctrl, shift) {
+            (true, true) => self.clear_all(),
+            // could be either, and guessing wrong loses a row or the whole table
+            (true, false) if self.modifiers == keyboard::Modifiers::Ambiguous => Err(
+                "This terminal sends ctrl+shift+d as ctrl+d, use :clear to clear all".to_string(),
+            ),
+            _ => self.delete_yank().map(|_| MsgState::Default),
         }
     }
 
     fn switch_to_template_window(&mut self) -> MsgState {
-        self.window = Window::Template;
+        self.switch_window(Window::Template);
         MsgState::Default
     }
 
-    fn handle_safe_keys(&mut self, code: KeyCode, _ctrl: bool, _shift: bool) {
+    fn handle_safe_keys(&mut self, code: KeyCode, _ctrl: bool, _shift: bool, count: usize) {
+        for _ in 0..count {
+            match code {
+                KeyCode::Char('j') | KeyCode::Down => self.next_row(),
+                KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
+                KeyCode::Char('l') | KeyCode::Right => self.move_column_focus(1),
+                KeyCode::Char('h') | KeyCode::Left => self.move_column_focus(-1),
+                _ => {}
+            }
+        }
+    }
+
+    // digits typed before a command plus an optional pending key (see PENDING_KEYS),
+    // consumed (and cleared) by the next key
+    fn take_prefix(&mut self) -> (usize, Option<char>) {
+        match std::mem::replace(&mut self.input_mode, InputMode::Normal) {
+            InputMode::Prefix(prefix) => {
+                let pending = prefix.chars().last().filter(|c| PENDING_KEYS.contains(c));
+                let count = prefix
+                    .trim_end_matches(PENDING_KEYS)
+                    .parse()
+                    .unwrap_or(1)
+                    .max(1);
+                (count, pending)
+            }
+            other => {
+                self.input_mode = other;
+                (1, None)
+            }
+        }
+    }
+
+    fn handle_pending_keys(
+        &mut self,
+        pending: char,
+        code: KeyCode,
+        count: usize,
+    ) -> Result<MsgState, String> {
+        match pending {
+            _ if code == KeyCode::Esc => Ok(MsgState::Default),
+            'm' => self.set_mark(code),
+            'z' => self.handle_z_keys(code),
+            ']' if code == KeyCode::Char(']') => self.jump_section(true),
+            '[' if code == KeyCode::Char('[') => self.jump_section(false),
+            ']' | '[' => Ok(MsgState::Default),
+            '\'' => self.jump_to_mark(code),
+            'i' if code == KeyCode::Char('s') => self.open_snippet_picker(""),
+            'i' => Ok(MsgState::Default),
+            'r' => self.handle_r_keys(code),
+            _ => self.handle_g_keys(code, count),
+        }
+    }
+
+    fn handle_g_keys(&mut self, code: KeyCode, count: usize) -> Result<MsgState, String> {
         match code {
-            KeyCode::Char('j') | KeyCode::Down => self.next_row(),
-            KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
-            _ => {}
+            KeyCode::Char('J') => match self.readonly_refusal() {
+                Some(refusal) => Ok(refusal),
+                None => self.join_below(count),
+            },
+            KeyCode::Char('o') => self.open_outline(),
+            KeyCode::Char('r') => match self.readonly_refusal() {
+                Some(refusal) => Ok(refusal),
+                None => self.reverse_selection(),
+            },
+            KeyCode::Char('s') => match self.readonly_refusal() {
+                Some(refusal) => Ok(refusal),
+                None => self.swap_expected_ac(),
+            },
+            KeyCode::Char('p') => match self.readonly_refusal() {
+                Some(refusal) => Ok(refusal),
+                None => self.smart_paste(),
+            },
+            KeyCode::Char('g') => {
+                self.jump_to(0);
+                Ok(MsgState::Default)
+            }
+            _ => Ok(MsgState::Default),
+        }
+    }
+
+    // r1 r2 r3 for high, medium and low, r0 clears
+    fn handle_r_keys(&mut self, code: KeyCode) -> Result<MsgState, String> {
+        let KeyCode::Char(level @ '0'..='3') = code else {
+            return Ok(MsgState::Default);
+        };
+        match self.readonly_refusal() {
+            Some(refusal) => Ok(refusal),
+            None => self.set_priority(&level.to_string()),
         }
     }
 
@@ -525,7 +893,7 @@ impl App {
             .gen_html()
             .with_err_msg(&"Failed to gen html for preview")?;
 
-        file.write_all(&html.as_bytes())
+        file.write_all(html.as_bytes())
             .with_err_msg(&"Failed to populate /tmp/uat_editor_preview.html")?;
 
         opener::open("/tmp/uat_editor_preview.html")
@@ -553,48 +921,115 @@ impl App {
         code: KeyCode,
         ctrl: bool,
         shift: bool,
+        count: usize,
     ) -> Result<MsgState, String> {
+        if let Some(refusal) = self.readonly_key(code, ctrl, shift) {
+            return Ok(refusal);
+        }
         let res = match code {
-            KeyCode::Char('q') => return Err("Quiting".to_string()),
-            KeyCode::Enter => MsgState::log_err_msg(self.edit_existing(terminal)),
+            KeyCode::Char('q') => return self.quit(),
+            // most terminals send tab for ctrl+i, it only works where they're told apart
+            KeyCode::Char('o') if ctrl => self.jump_back(),
+            KeyCode::Char('i') if ctrl => self.jump_forward(),
+            KeyCode::Char('/') => {
+                self.start_search();
+                MsgState::Default
+            }
+            KeyCode::Char('n') => MsgState::log_err_msg_or(self.search_next(true, false)),
+            KeyCode::Char('N') => MsgState::log_err_msg_or(self.search_next(false, false)),
+            KeyCode::Char('G') => {
+                self.jump_to(self.items.len().saturating_sub(1));
+                MsgState::Default
+            }
+            KeyCode::Enter => MsgState::log_err_msg_or(self.edit_existing(terminal)),
             KeyCode::Char('y') => MsgState::log_err_msg_or(self.yank()),
-            KeyCode::Char('$') => MsgState::log_err_msg_or(self.compile_to_clipboard()),
+            KeyCode::Char('$') => {
+                self.open_export_picker();
+                MsgState::Default
+            }
+            KeyCode::Char('Y') => MsgState::log_err_msg_or(self.copy_plain_text()),
             KeyCode::Char('+') => MsgState::log_err_msg(self.load_from_clipboard()),
-            KeyCode::Char('d') => MsgState::log_err_msg(self.handle_deletion(ctrl, shift)),
+            KeyCode::Char('d') => MsgState::log_err_msg_or(self.handle_deletion(ctrl, shift)),
             KeyCode::Char('p') => {
                 MsgState::log_err_msg(self.paste_or_preview(ctrl, shift, InsertDirection::Down))
             }
             KeyCode::Char('P') => {
                 MsgState::log_err_msg(self.paste_or_preview(ctrl, shift, InsertDirection::Up))
             }
-            KeyCode::Char('o') => MsgState::log_err_msg(self.insert_step(
+            KeyCode::Char('o') => MsgState::log_err_msg_or(self.insert_step(
                 terminal,
                 InsertDirection::Down,
-                false,
-                false,
+                StepKind::Step,
             )),
-            KeyCode::Char('O') => {
-                MsgState::log_err_msg(self.insert_step(terminal, InsertDirection::Up, false, false))
-            }
-            KeyCode::Char('s') => MsgState::log_err_msg(self.insert_step(
+            KeyCode::Char('O') => MsgState::log_err_msg_or(self.insert_step(
+                terminal,
+                InsertDirection::Up,
+                StepKind::Step,
+            )),
+            KeyCode::Char('s') => MsgState::log_err_msg_or(self.insert_step(
                 terminal,
                 InsertDirection::Down,
-                true,
-                false,
+                StepKind::Section,
             )),
-            KeyCode::Char('S') => {
-                MsgState::log_err_msg(self.insert_step(terminal, InsertDirection::Up, true, false))
-            }
-            KeyCode::Char('c') => MsgState::log_err_msg(self.insert_step(
+            KeyCode::Char('S') => MsgState::log_err_msg_or(self.insert_step(
+                terminal,
+                InsertDirection::Up,
+                StepKind::Section,
+            )),
+            KeyCode::Char('c') => MsgState::log_err_msg_or(self.insert_step(
                 terminal,
                 InsertDirection::Down,
-                false,
-                true,
+                StepKind::Comment,
             )),
-            KeyCode::Char('C') => {
-                MsgState::log_err_msg(self.insert_step(terminal, InsertDirection::Up, false, true))
+            KeyCode::Char('C') => MsgState::log_err_msg_or(self.insert_step(
+                terminal,
+                InsertDirection::Up,
+                StepKind::Comment,
+            )),
+            KeyCode::Char('a') => {
+                MsgState::log_err_msg_or(self.insert_blank(InsertDirection::Down, count))
+            }
+            KeyCode::Char('A') => {
+                MsgState::log_err_msg_or(self.insert_blank(InsertDirection::Up, count))
             }
+            KeyCode::Char('|') => MsgState::log_err_msg_or(self.split_step(terminal)),
             KeyCode::Char('t') => self.switch_to_template_window(),
+            KeyCode::Char(':') => {
+                self.open_command_line("");
+                MsgState::Default
+            }
+            KeyCode::Char('!') => {
+                self.open_command_line("pipe ");
+                MsgState::Default
+            }
+            KeyCode::Char('u') => MsgState::log_err_msg_or(self.undo()),
+            KeyCode::Char('K') => MsgState::log_err_msg_or(self.peek()),
+            KeyCode::Char('i') => MsgState::log_err_msg_or(self.start_cell_edit()),
+            KeyCode::Char('?') => {
+                self.open_help();
+                MsgState::Default
+            }
+            KeyCode::Char('x') => MsgState::log_err_msg_or(self.cycle_status()),
+            KeyCode::Char('X') => {
+                MsgState::log_err_msg_or(self.export_command(terminal, "junit", false))
+            }
+            KeyCode::Char('r') if ctrl => MsgState::log_err_msg_or(self.redo()),
+            KeyCode::Char('M') => MsgState::log_err_msg_or(self.start_reorder()),
+            KeyCode::Char('V') => {
+                self.visual_anchor = match self.visual_anchor {
+                    Some(_) => None,
+                    None => self.state.selected(),
+                };
+                MsgState::Default
+            }
+            KeyCode::Esc => {
+                self.visual_anchor = None;
+                MsgState::Default
+            }
+            KeyCode::Tab => {
+                self.show_detail = !self.show_detail;
+                MsgState::Default
+            }
             _ => MsgState::Default,
         };
 
@@ -610,30 +1045,44 @@ impl App {
         let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
         if key.kind == KeyEventKind::Press {
             let code = key.code;
-            self.handle_safe_keys(code, ctrl, shift);
-            self.handle_unsafe_keys(terminal, code, ctrl, shift)
+            let pending =
+                matches!(&self.input_mode, InputMode::Prefix(p) if p.ends_with(PENDING_KEYS));
+            // with a cell focused `i` edits it rather than waiting for `i s`
+            let edits_cell = code == KeyCode::Char('i')
+                && self.state.selected_column().is_some()
+                && matches!(self.input_mode, InputMode::Normal);
+            if let KeyCode::Char(c) = code
+                && (c.is_ascii_digit() || PENDING_KEYS.contains(&c))
+                && !ctrl
+                && !pending
+                && !edits_cell
+            {
+                match &mut self.input_mode {
+                    InputMode::Prefix(prefix) => prefix.push(c),
+                    _ if c != '0' => self.input_mode = InputMode::Prefix(c.to_string()),
+                    _ => {}
+                }
+                return Ok(MsgState::Default);
+            }
+            let (count, pending) = self.take_prefix();
+            if let Some(pending) = pending {
+                return Ok(MsgState::log_err_msg_or(
+                    self.handle_pending_keys(pending, code, count),
+                ));
+            }
+            self.handle_safe_keys(code, ctrl, shift, count);
+            self.handle_unsafe_keys(terminal, code, ctrl, shift, count)
         } else {
             Ok(MsgState::Default)
         }
     }
 
     fn prompt(&mut self, terminal: &mut DefaultTerminal, msg: &str) -> Result<String, String> {
-        ratatui::restore();
-
-        println!();
-        print!("{}: ", msg);
-
-        let mut input = String::new();
-
-        std::io::stdin()
-            .read_line(&mut input)
-            .with_err_msg(&"Failed to read user input from prompt")?;
-
-        input.pop();
-
-        *terminal = ratatui::init();
-
-        Ok(input)
+        self.suspend_terminal();
+        let input = read_answer(msg);
+        paste::discard_pending_input();
+        *terminal = self.resume_terminal();
+        input
     }
 
     fn save_template(&mut self, terminal: &mut DefaultTerminal) -> Result<MsgState, String> {
@@ -643,16 +1092,8 @@ impl App {
             .insert(template_name.clone(), self.items.clone());
         self.config.save_config()?;
         self.config = Config::load_config()?;
-        self.template_list = self
-            .config
-            .templates
-            .keys()
-            .map(|name| {
-                let mut data = TestStep::new(false, false);
-                data.instructions = name.clone();
-                data
-            })
-            .collect();
+        self.template_list = reload::template_list(&self.config);
+        self.clamp_to_visible();
         Ok(MsgState::DynamicMsg(
             "Saved current UAT as template".to_string(),
         ))
@@ -664,21 +1105,38 @@ impl App {
             .selected()
             .with_err_msg(&"No item is currently selected")?;
 
+        if let Some(name) = self.template_list.get(idx) {
+            if builtin::builtin(&name.instructions).is_some() {
+                return Err("Built-in templates can't be deleted".to_string());
+            }
+            if let Some(project) = &self.config.project
+                && project::project_template(&self.config, &name.instructions).is_some()
+            {
+                return Err(format!("Project templates are edited in {}", project.path));
+            }
+        }
         let template_name = self.template_list.remove(idx);
 
+        self.push_undo();
         self.items = self
             .config
             .templates
             .remove(&template_name.instructions)
             .with_err_msg(&"No template found with matching name")?
             .clone();
+        self.trash(
+            &format!("template '{}'", template_name.instructions),
+            self.items.clone(),
+        );
+        TestStep::assign_ids(&mut self.items);
         self.config.save_config()?;
         self.config = Config::load_config()?;
+        self.clamp_to_visible();
 
         Ok(MsgState::DynamicMsg("Deleted template".to_string()))
     }
 
-    fn load_template(&mut self) -> Result<MsgState, String> {
+    fn load_template(&mut self, terminal: &mut DefaultTerminal) -> Result<MsgState, String> {
         let idx = self
             .state
             .selected()
@@ -689,14 +1147,23 @@ impl App {
             .get(idx)
             .with_err_msg(&"No template name found at selection")?;
 
-        self.items = self
-            .config
-            .templates
-            .get(&template_name.instructions)
-            .with_err_msg(&"No template found with matching name")?
-            .clone();
+        let mut items = match builtin::builtin(&template_name.instructions) {
+            Some(items) => items?,
+            None => project::project_template(&self.config, &template_name.instructions)
+                .or_else(|| {
+                    self.config
+                        .templates
+                        .get(&template_name.instructions)
+                        .cloned()
+                })
+                .with_err_msg(&"No template found with matching name")?,
+        };
+        self.fill_placeholders(terminal, &mut items)?;
+        TestStep::assign_ids(&mut items);
+        self.push_undo();
+        self.items = items;
 
-        self.window = Window::UAT;
+        self.switch_window(Window::Uat);
 
         Ok(MsgState::Default)
     }
@@ -717,14 +1184,27 @@ impl App {
                 _ => {}
             }
             Ok(match key.code {
-                KeyCode::Enter => self.load_template()?,
+                KeyCode::Enter => match self.readonly_refusal() {
+                    Some(refusal) => refusal,
+                    None => MsgState::log_err_msg_or(self.load_template(terminal)),
+                },
                 KeyCode::Esc => {
-                    self.window = Window::UAT;
+                    self.switch_window(Window::Uat);
                     MsgState::Default
                 }
-                KeyCode::Char('q') => return Err("Quiting".to_string()),
-                KeyCode::Char('d') => self.delete_template()?,
-                KeyCode::Char('$') => self.save_template(terminal)?,
+                KeyCode::Char('q') => return self.quit(),
+                KeyCode::Char('d') => match self.readonly_refusal() {
+                    Some(refusal) => refusal,
+                    None => MsgState::log_err_msg_or(self.delete_template()),
+                },
+                KeyCode::Char('$') => match self.readonly_refusal() {
+                    Some(refusal) => refusal,
+                    None => MsgState::log_err_msg_or(self.save_template(terminal)),
+                },
+                KeyCode::Char('m') => match self.readonly_refusal() {
+                    Some(refusal) => refusal,
+                    None => MsgState::log_err_msg_or(self.save_builtin_as_mine(terminal)),
+                },
                 _ => MsgState::Default,
             })
         } else {
@@ -737,143 +1217,285 @@ impl App {
         terminal: &mut DefaultTerminal,
         key: KeyEvent,
     ) -> Result<MsgState, String> {
+        // raw mode turns ctrl+c into a key, treat it as quitting rather than a kill
+        if key.kind == KeyEventKind::Press
+            && key.code == KeyCode::Char('c')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            return self.quit();
+        }
+        if self.cell_edit.is_some() {
+            return self.handle_cell_edit_keys(key);
+        }
+        if self.picker.is_some() {
+            return self.handle_picker_keys(terminal, key);
+        }
+        if self.export_picker.is_some() {
+            return self.handle_export_picker_keys(terminal, key);
+        }
+        if self.column_mapping.is_some() {
+            return self.handle_column_mapping_keys(terminal, key);
+        }
+        if self.overlay.is_some() {
+            return self.handle_overlay_keys(key);
+        }
+        if let InputMode::Command(_) = self.input_mode {
+            return self.handle_command_keys(terminal, key);
+        }
+        if self.reorder.is_some() {
+            return self.handle_reorder_keys(key);
+        }
         match self.window {
-            Window::UAT => self.handle_uat_keys(terminal, key),
+            Window::Uat => self.handle_uat_keys(terminal, key),
             Window::Template => self.handle_template_keys(terminal, key),
+            Window::Snippets => self.handle_snippet_keys(terminal, key),
         }
     }
 
     fn handle_events(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
-        let event = event::read().with_err_msg(&"Failed to read terminal event")?;
-        match event {
-            Event::Key(key) => {
-                self.msg_state = self.handle_keys(terminal, key)?;
+        if let Some(signal) = self.signals.received() {
+            return Err(format!("Received signal {}", signal));
+        }
+        if let Some(msg) = self.finished_hook() {
+            if let MsgState::DynamicMsg(msg) = &msg {
+                self.messages.push(msg);
             }
-            _ => {}
+            self.msg_state = msg;
+        }
+        // poll rather than block so a pending signal (or finished hook) is noticed without
+        // a keypress
+        if !event::poll(SIGNAL_POLL).with_err_msg(&"Failed to poll terminal events")? {
+            return Ok(());
+        }
+        let event = event::read().with_err_msg(&"Failed to read terminal event")?;
+        self.msg_state = match event {
+            Event::Key(key) => self.handle_keys(terminal, key)?,
+            Event::Paste(text) => self.handle_paste(&text),
+            _ => return Ok(()),
+        };
+        if let MsgState::DynamicMsg(msg) = &self.msg_state {
+            self.messages.push(msg);
         }
+        self.update_served();
 
         Ok(())
     }
 
+    // read back before it replaces the last backup, see backup::write
     pub fn write_backup(&self) -> Result<(), String> {
-        let home = std::env::var("HOME").with_err_msg(&"EXPECTED HOME VARIABLE")?;
-        let file_path = format!("{}/.config/uat_editor/backup.html", home);
-        let html_backup = self.gen_html()?;
-        let mut file = File::create(file_path)
-            .with_err_msg(&"Failed to open /.config/uat_editor/backup.html for backup")?;
-
-        file.write_all(html_backup.as_bytes())
-            .with_err_msg(&"Failed to populate /.config/uat_editor/backup.html for backup")?;
-
-        Ok(())
+        if self.backup_locked {
+            return Ok(());
+        }
+        let backup_dir = config::paths()?.backup_dir;
+        backup::write(Path::new(&backup_dir), &self.gen_html()?, &self.items)
     }
 
     pub fn load_backup(&mut self) -> Result<(), String> {
-        let home = std::env::var("HOME").with_err_msg(&"EXPECTED HOME VARIABLE")?;
-        let file_path = format!("{}/.config/uat_editor/backup.html", home);
-
-        let mut file = File::open(file_path).with_err_msg(&"Failed to open backup")?;
-
-        let mut buffer = String::new();
-
-        file.read_to_string(&mut buffer)
-            .with_err_msg(&"Failed to open backup to string")?;
-
-        self.parse_clipboard_context(buffer)?;
-
+        let backup_dir = config::paths()?.backup_dir;
+        let items = backup::load(Path::new(&backup_dir)).inspect_err(|_| {
+            self.backup_locked = backup::is_encrypted(Path::new(&backup_dir));
+        })?;
+        self.load_items(items);
         Ok(())
     }
 
-    pub fn run(&mut self, mut terminal: DefaultTerminal) -> Result<(), String> {
-        let _ = self.load_backup();
+    pub fn run(&mut self, terminal: DefaultTerminal) -> Result<(), String> {
+        self.start_input_modes();
+        let result = self.run_loop(terminal);
+        self.stop_input_modes();
+        self.write_exit_summary(exit::ExitReason::new(&result, self.signals.received()));
+        result
+    }
+
+    fn run_loop(&mut self, mut terminal: DefaultTerminal) -> Result<(), String> {
+        if self.needs_setup {
+            self.msg_state = self.run_wizard(&mut terminal)?;
+        }
+        edit_session::clean_orphans(&std::env::temp_dir());
+        if let Err(err) = self.load_backup()
+            && self.backup_locked
+        {
+            self.msg_state =
+                MsgState::DynamicMsg(format!("{}, the backup is left as it is this session", err));
+        }
+        self.remember_loaded();
+        self.load_trash();
+        self.restore_session();
+        if let Some(notice) = self.first_run_notice() {
+            self.msg_state = notice;
+        }
         loop {
             let _ = terminal.draw(|frame| self.draw(frame));
-            match self.handle_events(&mut terminal) {
-                Err(err_msg) => {
+            if let Err(err_msg) = self.handle_events(&mut terminal) {
+                let _ = self.write_session();
+                if !self.unchanged_since_load() {
                     self.write_backup()?;
-                    return Err(err_msg);
+                    self.backup_written = true;
                 }
-                _ => {}
+                // a quit key ends in Ok, anything else is the error that stopped the app
+                return if self.quitting { Ok(()) } else { Err(err_msg) };
             }
         }
     }
 
     fn draw(&mut self, frame: &mut Frame) {
-        let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(4)]);
-        let rects = vertical.split(frame.area());
+        // the footer grows when its hints need to wrap, + 2 is for the border
+        let footer = self.footer_lines(frame.area().width.saturating_sub(2) as usize);
+        let title_height = if self.config.title_bar { 1 } else { 0 };
+        let vertical = &Layout::vertical([
+            Constraint::Length(title_height),
+            Constraint::Min(5),
+            Constraint::Length(footer.len() as u16 + 2),
+        ]);
+        let [title, main, footer_area] = vertical.areas(frame.area());
+        if self.config.title_bar {
+            self.render_title(frame, title);
+        }
+
+        let table_area = match self.window {
+            Window::Uat if self.show_detail => {
+                let horizontal =
+                    &Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)]);
+                let cols = horizontal.split(main);
+                self.render_detail(frame, cols[1]);
+                cols[0]
+            }
+            _ => main,
+        };
+
+        if matches!(self.window, Window::Uat) && self.items.is_empty() {
+            self.render_empty_state(frame, table_area);
+        } else {
+            self.render_uat_table(frame, table_area);
+            match self.window {
+                Window::Uat => self.render_minimap(frame, table_area),
+                _ => self.render_scrollbar(frame, table_area),
+            }
+        }
+        self.render_footer(frame, footer_area, footer);
+        if let Some(cursor) = self.command_cursor(footer_area) {
+            frame.set_cursor_position(cursor);
+        }
+        self.render_overlay(frame);
+        self.render_picker(frame);
+        self.render_export_picker(frame);
+        self.render_column_mapping(frame);
+    }
 
-        self.render_uat_table(frame, rects[0]);
-        self.render_scrollbar(frame, rects[0]);
-        self.render_footer(frame, rects[1]);
+    fn show_ac(&self) -> bool {
+        match self.window {
+            Window::Uat | Window::Snippets => self.config.use_ac,
+            Window::Template => true,
+        }
     }
 
-    fn text_cell<'a>(text: String) -> Cell<'a> {
-        Cell::from(Text::from(text))
+    fn show_test_data(&self) -> bool {
+        match self.window {
+            Window::Uat => self.items.iter().any(|i| i.has_test_data()),
+            Window::Snippets => self.snippets.steps.iter().any(|i| i.has_test_data()),
+            Window::Template => false,
+        }
     }
 
-    fn build_row<'a>(&self, section_idx: usize, i: usize, data: &TestStep) -> Row<'a> {
-        let item = data.ref_array();
+    fn row_annotation(&self, data: &TestStep) -> String {
+        let mut annotation = Vec::new();
+        if let Some(updated) = data.updated_at.filter(|_| self.config.show_age) {
+            let age = timestamp::now().saturating_sub(updated);
+            annotation.push(format!("edited {}", timestamp::format_age(age)));
+        }
+        if let Some(estimate) = data.estimate_minutes.filter(|_| data.is_step()) {
+            annotation.push(TestStep::format_minutes(estimate));
+        }
+        if let Some(status) = data.status {
+            annotation.push(status.label().to_string());
+        }
+        match data.unresolved_notes() {
+            0 => {}
+            1 => annotation.push("1 note".to_string()),
+            notes => annotation.push(format!("{} notes", notes)),
+        }
+        if !data.links.is_empty() {
+            let links: String = (1..=data.links.len()).map(|i| format!("[{}]", i)).collect();
+            annotation.push(links);
+        }
+        annotation.join(" · ")
+    }
+
+    fn build_row<'a>(&self, i: usize, label: &str, data: &TestStep, annotation: String) -> Row<'a> {
+        let item = self.cell_contents(data);
+        let fields = self.column_fields();
         let mut item: VecDeque<Cell> = item
             .into_iter()
-            .map(|content| Self::text_cell(format!("\n{content}\n")))
+            .enumerate()
+            .map(|(col, content)| {
+                let first = match col {
+                    0 => Line::from(annotation.clone()).right_aligned().dim(),
+                    _ => Line::from(""),
+                };
+                Cell::from(self.cell_text(fields.get(col).copied(), &content, first))
+            })
             .collect();
 
-        let row_idx = if data.is_stepless_comment {
-            format!("\n{}\n", section_idx)
-        } else {
-            format!("\n{}.{}\n", section_idx, i)
-        };
-        item.push_front(Self::text_cell(row_idx));
+        let mut row_idx = label.to_string();
+        if let Some(status) = data.status.filter(|_| self.status_glyphs()) {
+            row_idx.push_str(&format!(" {}", status.glyph()));
+        } else if data.is_new_section && self.colors.plain {
+            row_idx.push_str(" §");
+        }
+        let mut index = Line::from(row_idx);
+        if let Some(priority) = data.priority {
+            index.push_span(Span::styled(
+                format!(" {}", priority.glyph()),
+                self.colors.priority_style(priority),
+            ));
+        }
+        let mut index = vec![Line::from(""), index];
+        if matches!(self.window, Window::Uat) && self.config.show_ids {
+            index.push(Line::from(format!("#{}", data.id)));
+        }
+        item.push_front(Cell::from(Text::from(index)));
 
         item.into_iter()
-            .map(|i| i)
             .collect::<Row>()
-            .style(self.colors.row_style(i))
-            .height(4)
+            .style(self.colors.step_row_style(i, data))
+            .height(ITEM_HEIGHT as u16)
     }
 
-    fn build_rows<'a>(&self, data: &Vec<TestStep>) -> Vec<Row<'a>> {
+    fn build_rows<'a>(&self, data: &[TestStep]) -> Vec<Row<'a>> {
         let mut rows = Vec::new();
-        let mut section_idx = 1;
-        let mut step_idx = 0;
+        // the same labels the exports print
+        let labels = TestStep::step_labels(data, self.config.numbering_start);
         for (i, test_step) in data.iter().enumerate() {
-            if test_step.is_new_section && !self.last_was_comment(i) && i != 0 && i != 1 {
-                section_idx += 1;
-                step_idx = 0;
-            }
-            if test_step.is_stepless_comment && self.is_end_of_section(i) && i != 0 && i != 1 {
-                section_idx += 1;
-                step_idx = 1;
-            }
-            rows.push(self.build_row(section_idx, step_idx, test_step));
-            if !test_step.is_stepless_comment {
-                step_idx += 1;
-            }
+            // snippets are shown under their name
+            let annotation = match self.window {
+                Window::Uat => self.row_annotation(test_step),
+                Window::Snippets => self.snippets.names.get(i).cloned().unwrap_or_default(),
+                Window::Template => String::new(),
+            };
+            let row = self.build_row(i, &labels[i], test_step, annotation);
+            let in_visual = match (&self.window, self.selected_range()) {
+                (Window::Uat, Some(range)) => self.visual_anchor.is_some() && range.contains(&i),
+                _ => false,
+            };
+            let in_reorder = matches!(self.window, Window::Uat) && self.in_reorder(i);
+            rows.push(if in_reorder {
+                row.style(self.colors.reorder_row_style())
+            } else if in_visual {
+                row.style(self.colors.visual_row_style())
+            } else {
+                row
+            });
         }
 
         rows
     }
 
-    fn build_table<'a>(&self, data: Vec<Row<'a>>) -> Table<'a> {
-        Table::new(
-            data,
-            [
-                // + 1 is for padding.
-                Constraint::Length(self.col_constraints.0 + 1),
-                Constraint::Min(self.col_constraints.1 + 1),
-                Constraint::Min(self.col_constraints.2 + 1),
-                Constraint::Min(self.col_constraints.3),
-            ],
-        )
+    fn build_table<'a>(&self, data: Vec<Row<'a>>, area: Rect) -> Table<'a> {
+        Table::new(data, self.column_constraints(area.width))
     }
 
     fn build_headers<'a>(&self) -> Row<'a> {
-        let header = match self.window {
-            Window::UAT => ["#", "Test Directions", "Expected Results", "AC"],
-            Window::Template => ["#", "Template Name", "", ""],
-        };
-
-        header
+        self.header_labels()
             .into_iter()
             .map(Cell::from)
             .collect::<Row>()
@@ -886,14 +1508,56 @@ impl App {
     }
 
     fn render_uat_table(&mut self, frame: &mut Frame, area: Rect) {
+        // less the header row
+        self.table_rows = (area.height.saturating_sub(1) as usize / ITEM_HEIGHT).max(1);
         let table_rows = match self.window {
-            Window::UAT => self.build_rows(&self.items),
+            Window::Uat => self.build_rows(&self.items),
             Window::Template => self.build_rows(&self.template_list),
+            Window::Snippets => self.build_rows(&self.snippets.steps),
         };
+        let visible = self.visible_indices();
+        let selected = self
+            .state
+            .selected()
+            .and_then(|i| visible.iter().position(|v| *v == i));
+        let table_rows: Vec<Row> = table_rows
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| visible.contains(idx))
+            .map(|(_, row)| row)
+            .collect();
+        // every row is ITEM_HEIGHT for now, the offset is worked out from the list of
+        // heights so that can change
+        if let Some(selected) = selected {
+            let heights = vec![ITEM_HEIGHT as u16; table_rows.len()];
+            *self.view_state.offset_mut() = viewport::scroll_into_view(
+                self.view_state.offset(),
+                selected,
+                &heights,
+                area.height.saturating_sub(1),
+                self.config.scrolloff,
+            );
+        }
+        self.view_state.select(selected);
+        self.view_state.select_column(self.state.selected_column());
+
+        // the header is its own table pinned above the rows so no offset can scroll it
+        // away, built from the same constraints and highlight spacing so the columns line up
+        let [header_area, body_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+        let header = self
+            .build_table(Vec::new(), area)
+            .header(self.build_headers())
+            .column_highlight_style(self.colors.selected_col_style())
+            .highlight_symbol(Self::selection_symbol())
+            .bg(self.colors.buffer_bg)
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut header_state =
+            TableState::default().with_selected_column(self.state.selected_column());
+        frame.render_stateful_widget(header, header_area, &mut header_state);
 
         let table = self
-            .build_table(table_rows)
-            .header(self.build_headers())
+            .build_table(table_rows, area)
             .row_highlight_style(self.colors.selected_row_style())
             .column_highlight_style(self.colors.selected_col_style())
             .cell_highlight_style(self.colors.selected_cell_style())
@@ -901,7 +1565,115 @@ impl App {
             .bg(self.colors.buffer_bg)
             .highlight_spacing(HighlightSpacing::Always);
 
-        frame.render_stateful_widget(table, area, &mut self.state);
+        frame.render_stateful_widget(table, body_area, &mut self.view_state);
+        self.render_cell_edit(frame, body_area);
+    }
+
+    fn detail_lines(&self, item: &TestStep) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        // the searched fields get their matches highlighted
+        let mut push_section = |title: &str, content: String, field: Option<Option<Field>>| {
+            let mut heading = Line::from(title.to_string().bold());
+            // the step's own text gets a length hint, comments and links don't need one
+            if let Some(Some(_)) = field {
+                let count = words::word_count(&content);
+                heading.push_span(format!("  {}", words::describe(count)).dim());
+            }
+            lines.push(heading);
+            let code = test_step::code_lines(content.lines());
+            lines.extend(content.lines().zip(code).map(|(l, in_code)| {
+                let line = match field {
+                    Some(field) => self.highlight_line(field, l),
+                    None => Line::from(l.to_string()),
+                };
+                self.code_line(line, in_code)
+            }));
+            lines.push(Line::from(""));
+        };
+        let text = |key| self.config.locale.text(key);
+        push_section(
+            text(Key::Instructions),
+            item.instructions(),
+            Some(Some(Field::Instructions)),
+        );
+        if item.has_test_data() {
+            push_section(
+                text(Key::TestData),
+                item.test_data(),
+                Some(Some(Field::TestData)),
+            );
+        }
+        push_section(
+            text(Key::ExpectedResults),
+            item.expected_results(),
+            Some(Some(Field::Expected)),
+        );
+        push_section(text(Key::Ac), item.ac(), Some(Some(Field::Ac)));
+        if let Some(status) = item.status {
+            push_section(text(Key::Status), status.label().to_string(), None);
+        }
+        if let Some(priority) = item.priority {
+            push_section(text(Key::Priority), priority.label().to_string(), None);
+        }
+        if !item.comment.trim().is_empty() {
+            push_section(
+                text(Key::Comment),
+                item.comment.trim().to_string(),
+                Some(None),
+            );
+        }
+        if !item.links.is_empty() {
+            push_section(text(Key::Links), item.links.join("\n"), None);
+        }
+        if !item.review_notes.is_empty() {
+            let notes = item
+                .review_notes
+                .iter()
+                .map(|note| {
+                    let mark = if note.resolved { "[x]" } else { "[ ]" };
+                    format!("{} {}", mark, note.text.replace('\n', "\n    "))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            push_section("Review", notes, None);
+        }
+
+        let now = timestamp::now();
+        let mut stamp = |label: &str, at: Option<u64>| {
+            if let Some(at) = at {
+                lines.push(
+                    Line::from(format!(
+                        "{} {} ({})",
+                        label,
+                        timestamp::format_date(at),
+                        timestamp::format_age(now.saturating_sub(at))
+                    ))
+                    .dim(),
+                );
+            }
+        };
+        stamp("created", item.created_at);
+        stamp("updated", item.updated_at);
+        if let Some(author) = &item.author {
+            lines.push(Line::from(format!("author {}", author)).dim());
+        }
+        lines
+    }
+
+    fn render_detail(&self, frame: &mut Frame, area: Rect) {
+        let lines = self
+            .state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+            .map(|item| self.detail_lines(item))
+            .unwrap_or_default();
+
+        let detail = Paragraph::new(Text::from(lines))
+            .style(self.colors.info_style())
+            .wrap(Wrap { trim: false })
+            .block(self.colors.info_block().title(" Details "));
+
+        frame.render_widget(detail, area);
     }
 
     fn render_scrollbar(&mut self, frame: &mut Frame, area: Rect) {
@@ -919,38 +1691,342 @@ impl App {
         );
     }
 
-    fn gen_msg(&self, line_one: &str) -> [String; 2] {
-        //TODO: figure out why this doesn't pad anything
-        [format!("{:=^16}", line_one), "".to_string()]
+    fn filter_summary(&self) -> String {
+        let mut summary = String::new();
+        if self.config_error.is_some() {
+            summary.push_str(" CONFIG FAILED TO PARSE, fix it or :backupconfig ");
+        }
+        if self.readonly {
+            summary.push_str(" [RO] ");
+        }
+        if self.visual_anchor.is_some() {
+            summary.push_str(" VISUAL ");
+        }
+        if let (Window::Uat, Some(filter)) = (&self.window, &self.filter) {
+            summary.push_str(&format!(" filter: {} ", filter.query));
+        }
+        summary
     }
 
-    fn render_footer(&self, frame: &mut Frame, area: Rect) {
-        let to_display = match &self.msg_state {
-            MsgState::Default => {
-                match self.window {
-                    Window::UAT => [
-                        "(q) quit | (k/j) move up/down | (Enter) edit | ($) compile to html | (+) load from clipboard".to_string(),
-                        "(O/o) insert above/below | (d) delete to reg | (P/p) paste above/below | (t) templates & config".to_string(),
-                    ],
-                    Window::Template =>[
-                        "(Esc) back | (k/j) move up/down | (Enter) load".to_string(),
-                        "($) save current table as template".to_string(),
-                    ],
+    fn estimate_summary(&self) -> String {
+        match self.window {
+            Window::Uat => {
+                let total = TestStep::total_estimate(&self.items);
+                if total == 0 {
+                    return String::new();
                 }
-            },
-            MsgState::Compile => {
-                self.gen_msg("COMPILED HTML COPIED TO CLIPBOARD")
+                let selected = self
+                    .selected_range()
+                    .map(|range| TestStep::total_estimate(&self.items[range]))
+                    .unwrap_or(0);
+                let filtered = match self.filter {
+                    Some(_) => {
+                        let visible = self.visible_indices();
+                        let filtered = TestStep::total_estimate(
+                            visible.iter().filter_map(|idx| self.items.get(*idx)),
+                        );
+                        format!(" | {} filtered", TestStep::format_minutes(filtered))
+                    }
+                    None => String::new(),
+                };
+                format!(
+                    " est. {} total{} | {} selected ",
+                    TestStep::format_minutes(total),
+                    filtered,
+                    TestStep::format_minutes(selected)
+                )
             }
-            MsgState::Yanked => self.gen_msg("YANKED TO REGISTER"),
-            MsgState::Loaded => self.gen_msg("LOADED CONTEXT FROM CLIPBOARD"),
-            MsgState::DynamicMsg(msg)=> self.gen_msg(msg.as_str()),
-        };
+            Window::Template | Window::Snippets => String::new(),
+        }
+    }
 
-        let info_footer = Paragraph::new(Text::from_iter(to_display))
+    fn render_footer(&self, frame: &mut Frame, area: Rect, lines: Vec<String>) {
+        let info_footer = Paragraph::new(Text::from_iter(lines))
             .style(self.colors.info_style())
             .centered()
-            .block(self.colors.info_block());
+            .block(
+                self.colors
+                    .info_block()
+                    .title_bottom(Line::from(self.filter_summary()).left_aligned())
+                    .title_bottom(Line::from(self.estimate_summary()).right_aligned()),
+            );
 
         frame.render_widget(info_footer, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_step::Priority;
+
+    fn table(len: usize, selected: Option<usize>) -> (Vec<usize>, TableState) {
+        let state = TableState::default().with_selected(selected);
+        ((0..len).collect(), state)
+    }
+
+    #[test]
+    fn insert_selects_the_new_row_in_both_directions() {
+        let (mut items, mut state) = table(3, Some(1));
+        assert_eq!(
+            insert_at_selection(&mut items, &mut state, InsertDirection::Down, [9]),
+            2
+        );
+        assert_eq!(items, vec![0, 1, 9, 2]);
+        assert_eq!(state.selected(), Some(2));
+
+        let (mut items, mut state) = table(3, Some(1));
+        assert_eq!(
+            insert_at_selection(&mut items, &mut state, InsertDirection::Up, [9]),
+            1
+        );
+        assert_eq!(items, vec![0, 9, 1, 2]);
+        assert_eq!(state.selected(), Some(1));
+    }
+
+    #[test]
+    fn insert_at_the_edges() {
+        let (mut items, mut state) = table(3, Some(0));
+        insert_at_selection(&mut items, &mut state, InsertDirection::Up, [9]);
+        assert_eq!((items, state.selected()), (vec![9, 0, 1, 2], Some(0)));
+
+        let (mut items, mut state) = table(3, Some(2));
+        insert_at_selection(&mut items, &mut state, InsertDirection::Down, [9]);
+        assert_eq!((items, state.selected()), (vec![0, 1, 2, 9], Some(3)));
+
+        // a selection left past the end (e.g. after deleting the last row) appends
+        let (mut items, mut state) = table(3, Some(5));
+        insert_at_selection(&mut items, &mut state, InsertDirection::Up, [9]);
+        assert_eq!((items, state.selected()), (vec![0, 1, 2, 9], Some(3)));
+    }
+
+    #[test]
+    fn insert_into_an_empty_table() {
+        for direction in [InsertDirection::Up, InsertDirection::Down] {
+            let (mut items, mut state) = table(0, Some(0));
+            insert_at_selection(&mut items, &mut state, direction, [9]);
+            assert_eq!((items, state.selected()), (vec![9], Some(0)));
+        }
+        let (mut items, mut state) = table(0, None);
+        insert_at_selection(&mut items, &mut state, InsertDirection::Down, [9]);
+        assert_eq!((items, state.selected()), (vec![9], Some(0)));
+    }
+
+    #[test]
+    fn block_inserts_select_the_first_row() {
+        let (mut items, mut state) = table(2, Some(0));
+        insert_at_selection(&mut items, &mut state, InsertDirection::Down, [7, 8, 9]);
+        assert_eq!((items, state.selected()), (vec![0, 7, 8, 9, 1], Some(1)));
+
+        let (mut items, mut state) = table(2, Some(1));
+        insert_at_selection(&mut items, &mut state, InsertDirection::Up, [7, 8]);
+        assert_eq!((items, state.selected()), (vec![0, 7, 8, 1], Some(1)));
+    }
+
+    #[test]
+    fn editor_failures_name_the_command() {
+        let missing = run_editor("uat-editor-no-such-editor", Path::new("step.md"), false);
+        assert!(matches!(
+            missing,
+            Err(EditorError::NotFound(msg))
+                if msg.starts_with("Editor not found: `uat-editor-no-such-editor step.md`")
+        ));
+
+        // sh runs the "file" as a script that complains and exits non-zero
+        let script =
+            std::env::temp_dir().join(format!("uat_editor_fail_{}.sh", std::process::id()));
+        std::fs::write(
+            &script,
+            "echo >&2\necho bad flag >&2\necho usage: ed >&2\nexit 2\n",
+        )
+        .unwrap();
+        let err = String::from(run_editor("sh", &script, true).unwrap_err());
+        assert_eq!(
+            err,
+            format!(
+                "Editor `sh {}` failed (exit status: 2): bad flag | usage: ed",
+                script.display()
+            )
+        );
+        std::fs::remove_file(&script).unwrap();
+    }
+
+    #[test]
+    fn readonly_template_window_leaves_the_document_alone() {
+        let items = vec![TestStep::new().with_instructions("Open app")];
+        let mut app = App::headless(items.clone()).unwrap();
+        app.readonly = true;
+        app.config.templates.insert(
+            "mine".to_string(),
+            vec![TestStep::new().with_instructions("From the template")],
+        );
+        app.template_list = reload::template_list(&app.config);
+        app.switch_window(Window::Template);
+        let idx = app
+            .template_list
+            .iter()
+            .position(|template| template.instructions == "mine")
+            .unwrap();
+        app.state.select(Some(idx));
+
+        let msg = press_template_key(&mut app, 'd').unwrap();
+        assert!(matches!(msg, MsgState::DynamicMsg(msg) if msg.starts_with("Read-only mode")));
+        assert_eq!(app.items, items);
+        assert!(app.config.templates.contains_key("mine"));
+    }
+
+    fn press_template_key(app: &mut App, c: char) -> Result<MsgState, String> {
+        // a fixed viewport never asks the tty for its size
+        let options = ratatui::TerminalOptions {
+            viewport: ratatui::Viewport::Fixed(ratatui::layout::Rect::new(0, 0, 80, 24)),
+        };
+        let backend = ratatui::backend::CrosstermBackend::new(std::io::stdout());
+        let mut terminal = ratatui::Terminal::with_options(backend, options).unwrap();
+        let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        app.handle_template_keys(&mut terminal, key)
+    }
+
+    #[test]
+    fn template_refusals_stay_in_the_footer() {
+        let mut app = App::headless(Vec::new()).unwrap();
+        app.config.templates.insert(
+            "mine".to_string(),
+            vec![TestStep::new().with_instructions("From the template")],
+        );
+        app.template_list = reload::template_list(&app.config);
+        app.switch_window(Window::Template);
+        let select = |app: &mut App, found: fn(&str) -> bool| {
+            let idx = app
+                .template_list
+                .iter()
+                .position(|template| found(&template.instructions))
+                .unwrap();
+            app.state.select(Some(idx));
+        };
+        let listed = app.template_list.len();
+
+        select(&mut app, |name| name.ends_with(builtin::BUILTIN_SUFFIX));
+        let msg = press_template_key(&mut app, 'd');
+        assert!(
+            matches!(msg, Ok(MsgState::DynamicMsg(msg)) if msg == "Built-in templates can't be deleted")
+        );
+        assert_eq!(app.template_list.len(), listed);
+
+        select(&mut app, |name| name == "mine");
+        let msg = press_template_key(&mut app, 'm');
+        assert!(matches!(msg, Ok(MsgState::DynamicMsg(msg)) if msg.starts_with("Only built-in")));
+        assert!(app.config.templates.contains_key("mine"));
+    }
+
+    #[test]
+    fn exit_backup_is_skipped_only_when_readonly_from_the_start() {
+        let items = vec![TestStep::new().with_instructions("Open app")];
+        let mut app = App::headless(items.clone()).unwrap();
+        app.readonly = true;
+        app.remember_loaded();
+        assert!(app.unchanged_since_load());
+        // + is allowed in read-only mode and what it loads is kept
+        app.load_items(vec![
+            TestStep::new().with_instructions("From the clipboard"),
+        ]);
+        assert!(!app.unchanged_since_load());
+
+        // edits, then :set readonly to review them before quitting
+        let mut app = App::headless(items).unwrap();
+        app.remember_loaded();
+        app.items[0].instructions = "Open the app".to_string();
+        app.set_option("readonly").unwrap();
+        assert!(app.readonly);
+        assert!(!app.unchanged_since_load());
+    }
+
+    #[test]
+    fn prio_and_r_keys_set_the_priority() {
+        let items: Vec<TestStep> = ["Pay", "Browse", "Change avatar"]
+            .into_iter()
+            .map(|text| TestStep::new().with_instructions(text))
+            .collect();
+        let mut app = App::headless(items).unwrap();
+        app.state.select(Some(1));
+        app.handle_pending_keys('r', KeyCode::Char('1'), 1).unwrap();
+        assert_eq!(app.items[1].priority, Some(Priority::High));
+        app.handle_pending_keys('r', KeyCode::Char('0'), 1).unwrap();
+        assert_eq!(app.items[1].priority, None);
+        // anything but 0-3 after r leaves it alone
+        app.handle_pending_keys('r', KeyCode::Char('9'), 1).unwrap();
+        assert_eq!(app.items[1].priority, None);
+
+        // :prio covers the whole selection
+        app.visual_anchor = Some(0);
+        app.set_priority("low").unwrap();
+        let priorities: Vec<_> = app.items.iter().map(|item| item.priority).collect();
+        assert_eq!(priorities, [Some(Priority::Low), Some(Priority::Low), None]);
+        assert!(app.set_priority("urgent").is_err());
+    }
+
+    #[test]
+    fn joined_steps_all_go_to_the_register() {
+        let items: Vec<TestStep> = ["Open app", "Sign in", "Open settings", "Sign out"]
+            .into_iter()
+            .map(|text| TestStep::new().with_instructions(text))
+            .collect();
+        let mut app = App::headless(items.clone()).unwrap();
+        app.state.select(Some(0));
+        app.join_below(2).unwrap();
+        assert_eq!(app.items.len(), 2);
+        assert_eq!(
+            app.items[0].instructions,
+            "Open app\n\nSign in\n\nOpen settings"
+        );
+        assert_eq!(app.internal_clipboard, items[1..3]);
+
+        // pasted back together, in order and with distinct ids
+        app.paste(InsertDirection::Down).unwrap();
+        let pasted = &app.items[1..3];
+        assert_eq!(pasted[0].instructions, "Sign in");
+        assert_eq!(pasted[1].instructions, "Open settings");
+        assert_ne!(pasted[0].id, pasted[1].id);
+    }
+
+    #[test]
+    fn table_rows_carry_the_export_labels() {
+        let items = vec![
+            TestStep::section("Login"),
+            TestStep::new().with_instructions("Open app"),
+            TestStep::new().with_instructions("Sign in"),
+            TestStep::section("Settings"),
+            TestStep::new().with_instructions("Open settings"),
+        ];
+        let mut app = App::headless(items.clone()).unwrap();
+        app.config.numbering_start = 41;
+        let backend = ratatui::backend::TestBackend::new(120, 60);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+        let html = html::gen_html(&items, &html::HtmlOptions::from_config(&app.config)).unwrap();
+
+        let labels = TestStep::step_labels(&items, 41);
+        assert_eq!(labels, vec!["41.1", "41.2", "41.3", "42.1", "42.2"]);
+        for label in labels {
+            assert!(
+                html.contains(&format!(">{}<", label)),
+                "{} not in the html",
+                label
+            );
+            let first_cell = |line: &String| {
+                line.trim_start_matches(|c: char| !c.is_ascii_digit())
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|cell| cell == label)
+            };
+            assert!(screen.iter().any(first_cell), "{} not in the table", label);
+        }
+    }
+}
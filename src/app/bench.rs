@@ -0,0 +1,15 @@
+use super::{App, AppOptions};
+use crate::{signals::Signals, test_step::TestStep};
+
+impl App {
+    // an app holding `items` that has never drawn, for benches/
+    pub(crate) fn headless(items: Vec<TestStep>) -> Result<App, String> {
+        let mut app = App::new(Signals::default(), AppOptions::default())?;
+        app.load_items(items);
+        Ok(app)
+    }
+
+    pub(crate) fn row_count(&self) -> usize {
+        self.build_rows(&self.items).len()
+    }
+}
@@ -0,0 +1,58 @@
+use super::{App, MsgState, Window, history::History};
+use crate::test_step::TestStep;
+
+// the document that isn't showing, `:b#` swaps it with the one that is
+pub(super) struct Buffer {
+    items: Vec<TestStep>,
+    document: Option<String>,
+    dirty: bool,
+    history: History,
+    selected: Option<usize>,
+}
+
+impl App {
+    fn take_buffer(&mut self, items: Vec<TestStep>) -> Buffer {
+        Buffer {
+            items: std::mem::replace(&mut self.items, items),
+            document: self.document.take(),
+            dirty: self.dirty,
+            history: std::mem::take(&mut self.history),
+            selected: self.state.selected(),
+        }
+    }
+
+    // `items` becomes an unsaved document in front of the current one, which is kept as
+    // the alternate. anything that was the alternate before is replaced
+    pub(super) fn open_buffer(&mut self, items: Vec<TestStep>) {
+        self.switch_window(Window::Uat);
+        self.alternate = Some(self.take_buffer(items));
+        self.dirty = true;
+        self.visual_anchor = None;
+        self.reorder = None;
+        self.sorted_by = None;
+        self.state.select(Some(0));
+        self.clamp_to_visible();
+    }
+
+    // `:b#`
+    pub(super) fn alternate_buffer(&mut self) -> Result<MsgState, String> {
+        let Some(buffer) = self.alternate.take() else {
+            return Err("No alternate buffer".to_string());
+        };
+        self.switch_window(Window::Uat);
+        let current = self.take_buffer(buffer.items);
+        self.document = buffer.document;
+        self.dirty = buffer.dirty;
+        self.history = buffer.history;
+        self.visual_anchor = None;
+        self.reorder = None;
+        self.sorted_by = None;
+        self.state.select(buffer.selected);
+        self.clamp_to_visible();
+        self.alternate = Some(current);
+        Ok(MsgState::DynamicMsg(format!(
+            "Switched to {}",
+            self.document.as_deref().unwrap_or("[unsaved]")
+        )))
+    }
+}
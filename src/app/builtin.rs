@@ -0,0 +1,97 @@
+use super::{App, MsgState, project, reload};
+use crate::{config::Config, embedding, err_msg::WithErrMsg, test_step::TestStep};
+use ratatui::DefaultTerminal;
+
+pub(super) const BUILTIN_SUFFIX: &str = " (built-in)";
+
+// shipped with the binary so the template window isn't empty on a fresh install, they
+// live outside the config and are never written to it
+const BUILTINS: &[(&str, &str)] = &[
+    (
+        "Web feature UAT",
+        include_str!("../templates/web_feature.json"),
+    ),
+    (
+        "Regression checklist",
+        include_str!("../templates/regression.json"),
+    ),
+    ("API testing", include_str!("../templates/api.json")),
+];
+
+pub(super) fn builtin_names(config: &Config) -> Vec<String> {
+    if !config.show_builtin_templates {
+        return Vec::new();
+    }
+    BUILTINS
+        .iter()
+        .map(|(name, _)| format!("{}{}", name, BUILTIN_SUFFIX))
+        .collect()
+}
+
+// the steps of a built-in given its name as shown in the template list
+pub(super) fn builtin(listed_name: &str) -> Option<Result<Vec<TestStep>, String>> {
+    let name = listed_name.strip_suffix(BUILTIN_SUFFIX)?;
+    BUILTINS
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, json)| embedding::parse_document(json))
+}
+
+impl App {
+    // copies the selected built-in or project template into the user's own templates
+    pub(super) fn save_builtin_as_mine(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<MsgState, String> {
+        let idx = self
+            .state
+            .selected()
+            .with_err_msg(&"No item is currently selected")?;
+        let listed_name = self
+            .template_list
+            .get(idx)
+            .with_err_msg(&"No template name found at selection")?
+            .instructions
+            .clone();
+        let items = match builtin(&listed_name) {
+            Some(items) => items?,
+            None => project::project_template(&self.config, &listed_name)
+                .with_err_msg(&"Only built-in and project templates can be copied")?,
+        };
+
+        let template_name = self.prompt(terminal, "Enter a name for your copy")?;
+        let template_name = template_name.trim();
+        if template_name.is_empty() {
+            return Err("Template name can't be empty".to_string());
+        }
+        if self.config.templates.contains_key(template_name) {
+            return Err(format!("A template named {} already exists", template_name));
+        }
+        self.config
+            .templates
+            .insert(template_name.to_string(), items);
+        self.config.save_config()?;
+        self.config = Config::load_config()?;
+        self.template_list = reload::template_list(&self.config);
+        self.clamp_to_visible();
+        Ok(MsgState::DynamicMsg(format!(
+            "Saved {} as {}",
+            listed_name, template_name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_parse() {
+        for (name, json) in BUILTINS {
+            let items = embedding::parse_document(json).unwrap();
+            assert!(!items.is_empty(), "{} has no steps", name);
+        }
+        assert!(builtin("API testing (built-in)").unwrap().is_ok());
+        assert!(builtin("API testing").is_none());
+    }
+}
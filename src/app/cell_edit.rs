@@ -0,0 +1,142 @@
+use super::{App, ITEM_HEIGHT, MsgState, Window};
+use crate::{
+    err_msg::WithErrMsg,
+    query::Field,
+    test_step::TestStep,
+    text_input::{Action, TextInput},
+    timestamp,
+};
+use ratatui::{
+    Frame,
+    crossterm::event::{KeyEvent, KeyEventKind},
+    layout::{Position, Rect},
+    text::{Line, Text},
+    widgets::{Clear, Paragraph},
+};
+
+// the prose fields get a few lines to work in, one word AC values and section titles a
+// single line unless they already span more
+const MULTILINE_ROWS: u16 = ITEM_HEIGHT as u16 - 1;
+
+// `i` on a focused cell, the field is written back on Enter
+pub(super) struct CellEdit {
+    idx: usize,
+    field: Field,
+    input: TextInput,
+}
+
+fn field_mut(step: &mut TestStep, field: Field) -> &mut String {
+    match field {
+        Field::Instructions => &mut step.instructions,
+        Field::TestData => &mut step.test_data,
+        Field::Expected => &mut step.expected_results,
+        Field::Ac => &mut step.ac,
+    }
+}
+
+impl CellEdit {
+    pub(super) fn paste(&mut self, text: &str) -> usize {
+        self.input.paste(text)
+    }
+}
+
+impl App {
+    pub(super) fn start_cell_edit(&mut self) -> Result<MsgState, String> {
+        let (idx, col) = self
+            .state
+            .selected()
+            .zip(self.state.selected_column())
+            .filter(|_| self.window == Window::Uat)
+            .with_err_msg(&"Focus a cell with l first")?;
+        let field = self
+            .column_fields()
+            .get(col - 1)
+            .copied()
+            .with_err_msg(&"No field under the cell focus")?;
+        let step = self
+            .items
+            .get_mut(idx)
+            .with_err_msg(&"No item is currently selected")?;
+        let text = field_mut(step, field).clone();
+        let input = if text.contains('\n') || (step.is_step() && field != Field::Ac) {
+            TextInput::multiline(&text)
+        } else {
+            TextInput::new(&text)
+        };
+        self.cell_edit = Some(CellEdit { idx, field, input });
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn handle_cell_edit_keys(&mut self, key: KeyEvent) -> Result<MsgState, String> {
+        let Some(edit) = &mut self.cell_edit else {
+            return Ok(MsgState::Default);
+        };
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        match edit.input.handle_key(key) {
+            Action::Editing => Ok(MsgState::Default),
+            Action::Cancel => {
+                self.cell_edit = None;
+                Ok(MsgState::Default)
+            }
+            Action::Commit => {
+                let Some(edit) = self.cell_edit.take() else {
+                    return Ok(MsgState::Default);
+                };
+                let unchanged = self
+                    .items
+                    .get_mut(edit.idx)
+                    .is_none_or(|step| field_mut(step, edit.field) == edit.input.text());
+                if unchanged {
+                    return Ok(MsgState::Default);
+                }
+                self.push_undo();
+                let step = &mut self.items[edit.idx];
+                *field_mut(step, edit.field) = edit.input.text().to_string();
+                step.updated_at = Some(timestamp::now());
+                Ok(MsgState::Default)
+            }
+        }
+    }
+
+    // drawn over the focused cell, below the row's annotation line, once the table has
+    // settled its offset
+    pub(super) fn render_cell_edit(&self, frame: &mut Frame, body: Rect) {
+        let Some(edit) = &self.cell_edit else {
+            return;
+        };
+        let visible = self.visible_indices();
+        let (Some(pos), Some(col)) = (
+            visible.iter().position(|idx| *idx == edit.idx),
+            self.state.selected_column(),
+        ) else {
+            return;
+        };
+        let Some(row) = pos.checked_sub(self.view_state.offset()) else {
+            return;
+        };
+        let Some(cell) = self.column_rects(body).get(col).copied() else {
+            return;
+        };
+        let height = if edit.input.is_multiline() {
+            MULTILINE_ROWS
+        } else {
+            1
+        };
+        let area = Rect {
+            y: body.y + (row * ITEM_HEIGHT) as u16 + 1,
+            height,
+            ..cell
+        }
+        .intersection(body);
+        if area.is_empty() {
+            return;
+        }
+        let (lines, (x, y)) = edit.input.view(area.width, area.height);
+        let text = Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>());
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(text).style(self.colors.info_style()), area);
+        frame.set_cursor_position(Position::new(area.x + x, area.y + y));
+    }
+}
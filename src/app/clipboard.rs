@@ -0,0 +1,202 @@
+use super::App;
+use crate::{config, crypto, err_msg::WithErrMsg};
+use std::path::Path;
+
+// where the html goes when the clipboard didn't take all of it, beside the backups
+const TRUNCATED_HTML: &str = "clipboard.html";
+
+// the system clipboard as the app uses it, so a test can stand in for the platform
+pub(super) trait SystemClipboard {
+    fn set_text(&mut self, text: String) -> Result<(), String>;
+    fn get_text(&mut self) -> Result<String, String>;
+    fn get_html(&mut self) -> Result<String, String>;
+}
+
+impl SystemClipboard for arboard::Clipboard {
+    fn set_text(&mut self, text: String) -> Result<(), String> {
+        arboard::Clipboard::set_text(self, text).map_err(|err| err.to_string())
+    }
+
+    fn get_text(&mut self) -> Result<String, String> {
+        arboard::Clipboard::get_text(self).map_err(|err| err.to_string())
+    }
+
+    fn get_html(&mut self) -> Result<String, String> {
+        self.get().html().map_err(|err| err.to_string())
+    }
+}
+
+pub(super) fn system() -> Result<Box<dyn SystemClipboard>, String> {
+    let clipboard = arboard::Clipboard::new().with_err_msg(&"Failed to grab system clipboard")?;
+    Ok(Box::new(clipboard))
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum ReadBack {
+    Intact,
+    // bytes sent and bytes the clipboard gave back
+    Truncated(usize, usize),
+    // the platform can't give the text back, so there's nothing to compare
+    Unverified(String),
+}
+
+// some platforms cap what a clipboard holds and cut the rest without saying so. line
+// endings may come back converted, that isn't a loss
+pub(super) fn set_and_read_back(
+    clipboard: &mut dyn SystemClipboard,
+    text: String,
+) -> Result<ReadBack, String> {
+    let sent = text.len();
+    let expected = text.replace("\r\n", "\n");
+    clipboard
+        .set_text(text)
+        .with_err_msg(&"Failed to set clipboard content")?;
+    let read = match clipboard.get_text() {
+        Ok(read) => read,
+        Err(err) => return Ok(ReadBack::Unverified(err)),
+    };
+    if read.replace("\r\n", "\n") == expected {
+        Ok(ReadBack::Intact)
+    } else {
+        Ok(ReadBack::Truncated(sent, read.len()))
+    }
+}
+
+fn save_truncated(dir: &Path, html: &str) -> Result<String, String> {
+    std::fs::create_dir_all(dir).with_err_msg(&format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(TRUNCATED_HTML).display().to_string();
+    crypto::write(&path, html)?;
+    Ok(path)
+}
+
+fn truncated_msg(sent: usize, read: usize, saved: Result<String, String>) -> String {
+    let kept = match saved {
+        Ok(path) => format!("the full html is in {}", path),
+        Err(err) => format!("and it could not be kept ({})", err),
+    };
+    format!(
+        "CLIPBOARD TRUNCATED ({} of {} bytes), {}, send the file or use :export instead",
+        read, sent, kept
+    )
+}
+
+fn unverified_msg(err: &str) -> String {
+    format!(
+        "Compiled, but the clipboard couldn't be read back to check it ({}), verify_clipboard = false skips the check",
+        err
+    )
+}
+
+impl App {
+    pub(super) fn clipboard(&mut self) -> Result<&mut Box<dyn SystemClipboard>, String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(system()?);
+        }
+        self.clipboard
+            .as_mut()
+            .with_err_msg(&"Failed to grab system clipboard")
+    }
+
+    // Some is the warning to show in place of the compile message, the clipboard holds a
+    // cut down copy by then or one that couldn't be checked
+    pub(super) fn copy_compiled_html(&mut self, html: &str) -> Result<Option<String>, String> {
+        if !self.config.verify_clipboard {
+            self.clipboard()?
+                .set_text(html.to_string())
+                .with_err_msg(&"Failed to set clipboard content")?;
+            return Ok(None);
+        }
+        match set_and_read_back(self.clipboard()?.as_mut(), html.to_string())? {
+            ReadBack::Intact => Ok(None),
+            ReadBack::Truncated(sent, read) => {
+                let saved = config::paths()
+                    .and_then(|paths| save_truncated(Path::new(&paths.backup_dir), html));
+                Ok(Some(truncated_msg(sent, read, saved)))
+            }
+            ReadBack::Unverified(err) => Ok(Some(unverified_msg(&err))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // keeps the first `limit` bytes like a platform with a size cap
+    struct Capped {
+        limit: usize,
+        text: String,
+    }
+
+    impl SystemClipboard for Capped {
+        fn set_text(&mut self, text: String) -> Result<(), String> {
+            self.text = text.chars().take(self.limit).collect();
+            Ok(())
+        }
+
+        fn get_text(&mut self) -> Result<String, String> {
+            Ok(self.text.replace('\n', "\r\n"))
+        }
+
+        fn get_html(&mut self) -> Result<String, String> {
+            Err("no html".to_string())
+        }
+    }
+
+    #[test]
+    fn a_cut_down_clipboard_is_caught_and_the_html_kept() {
+        let html = "<table>\n<tr><td>Open app</td></tr>\n</table>".to_string();
+        let mut roomy = Capped {
+            limit: 1000,
+            text: String::new(),
+        };
+        assert_eq!(
+            set_and_read_back(&mut roomy, html.clone()),
+            Ok(ReadBack::Intact)
+        );
+
+        let mut capped = Capped {
+            limit: 10,
+            text: String::new(),
+        };
+        let read_back = set_and_read_back(&mut capped, html.clone()).unwrap();
+        assert_eq!(read_back, ReadBack::Truncated(html.len(), 11));
+
+        let dir = std::env::temp_dir().join(format!("uat_editor_clipboard_{}", std::process::id()));
+        let path = save_truncated(&dir, &html).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), html);
+        let msg = truncated_msg(html.len(), 11, Ok(path));
+        assert!(msg.starts_with("CLIPBOARD TRUNCATED (11 of"), "{}", msg);
+        assert!(msg.contains("clipboard.html"), "{}", msg);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // takes the text but can't hand it back, like a write-only clipboard
+    struct WriteOnly;
+
+    impl SystemClipboard for WriteOnly {
+        fn set_text(&mut self, _text: String) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn get_text(&mut self) -> Result<String, String> {
+            Err("not supported".to_string())
+        }
+
+        fn get_html(&mut self) -> Result<String, String> {
+            Err("no html".to_string())
+        }
+    }
+
+    #[test]
+    fn a_clipboard_that_cant_be_read_is_not_called_truncated() {
+        let read_back = set_and_read_back(&mut WriteOnly, "<table></table>".to_string());
+        assert_eq!(
+            read_back,
+            Ok(ReadBack::Unverified("not supported".to_string()))
+        );
+        let msg = unverified_msg("not supported");
+        assert!(!msg.contains("TRUNCATED"), "{}", msg);
+        assert!(msg.contains("not supported"), "{}", msg);
+    }
+}
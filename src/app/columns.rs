@@ -0,0 +1,277 @@
+use super::{App, Window};
+use crate::{config::MarkdownExtension, images, locale::Key, test_step::TestStep};
+use ratatui::layout::{Constraint, Layout, Rect};
+use std::rc::Rc;
+use unicode_width::UnicodeWidthStr;
+
+// the narrowest the index column gets, content columns use their header's minimum
+const MIN_INDEX_WIDTH: u16 = 4;
+const MIN_TEXT_WIDTH: u16 = 20;
+const MIN_AC_WIDTH: u16 = 10;
+
+// the table's selection symbol and the space ratatui puts between columns
+const HIGHLIGHT_WIDTH: u16 = 3;
+const COLUMN_SPACING: u16 = 1;
+
+// "- [ ] item" drawn as a box, the list marker dropped. ☐ and ☑ with theme glyphs on
+fn task_markers(text: &str, glyphs: bool) -> String {
+    let (open, done) = if glyphs {
+        ("☐", "☑")
+    } else {
+        ("[ ]", "[x]")
+    };
+    text.split('\n')
+        .map(|line| {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let rest = line.trim_start();
+            let task = ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|bullet| rest.strip_prefix(bullet))
+                .and_then(|item| {
+                    let (mark, text) = item.split_at_checked(3)?;
+                    let mark = match mark {
+                        "[ ]" => open,
+                        "[x]" | "[X]" => done,
+                        _ => return None,
+                    };
+                    Some(format!("{}{}{}", indent, mark, text))
+                });
+            task.unwrap_or_else(|| line.to_string())
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// tabs are expanded to the next tab stop so they take up the space a terminal
+// would give them, ratatui itself renders them with no width at all
+pub fn expand_tabs(text: &str, tab_width: usize) -> String {
+    if !text.contains('\t') {
+        return text.to_string();
+    }
+    text.split('\n')
+        .map(|line| {
+            let mut out = String::with_capacity(line.len());
+            for c in line.chars() {
+                if c == '\t' {
+                    if tab_width > 0 {
+                        let col = out.width();
+                        out.push_str(&" ".repeat(tab_width - col % tab_width));
+                    }
+                } else {
+                    out.push(c);
+                }
+            }
+            out
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// display width of the widest line
+pub fn text_width(text: &str, tab_width: usize) -> u16 {
+    expand_tabs(text, tab_width)
+        .lines()
+        .map(|line| line.width())
+        .max()
+        .unwrap_or(0)
+        .try_into()
+        .unwrap_or(u16::MAX)
+}
+
+// columns too wide for the terminal all give up the same share of their width
+pub fn fit_widths(widths: &[u16], available: u16) -> Vec<u16> {
+    let total: u32 = widths.iter().map(|w| *w as u32).sum();
+    if total <= available as u32 {
+        return widths.to_vec();
+    }
+    widths
+        .iter()
+        .map(|w| ((*w as u32 * available as u32) / total).max(1) as u16)
+        .collect()
+}
+
+impl App {
+    pub(super) fn header_titles(&self) -> Vec<&'static str> {
+        let text = |key| self.config.locale.text(key);
+        let mut header = match self.window {
+            Window::Uat | Window::Snippets => {
+                vec![
+                    "#",
+                    text(Key::TestDirections),
+                    text(Key::ExpectedResults),
+                    text(Key::Ac),
+                ]
+            }
+            Window::Template => vec!["#", text(Key::TemplateName), "", ""],
+        };
+        if self.show_test_data() {
+            header.insert(2, text(Key::TestData));
+        }
+        if !self.show_ac() {
+            header.pop();
+        }
+        header
+    }
+
+    // where each column lands in `area`, the same split ratatui's table makes after
+    // setting aside room for the selection symbol
+    pub(super) fn column_rects(&self, area: Rect) -> Rc<[Rect]> {
+        let columns = Rect {
+            x: area.x + HIGHLIGHT_WIDTH.min(area.width),
+            width: area.width.saturating_sub(HIGHLIGHT_WIDTH),
+            ..area
+        };
+        Layout::horizontal(self.column_constraints(area.width))
+            .spacing(COLUMN_SPACING)
+            .split(columns)
+    }
+
+    // the titles as drawn, with the sort indicator on the directions column
+    pub(super) fn header_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .header_titles()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        if let (Window::Uat, Some(sort)) = (self.window, self.sorted_by) {
+            labels[1] = format!("{} {}", labels[1], sort.indicator());
+        }
+        labels
+    }
+
+    // the text each cell shows, without the index column
+    pub(super) fn cell_contents(&self, data: &TestStep) -> Vec<String> {
+        let mut item = data.ref_array(self.show_test_data());
+        if !self.show_ac() {
+            item.pop();
+        }
+        if matches!(self.window, Window::Uat) && data.is_blank() {
+            item[0] = "(empty)".to_string();
+        }
+        let tasks = self
+            .config
+            .markdown_extensions
+            .contains(&MarkdownExtension::Tasklists);
+        item.into_iter()
+            .map(|content| match tasks {
+                true => task_markers(&content, self.status_glyphs()),
+                false => content,
+            })
+            .map(|content| expand_tabs(&images::caption_markers(&content), self.config.tab_width))
+            .collect()
+    }
+
+    // with no colors to tell pass from fail the glyphs are always on
+    pub(super) fn status_glyphs(&self) -> bool {
+        self.config.theme.glyphs || self.colors.plain
+    }
+
+    fn index_width(&self) -> u16 {
+        let widest_id = match self.window {
+            Window::Uat if self.config.show_ids => self.items.iter().map(|i| i.id).max(),
+            _ => None,
+        };
+        let id_width = widest_id.map_or(0, |id| format!("#{}", id).len() as u16);
+        // room for " ✓" after the row number
+        let glyph_width = if self.status_glyphs() || self.colors.plain {
+            2
+        } else {
+            0
+        };
+        // and " ▲" after that
+        let priority_width = match self.window {
+            Window::Uat if self.items.iter().any(|i| i.priority.is_some()) => 2,
+            _ => 0,
+        };
+        (MIN_INDEX_WIDTH + glyph_width + priority_width).max(id_width)
+    }
+
+    // measured every draw so edits, pastes and loads resize the columns straight away
+    pub(super) fn column_constraints(&self, area_width: u16) -> Vec<Constraint> {
+        let titles = self.header_labels();
+        let items = match self.window {
+            Window::Uat => &self.items,
+            Window::Template => &self.template_list,
+            Window::Snippets => &self.snippets.steps,
+        };
+        let last = titles.len() - 1;
+        let mut widths: Vec<u16> = titles[1..]
+            .iter()
+            .enumerate()
+            .map(|(col, title)| {
+                let min = if col + 1 == last && self.show_ac() {
+                    MIN_AC_WIDTH
+                } else {
+                    MIN_TEXT_WIDTH
+                };
+                min.max(title.width() as u16)
+            })
+            .collect();
+        for item in items {
+            for (width, content) in widths.iter_mut().zip(self.cell_contents(item)) {
+                *width = (*width).max(text_width(&content, self.config.tab_width));
+            }
+        }
+
+        // + 1 is for padding.
+        let index = self.index_width() + 1;
+        let spacing = COLUMN_SPACING * last as u16;
+        let available = area_width
+            .saturating_sub(HIGHLIGHT_WIDTH + spacing + index)
+            .saturating_sub(last as u16);
+        let widths = fit_widths(&widths, available);
+
+        let mut constraints = vec![Constraint::Length(index)];
+        constraints.extend(widths.iter().enumerate().map(|(col, width)| {
+            if col + 1 == last {
+                Constraint::Min(*width)
+            } else {
+                Constraint::Min(width + 1)
+            }
+        }));
+        constraints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabs_expand_to_the_next_stop() {
+        assert_eq!(
+            expand_tabs("-\titem\n\t- nested", 4),
+            "-   item\n    - nested"
+        );
+        assert_eq!(expand_tabs("日\tx", 4), "日  x");
+        assert_eq!(text_width("日本\tx", 4), 9);
+        assert_eq!(text_width("\t\tdeep", 4), 12);
+        assert_eq!(text_width("a\tb", 0), 2);
+    }
+
+    #[test]
+    fn task_items_draw_as_boxes() {
+        let cell = "- [ ] open app\n  * [X] log in\n- [y] not a task\n- plain";
+        assert_eq!(
+            task_markers(cell, false),
+            "[ ] open app\n  [x] log in\n- [y] not a task\n- plain"
+        );
+        assert_eq!(task_markers("- [x] done", true), "☑ done");
+        assert_eq!(task_markers("- [ ]", true), "☐");
+    }
+
+    #[test]
+    fn wide_characters_are_measured_by_display_width() {
+        assert_eq!(text_width("テスト手順", 4), 10);
+        assert_eq!(text_width("ok 👍", 4), 5);
+        assert_eq!(text_width("👨\u{200d}👩\u{200d}👧", 4), 2);
+        assert_eq!(text_width("short\n漢字漢字漢字漢字", 4), 16);
+    }
+
+    #[test]
+    fn widths_shrink_proportionally_when_too_wide() {
+        assert_eq!(fit_widths(&[20, 30, 10], 100), vec![20, 30, 10]);
+        assert_eq!(fit_widths(&[40, 120, 40], 100), vec![20, 60, 20]);
+        assert_eq!(fit_widths(&[1, 300], 30), vec![1, 29]);
+    }
+}
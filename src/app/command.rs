@@ -0,0 +1,158 @@
+use super::{App, InputMode, MsgState, overlay::Overlay};
+use crate::{
+    config::Config,
+    filter::Filter,
+    text_input::{Action, TextInput},
+    validate,
+};
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{KeyCode, KeyEvent, KeyEventKind},
+};
+
+impl App {
+    pub(super) fn handle_command_keys(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        key: KeyEvent,
+    ) -> Result<MsgState, String> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        let InputMode::Command(input) = &mut self.input_mode else {
+            return Ok(MsgState::Default);
+        };
+        if key.code == KeyCode::Backspace && input.is_empty() {
+            self.input_mode = InputMode::Normal;
+            return Ok(MsgState::Default);
+        }
+        match input.handle_key(key) {
+            Action::Editing => {}
+            Action::Cancel => self.input_mode = InputMode::Normal,
+            Action::Commit => {
+                let cmd = input.text().to_string();
+                self.input_mode = InputMode::Normal;
+                return Ok(MsgState::log_err_msg_or(self.run_command(terminal, &cmd)));
+            }
+        }
+        Ok(MsgState::Default)
+    }
+
+    // `:` and `/` open the command line with `text` already typed
+    pub(super) fn open_command_line(&mut self, text: &str) {
+        self.input_mode = InputMode::Command(TextInput::new(text));
+    }
+
+    fn run_command(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        cmd: &str,
+    ) -> Result<MsgState, String> {
+        let cmd = cmd.trim();
+        let (name, args) = cmd.split_once(' ').unwrap_or((cmd, ""));
+        let args = args.trim();
+        self.readonly_command(name)?;
+        match name {
+            "w" | "write" => self.write_document(args),
+            "e" | "edit" => self.edit_document(args, false),
+            "e!" | "edit!" => self.edit_document(args, true),
+            "b#" | "alt" => self.alternate_buffer(),
+            "gdiff" => self.git_diff(),
+            "transform" => self.transform_command(args),
+            "filter" => self.set_filter(args),
+            "search" => self.search_command(args),
+            "stats" => self.open_stats(),
+            "check" => self.open_check(),
+            "spellignore" => self.spell_ignore(args),
+            "pipe" => self.pipe_selection(args),
+            "status" => self.set_status(args),
+            "prio" | "priority" => self.set_priority(args),
+            "comment" => self.set_comment(args),
+            "setfield" => self.set_field_command(args),
+            "movefield" => self.move_field_command(args),
+            "smartpaste" => self.smart_paste(),
+            "review" => self.review_command(args),
+            "export" => self.export_command(terminal, args, false),
+            "export!" => self.export_command(terminal, args, true),
+            "compile" => self.compile_to_clipboard(false),
+            "compile!" => self.compile_to_clipboard(true),
+            "open" => self.open_last_export(),
+            "messages" => self.open_messages(),
+            "serve" => self.serve_command(args),
+            "import" => self.import_html(terminal, args),
+            "split" => self.split_step(terminal),
+            "sort" => self.sort_command(args, false),
+            "sort!" => self.sort_command(args, true),
+            "reverse" => self.reverse_selection(),
+            "normalize" => self.normalize_command(args),
+            "dedup" => self.dedup_command(),
+            "marks" => self.open_marks(),
+            "trash" => self.open_trash(),
+            "clear" => self.clear_all(),
+            "restore-last-clear" => self.restore_last_clear(),
+            "snippet" => self.snippet_command(terminal, args),
+            "snippets" => self.open_snippets(),
+            "savesnippet" => self.save_snippet(terminal, args),
+            "set" => self.set_option(args),
+            "backupconfig" => self.backup_config(),
+            "reload-config" => self.reload_config(terminal),
+            "" => Ok(MsgState::Default),
+            _ => Err(format!("Unknown command: {}", name)),
+        }
+    }
+
+    fn open_check(&mut self) -> Result<MsgState, String> {
+        let mut issues = validate::validate(&self.items, &self.config);
+        issues.extend(validate::spellcheck(&self.items, &self.config));
+        if issues.is_empty() {
+            return Ok(MsgState::DynamicMsg("No issues found".to_string()));
+        }
+        let lines = issues
+            .iter()
+            .map(|issue| issue.to_string().into())
+            .collect();
+        self.overlay = Some(Overlay::new("Check", lines));
+        Ok(MsgState::Default)
+    }
+
+    fn backup_config(&mut self) -> Result<MsgState, String> {
+        if self.config_error.is_none() {
+            return Err("Config loaded fine, nothing to back up".to_string());
+        }
+        let broken = Config::backup_broken()?;
+        self.config_error = None;
+        Ok(MsgState::DynamicMsg(format!(
+            "Moved broken config to {}, saving works again",
+            broken
+        )))
+    }
+
+    fn spell_ignore(&mut self, word: &str) -> Result<MsgState, String> {
+        if word.is_empty() {
+            return Err("Usage: spellignore <word>".to_string());
+        }
+        self.config.spellcheck_ignore.push(word.to_string());
+        self.config.save_config()?;
+        Ok(MsgState::DynamicMsg(format!(
+            "Ignoring '{}' in spell check",
+            word
+        )))
+    }
+
+    fn set_filter(&mut self, query: &str) -> Result<MsgState, String> {
+        if query.is_empty() {
+            self.remember_position();
+            self.filter = None;
+            return Ok(MsgState::DynamicMsg("Filter cleared".to_string()));
+        }
+        let filter = Filter::parse(query)?;
+        let matches = filter.matching(&self.items).len();
+        self.filter = Some(filter);
+        self.clamp_to_visible();
+        Ok(MsgState::DynamicMsg(format!(
+            "Filter matched {} of {} steps",
+            matches,
+            self.items.len()
+        )))
+    }
+}
@@ -0,0 +1,39 @@
+use super::{App, MsgState};
+use crate::embedding;
+
+impl App {
+    // `:w [path]`, always the canonical json so a saved document diffs cleanly in review.
+    // compiled html can't be written back to, it's saved alongside as json instead
+    pub(super) fn write_document(&mut self, path: &str) -> Result<MsgState, String> {
+        let path = match path {
+            "" => self
+                .document
+                .clone()
+                .filter(|doc| doc.ends_with(".json"))
+                .ok_or_else(|| "No json file to write to, use :w <path>".to_string())?,
+            path => path.to_string(),
+        };
+        embedding::save_file(&path, &self.items)?;
+        self.document = Some(path.clone());
+        self.dirty = false;
+        let steps = self.items.iter().filter(|i| i.is_step()).count();
+        Ok(MsgState::DynamicMsg(format!(
+            "Wrote {} steps to {}",
+            steps, path
+        )))
+    }
+
+    // `:e <path>` opens a json document or compiled html, `:e!` drops unsaved changes
+    pub(super) fn edit_document(&mut self, path: &str, force: bool) -> Result<MsgState, String> {
+        if path.is_empty() {
+            return Err("Usage: e <path>".to_string());
+        }
+        if self.dirty && !force {
+            return Err("Unsaved changes, :w them first or :e! to discard".to_string());
+        }
+        let items = embedding::load_file(path)?;
+        self.load_items(items);
+        self.document = Some(path.to_string());
+        Ok(MsgState::DynamicMsg(format!("Opened {}", path)))
+    }
+}
@@ -0,0 +1,44 @@
+use super::{App, footer, overlay::Overlay};
+use crate::config::Config;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Text},
+    widgets::Paragraph,
+};
+
+impl App {
+    // shown in place of an empty uat table until the first step exists
+    pub(super) fn render_empty_state(&self, frame: &mut Frame, area: Rect) {
+        let config_path = Config::path().unwrap_or_else(|err| err);
+        let lines = vec![
+            Line::from(""),
+            Line::from("No steps yet").bold(),
+            Line::from(""),
+            Line::from("press o to create your first step, t for templates, ? for help"),
+            Line::from("or + to load a compiled uat from the clipboard"),
+            Line::from(""),
+            Line::from(format!("config: {}", config_path)).dim(),
+            Line::from(format!("editor: {}", self.config.editor())).dim(),
+        ];
+        let panel = Paragraph::new(Text::from(lines))
+            .style(self.colors.info_style())
+            .centered()
+            .block(self.colors.info_block());
+        frame.render_widget(panel, area);
+    }
+
+    // `?`, every key hint regardless of how many fit in the footer
+    pub(super) fn open_help(&mut self) {
+        let lines = footer::all_hints(
+            &self.window,
+            self.config.locale,
+            self.modifiers.clear_all_key(),
+        )
+        .into_iter()
+        .map(Line::from)
+        .collect();
+        self.overlay = Some(Overlay::new("Help", lines));
+    }
+}
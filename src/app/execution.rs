@@ -0,0 +1,54 @@
+use super::{App, MsgState};
+use crate::{
+    err_msg::WithErrMsg,
+    test_step::{Priority, StepStatus},
+};
+
+impl App {
+    pub(super) fn cycle_status(&mut self) -> Result<MsgState, String> {
+        let range = self
+            .selected_range()
+            .with_err_msg(&"No item is currently selected")?;
+        self.push_undo();
+        for item in &mut self.items[range] {
+            item.status = StepStatus::cycle(item.status);
+        }
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn set_status(&mut self, status: &str) -> Result<MsgState, String> {
+        let status = StepStatus::parse(status)?;
+        let range = self
+            .selected_range()
+            .with_err_msg(&"No item is currently selected")?;
+        self.push_undo();
+        for item in &mut self.items[range] {
+            item.status = status;
+        }
+        Ok(MsgState::Default)
+    }
+
+    // `:prio <level>` and `r1` `r2` `r3` `r0`, on the selection like :status
+    pub(super) fn set_priority(&mut self, priority: &str) -> Result<MsgState, String> {
+        let priority = Priority::parse(priority)?;
+        let range = self
+            .selected_range()
+            .with_err_msg(&"No item is currently selected")?;
+        self.push_undo();
+        for item in &mut self.items[range] {
+            item.priority = priority;
+        }
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn set_comment(&mut self, comment: &str) -> Result<MsgState, String> {
+        let range = self
+            .selected_range()
+            .with_err_msg(&"No item is currently selected")?;
+        self.push_undo();
+        for item in &mut self.items[range] {
+            item.comment = comment.to_string();
+        }
+        Ok(MsgState::Default)
+    }
+}
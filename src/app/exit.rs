@@ -0,0 +1,124 @@
+use super::{App, MsgState};
+use serde::Serialize;
+
+// why the tui stopped, an intentional quit is told apart from a failure so a wrapper
+// script only reacts to what the user meant
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", content = "detail", rename_all = "lowercase")]
+pub enum ExitReason {
+    Quit,
+    Signal(i32),
+    Error(String),
+}
+
+impl ExitReason {
+    pub fn new(result: &Result<(), String>, signal: Option<i32>) -> ExitReason {
+        match (result, signal) {
+            (Ok(()), _) => ExitReason::Quit,
+            (Err(_), Some(signal)) => ExitReason::Signal(signal),
+            (Err(err), None) => ExitReason::Error(err.clone()),
+        }
+    }
+}
+
+// the last file written by an export, `format` is the export format or exporter name
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LastExport {
+    pub path: String,
+    pub format: String,
+}
+
+// written to --exit-summary as json for scripts that wrap the tui
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ExitSummary {
+    pub exit: ExitReason,
+    pub steps: usize,
+    pub dirty: bool,
+    pub document: Option<String>,
+    pub compiled: bool,
+    pub last_export: Option<LastExport>,
+    pub backup_written: bool,
+}
+
+impl App {
+    // the quit keys end the event loop through here so run_loop can tell it wasn't a
+    // failure
+    pub(super) fn quit(&mut self) -> Result<MsgState, String> {
+        self.quitting = true;
+        Err("Quiting".to_string())
+    }
+
+    pub(super) fn exit_summary(&self, exit: ExitReason) -> ExitSummary {
+        ExitSummary {
+            exit,
+            steps: self.items.iter().filter(|item| item.is_step()).count(),
+            dirty: self.dirty,
+            document: self.document.clone(),
+            compiled: self.compiled,
+            last_export: self.last_export.clone(),
+            backup_written: self.backup_written,
+        }
+    }
+
+    // runs on the way out, so a summary that can't be written is only reported and
+    // never keeps the app from exiting
+    pub(super) fn write_exit_summary(&self, exit: ExitReason) {
+        let Some(path) = &self.exit_summary_path else {
+            return;
+        };
+        let written = serde_json::to_string_pretty(&self.exit_summary(exit))
+            .map_err(|err| err.to_string())
+            .and_then(|json| std::fs::write(path, json + "\n").map_err(|err| err.to_string()));
+        if let Err(err) = written {
+            eprintln!("Failed to write exit summary to {}: {}", path, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quitting_is_not_an_error() {
+        assert_eq!(ExitReason::new(&Ok(()), None), ExitReason::Quit);
+        let stopped: Result<(), String> = Err("Received signal 15".to_string());
+        assert_eq!(ExitReason::new(&stopped, Some(15)), ExitReason::Signal(15));
+        let failed: Result<(), String> = Err("Failed to read terminal event".to_string());
+        assert_eq!(
+            ExitReason::new(&failed, None),
+            ExitReason::Error("Failed to read terminal event".to_string())
+        );
+    }
+
+    #[test]
+    fn summary_json_shape() {
+        let summary = ExitSummary {
+            exit: ExitReason::Error("Failed to poll terminal events".to_string()),
+            steps: 3,
+            dirty: true,
+            document: None,
+            compiled: false,
+            last_export: Some(LastExport {
+                path: "uat.pdf".to_string(),
+                format: "pdf".to_string(),
+            }),
+            backup_written: true,
+        };
+        let json: serde_json::Value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "exit": {"reason": "error", "detail": "Failed to poll terminal events"},
+                "steps": 3,
+                "dirty": true,
+                "document": null,
+                "compiled": false,
+                "last_export": {"path": "uat.pdf", "format": "pdf"},
+                "backup_written": true,
+            })
+        );
+        let quit = serde_json::to_value(ExitReason::Quit).unwrap();
+        assert_eq!(quit, serde_json::json!({"reason": "quit"}));
+    }
+}
@@ -0,0 +1,154 @@
+use super::{App, MsgState, exit::LastExport};
+use crate::{
+    config::ExporterConfig,
+    err_msg::WithErrMsg,
+    export::{FORMAT_NAMES, Format, ado::AdoOptions, external, html::HtmlOptions, text},
+    shell, validate,
+};
+use ratatui::DefaultTerminal;
+
+impl App {
+    // a table with no steps is an empty shell that is easy to paste somewhere without
+    // noticing, `force` is the ! form of the command
+    pub(super) fn check_exportable(&self, force: bool) -> Result<(), String> {
+        if force || self.config.allow_empty_export || validate::has_steps(&self.items) {
+            return Ok(());
+        }
+        Err("No steps to export, :compile! or :export! does it anyway".to_string())
+    }
+
+    // `export <format> [path]`, prompting for the path when it is left off
+    pub(super) fn export_command(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        args: &str,
+        force: bool,
+    ) -> Result<MsgState, String> {
+        let (name, path) = args.split_once(' ').unwrap_or((args, ""));
+        if name.is_empty() {
+            let mut names: Vec<&str> = self.config.exporters.keys().map(String::as_str).collect();
+            names.sort();
+            names.insert(0, FORMAT_NAMES);
+            return Err(format!("Usage: export {} [path]", names.join("|")));
+        }
+        self.check_exportable(force)?;
+        // the built in formats win over an exporter with the same name
+        if !FORMAT_NAMES.split('|').any(|format| format == name)
+            && let Some(exporter) = self.config.exporters.get(name).cloned()
+        {
+            return self.export_external(terminal, name, &exporter, path.trim());
+        }
+        let mut format = Format::parse(name, AdoOptions::default(), self.config.plain_text)?;
+        if let Format::Pdf(pdf, _) = &mut format {
+            *pdf = self.config.pdf.clone();
+        }
+        if let Format::Print(print) | Format::Pdf(_, print) = &mut format {
+            print.source = self.document.clone();
+        }
+        let path = path.trim();
+        let path = if path.is_empty() {
+            self.prompt(terminal, &format!("{} output path", format.title()))?
+        } else {
+            path.to_string()
+        };
+        self.export_to(&format, &path)
+    }
+
+    // `Y`, for pasting into an email or a ticket that strips formatting
+    pub(super) fn copy_plain_text(&mut self) -> Result<MsgState, String> {
+        self.check_exportable(false)?;
+        self.normalize_before_export();
+        let text = text::export(
+            &self.items,
+            self.config.plain_text,
+            self.config.use_ac,
+            self.config.locale,
+            self.config.numbering_start,
+        );
+        self.clipboard()?
+            .set_text(text)
+            .with_err_msg(&"Failed to set clipboard content")?;
+        Ok(MsgState::DynamicMsg(
+            "PLAIN TEXT COPIED TO CLIPBOARD".to_string(),
+        ))
+    }
+
+    pub(super) fn export_to(&mut self, format: &Format, path: &str) -> Result<MsgState, String> {
+        let path = if path.trim().is_empty() {
+            format.default_path()
+        } else {
+            path.trim()
+        };
+        self.normalize_before_export();
+        format.write(&self.items, &HtmlOptions::from_config(&self.config), path)?;
+        // only confluence pages carry the embedding, the rest can't be loaded back
+        if matches!(format, Format::Confluence) {
+            self.document = Some(path.to_string());
+            self.dirty = false;
+        }
+        self.last_export = Some(LastExport {
+            path: path.to_string(),
+            format: format.name().to_string(),
+        });
+        Ok(MsgState::DynamicMsg(format!(
+            "Wrote {} to {} (:open to view it)",
+            format.title(),
+            path
+        )))
+    }
+
+    // a configured exporter: stderr goes to the message log in full, the footer gets the
+    // first line of it when the command fails
+    fn export_external(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        name: &str,
+        exporter: &ExporterConfig,
+        path: &str,
+    ) -> Result<MsgState, String> {
+        self.normalize_before_export();
+        let output = external::run(exporter, &self.items)?;
+        for line in output.stderr.lines().filter(|l| !l.trim().is_empty()) {
+            self.messages.push(&format!("{}: {}", name, line));
+        }
+        let exported = external::check(name, &output)?;
+        if exporter.clipboard {
+            self.clipboard()?
+                .set_text(exported)
+                .with_err_msg(&"Failed to set clipboard content")?;
+            return Ok(MsgState::DynamicMsg(format!(
+                "Copied {} export to the clipboard",
+                name
+            )));
+        }
+        let path = if path.is_empty() {
+            self.prompt(terminal, &format!("{} output path", name))?
+        } else {
+            path.to_string()
+        };
+        if path.trim().is_empty() {
+            return Err("No output path given".to_string());
+        }
+        let path = path.trim();
+        std::fs::write(path, exported).with_err_msg(&format!("Failed to write {}", path))?;
+        self.last_export = Some(LastExport {
+            path: path.to_string(),
+            format: name.to_string(),
+        });
+        Ok(MsgState::DynamicMsg(format!(
+            "Wrote {} to {} (:open to view it)",
+            name, path
+        )))
+    }
+
+    // `:open`, the last export in whatever the desktop opens that kind of file with
+    pub(super) fn open_last_export(&mut self) -> Result<MsgState, String> {
+        let path = self
+            .last_export
+            .as_ref()
+            .map(|export| export.path.clone())
+            .with_err_msg(&"Nothing exported yet")?;
+        shell::open(&path)?;
+        Ok(MsgState::DynamicMsg(format!("Opened {}", path)))
+    }
+}
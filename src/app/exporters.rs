@@ -0,0 +1,329 @@
+use super::{App, MsgState, overlay};
+use crate::{
+    config::Config,
+    export::{FORMAT_NAMES, Format, ado::AdoOptions},
+    fuzzy,
+};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::Stylize,
+    text::{Line, Text},
+    widgets::{Clear, Paragraph},
+};
+
+const PICKER_ROWS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Destination {
+    Clipboard,
+    File,
+}
+
+impl Destination {
+    fn label(self) -> &'static str {
+        match self {
+            Destination::Clipboard => "clipboard",
+            Destination::File => "file",
+        }
+    }
+}
+
+// one entry in the `$` picker. a new format implements this and goes in `registry`
+pub(super) trait Exporter {
+    fn name(&self) -> String;
+    fn title(&self) -> String;
+    fn destination(&self) -> Destination;
+    // asks for whatever it still needs, an output path for files
+    fn run(&self, app: &mut App, terminal: &mut DefaultTerminal) -> Result<MsgState, String>;
+}
+
+// what `$` did before there was a picker
+struct CompiledHtml;
+
+impl Exporter for CompiledHtml {
+    fn name(&self) -> String {
+        "html".to_string()
+    }
+
+    fn title(&self) -> String {
+        "Compiled HTML".to_string()
+    }
+
+    fn destination(&self) -> Destination {
+        Destination::Clipboard
+    }
+
+    fn run(&self, app: &mut App, _: &mut DefaultTerminal) -> Result<MsgState, String> {
+        app.compile_to_clipboard(false)
+    }
+}
+
+// `Y`
+struct PlainText;
+
+impl Exporter for PlainText {
+    fn name(&self) -> String {
+        "plain".to_string()
+    }
+
+    fn title(&self) -> String {
+        "Plain text".to_string()
+    }
+
+    fn destination(&self) -> Destination {
+        Destination::Clipboard
+    }
+
+    fn run(&self, app: &mut App, _: &mut DefaultTerminal) -> Result<MsgState, String> {
+        app.copy_plain_text()
+    }
+}
+
+// a format of `:export`, or an exporter from the config
+struct Export {
+    name: String,
+    title: String,
+    destination: Destination,
+}
+
+impl Exporter for Export {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn destination(&self) -> Destination {
+        self.destination
+    }
+
+    fn run(&self, app: &mut App, terminal: &mut DefaultTerminal) -> Result<MsgState, String> {
+        app.export_command(terminal, &self.name, false)
+    }
+}
+
+// in the order the picker lists them with nothing typed
+pub(super) fn registry(config: &Config) -> Vec<Box<dyn Exporter>> {
+    let mut exporters: Vec<Box<dyn Exporter>> = vec![Box::new(CompiledHtml), Box::new(PlainText)];
+    for name in FORMAT_NAMES.split('|') {
+        if let Ok(format) = Format::parse(name, AdoOptions::default(), config.plain_text) {
+            exporters.push(Box::new(Export {
+                name: name.to_string(),
+                title: format.title().to_string(),
+                destination: Destination::File,
+            }));
+        }
+    }
+    let mut external: Vec<(&String, bool)> = config
+        .exporters
+        .iter()
+        .filter(|(name, _)| !FORMAT_NAMES.split('|').any(|format| format == *name))
+        .map(|(name, exporter)| (name, exporter.clipboard))
+        .collect();
+    external.sort();
+    for (name, clipboard) in external {
+        exporters.push(Box::new(Export {
+            name: name.clone(),
+            title: "Configured exporter".to_string(),
+            destination: match clipboard {
+                true => Destination::Clipboard,
+                false => Destination::File,
+            },
+        }));
+    }
+    exporters
+}
+
+pub(super) struct ExportPicker {
+    query: String,
+    selected: usize,
+}
+
+impl ExportPicker {
+    pub(super) fn paste(&mut self, text: &str) -> usize {
+        let (text, dropped) = crate::text_input::clean_paste(text, false);
+        self.query.push_str(&text);
+        self.selected = 0;
+        dropped
+    }
+}
+
+// the registry filtered by the query, nothing typed keeps the registry order
+fn matches(config: &Config, query: &str) -> Vec<Box<dyn Exporter>> {
+    let mut exporters = registry(config);
+    if query.trim().is_empty() {
+        return exporters;
+    }
+    let names: Vec<String> = exporters.iter().map(|exporter| exporter.name()).collect();
+    let ranked: Vec<String> = fuzzy::rank(query, names.iter().map(String::as_str))
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    exporters.retain(|exporter| ranked.contains(&exporter.name()));
+    exporters.sort_by_key(|exporter| ranked.iter().position(|name| *name == exporter.name()));
+    exporters
+}
+
+impl App {
+    // `$`, Enter on the first entry compiles to the clipboard like `$` always has
+    pub(super) fn open_export_picker(&mut self) {
+        self.export_picker = Some(ExportPicker {
+            query: String::new(),
+            selected: 0,
+        });
+    }
+
+    fn run_exporter(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        exporter: &dyn Exporter,
+    ) -> Result<MsgState, String> {
+        let msg = exporter.run(self, terminal)?;
+        self.last_exporter = Some(exporter.name());
+        Ok(msg)
+    }
+
+    // `$$`, the last exporter run or the compiled html when there isn't one yet
+    fn rerun_exporter(&mut self, terminal: &mut DefaultTerminal) -> Result<MsgState, String> {
+        let name = self.last_exporter.clone().unwrap_or(CompiledHtml.name());
+        let exporter = registry(&self.config)
+            .into_iter()
+            .find(|exporter| exporter.name() == name)
+            .ok_or(format!("The {} exporter is no longer configured", name))?;
+        self.run_exporter(terminal, exporter.as_ref())
+    }
+
+    pub(super) fn handle_export_picker_keys(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        key: KeyEvent,
+    ) -> Result<MsgState, String> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        let count = match &self.export_picker {
+            Some(picker) => matches(&self.config, &picker.query).len(),
+            None => return Ok(MsgState::Default),
+        };
+        let Some(picker) = &mut self.export_picker else {
+            return Ok(MsgState::Default);
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let last = count.saturating_sub(1);
+        match key.code {
+            KeyCode::Esc => self.export_picker = None,
+            KeyCode::Down => picker.selected = (picker.selected + 1).min(last),
+            KeyCode::Char('j' | 'n') if ctrl => picker.selected = (picker.selected + 1).min(last),
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Char('k' | 'p') if ctrl => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                picker.query.pop();
+                picker.selected = 0;
+            }
+            KeyCode::Char('$') => {
+                self.export_picker = None;
+                return self.rerun_exporter(terminal);
+            }
+            KeyCode::Char(c) => {
+                picker.query.push(c);
+                picker.selected = 0;
+            }
+            KeyCode::Enter => {
+                let selected = picker.selected;
+                let query = picker.query.clone();
+                self.export_picker = None;
+                let Some(exporter) = matches(&self.config, &query).into_iter().nth(selected) else {
+                    return Ok(MsgState::Default);
+                };
+                return self.run_exporter(terminal, exporter.as_ref());
+            }
+            _ => {}
+        }
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn render_export_picker(&self, frame: &mut Frame) {
+        let Some(picker) = &self.export_picker else {
+            return;
+        };
+        let exporters = matches(&self.config, &picker.query);
+        let selected = picker.selected.min(exporters.len().saturating_sub(1));
+        let first = (selected + 1).saturating_sub(PICKER_ROWS);
+        let width = exporters
+            .iter()
+            .map(|exporter| exporter.name().chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines = vec![Line::from(format!("> {}▏", picker.query)), Line::from("")];
+        if exporters.is_empty() {
+            lines.push(Line::from("no matches").dim());
+        }
+        for (idx, exporter) in exporters.iter().enumerate().skip(first).take(PICKER_ROWS) {
+            let line = Line::from(vec![
+                format!("{:<width$}", exporter.name(), width = width).bold(),
+                format!("  {:<9}  ", exporter.destination().label()).into(),
+                exporter.title().dim(),
+            ]);
+            lines.push(match idx == selected {
+                true => line.style(self.colors.selected_row_style()),
+                false => line,
+            });
+        }
+        lines.push(Line::from(""));
+        let last = self.last_exporter.clone().unwrap_or(CompiledHtml.name());
+        lines.push(Line::from(format!("enter run · $ run {} again · esc close", last)).dim());
+        let area = overlay::popup_area(frame.area(), &lines);
+        let popup = Paragraph::new(Text::from(lines))
+            .style(self.colors.info_style())
+            .block(self.colors.info_block().title(" Export "));
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExporterConfig;
+
+    fn names(exporters: &[Box<dyn Exporter>]) -> Vec<String> {
+        exporters.iter().map(|exporter| exporter.name()).collect()
+    }
+
+    #[test]
+    fn configured_exporters_join_the_built_in_ones() {
+        let mut config = Config::default();
+        let exporter = |clipboard| ExporterConfig {
+            command: "jira-export".to_string(),
+            clipboard,
+            timeout_secs: 5,
+        };
+        config.exporters.insert("jira".to_string(), exporter(true));
+        // shadowed by the built in format, as in :export
+        config.exporters.insert("text".to_string(), exporter(false));
+        let all = registry(&config);
+        assert_eq!(
+            names(&all),
+            vec![
+                "html",
+                "plain",
+                "junit",
+                "ado",
+                "confluence",
+                "text",
+                "print",
+                "pdf",
+                "jira"
+            ]
+        );
+        assert_eq!(all[8].destination(), Destination::Clipboard);
+        assert_eq!(all[2].destination(), Destination::File);
+
+        assert_eq!(names(&matches(&config, "jr")), vec!["jira"]);
+        assert_eq!(names(&matches(&config, "pd")), vec!["pdf"]);
+        assert!(matches(&config, "zzz").is_empty());
+    }
+}
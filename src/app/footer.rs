@@ -0,0 +1,290 @@
+use super::{App, InputMode, MsgState, Window};
+use crate::locale::{Key, Locale};
+use ratatui::layout::{Position, Rect};
+use unicode_width::UnicodeWidthStr;
+
+const SEPARATOR: &str = " | ";
+const GRID_GAP: &str = "   ";
+const MAX_HINT_LINES: usize = 4;
+// the footer keeps its old two line height even for a single line message
+const MIN_FOOTER_LINES: usize = 2;
+
+// lower priority numbers are kept longest when the terminal is too narrow for all of them
+#[derive(Clone, Copy)]
+pub(super) struct Hint {
+    key: &'static str,
+    desc: Key,
+    priority: u8,
+}
+
+const fn hint(key: &'static str, desc: Key, priority: u8) -> Hint {
+    Hint {
+        key,
+        desc,
+        priority,
+    }
+}
+
+const UAT_HINTS: &[Hint] = &[
+    hint("q", Key::HintQuit, 0),
+    hint("k/j", Key::HintMoveUpDown, 0),
+    hint("Enter", Key::HintEdit, 0),
+    hint("$", Key::HintCompile, 1),
+    hint("Y", Key::HintCopyPlainText, 2),
+    hint("+", Key::HintLoadClipboard, 1),
+    hint("O/o", Key::HintInsertAboveBelow, 1),
+    hint("A/a", Key::HintBlankAboveBelow, 2),
+    hint("d", Key::HintDeleteToRegister, 1),
+    hint("P/p", Key::HintPasteAboveBelow, 1),
+    hint("t", Key::HintTemplates, 2),
+    hint(":", Key::HintCommand, 0),
+    hint("Tab", Key::HintDetails, 2),
+    hint("K", Key::HintPeek, 2),
+    hint("M", Key::HintMove, 2),
+];
+
+// clear-all is bound to whatever this terminal can actually send, see keyboard::Modifiers
+fn uat_hints(clear_all: &'static str) -> Vec<Hint> {
+    let mut hints = UAT_HINTS.to_vec();
+    hints.push(hint(clear_all, Key::HintClearAll, 3));
+    hints
+}
+
+const REORDER_HINTS: &[Hint] = &[
+    hint("k/j", Key::HintMoveUpDown, 0),
+    hint("Enter", Key::HintDrop, 0),
+    hint("Esc", Key::HintCancel, 0),
+    hint("Home/G", Key::HintMoveTopBottom, 1),
+];
+
+const CELL_EDIT_HINTS: &[Hint] = &[
+    hint("Enter", Key::HintSave, 0),
+    hint("Esc", Key::HintCancel, 0),
+    hint("Alt+Enter", Key::HintNewLine, 1),
+    hint("Home/End", Key::HintStartEndOfLine, 2),
+];
+
+const TEMPLATE_HINTS: &[Hint] = &[
+    hint("Esc", Key::HintBack, 0),
+    hint("k/j", Key::HintMoveUpDown, 0),
+    hint("Enter", Key::HintLoad, 0),
+    hint("$", Key::HintSaveAsTemplate, 1),
+    hint("m", Key::HintSaveBuiltinAsMine, 2),
+];
+
+const SNIPPET_HINTS: &[Hint] = &[
+    hint("Esc", Key::HintBack, 0),
+    hint("k/j", Key::HintMoveUpDown, 0),
+    hint("Enter", Key::HintInsertBelowCursor, 0),
+    hint("r", Key::HintRename, 1),
+    hint("d", Key::HintDelete, 1),
+];
+
+fn entry(hint: &Hint, locale: Locale) -> String {
+    format!("({}) {}", hint.key, locale.text(hint.desc))
+}
+
+pub(super) fn all_hints(window: &Window, locale: Locale, clear_all: &'static str) -> Vec<String> {
+    let hints = match window {
+        Window::Uat => uat_hints(clear_all),
+        Window::Template => TEMPLATE_HINTS.to_vec(),
+        Window::Snippets => SNIPPET_HINTS.to_vec(),
+    };
+    hints.iter().map(|hint| entry(hint, locale)).collect()
+}
+
+// cuts a line down to `width` columns, ending it with an ellipsis when anything was lost
+pub(super) fn fit_message(msg: &str, width: usize) -> String {
+    if msg.width() <= width {
+        return msg.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in msg.chars() {
+        let w = c.to_string().width();
+        if used + w + 1 > width {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
+fn wrap(entries: &[String], width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for entry in entries {
+        match lines.last_mut() {
+            Some(line) if line.width() + SEPARATOR.len() + entry.width() <= width => {
+                line.push_str(SEPARATOR);
+                line.push_str(entry);
+            }
+            _ => lines.push(fit_message(entry, width)),
+        }
+    }
+    lines
+}
+
+// every row is padded to the same width so the columns still line up once centered
+fn grid(entries: &[String], width: usize) -> Vec<String> {
+    let col_width = entries.iter().map(|e| e.width()).max().unwrap_or(0);
+    let cols = ((width + GRID_GAP.len()) / (col_width + GRID_GAP.len())).max(1);
+    entries
+        .chunks(cols)
+        .map(|row| {
+            (0..cols)
+                .map(|col| {
+                    let entry = row.get(col).map(String::as_str).unwrap_or("");
+                    format!("{}{}", entry, " ".repeat(col_width - entry.width()))
+                })
+                .collect::<Vec<_>>()
+                .join(GRID_GAP)
+        })
+        .map(|line| fit_message(&line, width))
+        .collect()
+}
+
+// aligned columns when that takes no more lines than wrapping, dropping the least
+// important hints until everything fits in `max_lines`
+pub(super) fn layout_hints(
+    hints: &[Hint],
+    width: usize,
+    max_lines: usize,
+    locale: Locale,
+) -> Vec<String> {
+    let mut hints: Vec<&Hint> = hints.iter().collect();
+    loop {
+        let entries: Vec<String> = hints.iter().map(|h| entry(h, locale)).collect();
+        let wrapped = wrap(&entries, width);
+        let grid = grid(&entries, width);
+        let lines = if grid.len() <= wrapped.len() {
+            grid
+        } else {
+            wrapped
+        };
+        if lines.len() <= max_lines || hints.len() <= 1 {
+            return lines;
+        }
+        let drop = hints
+            .iter()
+            .enumerate()
+            .max_by_key(|(idx, hint)| (hint.priority, *idx))
+            .map(|(idx, _)| idx)
+            .unwrap_or(0);
+        hints.remove(drop);
+    }
+}
+
+impl App {
+    // what the footer shows for a given inner width, at least MIN_FOOTER_LINES long
+    pub(super) fn footer_lines(&self, width: usize) -> Vec<String> {
+        let locale = self.config.locale;
+        let mut lines = match (&self.msg_state, &self.input_mode) {
+            (_, InputMode::Command(input)) => {
+                let (lines, _) = input.view(width.saturating_sub(1) as u16, 1);
+                vec![format!(":{}", lines.concat())]
+            }
+            (_, InputMode::Prefix(digits)) => vec![digits.clone()],
+            (MsgState::Default, _) if self.cell_edit.is_some() => {
+                layout_hints(CELL_EDIT_HINTS, width, MAX_HINT_LINES, locale)
+            }
+            (MsgState::Default, _) => {
+                let hints = match self.window {
+                    Window::Uat if self.reorder.is_some() => REORDER_HINTS.to_vec(),
+                    Window::Uat => uat_hints(self.modifiers.clear_all_key()),
+                    Window::Template => TEMPLATE_HINTS.to_vec(),
+                    Window::Snippets => SNIPPET_HINTS.to_vec(),
+                };
+                layout_hints(&hints, width, MAX_HINT_LINES, locale)
+            }
+            (MsgState::Compile, _) => vec![fit_message(locale.text(Key::Compiled), width)],
+            (MsgState::Yanked, _) => vec![fit_message(locale.text(Key::Yanked), width)],
+            (MsgState::Loaded, _) => vec![fit_message(locale.text(Key::Loaded), width)],
+            (MsgState::DynamicMsg(msg), _) => vec![fit_message(msg, width)],
+        };
+        lines.resize(lines.len().max(MIN_FOOTER_LINES), String::new());
+        lines
+    }
+
+    // where the terminal cursor goes on the command line, the line is centered like the rest
+    // of the footer and + 1 is for the border
+    pub(super) fn command_cursor(&self, area: Rect) -> Option<Position> {
+        let InputMode::Command(input) = &self.input_mode else {
+            return None;
+        };
+        let inner = area.width.saturating_sub(2);
+        let (lines, (column, _)) = input.view(inner.saturating_sub(1), 1);
+        let width = 1 + lines.concat().width() as u16;
+        Some(Position::new(
+            area.x + 1 + inner.saturating_sub(width) / 2 + 1 + column,
+            area.y + 1,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_terminals_get_aligned_columns() {
+        let lines = layout_hints(UAT_HINTS, 240, MAX_HINT_LINES, Locale::En);
+        assert_eq!(
+            lines,
+            vec![
+                "(q) quit                   (k/j) move up/down         (Enter) edit               ($) export                 (Y) copy plain text        (+) load from clipboard    (O/o) insert above/below   (A/a) blank above/below    (d) delete to reg       ",
+                "(P/p) paste above/below    (t) templates & config     (:) command                (Tab) details              (K) peek                   (M) move                                                                                                 ",
+            ]
+        );
+    }
+
+    #[test]
+    fn eighty_columns_fits_without_clipping() {
+        let lines = layout_hints(UAT_HINTS, 78, MAX_HINT_LINES, Locale::En);
+        assert_eq!(
+            lines,
+            vec![
+                "(q) quit | (k/j) move up/down | (Enter) edit | ($) export",
+                "(Y) copy plain text | (+) load from clipboard | (O/o) insert above/below",
+                "(A/a) blank above/below | (d) delete to reg | (P/p) paste above/below",
+                "(t) templates & config | (:) command | (Tab) details | (K) peek | (M) move",
+            ]
+        );
+        assert!(lines.iter().all(|l| l.width() <= 78));
+    }
+
+    #[test]
+    fn narrow_terminals_drop_low_priority_hints() {
+        let lines = layout_hints(UAT_HINTS, 40, MAX_HINT_LINES, Locale::En);
+        assert_eq!(
+            lines,
+            vec![
+                "(q) quit | (k/j) move up/down",
+                "(Enter) edit | ($) export",
+                "(+) load from clipboard",
+                "(O/o) insert above/below | (:) command",
+            ]
+        );
+    }
+
+    #[test]
+    fn clear_all_hint_follows_the_terminal() {
+        let hints = all_hints(&Window::Uat, Locale::En, ":clear");
+        assert_eq!(hints.last().unwrap(), "(:clear) clear all");
+        let hints = all_hints(&Window::Uat, Locale::En, "Ctrl+Shift+d");
+        assert_eq!(hints.last().unwrap(), "(Ctrl+Shift+d) clear all");
+        // the first hint dropped on a narrow footer
+        let lines = layout_hints(&uat_hints(":clear"), 40, MAX_HINT_LINES, Locale::En);
+        assert!(lines.iter().all(|line| !line.contains("clear all")));
+    }
+
+    #[test]
+    fn messages_truncate_with_an_ellipsis() {
+        assert_eq!(fit_message("YANKED TO REGISTER", 40), "YANKED TO REGISTER");
+        assert_eq!(fit_message("YANKED TO REGISTER", 10), "YANKED TO…");
+        assert_eq!(fit_message("日本語のメッセージ", 7), "日本語…");
+    }
+}
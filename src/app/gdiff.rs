@@ -0,0 +1,85 @@
+use super::{App, MsgState, footer, overlay::Overlay};
+use crate::{
+    diff::{self, Change},
+    embedding,
+    err_msg::WithErrMsg,
+    git,
+};
+use ratatui::{style::Stylize, text::Line};
+
+const TITLE_WIDTH: usize = 60;
+
+fn change_lines(changes: &[Change]) -> Vec<Line<'static>> {
+    let heading = |sign: &str, label: &str, title: &str| {
+        format!(
+            "{} {:<6} {}",
+            sign,
+            label,
+            footer::fit_message(title, TITLE_WIDTH)
+        )
+    };
+    let mut lines = Vec::new();
+    for change in changes {
+        match change {
+            Change::Added { label, title } => {
+                lines.push(Line::from(heading("+", label, title)).green());
+            }
+            Change::Removed { label, title } => {
+                lines.push(Line::from(heading("-", label, title)).red());
+            }
+            Change::Modified {
+                label,
+                title,
+                fields,
+            } => {
+                lines.push(Line::from(heading("~", label, title)).bold());
+                for field in fields {
+                    lines.push(Line::from(format!("    {}", field.field)).dim());
+                    lines.extend(
+                        field
+                            .removed
+                            .iter()
+                            .map(|l| Line::from(format!("      - {}", l)).red()),
+                    );
+                    lines.extend(
+                        field
+                            .added
+                            .iter()
+                            .map(|l| Line::from(format!("      + {}", l)).green()),
+                    );
+                }
+            }
+        }
+    }
+    lines
+}
+
+impl App {
+    // `:gdiff`, what changed in the document since it was last committed
+    pub(super) fn git_diff(&mut self) -> Result<MsgState, String> {
+        let path = self
+            .document
+            .clone()
+            .with_err_msg(&"The document has no file yet, :w it first")?;
+        let committed = git::show_head(&path)?;
+        let old = embedding::parse_file_content(&committed).map_err(|err| {
+            format!(
+                "HEAD version of {} is not a document this version can read: {}",
+                path, err
+            )
+        })?;
+        let changes = diff::diff(&old, &self.items, self.config.numbering_start);
+        if changes.is_empty() {
+            return Ok(MsgState::DynamicMsg(format!(
+                "No changes to {} since HEAD",
+                path
+            )));
+        }
+        let summary = diff::describe(&changes);
+        self.overlay = Some(Overlay::new(
+            &format!("Changes since HEAD: {}", summary),
+            change_lines(&changes),
+        ));
+        Ok(MsgState::DynamicMsg(format!("HEAD: {}", summary)))
+    }
+}
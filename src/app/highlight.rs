@@ -0,0 +1,150 @@
+use super::{App, ITEM_HEIGHT, Window};
+use crate::{filter::Filter, query::Field, test_step};
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span, Text},
+};
+use std::ops::Range;
+
+// a row's first line is the annotation, the rest is the cell's content
+const CONTENT_LINES: usize = ITEM_HEIGHT - 1;
+const TRUNCATED: &str = "…";
+
+// `line` cut into (text, matched) pieces around `ranges`, which may overlap
+pub(super) fn split_matches<'a>(line: &'a str, ranges: &[Range<usize>]) -> Vec<(&'a str, bool)> {
+    let mut pieces = Vec::new();
+    let mut at = 0;
+    for range in ranges {
+        let start = range.start.max(at);
+        let end = range.end.min(line.len());
+        if start >= end {
+            continue;
+        }
+        if start > at {
+            pieces.push((&line[at..start], false));
+        }
+        pieces.push((&line[start..end], true));
+        at = end;
+    }
+    if at < line.len() || pieces.is_empty() {
+        pieces.push((&line[at..], false));
+    }
+    pieces
+}
+
+impl App {
+    // the search wins over the filter when both are set, templates are never searched
+    pub(super) fn active_query(&self) -> Option<&Filter> {
+        match self.window {
+            Window::Uat => self.search.as_ref().or(self.filter.as_ref()),
+            Window::Template | Window::Snippets => None,
+        }
+    }
+
+    // what each content column of the table holds, in cell_contents order
+    pub(super) fn column_fields(&self) -> Vec<Field> {
+        let mut fields = vec![Field::Instructions];
+        if self.show_test_data() {
+            fields.push(Field::TestData);
+        }
+        fields.push(Field::Expected);
+        if self.show_ac() {
+            fields.push(Field::Ac);
+        }
+        fields
+    }
+
+    // a table cell with its matches highlighted. content that doesn't fit in the row ends
+    // in an ellipsis, highlighted too when a match is among the hidden lines
+    pub(super) fn cell_text(
+        &self,
+        field: Option<Field>,
+        content: &str,
+        first: Line<'static>,
+    ) -> Text<'static> {
+        let content_lines: Vec<&str> = content.lines().collect();
+        let mut lines = vec![first];
+        let truncated = content_lines.len() > CONTENT_LINES;
+        let shown = if truncated {
+            CONTENT_LINES - 1
+        } else {
+            content_lines.len()
+        };
+        let code = test_step::code_lines(content_lines.iter().copied());
+        lines.extend(
+            content_lines[..shown]
+                .iter()
+                .zip(code)
+                .map(|(line, in_code)| self.code_line(self.highlight_line(field, line), in_code)),
+        );
+        if truncated {
+            let hidden_match = self.active_query().is_some_and(|query| {
+                content_lines[shown..]
+                    .iter()
+                    .any(|line| !query.match_ranges(field, line).is_empty())
+            });
+            lines.push(if hidden_match {
+                Line::from(Span::styled(TRUNCATED, self.colors.match_style()))
+            } else {
+                Line::from(TRUNCATED).dim()
+            });
+        }
+        Text::from(lines)
+    }
+
+    // code keeps its own background so a command stands apart from the prose around it
+    pub(super) fn code_line(&self, line: Line<'static>, in_code: bool) -> Line<'static> {
+        match in_code {
+            true => line.patch_style(self.colors.code_style()),
+            false => line,
+        }
+    }
+
+    // one line of `field`, with whatever the active query matched in it highlighted
+    pub(super) fn highlight_line(&self, field: Option<Field>, line: &str) -> Line<'static> {
+        let Some(query) = self.active_query() else {
+            return Line::from(line.to_string());
+        };
+        let ranges = query.match_ranges(field, line);
+        Line::from(
+            split_matches(line, &ranges)
+                .into_iter()
+                .map(|(text, matched)| {
+                    if matched {
+                        Span::styled(text.to_string(), self.colors.match_style())
+                    } else {
+                        Span::raw(text.to_string())
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_split_around_matches() {
+        assert_eq!(
+            split_matches("order ORD-12 and ORD-3", &[6..12, 17..22]),
+            vec![
+                ("order ", false),
+                ("ORD-12", true),
+                (" and ", false),
+                ("ORD-3", true)
+            ]
+        );
+        assert_eq!(split_matches("abc", &[]), vec![("abc", false)]);
+        assert_eq!(split_matches("", &[]), vec![("", false)]);
+    }
+
+    #[test]
+    fn overlapping_matches_merge() {
+        assert_eq!(
+            split_matches("abcdef", &[0..3, 2..5]),
+            vec![("abc", true), ("de", true), ("f", false)]
+        );
+    }
+}
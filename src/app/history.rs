@@ -0,0 +1,56 @@
+use super::{App, ITEM_HEIGHT, MsgState};
+use crate::test_step::TestStep;
+
+const UNDO_LIMIT: usize = 100;
+
+#[derive(Default)]
+pub(super) struct History {
+    undo: Vec<Vec<TestStep>>,
+    redo: Vec<Vec<TestStep>>,
+}
+
+impl App {
+    // snapshot the items before a mutation so it can be undone
+    pub(super) fn push_undo(&mut self) {
+        self.push_undo_snapshot(self.items.clone());
+    }
+
+    // for mutations that can only snapshot before borrowing the items mutably
+    pub(super) fn push_undo_snapshot(&mut self, snapshot: Vec<TestStep>) {
+        self.history.undo.push(snapshot);
+        self.dirty = true;
+        if self.history.undo.len() > UNDO_LIMIT {
+            self.history.undo.remove(0);
+        }
+        self.history.redo.clear();
+    }
+
+    fn restore_snapshot(&mut self, items: Vec<TestStep>) {
+        self.items = items;
+        self.dirty = true;
+        let max = self.items.len().saturating_sub(1);
+        let i = self.state.selected().unwrap_or(0).min(max);
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+    }
+
+    pub(super) fn undo(&mut self) -> Result<MsgState, String> {
+        let Some(items) = self.history.undo.pop() else {
+            return Err("Already at oldest change".to_string());
+        };
+        let current = std::mem::take(&mut self.items);
+        self.history.redo.push(current);
+        self.restore_snapshot(items);
+        Ok(MsgState::DynamicMsg("Undid last change".to_string()))
+    }
+
+    pub(super) fn redo(&mut self) -> Result<MsgState, String> {
+        let Some(items) = self.history.redo.pop() else {
+            return Err("Already at newest change".to_string());
+        };
+        let current = std::mem::take(&mut self.items);
+        self.history.undo.push(current);
+        self.restore_snapshot(items);
+        Ok(MsgState::DynamicMsg("Redid last change".to_string()))
+    }
+}
@@ -0,0 +1,126 @@
+use super::{App, MsgState};
+use crate::{export::first_line, shell, test_step::TestStep, timestamp};
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+// hooks run off the ui thread, each one hands back the footer message it ends with
+#[derive(Default)]
+pub(super) struct Hooks {
+    running: Vec<Receiver<String>>,
+}
+
+// what the hook can tell about the document without parsing the html
+fn hook_env(items: &[TestStep], document: Option<&str>) -> Vec<(&'static str, String)> {
+    let title = document
+        .and_then(|path| Path::new(path).file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .or_else(|| {
+            items
+                .iter()
+                .find(|item| item.is_new_section)
+                .map(|item| first_line(&item.instructions))
+        })
+        .unwrap_or_else(|| "UAT".to_string());
+    vec![
+        (
+            "UAT_STEP_COUNT",
+            items.iter().filter(|i| i.is_step()).count().to_string(),
+        ),
+        (
+            "UAT_SECTION_COUNT",
+            items
+                .iter()
+                .filter(|i| i.is_new_section)
+                .count()
+                .to_string(),
+        ),
+        ("UAT_DOC_TITLE", title),
+        ("UAT_DOC_PATH", document.unwrap_or_default().to_string()),
+        ("UAT_DATE", timestamp::format_date(timestamp::now())),
+    ]
+}
+
+fn run_hook(cmd: &str, html: &str, env: &[(&str, String)], timeout: Duration) -> String {
+    match shell::run_timeout_env(cmd, html, timeout, env) {
+        Ok(output) if output.status.success() => "Post-compile hook finished".to_string(),
+        Ok(output) => format!(
+            "Post-compile hook failed ({}) {}",
+            output.status,
+            output.first_stderr_line()
+        )
+        .trim_end()
+        .to_string(),
+        Err(err) => format!("Post-compile hook: {}", err),
+    }
+}
+
+impl App {
+    // the clipboard already holds the html by now, whatever the hook does
+    pub(super) fn start_post_compile(&mut self, html: String) {
+        let Some(cmd) = self.config.post_compile_cmd.clone() else {
+            return;
+        };
+        let env = hook_env(&self.items, self.document.as_deref());
+        let timeout = Duration::from_secs(self.config.post_compile_timeout_secs);
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(run_hook(&cmd, &html, &env, timeout));
+        });
+        self.hooks.running.push(receiver);
+    }
+
+    // the message of a hook that has finished since the last call
+    pub(super) fn finished_hook(&mut self) -> Option<MsgState> {
+        let mut finished = None;
+        self.hooks
+            .running
+            .retain(|receiver| match receiver.try_recv() {
+                Ok(msg) => {
+                    finished = Some(msg);
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                Err(TryRecvError::Disconnected) => false,
+            });
+        finished.map(MsgState::DynamicMsg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_describes_the_document() {
+        let items = vec![
+            TestStep::section("Checkout"),
+            TestStep::new().with_instructions("pay"),
+        ];
+        let env = hook_env(&items, None);
+        assert_eq!(env[0], ("UAT_STEP_COUNT", "1".to_string()));
+        assert_eq!(env[2], ("UAT_DOC_TITLE", "Checkout".to_string()));
+        let env = hook_env(&items, Some("/share/release_42.html"));
+        assert_eq!(env[2], ("UAT_DOC_TITLE", "release_42".to_string()));
+    }
+
+    #[test]
+    fn hook_results_become_footer_messages() {
+        let env = vec![("UAT_STEP_COUNT", "3".to_string())];
+        let timeout = Duration::from_secs(10);
+        assert_eq!(
+            run_hook("cat > /dev/null", "<table>", &env, timeout),
+            "Post-compile hook finished"
+        );
+        assert_eq!(
+            run_hook("echo $UAT_STEP_COUNT steps >&2; exit 4", "", &env, timeout),
+            "Post-compile hook failed (exit status: 4) 3 steps"
+        );
+        assert_eq!(
+            run_hook("sleep 10", "", &env, Duration::from_secs(1)),
+            "Post-compile hook: Command timed out after 1s: sleep 10"
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use super::{App, MsgState, overlay, overlay::Overlay};
+use crate::{
+    err_msg::WithErrMsg,
+    import::{
+        external,
+        html::{Imported, Table},
+        mapping::{self, FIELDS, Field},
+        sheet,
+    },
+    test_step::TestStep,
+    timestamp,
+};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Clear, Paragraph},
+};
+
+// values shown next to each column in the mapping overlay
+const SAMPLES: usize = 3;
+const SAMPLE_WIDTH: usize = 24;
+const FIELD_LIST_WIDTH: u16 = 16;
+
+// a spreadsheet whose headers weren't recognised, waiting on the user to say which
+// column is which
+pub(super) struct ColumnMapping {
+    path: String,
+    table: Table,
+    fields: Vec<Field>,
+    selected: usize,
+    // the field list is open, on this field
+    choosing: Option<usize>,
+}
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(SAMPLE_WIDTH) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+impl App {
+    // best effort for tables written by hand, anything with an embedding should
+    // go through load_from_clipboard instead
+    pub(super) fn import_html(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        path: &str,
+    ) -> Result<MsgState, String> {
+        let (name, rest) = path.split_once(' ').unwrap_or((path, ""));
+        if let Some(importer) = self.config.importers.get(name).cloned() {
+            let rest = rest.trim();
+            if rest.is_empty() {
+                return Err(format!("Usage: import {} <path>", name));
+            }
+            let items = external::run(name, &importer, rest)?;
+            return self.add_imported(terminal, items, rest, Some(rest));
+        }
+        if sheet::is_sheet(path) {
+            return self.import_sheet(terminal, path);
+        }
+        let html = if path.is_empty() {
+            let clipboard = self.clipboard()?;
+            match clipboard.get_html() {
+                Ok(html) => html,
+                Err(_) => clipboard
+                    .get_text()
+                    .with_err_msg(&"Failed to get HTML from system clipboard")?,
+            }
+        } else {
+            std::fs::read_to_string(path).with_err_msg(&format!("Failed to read {}", path))?
+        };
+
+        let table = Table::parse(&html)?;
+        let mut mapping = table.guess_mapping();
+        let overrides = self.prompt(
+            terminal,
+            &format!(
+                "Columns {} (enter to accept, or e.g. 2=expected 3=ac)",
+                table.describe_mapping(&mapping)
+            ),
+        )?;
+        table.apply_overrides(&mut mapping, &overrides)?;
+
+        let mut imported = table.import(&mapping);
+        self.stamp_imported(&mut imported.items);
+        let count = imported.items.len();
+        self.load_items(imported.items);
+        self.document = (!path.is_empty()).then(|| path.to_string());
+
+        if imported.warnings.is_empty() {
+            return Ok(MsgState::DynamicMsg(format!("Imported {} rows", count)));
+        }
+        self.show_import_warnings(&imported.warnings);
+        Ok(MsgState::DynamicMsg(format!(
+            "Imported {} rows with {} warnings",
+            count,
+            imported.warnings.len()
+        )))
+    }
+
+    pub(super) fn stamp_imported(&self, items: &mut [TestStep]) {
+        let now = timestamp::now();
+        let author = self.config.author();
+        for item in items {
+            item.created_at = Some(now);
+            item.updated_at = Some(now);
+            item.author = author.clone();
+        }
+    }
+
+    fn show_import_warnings(&mut self, warnings: &[String]) {
+        let lines = warnings
+            .iter()
+            .map(|warning| warning.clone().into())
+            .collect();
+        self.overlay = Some(Overlay::new("Import warnings", lines));
+    }
+
+    // csv, xlsx and the like. headers seen before or recognised outright import straight
+    // away, anything else opens the mapping overlay
+    fn import_sheet(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        path: &str,
+    ) -> Result<MsgState, String> {
+        let table = sheet::read(path)?;
+        let (fields, confident) = mapping::resolve(&table.headers, &self.config.import_mappings);
+        if confident {
+            return self.finish_sheet(terminal, path, &table, &fields);
+        }
+        self.column_mapping = Some(ColumnMapping {
+            path: path.to_string(),
+            table,
+            fields,
+            selected: 0,
+            choosing: None,
+        });
+        Ok(MsgState::DynamicMsg(
+            "Pick a field for each column, then i to import".to_string(),
+        ))
+    }
+
+    fn finish_sheet(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        path: &str,
+        table: &Table,
+        fields: &[Field],
+    ) -> Result<MsgState, String> {
+        let Imported {
+            mut items,
+            warnings,
+        } = table.import(fields);
+        if items.is_empty() {
+            return Err(format!("No steps found in {}", path));
+        }
+        self.stamp_imported(&mut items);
+        // :w shouldn't write json over the spreadsheet
+        let msg = self.add_imported(terminal, items, path, None)?;
+        if warnings.is_empty() {
+            return Ok(msg);
+        }
+        self.show_import_warnings(&warnings);
+        Ok(MsgState::DynamicMsg(format!(
+            "Imported {} with {} warnings",
+            path,
+            warnings.len()
+        )))
+    }
+
+    pub(super) fn handle_column_mapping_keys(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        key: KeyEvent,
+    ) -> Result<MsgState, String> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        let Some(mapping) = &mut self.column_mapping else {
+            return Ok(MsgState::Default);
+        };
+        let last = mapping.fields.len().saturating_sub(1);
+        match (key.code, mapping.choosing) {
+            (KeyCode::Char('j') | KeyCode::Down, Some(field)) => {
+                mapping.choosing = Some((field + 1).min(FIELDS.len() - 1));
+            }
+            (KeyCode::Char('k') | KeyCode::Up, Some(field)) => {
+                mapping.choosing = Some(field.saturating_sub(1));
+            }
+            (KeyCode::Enter, Some(field)) => {
+                mapping.fields[mapping.selected] = FIELDS[field];
+                mapping.choosing = None;
+                mapping.selected = (mapping.selected + 1).min(last);
+            }
+            (KeyCode::Esc, Some(_)) => mapping.choosing = None,
+            (KeyCode::Char('j') | KeyCode::Down, None) => {
+                mapping.selected = (mapping.selected + 1).min(last);
+            }
+            (KeyCode::Char('k') | KeyCode::Up, None) => {
+                mapping.selected = mapping.selected.saturating_sub(1);
+            }
+            (KeyCode::Enter, None) => {
+                let current = mapping.fields[mapping.selected];
+                mapping.choosing = FIELDS.iter().position(|field| *field == current);
+            }
+            (KeyCode::Char('i'), None) => {
+                mapping::check(&mapping.fields)?;
+                let Some(mapping) = self.column_mapping.take() else {
+                    return Ok(MsgState::Default);
+                };
+                self.config.import_mappings.insert(
+                    mapping::fingerprint(&mapping.table.headers),
+                    mapping.fields.clone(),
+                );
+                let msg =
+                    self.finish_sheet(terminal, &mapping.path, &mapping.table, &mapping.fields)?;
+                self.config.save_config()?;
+                return Ok(msg);
+            }
+            (KeyCode::Esc | KeyCode::Char('q'), None) => {
+                self.column_mapping = None;
+                return Ok(MsgState::DynamicMsg("Import cancelled".to_string()));
+            }
+            _ => {}
+        }
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn render_column_mapping(&self, frame: &mut Frame) {
+        let Some(mapping) = &self.column_mapping else {
+            return;
+        };
+        let width = mapping
+            .table
+            .headers
+            .iter()
+            .map(|header| header.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines: Vec<Line> = Vec::new();
+        for (idx, (header, field)) in mapping
+            .table
+            .headers
+            .iter()
+            .zip(&mapping.fields)
+            .enumerate()
+        {
+            let samples: Vec<String> = mapping
+                .table
+                .samples(idx, SAMPLES)
+                .iter()
+                .map(|sample| shorten(sample))
+                .collect();
+            let line = Line::from(vec![
+                Span::from(format!("{:<12}", field.name())).bold(),
+                Span::from(format!("{:<width$}  ", header, width = width)),
+                Span::from(samples.join(" · ")).dim(),
+            ]);
+            lines.push(match idx == mapping.selected {
+                true => line.style(self.colors.selected_row_style()),
+                false => line,
+            });
+        }
+        lines.push(Line::from(""));
+        if let Err(missing) = mapping::check(&mapping.fields) {
+            lines.push(Line::from(missing).italic());
+        }
+        lines.push(Line::from("j/k column · enter change · i import · esc cancel").dim());
+
+        let mut area = overlay::popup_area(frame.area(), &lines);
+        let title = format!(" Map the columns of {} ", mapping.path);
+        if mapping.choosing.is_some() {
+            area.width = (area.width + FIELD_LIST_WIDTH).min(frame.area().width - area.x);
+        }
+        frame.render_widget(Clear, area);
+        if let Some(choosing) = mapping.choosing {
+            let [left, right] =
+                Layout::horizontal([Constraint::Min(0), Constraint::Length(FIELD_LIST_WIDTH)])
+                    .areas(area);
+            let choices: Vec<Line> = FIELDS
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| match idx == choosing {
+                    true => Line::from(field.name()).style(self.colors.selected_row_style()),
+                    false => Line::from(field.name()),
+                })
+                .collect();
+            let list = Paragraph::new(Text::from(choices))
+                .style(self.colors.info_style())
+                .block(self.colors.info_block().title(" Field "));
+            frame.render_widget(list, right);
+            area = left;
+        }
+        let popup = Paragraph::new(Text::from(lines))
+            .style(self.colors.info_style())
+            .block(self.colors.info_block().title(title));
+        frame.render_widget(popup, area);
+    }
+
+    // a document already open gets the choice of keeping its steps, `document` is where
+    // :w writes to afterwards when they are replaced
+    fn add_imported(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        mut items: Vec<TestStep>,
+        path: &str,
+        document: Option<&str>,
+    ) -> Result<MsgState, String> {
+        let count = items.len();
+        let append = !self.items.is_empty()
+            && match self
+                .prompt(
+                    terminal,
+                    &format!("Import {} steps: (a)ppend or (r)eplace? [a/r]", count),
+                )?
+                .trim()
+            {
+                "a" | "" => true,
+                "r" => false,
+                other => return Err(format!("Import cancelled ({} is not a or r)", other)),
+            };
+        if append {
+            self.push_undo();
+            self.items.append(&mut items);
+            TestStep::assign_ids(&mut self.items);
+            return Ok(MsgState::DynamicMsg(format!(
+                "Appended {} steps from {}",
+                count, path
+            )));
+        }
+        self.load_items(items);
+        self.document = document.map(str::to_string);
+        Ok(MsgState::DynamicMsg(format!(
+            "Imported {} steps from {}",
+            count, path
+        )))
+    }
+}
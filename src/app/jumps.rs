@@ -0,0 +1,133 @@
+use super::{App, ITEM_HEIGHT, MsgState};
+
+const JUMP_LIMIT: usize = 50;
+
+// rows the selection jumped away from, walked with ctrl+o / ctrl+i like vim's jump list.
+// positions are plain row indices and get clamped if the table has shrunk since
+#[derive(Default)]
+pub(super) struct JumpList {
+    entries: Vec<usize>,
+    // where ctrl+o/ctrl+i are in `entries`, equal to its length when not walking
+    pos: usize,
+}
+
+impl JumpList {
+    pub(super) fn push(&mut self, from: usize) {
+        self.entries.truncate(self.pos);
+        if self.entries.last() != Some(&from) {
+            self.entries.push(from);
+        }
+        if self.entries.len() > JUMP_LIMIT {
+            self.entries.remove(0);
+        }
+        self.pos = self.entries.len();
+    }
+
+    // the first step back also records `current` so ctrl+i can return to it
+    pub(super) fn back(&mut self, current: usize) -> Option<usize> {
+        if self.pos == 0 {
+            return None;
+        }
+        if self.pos == self.entries.len() && self.entries.last() != Some(&current) {
+            self.entries.push(current);
+        }
+        self.pos -= 1;
+        self.entries.get(self.pos).copied()
+    }
+
+    pub(super) fn forward(&mut self) -> Option<usize> {
+        if self.pos + 1 >= self.entries.len() {
+            return None;
+        }
+        self.pos += 1;
+        self.entries.get(self.pos).copied()
+    }
+}
+
+impl App {
+    fn select_row(&mut self, idx: usize) {
+        let idx = idx.min(self.items.len().saturating_sub(1));
+        self.state.select(Some(idx));
+        self.scroll_state = self.scroll_state.position(idx * ITEM_HEIGHT);
+        self.clamp_to_visible();
+    }
+
+    // a large movement, remembered so ctrl+o can come back to where it started
+    pub(super) fn jump_to(&mut self, idx: usize) {
+        let current = self.state.selected().unwrap_or(0);
+        if current != idx {
+            self.jumps.push(current);
+        }
+        self.select_row(idx);
+    }
+
+    pub(super) fn remember_position(&mut self) {
+        if let Some(current) = self.state.selected() {
+            self.jumps.push(current);
+        }
+    }
+
+    pub(super) fn jump_back(&mut self) -> MsgState {
+        let current = self.state.selected().unwrap_or(0);
+        match self.jumps.back(current) {
+            Some(idx) => {
+                self.select_row(idx);
+                MsgState::Default
+            }
+            None => MsgState::DynamicMsg("jumplist empty".to_string()),
+        }
+    }
+
+    pub(super) fn jump_forward(&mut self) -> MsgState {
+        match self.jumps.forward() {
+            Some(idx) => {
+                self.select_row(idx);
+                MsgState::Default
+            }
+            None => MsgState::DynamicMsg("jumplist empty".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_back_and_forward() {
+        let mut jumps = JumpList::default();
+        jumps.push(1);
+        jumps.push(5);
+        assert_eq!(jumps.back(9), Some(5));
+        assert_eq!(jumps.back(5), Some(1));
+        assert_eq!(jumps.back(1), None);
+        assert_eq!(jumps.forward(), Some(5));
+        assert_eq!(jumps.forward(), Some(9));
+        assert_eq!(jumps.forward(), None);
+    }
+
+    #[test]
+    fn a_new_jump_drops_the_forward_history() {
+        let mut jumps = JumpList::default();
+        jumps.push(1);
+        jumps.push(5);
+        assert_eq!(jumps.back(9), Some(5));
+        jumps.push(5);
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back(3), Some(5));
+        assert_eq!(jumps.back(5), Some(1));
+    }
+
+    #[test]
+    fn capped_at_the_limit() {
+        let mut jumps = JumpList::default();
+        for i in 0..JUMP_LIMIT + 10 {
+            jumps.push(i);
+        }
+        let mut walked = 0;
+        while jumps.back(999).is_some() {
+            walked += 1;
+        }
+        assert_eq!(walked, JUMP_LIMIT);
+    }
+}
@@ -0,0 +1,84 @@
+use super::App;
+use ratatui::{
+    DefaultTerminal,
+    crossterm::{
+        event::{
+            DisableBracketedPaste, EnableBracketedPaste, KeyboardEnhancementFlags,
+            PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        },
+        execute,
+        terminal::supports_keyboard_enhancement,
+    },
+};
+
+// whether ctrl+shift on a letter reaches us as such. most terminals send ctrl+shift+d as
+// ctrl+d, so there clear-all is only offered as :clear and ctrl+d is refused rather than
+// guessed at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Modifiers {
+    Precise,
+    Ambiguous,
+}
+
+impl Modifiers {
+    // asks the terminal, so raw mode has to be on already. terminals that answer get the
+    // kitty keyboard protocol, which reports every modifier
+    pub fn detect(enabled: bool) -> Modifiers {
+        match enabled && supports_keyboard_enhancement().unwrap_or(false) {
+            true => {
+                Modifiers::Precise.push();
+                Modifiers::Precise
+            }
+            false => Modifiers::Ambiguous,
+        }
+    }
+
+    fn push(self) {
+        if self == Modifiers::Precise {
+            let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+            let _ = execute!(std::io::stdout(), PushKeyboardEnhancementFlags(flags));
+        }
+    }
+
+    // before the terminal goes back to the shell or an editor, which may not speak it
+    pub fn pop(self) {
+        if self == Modifiers::Precise {
+            let _ = execute!(std::io::stdout(), PopKeyboardEnhancementFlags);
+        }
+    }
+
+    // as the footer shows it
+    pub fn clear_all_key(self) -> &'static str {
+        match self {
+            Modifiers::Precise => "Ctrl+Shift+d",
+            Modifiers::Ambiguous => ":clear",
+        }
+    }
+}
+
+impl App {
+    // the modes the tui reads input in on top of raw mode: precise modifiers where the
+    // terminal has them, and pastes as one event rather than keys
+    pub(super) fn start_input_modes(&mut self) {
+        self.modifiers = Modifiers::detect(self.config.keyboard_enhancement);
+        let _ = execute!(std::io::stdout(), EnableBracketedPaste);
+    }
+
+    pub(super) fn stop_input_modes(&self) {
+        let _ = execute!(std::io::stdout(), DisableBracketedPaste);
+        self.modifiers.pop();
+    }
+
+    // hands the terminal to a prompt or an editor, `resume_terminal` takes it back
+    pub(super) fn suspend_terminal(&self) {
+        self.stop_input_modes();
+        ratatui::restore();
+    }
+
+    pub(super) fn resume_terminal(&self) -> DefaultTerminal {
+        let terminal = ratatui::init();
+        self.modifiers.push();
+        let _ = execute!(std::io::stdout(), EnableBracketedPaste);
+        terminal
+    }
+}
@@ -0,0 +1,95 @@
+use super::{App, MsgState, footer, overlay::Overlay};
+use crate::test_step::TestStep;
+use ratatui::{crossterm::event::KeyCode, text::Line};
+
+const SNIPPET_WIDTH: usize = 40;
+
+fn mark_name(code: KeyCode) -> Result<char, String> {
+    match code {
+        KeyCode::Char(c) if c.is_ascii_alphabetic() => Ok(c),
+        _ => Err("Marks are named a-z or A-Z".to_string()),
+    }
+}
+
+impl App {
+    // `m{a-z}`, marks follow the step's id so inserts and deletes above don't move them
+    pub(super) fn set_mark(&mut self, code: KeyCode) -> Result<MsgState, String> {
+        let name = mark_name(code)?;
+        let item = self
+            .state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+            .ok_or_else(|| "No item is currently selected".to_string())?;
+        self.marks.insert(name, item.id);
+        Ok(MsgState::DynamicMsg(format!("Marked '{}", name)))
+    }
+
+    fn mark_index(&self, id: u64) -> Option<usize> {
+        self.items.iter().position(|item| item.id == id)
+    }
+
+    // `'{a-z}`
+    pub(super) fn jump_to_mark(&mut self, code: KeyCode) -> Result<MsgState, String> {
+        let name = mark_name(code)?;
+        let id = *self
+            .marks
+            .get(&name)
+            .ok_or_else(|| format!("Mark '{} not set", name))?;
+        match self.mark_index(id) {
+            Some(idx) => {
+                self.jump_to(idx);
+                Ok(MsgState::Default)
+            }
+            None => {
+                self.marks.remove(&name);
+                Err(format!("Mark '{} was dropped, its step was deleted", name))
+            }
+        }
+    }
+
+    // `:marks`, marks whose step is gone are dropped on the way
+    pub(super) fn open_marks(&mut self) -> Result<MsgState, String> {
+        let before = self.marks.len();
+        let marks: Vec<(char, usize)> = self
+            .marks
+            .iter()
+            .filter_map(|(name, id)| self.mark_index(*id).map(|idx| (*name, idx)))
+            .collect();
+        self.marks
+            .retain(|name, _| marks.iter().any(|(kept, _)| kept == name));
+        let dropped = before - marks.len();
+        if marks.is_empty() {
+            return Err(match dropped {
+                0 => "No marks set, use m{a-z}".to_string(),
+                _ => format!(
+                    "No marks left, dropped {} whose steps were deleted",
+                    dropped
+                ),
+            });
+        }
+
+        let labels = TestStep::step_labels(&self.items, self.config.numbering_start);
+        let lines = marks
+            .into_iter()
+            .map(|(name, idx)| {
+                let first_line = self.items[idx].instructions();
+                let first_line = first_line.lines().next().unwrap_or("");
+                Line::from(format!(
+                    "'{}  {:<6} {}",
+                    name,
+                    labels[idx],
+                    footer::fit_message(first_line, SNIPPET_WIDTH)
+                ))
+            })
+            .collect();
+        self.overlay = Some(Overlay::new("Marks", lines));
+        Ok(match dropped {
+            0 => MsgState::Default,
+            _ => MsgState::DynamicMsg(format!(
+                "Dropped {} mark{} whose steps were deleted",
+                dropped,
+                if dropped == 1 { "" } else { "s" }
+            )),
+        })
+    }
+}
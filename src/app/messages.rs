@@ -0,0 +1,36 @@
+use super::{App, MsgState, overlay::Overlay};
+use ratatui::text::Line;
+use std::collections::VecDeque;
+
+const MESSAGE_LIMIT: usize = 200;
+
+// every footer message and anything too long for it, oldest first, for `:messages`
+#[derive(Default)]
+pub(super) struct MessageLog {
+    entries: VecDeque<String>,
+}
+
+impl MessageLog {
+    pub(super) fn push(&mut self, msg: &str) {
+        self.entries.push_back(msg.to_string());
+        if self.entries.len() > MESSAGE_LIMIT {
+            self.entries.pop_front();
+        }
+    }
+}
+
+impl App {
+    pub(super) fn open_messages(&mut self) -> Result<MsgState, String> {
+        if self.messages.entries.is_empty() {
+            return Err("No messages yet".to_string());
+        }
+        let lines: Vec<Line<'static>> = self
+            .messages
+            .entries
+            .iter()
+            .map(|msg| Line::from(msg.clone()))
+            .collect();
+        self.overlay = Some(Overlay::new("Messages", lines));
+        Ok(MsgState::Default)
+    }
+}
@@ -0,0 +1,141 @@
+use super::App;
+use crate::test_step::{StepStatus, TestStep};
+use ratatui::{
+    Frame,
+    layout::{Margin, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+use std::ops::Range;
+
+// what a minimap cell shows, when a cell covers several rows the highest wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Mark {
+    Plain,
+    Skipped,
+    Section,
+    Pass,
+    Blocked,
+    Fail,
+}
+
+// by status once anything has one, by where the sections start before that
+fn mark(step: &TestStep, executing: bool) -> Mark {
+    match (executing, step.status) {
+        (true, Some(StepStatus::Fail)) => Mark::Fail,
+        (true, Some(StepStatus::Blocked)) => Mark::Blocked,
+        (true, Some(StepStatus::Pass)) => Mark::Pass,
+        (true, Some(StepStatus::Skipped)) => Mark::Skipped,
+        _ if step.is_new_section => Mark::Section,
+        _ => Mark::Plain,
+    }
+}
+
+// the rows each cell stands for. a table shorter than the strip gets a cell per row,
+// a longer one spreads its rows as evenly as it can
+pub(super) fn cell_rows(len: usize, cells: usize) -> Vec<Range<usize>> {
+    let cells = cells.min(len);
+    (0..cells)
+        .map(|cell| cell * len / cells..(cell + 1) * len / cells)
+        .collect()
+}
+
+pub(super) fn marks(steps: &[&TestStep], cells: usize) -> Vec<Mark> {
+    let executing = steps.iter().any(|step| step.status.is_some());
+    cell_rows(steps.len(), cells)
+        .into_iter()
+        .map(|rows| {
+            steps[rows]
+                .iter()
+                .map(|step| mark(step, executing))
+                .max()
+                .unwrap_or(Mark::Plain)
+        })
+        .collect()
+}
+
+impl App {
+    fn mark_color(&self, mark: Mark) -> Color {
+        match mark {
+            Mark::Plain | Mark::Skipped => self.colors.alt_row_color,
+            Mark::Section => self
+                .colors
+                .section_row_color
+                .unwrap_or(self.colors.footer_border_color),
+            Mark::Pass => self.colors.pass_row_color,
+            Mark::Blocked => self.colors.blocked_row_color,
+            Mark::Fail => self.colors.fail_row_color,
+        }
+    }
+
+    // a strip down the right edge of the document, worked out from the rows showing
+    // each time it is drawn. the cells the table is scrolled to carry a bar
+    pub(super) fn render_minimap(&self, frame: &mut Frame, area: Rect) {
+        let inner = area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        if inner.width == 0 || inner.height == 0 {
+            return;
+        }
+        let steps: Vec<&TestStep> = self
+            .visible_indices()
+            .into_iter()
+            .filter_map(|idx| self.items.get(idx))
+            .collect();
+        let cells = inner.height as usize;
+        let offset = self.view_state.offset();
+        let shown = offset..offset + self.table_rows;
+        let lines: Vec<Line> = marks(&steps, cells)
+            .into_iter()
+            .zip(cell_rows(steps.len(), cells))
+            .map(|(mark, rows)| {
+                let in_view = rows.start < shown.end && shown.start < rows.end;
+                let style = Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.mark_color(mark));
+                Line::from(Span::styled(if in_view { "▐" } else { " " }, style))
+            })
+            .collect();
+        let strip = Rect {
+            x: inner.right() - 1,
+            width: 1,
+            ..inner
+        };
+        frame.render_widget(Paragraph::new(lines), strip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_row_lands_in_exactly_one_cell() {
+        for (len, cells) in [(0, 10), (3, 10), (10, 10), (25, 10), (101, 7)] {
+            let rows = cell_rows(len, cells);
+            assert_eq!(rows.len(), cells.min(len));
+            let covered: Vec<usize> = rows.into_iter().flatten().collect();
+            assert_eq!(covered, (0..len).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn failures_stand_out_once_executing() {
+        let mut steps = [
+            TestStep::section("Login"),
+            TestStep::new(),
+            TestStep::new(),
+            TestStep::new(),
+        ];
+        let refs: Vec<&TestStep> = steps.iter().collect();
+        assert_eq!(marks(&refs, 2), vec![Mark::Section, Mark::Plain]);
+
+        steps[0].status = Some(StepStatus::Pass);
+        steps[3].status = Some(StepStatus::Fail);
+        steps[2].status = Some(StepStatus::Pass);
+        let refs: Vec<&TestStep> = steps.iter().collect();
+        assert_eq!(marks(&refs, 2), vec![Mark::Pass, Mark::Fail]);
+    }
+}
@@ -0,0 +1,67 @@
+use super::{App, MsgState};
+use crate::{normalize, validate};
+
+impl App {
+    // `:normalize [titles]`, one undo step for the whole cleanup
+    pub(super) fn normalize_command(&mut self, args: &str) -> Result<MsgState, String> {
+        let title_case = match args {
+            "" => false,
+            "titles" => true,
+            _ => return Err("Usage: normalize [titles]".to_string()),
+        };
+        let before = self.items.len();
+        let snapshot = self.items.clone();
+        let summary = normalize::normalize(&mut self.items, title_case);
+        if summary.is_empty() {
+            return Ok(MsgState::DynamicMsg("Nothing to normalize".to_string()));
+        }
+        self.push_undo_snapshot(snapshot);
+        self.visual_anchor = None;
+        self.clamp_to_visible();
+        let mut msg = format!(
+            "Normalized {} rows to {}: removed {} empty, cleaned {} field{}",
+            before,
+            self.items.len(),
+            summary.removed,
+            summary.fields,
+            if summary.fields == 1 { "" } else { "s" }
+        );
+        if title_case {
+            msg.push_str(&format!(", title-cased {} sections", summary.titles));
+        }
+        Ok(MsgState::DynamicMsg(msg))
+    }
+
+    // `:dedup`, the quick fix for duplicates in :check, keeps the first copy of each step
+    pub(super) fn dedup_command(&mut self) -> Result<MsgState, String> {
+        let extra: Vec<usize> = validate::duplicate_groups(&self.items)
+            .into_iter()
+            .flat_map(|group| group.into_iter().skip(1))
+            .collect();
+        if extra.is_empty() {
+            return Ok(MsgState::DynamicMsg("No duplicate steps".to_string()));
+        }
+        self.push_undo();
+        let removed = extra.iter().map(|idx| self.items[*idx].clone()).collect();
+        self.trash("duplicates from :dedup", removed);
+        let mut idx = 0;
+        self.items.retain(|_| {
+            idx += 1;
+            !extra.contains(&(idx - 1))
+        });
+        self.visual_anchor = None;
+        self.clamp_to_visible();
+        Ok(MsgState::DynamicMsg(format!(
+            "Deleted {} duplicate step{}",
+            extra.len(),
+            if extra.len() == 1 { "" } else { "s" }
+        )))
+    }
+
+    // behind `normalize_on_export`, never in readonly mode since it edits the document
+    pub(super) fn normalize_before_export(&mut self) {
+        if self.config.normalize_on_export && !self.readonly {
+            let _ = self.normalize_command("");
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use super::{App, MsgState, overlay::Overlay};
+use crate::test_step::TestStep;
+use ratatui::text::Line;
+
+pub(super) struct Section {
+    pub(super) idx: usize,
+    pub(super) title: String,
+    // labels of the first and last step in the section
+    pub(super) steps: Option<(String, String)>,
+}
+
+// derived from the items every time so it can't go stale
+pub(super) fn outline(items: &[TestStep], first_section: usize) -> Vec<Section> {
+    let labels = TestStep::step_labels(items, first_section);
+    let headers: Vec<usize> = (0..items.len())
+        .filter(|i| items[*i].is_new_section)
+        .collect();
+    headers
+        .iter()
+        .enumerate()
+        .map(|(n, idx)| {
+            let end = headers.get(n + 1).copied().unwrap_or(items.len());
+            let mut steps = (idx + 1..end).filter(|i| items[*i].is_step());
+            let first = steps.next();
+            let last = steps.next_back().or(first);
+            let title = items[*idx].instructions();
+            Section {
+                idx: *idx,
+                title: if title.is_empty() {
+                    "(untitled)".to_string()
+                } else {
+                    title.lines().next().unwrap_or("").to_string()
+                },
+                steps: first
+                    .zip(last)
+                    .map(|(f, l)| (labels[f].clone(), labels[l].clone())),
+            }
+        })
+        .collect()
+}
+
+// the next (or previous) section header after `from`, optionally wrapping around
+pub(super) fn section_from(
+    items: &[TestStep],
+    from: usize,
+    forward: bool,
+    wrap: bool,
+) -> Option<usize> {
+    let headers: Vec<usize> = (0..items.len())
+        .filter(|i| items[*i].is_new_section)
+        .collect();
+    let found = if forward {
+        headers.iter().find(|i| **i > from)
+    } else {
+        headers.iter().rev().find(|i| **i < from)
+    };
+    match found {
+        Some(idx) => Some(*idx),
+        None if wrap && forward => headers.first().copied(),
+        None if wrap => headers.last().copied(),
+        None => None,
+    }
+}
+
+impl App {
+    // `]]` and `[[`
+    pub(super) fn jump_section(&mut self, forward: bool) -> Result<MsgState, String> {
+        if !self.items.iter().any(|i| i.is_new_section) {
+            return Err("No sections, add one with s or S".to_string());
+        }
+        let from = self.state.selected().unwrap_or(0);
+        let to = section_from(&self.items, from, forward, self.config.wrap_sections).ok_or_else(
+            || {
+                if forward {
+                    "No section below".to_string()
+                } else {
+                    "No section above".to_string()
+                }
+            },
+        )?;
+        self.jump_to(to);
+        Ok(MsgState::Default)
+    }
+
+    // `g o`, an outline of the sections, Enter jumps to the selected one
+    pub(super) fn open_outline(&mut self) -> Result<MsgState, String> {
+        let sections = outline(&self.items, self.config.numbering_start);
+        if sections.is_empty() {
+            return Err("No sections, add one with s or S".to_string());
+        }
+        let lines = sections
+            .iter()
+            .map(|section| {
+                let steps = match &section.steps {
+                    Some((first, last)) if first == last => format!("step {}", first),
+                    Some((first, last)) => format!("steps {}-{}", first, last),
+                    None => "no steps".to_string(),
+                };
+                Line::from(format!("{}  ({})", section.title, steps))
+            })
+            .collect();
+        let targets = sections.iter().map(|section| section.idx).collect();
+        self.overlay = Some(Overlay::picker("Outline", lines, targets));
+        Ok(MsgState::Default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items() -> Vec<TestStep> {
+        vec![
+            TestStep::new().with_instructions("intro"),
+            TestStep::section("Setup"),
+            TestStep::new().with_instructions("a"),
+            TestStep::new().with_instructions("b"),
+            TestStep::section("Empty"),
+            TestStep::section("Teardown"),
+            TestStep::new().with_instructions("c"),
+        ]
+    }
+
+    #[test]
+    fn sections_list_their_step_range() {
+        let sections = outline(&items(), 1);
+        let summary: Vec<(usize, &str, bool)> = sections
+            .iter()
+            .map(|s| (s.idx, s.title.as_str(), s.steps.is_some()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Setup", true),
+                (4, "Empty", false),
+                (5, "Teardown", true)
+            ]
+        );
+        assert!(outline(&[TestStep::new()], 1).is_empty());
+    }
+
+    #[test]
+    fn section_jumps_wrap_only_when_asked() {
+        let items = items();
+        assert_eq!(section_from(&items, 0, true, false), Some(1));
+        assert_eq!(section_from(&items, 1, true, false), Some(4));
+        assert_eq!(section_from(&items, 6, true, false), None);
+        assert_eq!(section_from(&items, 6, true, true), Some(1));
+        assert_eq!(section_from(&items, 1, false, false), None);
+        assert_eq!(section_from(&items, 1, false, true), Some(5));
+        assert_eq!(section_from(&[TestStep::new()], 0, true, true), None);
+    }
+}
@@ -0,0 +1,138 @@
+use super::{App, MsgState};
+use crate::test_step::TestStep;
+use ratatui::{
+    Frame,
+    crossterm::event::{KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Text},
+    widgets::{Clear, Paragraph, Wrap},
+};
+
+const MIN_WIDTH: u16 = 30;
+
+pub(super) struct Overlay {
+    pub title: String,
+    pub lines: Vec<Line<'static>>,
+    pub scroll: u16,
+    // the row each line jumps to, when set the overlay is a picker and `scroll` is the
+    // selected line
+    pub targets: Vec<usize>,
+    // the review note each picker line is on its row, `x` toggles it resolved
+    pub notes: Vec<usize>,
+    // a :trash listing, Enter restores the selected entry and `d` purges it
+    pub trash: bool,
+    // a smart paste preview, Enter inserts these below the cursor
+    pub insert: Vec<TestStep>,
+}
+
+impl Overlay {
+    pub fn new(title: &str, lines: Vec<Line<'static>>) -> Overlay {
+        Overlay {
+            title: format!(" {} ", title),
+            lines,
+            scroll: 0,
+            targets: Vec::new(),
+            notes: Vec::new(),
+            trash: false,
+            insert: Vec::new(),
+        }
+    }
+
+    pub fn picker(title: &str, lines: Vec<Line<'static>>, targets: Vec<usize>) -> Overlay {
+        Overlay {
+            targets,
+            ..Overlay::new(title, lines)
+        }
+    }
+}
+
+// sized to the content (+ 2 for the border) but capped at 80% of the frame, anything
+// longer wraps and scrolls
+pub(super) fn popup_area(area: Rect, lines: &[Line]) -> Rect {
+    let max_width = area.width * 4 / 5;
+    let max_height = area.height * 4 / 5;
+    let content_width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16;
+    let width = (content_width.saturating_add(2)).clamp(MIN_WIDTH.min(max_width), max_width);
+    let inner = width.saturating_sub(2).max(1) as usize;
+    let wrapped: usize = lines.iter().map(|l| l.width().div_ceil(inner).max(1)).sum();
+    let height = (wrapped as u16).saturating_add(2).min(max_height);
+
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
+
+impl App {
+    pub(super) fn handle_overlay_keys(&mut self, key: KeyEvent) -> Result<MsgState, String> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        let Some(overlay) = &mut self.overlay else {
+            return Ok(MsgState::Default);
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                let max = overlay.lines.len().saturating_sub(1) as u16;
+                overlay.scroll = (overlay.scroll + 1).min(max);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                overlay.scroll = overlay.scroll.saturating_sub(1);
+            }
+            KeyCode::Char('x') if !overlay.notes.is_empty() => {
+                let selected = overlay.scroll as usize;
+                return self.toggle_review_note(selected);
+            }
+            KeyCode::Enter if overlay.trash => {
+                let selected = overlay.scroll as usize;
+                self.overlay = None;
+                return self.restore_trash(selected);
+            }
+            KeyCode::Enter if !overlay.insert.is_empty() => {
+                let steps = std::mem::take(&mut overlay.insert);
+                self.overlay = None;
+                return self.insert_smart_paste(steps);
+            }
+            KeyCode::Char('d') if overlay.trash => {
+                let selected = overlay.scroll as usize;
+                return self.purge_trash(selected);
+            }
+            KeyCode::Enter if !overlay.targets.is_empty() => {
+                let target = overlay.targets.get(overlay.scroll as usize).copied();
+                self.overlay = None;
+                if let Some(target) = target {
+                    self.jump_to(target);
+                }
+            }
+            _ => self.overlay = None,
+        }
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn render_overlay(&self, frame: &mut Frame) {
+        let Some(overlay) = &self.overlay else {
+            return;
+        };
+        let area = popup_area(frame.area(), &overlay.lines);
+        let (lines, scroll) = if overlay.targets.is_empty() {
+            (overlay.lines.clone(), overlay.scroll)
+        } else {
+            // pickers highlight the selected line and only scroll to keep it in view
+            let mut lines = overlay.lines.clone();
+            if let Some(line) = lines.get_mut(overlay.scroll as usize) {
+                *line = line.clone().style(self.colors.selected_row_style());
+            }
+            let inner = area.height.saturating_sub(2).max(1);
+            (lines, overlay.scroll.saturating_sub(inner - 1))
+        };
+        let popup = Paragraph::new(Text::from(lines))
+            .style(self.colors.info_style())
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(self.colors.info_block().title(overlay.title.clone()));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup, area);
+    }
+}
@@ -0,0 +1,99 @@
+use super::{App, InputMode, MsgState, overlay::Overlay};
+use crate::{embedding, err_msg::WithErrMsg, import::loose, test_step::TestStep};
+use ratatui::{crossterm::event, style::Stylize, text::Line};
+use std::time::Duration;
+
+// how a parsed step reads in the smart paste preview
+fn preview_lines(steps: &[TestStep]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut number = 0;
+    for step in steps {
+        if step.is_new_section {
+            lines.push(Line::from(format!("§ {}", step.instructions)).bold());
+            continue;
+        }
+        number += 1;
+        for (idx, line) in step.instructions.lines().enumerate() {
+            lines.push(match idx {
+                0 => Line::from(format!("{:>3}. {}", number, line)),
+                _ => Line::from(format!("     {}", line)),
+            });
+        }
+        for line in step.expected_results.lines() {
+            lines.push(Line::from(format!("     → {}", line)).dim());
+        }
+    }
+    lines
+}
+
+impl App {
+    // bracketed paste arrives as one event, so a newline in it can't commit an input and
+    // nothing in it is read as a key
+    pub(super) fn handle_paste(&mut self, text: &str) -> MsgState {
+        let dropped = if let Some(edit) = &mut self.cell_edit {
+            edit.paste(text)
+        } else if let Some(picker) = &mut self.picker {
+            picker.paste(text)
+        } else if let Some(picker) = &mut self.export_picker {
+            picker.paste(text)
+        } else if self.column_mapping.is_some() || self.overlay.is_some() {
+            return MsgState::Default;
+        } else if let InputMode::Command(input) = &mut self.input_mode {
+            input.paste(text)
+        } else {
+            return MsgState::DynamicMsg(
+                "Paste ignored outside an input, + loads steps from the clipboard".to_string(),
+            );
+        };
+        match dropped {
+            0 => MsgState::Default,
+            n => MsgState::DynamicMsg(format!("Dropped {} control characters from the paste", n)),
+        }
+    }
+
+    // `gp`, a list typed in chat read into steps, shown for a look before it goes in
+    pub(super) fn smart_paste(&mut self) -> Result<MsgState, String> {
+        let text = self
+            .clipboard()?
+            .get_text()
+            .with_err_msg(&"Failed to get text from system clipboard")?;
+        if text.contains(embedding::MDEMBEDDING) {
+            return Err("The clipboard holds a compiled document, + loads it".to_string());
+        }
+        let mut steps = loose::parse(&text)?;
+        self.stamp_imported(&mut steps);
+        let count = steps.iter().filter(|step| step.is_step()).count();
+        let title = format!(
+            "Smart paste, Enter inserts {} step{} below, any other key cancels",
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+        let mut overlay = Overlay::new(&title, preview_lines(&steps));
+        overlay.insert = steps;
+        self.overlay = Some(overlay);
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn insert_smart_paste(&mut self, steps: Vec<TestStep>) -> Result<MsgState, String> {
+        if let Some(refusal) = self.readonly_refusal() {
+            return Ok(refusal);
+        }
+        let count = steps.len();
+        self.restore_steps(steps);
+        Ok(MsgState::DynamicMsg(format!(
+            "Inserted {} row{} from the paste, u undoes",
+            count,
+            if count == 1 { "" } else { "s" }
+        )))
+    }
+}
+
+// a prompt reads a single line, whatever else was pasted into it would reach the table
+// as keys
+pub(super) fn discard_pending_input() {
+    while event::poll(Duration::ZERO).unwrap_or(false) {
+        if event::read().is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use super::{App, MsgState, overlay::Overlay};
+use crate::err_msg::WithErrMsg;
+use ratatui::text::Line;
+
+impl App {
+    // h/l move a cell focus across the content columns, moving left off the first
+    // column goes back to focusing the whole row
+    pub(super) fn move_column_focus(&mut self, delta: isize) {
+        let last = self.header_titles().len() - 1;
+        let next = match (self.state.selected_column(), delta) {
+            (None, d) if d > 0 => Some(1),
+            (None, _) => None,
+            (Some(col), d) => match col as isize + d {
+                c if c < 1 => None,
+                c => Some((c as usize).min(last)),
+            },
+        };
+        self.state.select_column(next);
+    }
+
+    // `K`, the focused field's markdown or the whole step when no cell has focus
+    pub(super) fn peek(&mut self) -> Result<MsgState, String> {
+        let item = self
+            .state
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .with_err_msg(&"No item is currently selected")?;
+        let (title, content) = match self.state.selected_column() {
+            Some(col) => {
+                let mut fields = item.ref_array(self.show_test_data());
+                if !self.show_ac() {
+                    fields.pop();
+                }
+                let content = fields
+                    .get(col - 1)
+                    .with_err_msg(&"No field under the cell focus")?
+                    .clone();
+                (self.header_titles()[col].to_string(), content)
+            }
+            None => (
+                "Step".to_string(),
+                item.gen_markdown_in(self.config.use_ac, self.config.locale),
+            ),
+        };
+        let lines = content
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        self.overlay = Some(Overlay::new(&title, lines));
+        Ok(MsgState::Default)
+    }
+}
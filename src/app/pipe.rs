@@ -0,0 +1,59 @@
+use super::{App, MsgState};
+use crate::{err_msg::WithErrMsg, shell, test_step::TestStep, timestamp};
+
+impl App {
+    // runs the selected step (or visual selection) through a shell command
+    pub(super) fn pipe_selection(&mut self, cmd: &str) -> Result<MsgState, String> {
+        if cmd.is_empty() {
+            return Err("Usage: pipe <cmd>".to_string());
+        }
+        let range = self
+            .selected_range()
+            .with_err_msg(&"No item is currently selected")?;
+        let originals = self
+            .items
+            .get(range.clone())
+            .with_err_msg(&"Items vec did not contain selection")?
+            .to_vec();
+
+        let input = if originals.len() == 1 {
+            originals[0].gen_markdown(self.config.use_ac)
+        } else {
+            TestStep::gen_document(&originals, self.config.use_ac)
+        };
+
+        let output = shell::filter(cmd, &input)?;
+        let mut piped = TestStep::parse_document(&output)?;
+        if piped.is_empty() {
+            return Err("Command produced no steps".to_string());
+        }
+
+        let author = self.config.author();
+        let mut next_id = TestStep::next_id(&self.items);
+        for (idx, step) in piped.iter_mut().enumerate() {
+            match originals.get(idx) {
+                Some(original) => step.inherit_metadata(original, author.clone()),
+                None => {
+                    step.id = next_id;
+                    step.created_at = Some(timestamp::now());
+                    step.updated_at = step.created_at;
+                    step.author = author.clone();
+                    next_id += 1;
+                }
+            }
+        }
+
+        let count = piped.len();
+        self.push_undo();
+        self.items.splice(range.clone(), piped);
+        self.visual_anchor = None;
+        self.state.select(Some(*range.start()));
+
+        Ok(MsgState::DynamicMsg(format!(
+            "Piped {} step(s) through {}, got {}",
+            originals.len(),
+            cmd,
+            count
+        )))
+    }
+}
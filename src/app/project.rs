@@ -0,0 +1,27 @@
+use crate::{config::Config, test_step::TestStep};
+
+fn tag(config: &Config) -> Option<String> {
+    config
+        .project
+        .as_ref()
+        .map(|project| format!(" [{}]", project.name))
+}
+
+pub(super) fn project_template_names(config: &Config) -> Vec<String> {
+    let (Some(project), Some(tag)) = (&config.project, tag(config)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = project
+        .templates
+        .keys()
+        .map(|name| format!("{}{}", name, tag))
+        .collect();
+    names.sort();
+    names
+}
+
+// the steps of a project template given its name as shown in the template list
+pub(super) fn project_template(config: &Config, listed_name: &str) -> Option<Vec<TestStep>> {
+    let name = listed_name.strip_suffix(&tag(config)?)?;
+    config.project.as_ref()?.templates.get(name).cloned()
+}
@@ -0,0 +1,104 @@
+use super::{App, MsgState};
+use crate::config;
+use ratatui::crossterm::event::KeyCode;
+
+const READONLY_MSG: &str = "Read-only mode, use :set noreadonly to make changes";
+
+impl App {
+    // everything that changes the items, navigation/preview/export/stats stay available.
+    // loading from the clipboard is allowed, it's how a reviewer opens what they were sent
+    fn is_mutating_key(code: KeyCode, ctrl: bool, shift: bool) -> bool {
+        match code {
+            KeyCode::Enter => true,
+            KeyCode::Char('p' | 'P') => !(ctrl && shift),
+            KeyCode::Char('r') => ctrl,
+            KeyCode::Char('o') if ctrl => false,
+            KeyCode::Char(
+                'd' | 'o' | 'O' | 's' | 'S' | 'c' | 'C' | 'a' | 'A' | '|' | 'u' | 'x' | 'M' | 'i',
+            ) => true,
+            _ => false,
+        }
+    }
+
+    fn is_mutating_command(name: &str) -> bool {
+        matches!(
+            name,
+            "pipe"
+                | "status"
+                | "prio"
+                | "priority"
+                | "comment"
+                | "setfield"
+                | "movefield"
+                | "smartpaste"
+                | "import"
+                | "split"
+                | "sort"
+                | "sort!"
+                | "reverse"
+                | "normalize"
+                | "dedup"
+                | "clear"
+                | "restore-last-clear"
+        )
+    }
+
+    // Some(refusal) when readonly mode blocks the key
+    pub(super) fn readonly_key(&self, code: KeyCode, ctrl: bool, shift: bool) -> Option<MsgState> {
+        self.readonly_refusal()
+            .filter(|_| Self::is_mutating_key(code, ctrl, shift))
+    }
+
+    pub(super) fn readonly_command(&self, name: &str) -> Result<(), String> {
+        if self.readonly && Self::is_mutating_command(name) {
+            return Err(READONLY_MSG.to_string());
+        }
+        Ok(())
+    }
+
+    pub(super) fn readonly_refusal(&self) -> Option<MsgState> {
+        self.readonly
+            .then(|| MsgState::DynamicMsg(READONLY_MSG.to_string()))
+    }
+
+    pub(super) fn remember_loaded(&mut self) {
+        self.loaded_readonly = self.readonly.then(|| self.items.clone());
+    }
+
+    // read-only since startup with the items as they were loaded, the one case where the
+    // backup is left alone at exit. :set readonly after editing, or a + load, still writes
+    pub(super) fn unchanged_since_load(&self) -> bool {
+        self.loaded_readonly.as_ref() == Some(&self.items)
+    }
+
+    pub(super) fn set_option(&mut self, option: &str) -> Result<MsgState, String> {
+        if let Some(start) = option.strip_prefix("start=") {
+            self.config.numbering_start = config::parse_numbering_start(start)?;
+            return Ok(MsgState::DynamicMsg(format!(
+                "Sections are numbered from {} (numbering_start in the config keeps it)",
+                self.config.numbering_start
+            )));
+        }
+        match option {
+            "readonly" | "ro" => self.readonly = true,
+            "noreadonly" | "noro" => {
+                self.readonly = false;
+                self.loaded_readonly = None;
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown option: {} (expected readonly|noreadonly|start=<n>)",
+                    option
+                ));
+            }
+        }
+        Ok(MsgState::DynamicMsg(
+            if self.readonly {
+                "Read-only mode on"
+            } else {
+                "Read-only mode off"
+            }
+            .to_string(),
+        ))
+    }
+}
@@ -0,0 +1,98 @@
+use super::{App, MsgState, Window, builtin, project};
+use crate::{colors::Colors, config::Config, err_msg::WithErrMsg, test_step::TestStep};
+use ratatui::DefaultTerminal;
+
+// the user's templates, then the project's, then the built-ins (unless they're hidden)
+pub(super) fn template_list(config: &Config) -> Vec<TestStep> {
+    config
+        .templates
+        .keys()
+        .cloned()
+        .chain(project::project_template_names(config))
+        .chain(builtin::builtin_names(config))
+        .map(|name| TestStep::new().with_instructions(&name))
+        .collect()
+}
+
+// top level config keys whose values differ, templates are handled separately
+fn changed_keys(old: &Config, new: &Config) -> Result<Vec<String>, String> {
+    let old = toml::Value::try_from(old).with_err_msg(&"Failed to serialize config")?;
+    let new = toml::Value::try_from(new).with_err_msg(&"Failed to serialize config")?;
+    let (Some(old), Some(new)) = (old.as_table(), new.as_table()) else {
+        return Ok(Vec::new());
+    };
+    let mut keys: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|key| *key != "templates" && old.get(*key) != new.get(*key))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    Ok(keys)
+}
+
+impl App {
+    // `:reload-config`, nothing is applied unless the whole file loads
+    pub(super) fn reload_config(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<MsgState, String> {
+        let mut config = Config::load_config()?;
+        let colors = Colors::load(&config.theme, self.colors.plain)?;
+
+        let templates_differ = config.templates != self.config.templates;
+        if templates_differ {
+            let keep = self.prompt(
+                terminal,
+                "Templates on disk differ from the ones in memory, keep (d)isk or (m)emory? [d/m]",
+            )?;
+            match keep.trim() {
+                "m" | "memory" => config.templates = self.config.templates.clone(),
+                "d" | "disk" | "" => {}
+                other => return Err(format!("Unknown choice: {} (reload cancelled)", other)),
+            }
+        }
+
+        let mut changed = changed_keys(&self.config, &config)?;
+        if templates_differ {
+            changed.push("templates".to_string());
+        }
+        self.config = config;
+        self.colors = colors;
+        self.config_error = None;
+        self.template_list = template_list(&self.config);
+        self.snippets = super::snippets::snippet_list(&self.config);
+        if matches!(self.window, Window::Template | Window::Snippets) {
+            let max = self.length_constraint().saturating_sub(1);
+            self.state
+                .select(Some(self.state.selected().unwrap_or(0).min(max)));
+        }
+
+        Ok(MsgState::DynamicMsg(if changed.is_empty() {
+            "Reloaded config, nothing changed".to_string()
+        } else {
+            format!("Reloaded config, changed: {}", changed.join(", "))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(extra: &str) -> Config {
+        toml::from_str(&format!("editor = \"vim\"\n{}\n[templates]\n", extra)).unwrap()
+    }
+
+    #[test]
+    fn reports_changed_keys_only() {
+        let old = config("show_ids = false");
+        let new = config("show_ids = true\ntab_width = 8");
+        assert_eq!(
+            changed_keys(&old, &new).unwrap(),
+            vec!["show_ids", "tab_width"]
+        );
+        assert!(changed_keys(&old, &config("")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use super::{App, ITEM_HEIGHT, MsgState};
+use crate::test_step::TestStep;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+// a block of rows being moved, the items are rearranged live so the rest of the table
+// flows around it and `original` is put back if the move is cancelled
+pub(super) struct Reorder {
+    original: Vec<TestStep>,
+    original_start: usize,
+    pub(super) start: usize,
+    pub(super) len: usize,
+}
+
+// moves items[start..start + len] by `delta` rows, stopping at either end, and
+// returns where the block now starts
+pub(super) fn shift_block<T>(items: &mut [T], start: usize, len: usize, delta: isize) -> usize {
+    let max_start = items.len().saturating_sub(len);
+    let target = start.saturating_add_signed(delta).min(max_start);
+    if target > start {
+        items[start..target + len].rotate_right(target - start);
+    } else if target < start {
+        items[target..start + len].rotate_left(start - target);
+    }
+    target
+}
+
+impl App {
+    // `M`, on the selected row or the visual selection
+    pub(super) fn start_reorder(&mut self) -> Result<MsgState, String> {
+        if self.filter.is_some() {
+            return Err("Clear the filter before moving steps".to_string());
+        }
+        let range = self
+            .selected_range()
+            .ok_or_else(|| "No item is currently selected".to_string())?;
+        self.visual_anchor = None;
+        self.reorder = Some(Reorder {
+            original: self.items.clone(),
+            original_start: *range.start(),
+            start: *range.start(),
+            len: range.count(),
+        });
+        self.select_reorder_block();
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn in_reorder(&self, i: usize) -> bool {
+        self.reorder
+            .as_ref()
+            .is_some_and(|r| (r.start..r.start + r.len).contains(&i))
+    }
+
+    fn select_reorder_block(&mut self) {
+        if let Some(reorder) = &self.reorder {
+            self.state.select(Some(reorder.start));
+            self.scroll_state = self.scroll_state.position(reorder.start * ITEM_HEIGHT);
+        }
+    }
+
+    pub(super) fn handle_reorder_keys(&mut self, key: KeyEvent) -> Result<MsgState, String> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        let delta = match key.code {
+            KeyCode::Char('j') | KeyCode::Down => 1,
+            KeyCode::Char('k') | KeyCode::Up => -1,
+            KeyCode::Char('G') | KeyCode::End => isize::MAX,
+            KeyCode::Home => isize::MIN,
+            KeyCode::Enter => return Ok(self.finish_reorder()),
+            KeyCode::Esc => return Ok(self.cancel_reorder()),
+            _ => return Ok(MsgState::Default),
+        };
+        if let Some(reorder) = &mut self.reorder {
+            reorder.start = shift_block(&mut self.items, reorder.start, reorder.len, delta);
+        }
+        self.select_reorder_block();
+        Ok(MsgState::Default)
+    }
+
+    // the whole move is a single undo step
+    fn finish_reorder(&mut self) -> MsgState {
+        let Some(reorder) = self.reorder.take() else {
+            return MsgState::Default;
+        };
+        if reorder.start == reorder.original_start {
+            return MsgState::Default;
+        }
+        self.push_undo_snapshot(reorder.original);
+        MsgState::DynamicMsg(format!(
+            "Moved {} row{}",
+            reorder.len,
+            if reorder.len == 1 { "" } else { "s" }
+        ))
+    }
+
+    fn cancel_reorder(&mut self) -> MsgState {
+        if let Some(reorder) = self.reorder.take() {
+            self.items = reorder.original;
+            self.state.select(Some(reorder.original_start));
+            self.scroll_state = self
+                .scroll_state
+                .position(reorder.original_start * ITEM_HEIGHT);
+        }
+        MsgState::DynamicMsg("Move cancelled".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_move_through_the_rest() {
+        let mut items = vec![0, 1, 2, 3, 4, 5];
+        assert_eq!(shift_block(&mut items, 1, 2, 1), 2);
+        assert_eq!(items, vec![0, 3, 1, 2, 4, 5]);
+        assert_eq!(shift_block(&mut items, 2, 2, -2), 0);
+        assert_eq!(items, vec![1, 2, 0, 3, 4, 5]);
+    }
+
+    #[test]
+    fn blocks_stop_at_the_edges() {
+        let mut items = vec![0, 1, 2, 3];
+        assert_eq!(shift_block(&mut items, 0, 2, -1), 0);
+        assert_eq!(items, vec![0, 1, 2, 3]);
+        assert_eq!(shift_block(&mut items, 0, 2, isize::MAX), 2);
+        assert_eq!(items, vec![2, 3, 0, 1]);
+        assert_eq!(shift_block(&mut items, 2, 2, isize::MIN), 0);
+        assert_eq!(items, vec![0, 1, 2, 3]);
+        assert_eq!(shift_block(&mut items, 0, 4, 1), 0);
+    }
+}
@@ -0,0 +1,94 @@
+use super::{App, ITEM_HEIGHT, MsgState};
+use crate::{config::HeaderPolicy, test_step::TestStep};
+
+// reverses the rows of `items` that aren't pinned, returning the positions that took
+// part so a selection can follow its row to the mirrored slot
+pub(super) fn reverse_unpinned<T>(items: &mut [T], pinned: impl Fn(&T) -> bool) -> Vec<usize> {
+    let slots: Vec<usize> = (0..items.len()).filter(|i| !pinned(&items[*i])).collect();
+    for k in 0..slots.len() / 2 {
+        items.swap(slots[k], slots[slots.len() - 1 - k]);
+    }
+    slots
+}
+
+// where the row at `idx` ended up, rows that didn't move stay put
+pub(super) fn mirrored(slots: &[usize], idx: usize) -> usize {
+    slots
+        .iter()
+        .position(|slot| *slot == idx)
+        .map_or(idx, |k| slots[slots.len() - 1 - k])
+}
+
+impl App {
+    // `:reverse` or `g r` on the visual selection
+    pub(super) fn reverse_selection(&mut self) -> Result<MsgState, String> {
+        let range = self
+            .selected_range()
+            .ok_or_else(|| "No item is currently selected".to_string())?;
+        if range.clone().count() < 2 {
+            return Err("Select at least two rows to reverse".to_string());
+        }
+        let (start, end) = (*range.start(), *range.end());
+        let has_headers = self.items[range].iter().any(|i| i.is_new_section);
+        let skip_headers = match self.config.reverse_headers {
+            HeaderPolicy::Skip => true,
+            HeaderPolicy::Error if has_headers => {
+                return Err(
+                    "Selection contains a section header, set reverse_headers = \"skip\" to keep them in place"
+                        .to_string(),
+                );
+            }
+            HeaderPolicy::Error => false,
+        };
+
+        let snapshot = self.items.clone();
+        let slots = reverse_unpinned(&mut self.items[start..=end], |item: &TestStep| {
+            skip_headers && item.is_new_section
+        });
+        let slots: Vec<usize> = slots.into_iter().map(|slot| slot + start).collect();
+        self.push_undo_snapshot(snapshot);
+
+        let selected = mirrored(&slots, self.state.selected().unwrap_or(start));
+        self.visual_anchor = self.visual_anchor.map(|anchor| mirrored(&slots, anchor));
+        self.state.select(Some(selected));
+        self.scroll_state = self.scroll_state.position(selected * ITEM_HEIGHT);
+        Ok(MsgState::DynamicMsg(format!(
+            "Reversed {} rows",
+            slots.len()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_block_reverses() {
+        let mut items = vec![0, 1, 2, 3];
+        let slots = reverse_unpinned(&mut items, |_| false);
+        assert_eq!(items, vec![3, 2, 1, 0]);
+        assert_eq!(mirrored(&slots, 0), 3);
+        assert_eq!(mirrored(&slots, 2), 1);
+    }
+
+    #[test]
+    fn pinned_rows_stay_and_selection_follows_its_row() {
+        // negative numbers stand in for section headers
+        let mut items = vec![1, -1, 2, 3, -2];
+        let slots = reverse_unpinned(&mut items, |i| *i < 0);
+        assert_eq!(items, vec![3, -1, 2, 1, -2]);
+        assert_eq!(mirrored(&slots, 0), 3);
+        assert_eq!(mirrored(&slots, 3), 0);
+        assert_eq!(mirrored(&slots, 1), 1);
+        assert_eq!(mirrored(&slots, 4), 4);
+    }
+
+    #[test]
+    fn single_movable_row_is_a_no_op() {
+        let mut items = vec![-1, 5];
+        let slots = reverse_unpinned(&mut items, |i| *i < 0);
+        assert_eq!(items, vec![-1, 5]);
+        assert_eq!(mirrored(&slots, 1), 1);
+    }
+}
@@ -0,0 +1,138 @@
+use super::{App, MsgState, exit::LastExport, footer, overlay::Overlay};
+use crate::{err_msg::WithErrMsg, review, test_step::TestStep};
+use ratatui::{style::Stylize, text::Line};
+
+const NOTE_WIDTH: usize = 60;
+
+impl App {
+    // `:review`, `:review import <path>` and `:review export [path]`
+    pub(super) fn review_command(&mut self, args: &str) -> Result<MsgState, String> {
+        let (sub, path) = args.split_once(' ').unwrap_or((args, ""));
+        match sub {
+            "" => self.open_review(0),
+            "import" => self.import_review(path.trim()),
+            "export" => self.export_review(path.trim()),
+            _ => Err("Usage: review [import <path>|export [path]]".to_string()),
+        }
+    }
+
+    fn import_review(&mut self, path: &str) -> Result<MsgState, String> {
+        if let Some(refusal) = self.readonly_refusal() {
+            return Ok(refusal);
+        }
+        if path.is_empty() {
+            return Err("Usage: review import <path>".to_string());
+        }
+        let content =
+            std::fs::read_to_string(path).with_err_msg(&format!("Failed to read {}", path))?;
+        let annotations = review::parse(&content)?;
+        let snapshot = self.items.clone();
+        let applied = review::apply(&mut self.items, annotations, self.config.numbering_start);
+        if applied.attached > 0 {
+            self.push_undo_snapshot(snapshot);
+        }
+        for key in &applied.unmatched {
+            self.messages
+                .push(&format!("{}: no step matches {}", path, key));
+        }
+        let mut msg = format!("Attached {} review notes", applied.attached);
+        if applied.skipped > 0 {
+            msg.push_str(&format!(", {} already attached", applied.skipped));
+        }
+        if !applied.unmatched.is_empty() {
+            msg.push_str(&format!(", no step for {}", applied.unmatched.join(", ")));
+        }
+        Ok(MsgState::DynamicMsg(msg))
+    }
+
+    // the unresolved notes, for sending back to the reviewers
+    fn export_review(&mut self, path: &str) -> Result<MsgState, String> {
+        let open = self
+            .items
+            .iter()
+            .map(TestStep::unresolved_notes)
+            .sum::<usize>();
+        if open == 0 {
+            return Err("No unresolved review notes".to_string());
+        }
+        let summary = review::summary(&self.items, self.config.numbering_start);
+        if path.is_empty() {
+            self.clipboard()?
+                .set_text(summary)
+                .with_err_msg(&"Failed to set clipboard content")?;
+            return Ok(MsgState::DynamicMsg(format!(
+                "Copied {} unresolved review notes to the clipboard",
+                open
+            )));
+        }
+        std::fs::write(path, summary).with_err_msg(&format!("Failed to write {}", path))?;
+        self.last_export = Some(LastExport {
+            path: path.to_string(),
+            format: "review".to_string(),
+        });
+        Ok(MsgState::DynamicMsg(format!(
+            "Wrote {} unresolved review notes to {}",
+            open, path
+        )))
+    }
+
+    // one line per note, Enter jumps to its step and x toggles it resolved
+    fn open_review(&mut self, selected: usize) -> Result<MsgState, String> {
+        let labels = TestStep::step_labels(&self.items, self.config.numbering_start);
+        let mut lines = Vec::new();
+        let mut targets = Vec::new();
+        let mut notes = Vec::new();
+        for (idx, item) in self.items.iter().enumerate() {
+            for (note_idx, note) in item.review_notes.iter().enumerate() {
+                let text = note.text.lines().next().unwrap_or("");
+                let mark = if note.resolved { "[x]" } else { "[ ]" };
+                let line = Line::from(format!(
+                    "{} {:<6} {}",
+                    mark,
+                    labels[idx],
+                    footer::fit_message(text, NOTE_WIDTH)
+                ));
+                lines.push(if note.resolved { line.dim() } else { line });
+                targets.push(idx);
+                notes.push(note_idx);
+            }
+        }
+        if lines.is_empty() {
+            return Err("No review notes, use :review import <path>".to_string());
+        }
+        let mut overlay = Overlay::picker("Review notes (Enter jump, x resolve)", lines, targets);
+        overlay.notes = notes;
+        overlay.scroll = selected.min(overlay.targets.len() - 1) as u16;
+        self.overlay = Some(overlay);
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn toggle_review_note(&mut self, selected: usize) -> Result<MsgState, String> {
+        if let Some(refusal) = self.readonly_refusal() {
+            return Ok(refusal);
+        }
+        let Some(overlay) = &self.overlay else {
+            return Ok(MsgState::Default);
+        };
+        let (Some(&idx), Some(&note)) =
+            (overlay.targets.get(selected), overlay.notes.get(selected))
+        else {
+            return Ok(MsgState::Default);
+        };
+        self.push_undo();
+        let note = &mut self.items[idx].review_notes[note];
+        note.resolved = !note.resolved;
+        let resolved = note.resolved;
+        self.open_review(selected)?;
+        let open = self
+            .items
+            .iter()
+            .map(TestStep::unresolved_notes)
+            .sum::<usize>();
+        Ok(MsgState::DynamicMsg(format!(
+            "{} note, {} unresolved left",
+            if resolved { "Resolved" } else { "Reopened" },
+            open
+        )))
+    }
+}
@@ -0,0 +1,60 @@
+use super::{App, MsgState};
+use crate::filter::Filter;
+
+impl App {
+    // `/` opens the command line on `search `, so both share editing and history
+    pub(super) fn start_search(&mut self) {
+        self.open_command_line("search ");
+    }
+
+    // `:search <query>`, same syntax as :filter, jumps to the first hit below the selection
+    pub(super) fn search_command(&mut self, query: &str) -> Result<MsgState, String> {
+        if query.is_empty() {
+            self.search = None;
+            return Ok(MsgState::DynamicMsg("Search cleared".to_string()));
+        }
+        self.search = Some(Filter::parse(query)?);
+        self.search_next(true, true)
+    }
+
+    // `n` and `N`, wrapping around the ends
+    pub(super) fn search_next(
+        &mut self,
+        forward: bool,
+        include_current: bool,
+    ) -> Result<MsgState, String> {
+        let search = self
+            .search
+            .as_ref()
+            .ok_or_else(|| "No search, start one with /".to_string())?;
+        let visible = self.visible_indices();
+        let hits: Vec<usize> = search
+            .matching(&self.items)
+            .into_iter()
+            .filter(|idx| visible.contains(idx))
+            .collect();
+        let (Some(first), Some(last)) = (hits.first().copied(), hits.last().copied()) else {
+            return Err(format!("No matches for {}", search.query));
+        };
+        let current = self.state.selected().unwrap_or(0);
+        let next = if forward {
+            hits.iter()
+                .find(|idx| **idx > current || (include_current && **idx == current))
+                .copied()
+                .unwrap_or(first)
+        } else {
+            hits.iter()
+                .rev()
+                .find(|idx| **idx < current)
+                .copied()
+                .unwrap_or(last)
+        };
+        let position = hits.iter().position(|idx| *idx == next).unwrap_or(0);
+        self.jump_to(next);
+        Ok(MsgState::DynamicMsg(format!(
+            "match {} of {}",
+            position + 1,
+            hits.len()
+        )))
+    }
+}
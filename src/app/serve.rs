@@ -0,0 +1,50 @@
+use super::{App, MsgState};
+use crate::serve::{Server, Snapshot};
+
+impl App {
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            items: self.items.clone(),
+            columns: self.config.columns.clone(),
+            use_ac: self.config.use_ac,
+            show_ids: self.config.show_ids,
+            max_image_bytes: self.config.max_image_bytes,
+            locale: self.config.locale,
+            markdown_extensions: self.config.markdown_extensions.clone(),
+            numbering_start: self.config.numbering_start,
+        }
+    }
+
+    // `:serve [port]` and `:serve stop`
+    pub(super) fn serve_command(&mut self, args: &str) -> Result<MsgState, String> {
+        if args == "stop" {
+            return match self.server.take() {
+                Some(server) => Ok(MsgState::DynamicMsg(format!(
+                    "Stopped serving {}",
+                    server.url()
+                ))),
+                None => Err("Not serving".to_string()),
+            };
+        }
+        if let Some(server) = &self.server {
+            return Err(format!("Already serving at {}", server.url()));
+        }
+        let port = if args.is_empty() {
+            self.config.serve_port
+        } else {
+            args.parse()
+                .map_err(|_| format!("Usage: serve [port|stop], not {}", args))?
+        };
+        let server = Server::start(&self.config.serve_bind, port, self.snapshot())?;
+        let msg = format!("Serving at {} (:serve stop to end)", server.url());
+        self.server = Some(server);
+        Ok(MsgState::DynamicMsg(msg))
+    }
+
+    // called after every key so the page is never more than a refresh behind
+    pub(super) fn update_served(&self) {
+        if let Some(server) = &self.server {
+            server.update(self.snapshot());
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::{App, ITEM_HEIGHT, Window};
+use crate::{config::Config, err_msg::WithErrMsg, filter::Filter, timestamp};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs::OpenOptions, io::Write, path::Path};
+
+const SESSION_FILE: &str = "session.toml";
+const LOG_FILE: &str = "uat_editor.log";
+
+// where the user was when they last quit, reapplied on the next start
+#[derive(Debug, Deserialize, Serialize)]
+struct Session {
+    window: Window,
+    #[serde(default)]
+    selected: Option<usize>,
+    // the row is found again by id when steps were added or removed above it
+    #[serde(default)]
+    selected_id: Option<u64>,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    filter: Option<String>,
+    // mark name to step id, toml keys have to be strings
+    #[serde(default)]
+    marks: BTreeMap<String, u64>,
+}
+
+// session state lives next to the config file, wherever that was pointed
+fn config_dir_path(file: &str) -> Result<String, String> {
+    let config = Config::path()?;
+    let dir = Path::new(&config).parent().unwrap_or(Path::new("."));
+    Ok(dir.join(file).to_string_lossy().to_string())
+}
+
+// a bad session file isn't worth interrupting startup over, it's only noted here
+fn log(msg: &str) {
+    let Ok(path) = config_dir_path(LOG_FILE) else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{} {}", timestamp::now(), msg);
+    }
+}
+
+impl App {
+    pub(super) fn write_session(&self) -> Result<(), String> {
+        let selected = self.state.selected();
+        let selected_id = match self.window {
+            Window::Uat => selected
+                .and_then(|i| self.items.get(i))
+                .map(|item| item.id)
+                .filter(|id| *id != 0),
+            Window::Template | Window::Snippets => None,
+        };
+        let session = Session {
+            window: self.window,
+            selected,
+            selected_id,
+            offset: self.view_state.offset(),
+            filter: self.filter.as_ref().map(|f| f.query.clone()),
+            marks: self
+                .marks
+                .iter()
+                .map(|(name, id)| (name.to_string(), *id))
+                .collect(),
+        };
+        let toml = toml::to_string(&session).with_err_msg(&"Failed to serialize session")?;
+        std::fs::write(config_dir_path(SESSION_FILE)?, toml)
+            .with_err_msg(&"Failed to write session")
+    }
+
+    pub(super) fn restore_session(&mut self) {
+        let Ok(content) = config_dir_path(SESSION_FILE)
+            .and_then(|path| std::fs::read_to_string(path).with_err_msg(&"Failed to read session"))
+        else {
+            return;
+        };
+        if let Err(err) = self.apply_session(&content) {
+            log(&format!("Ignoring session.toml: {}", err));
+        }
+    }
+
+    // everything is checked before anything is applied, so a stale session changes nothing
+    fn apply_session(&mut self, content: &str) -> Result<(), String> {
+        let session: Session = toml::from_str(content).map_err(|err| err.to_string())?;
+        let filter = session.filter.as_deref().map(Filter::parse).transpose()?;
+        let len = match session.window {
+            Window::Uat => self.items.len(),
+            Window::Template => self.template_list.len(),
+            Window::Snippets => self.snippets.steps.len(),
+        };
+        let by_id = session
+            .selected_id
+            .and_then(|id| self.items.iter().position(|item| item.id == id));
+        let selected = by_id.or(session.selected.filter(|i| *i < len));
+        if session.selected.is_some() && selected.is_none() {
+            return Err("selected row no longer exists".to_string());
+        }
+
+        self.window = session.window;
+        self.filter = filter;
+        // marks on steps that no longer exist are dropped when they're next used
+        self.marks = session
+            .marks
+            .into_iter()
+            .filter_map(|(name, id)| Some((name.chars().next()?, id)))
+            .collect();
+        if let Some(i) = selected {
+            self.state.select(Some(i));
+            self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+            *self.view_state.offset_mut() = session.offset;
+        }
+        self.clamp_to_visible();
+        Ok(())
+    }
+}
@@ -0,0 +1,447 @@
+use super::{App, MsgState};
+use crate::{
+    test_step::{Priority, StepStatus, TestStep},
+    timestamp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Instructions,
+    TestData,
+    ExpectedResults,
+    Ac,
+    Comment,
+    Links,
+    Status,
+    Priority,
+    Estimate,
+    Author,
+}
+
+// every name `:setfield` takes, the first of each is the one listed in errors. a new
+// TestStep field goes here and in `assign`
+const FIELDS: [(&[&str], Field); 10] = [
+    (&["instructions", "action"], Field::Instructions),
+    (&["test_data", "data"], Field::TestData),
+    (&["expected", "expected_results"], Field::ExpectedResults),
+    (&["ac"], Field::Ac),
+    (&["comment"], Field::Comment),
+    (&["links", "link"], Field::Links),
+    (&["status"], Field::Status),
+    (&["priority", "prio"], Field::Priority),
+    (&["estimate"], Field::Estimate),
+    (&["author"], Field::Author),
+];
+
+const USAGE: &str = "Usage: setfield <field>=<value> or <field>+=<value>";
+
+impl Field {
+    fn parse(name: &str) -> Result<Field, String> {
+        FIELDS
+            .iter()
+            .find(|(names, _)| names.contains(&name))
+            .map(|(_, field)| *field)
+            .ok_or_else(|| {
+                let names: Vec<&str> = FIELDS.iter().map(|(names, _)| names[0]).collect();
+                format!("Unknown field '{}', one of {}", name, names.join(", "))
+            })
+    }
+
+    fn name(self) -> &'static str {
+        FIELDS
+            .iter()
+            .find(|(_, field)| *field == self)
+            .map(|(names, _)| names[0])
+            .unwrap_or_default()
+    }
+
+    // the cells that hold free text, the only ones content can move between
+    fn text_mut(self, step: &mut TestStep) -> Option<&mut String> {
+        match self {
+            Field::Instructions => Some(&mut step.instructions),
+            Field::TestData => Some(&mut step.test_data),
+            Field::ExpectedResults => Some(&mut step.expected_results),
+            Field::Ac => Some(&mut step.ac),
+            Field::Comment => Some(&mut step.comment),
+            _ => None,
+        }
+    }
+
+    // text cells take another line, the ac takes another reference
+    fn separator(self) -> &'static str {
+        match self {
+            Field::Ac => ", ",
+            _ => "\n",
+        }
+    }
+}
+
+fn append_text(text: &mut String, value: &str, separator: &str) {
+    match (text.is_empty(), value.is_empty()) {
+        (_, true) => {}
+        (true, false) => *text = value.to_string(),
+        (false, false) => *text = format!("{}{}{}", text, separator, value),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Assignment {
+    field: Field,
+    append: bool,
+    value: String,
+}
+
+impl Assignment {
+    // `ac=AC-7` replaces, `ac+=AC-7` adds to what is there
+    fn parse(args: &str) -> Result<Assignment, String> {
+        let (name, value) = args.split_once('=').ok_or(USAGE.to_string())?;
+        let (name, append) = match name.trim_end().strip_suffix('+') {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(USAGE.to_string());
+        }
+        Ok(Assignment {
+            field: Field::parse(name)?,
+            append,
+            value: value.trim().to_string(),
+        })
+    }
+
+    fn assign(&self, step: &mut TestStep) -> Result<(), String> {
+        let separator = self.field.separator();
+        if let Some(text) = self.field.text_mut(step) {
+            match self.append {
+                true => append_text(text, &self.value, separator),
+                false => *text = self.value.clone(),
+            }
+            return Ok(());
+        }
+        match self.field {
+            Field::Links => {
+                if !self.append {
+                    step.links.clear();
+                }
+                if !self.value.is_empty() && !step.links.contains(&self.value) {
+                    step.links.push(self.value.clone());
+                }
+            }
+            Field::Estimate => {
+                let minutes = match self.value.as_str() {
+                    "" => None,
+                    value => Some(TestStep::parse_estimate(value).ok_or(format!(
+                        "Invalid estimate: {}, expected e.g. 5, 30m or 1h30m",
+                        value
+                    ))?),
+                };
+                step.estimate_minutes = match (self.append, step.estimate_minutes, minutes) {
+                    (true, Some(current), Some(minutes)) => Some(current.saturating_add(minutes)),
+                    (true, current, None) => current,
+                    (_, _, minutes) => minutes,
+                };
+            }
+            field if self.append => {
+                return Err(format!(
+                    "{} holds a single value, use {}= to set it",
+                    field.name(),
+                    field.name()
+                ));
+            }
+            Field::Status => step.status = StepStatus::parse(&self.value)?,
+            Field::Priority => step.priority = Priority::parse(&self.value)?,
+            Field::Author => {
+                step.author = Some(self.value.clone()).filter(|author| !author.is_empty())
+            }
+            Field::Instructions
+            | Field::TestData
+            | Field::ExpectedResults
+            | Field::Ac
+            | Field::Comment => {}
+        }
+        Ok(())
+    }
+}
+
+const MOVE_USAGE: &str = "Usage: movefield <from>-><to> [replace|append]";
+
+// `expected->ac` empties expected into ac, over what ac held unless appending
+#[derive(Debug, PartialEq)]
+struct Move {
+    from: Field,
+    to: Field,
+    append: bool,
+}
+
+impl Move {
+    fn parse(args: &str) -> Result<Move, String> {
+        let mut args = args.split_whitespace();
+        let (from, to) = args
+            .next()
+            .and_then(|pair| pair.split_once("->"))
+            .ok_or(MOVE_USAGE.to_string())?;
+        let append = match args.next() {
+            None | Some("replace") => false,
+            Some("append") => true,
+            Some(_) => return Err(MOVE_USAGE.to_string()),
+        };
+        let (from, to) = (Field::parse(from)?, Field::parse(to)?);
+        for field in [from, to] {
+            if field.text_mut(&mut TestStep::new()).is_none() {
+                return Err(format!(
+                    "{} isn't text, movefield takes instructions, test_data, expected, ac or comment",
+                    field.name()
+                ));
+            }
+        }
+        if from == to {
+            return Err(format!("Both sides are {}", from.name()));
+        }
+        Ok(Move { from, to, append })
+    }
+
+    // a step with nothing in the source keeps its target
+    fn apply(&self, step: &mut TestStep) {
+        let Some(source) = self.from.text_mut(step).map(std::mem::take) else {
+            return;
+        };
+        if source.is_empty() {
+            return;
+        }
+        if let Some(target) = self.to.text_mut(step) {
+            match self.append {
+                true => append_text(target, &source, self.to.separator()),
+                false => *target = source,
+            }
+        }
+    }
+}
+
+fn swap_expected_ac(step: &mut TestStep) {
+    std::mem::swap(&mut step.expected_results, &mut step.ac);
+}
+
+// sections and comments are left alone, Ok is how many steps changed
+fn for_each_step<F>(items: &mut [TestStep], mut change: F) -> Result<usize, String>
+where
+    F: FnMut(&mut TestStep) -> Result<(), String>,
+{
+    if !items.iter().any(TestStep::is_step) {
+        return Err("No steps in the selection".to_string());
+    }
+    let mut changed = 0;
+    for step in items.iter_mut().filter(|item| item.is_step()) {
+        let before = step.clone();
+        change(step)?;
+        if *step != before {
+            step.updated_at = Some(timestamp::now());
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+impl App {
+    // on the visual selection or the selected row, one undo for the lot and none when
+    // nothing changed
+    fn change_selected_steps<F>(&mut self, change: F) -> Result<usize, String>
+    where
+        F: FnMut(&mut TestStep) -> Result<(), String>,
+    {
+        let range = self
+            .selected_range()
+            .ok_or_else(|| "No item is currently selected".to_string())?;
+        let snapshot = self.items.clone();
+        let changed = match for_each_step(&mut self.items[range], change) {
+            Ok(changed) => changed,
+            Err(err) => {
+                self.items = snapshot;
+                return Err(err);
+            }
+        };
+        if changed > 0 {
+            self.push_undo_snapshot(snapshot);
+        }
+        Ok(changed)
+    }
+
+    // `:setfield ac=AC-7`, `:setfield ac+=AC-7`
+    pub(super) fn set_field_command(&mut self, args: &str) -> Result<MsgState, String> {
+        let assignment = Assignment::parse(args)?;
+        let changed = self.change_selected_steps(|step| assignment.assign(step))?;
+        Ok(MsgState::DynamicMsg(format!(
+            "Set {} on {} step{}",
+            assignment.field.name(),
+            changed,
+            plural(changed)
+        )))
+    }
+
+    // `:movefield expected->ac [append]`
+    pub(super) fn move_field_command(&mut self, args: &str) -> Result<MsgState, String> {
+        let moved = Move::parse(args)?;
+        let changed = self.change_selected_steps(|step| {
+            moved.apply(step);
+            Ok(())
+        })?;
+        Ok(MsgState::DynamicMsg(format!(
+            "Moved {} to {} on {} step{}",
+            moved.from.name(),
+            moved.to.name(),
+            changed,
+            plural(changed)
+        )))
+    }
+
+    // `gs`, for imports that put the expected results and the ac in each other's column
+    pub(super) fn swap_expected_ac(&mut self) -> Result<MsgState, String> {
+        let changed = self.change_selected_steps(|step| {
+            swap_expected_ac(step);
+            Ok(())
+        })?;
+        Ok(MsgState::DynamicMsg(format!(
+            "Swapped expected and ac on {} step{}",
+            changed,
+            plural(changed)
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_field(items: &mut [TestStep], assignment: &Assignment) -> Result<usize, String> {
+        for_each_step(items, |step| assignment.assign(step))
+    }
+
+    fn steps() -> Vec<TestStep> {
+        vec![
+            TestStep::section("Login"),
+            TestStep::new().with_instructions("Open app"),
+            TestStep::new().with_instructions("Sign in"),
+        ]
+    }
+
+    #[test]
+    fn appending_adds_to_what_overwriting_replaces() {
+        let mut items = steps();
+        items[1].ac = "AC-1".to_string();
+
+        let append = Assignment::parse("ac += AC-7").unwrap();
+        assert_eq!(set_field(&mut items, &append), Ok(2));
+        assert_eq!(items[1].ac, "AC-1, AC-7");
+        assert_eq!(items[2].ac, "AC-7");
+        assert_eq!(items[0].ac, "");
+
+        let set = Assignment::parse("ac=AC-9").unwrap();
+        assert_eq!(set_field(&mut items, &set), Ok(2));
+        assert_eq!(items[1].ac, "AC-9");
+        // nothing left to change
+        assert_eq!(set_field(&mut items, &set), Ok(0));
+
+        let estimate = Assignment::parse("estimate+=10m").unwrap();
+        items[1].estimate_minutes = Some(5);
+        set_field(&mut items, &estimate).unwrap();
+        assert_eq!(items[1].estimate_minutes, Some(15));
+        assert_eq!(items[2].estimate_minutes, Some(10));
+        items[1].estimate_minutes = Some(u32::MAX);
+        set_field(&mut items, &estimate).unwrap();
+        assert_eq!(items[1].estimate_minutes, Some(u32::MAX));
+
+        let links = Assignment::parse("links+=https://ci/1").unwrap();
+        set_field(&mut items, &links).unwrap();
+        assert_eq!(set_field(&mut items, &links), Ok(0));
+        assert_eq!(items[1].links, vec!["https://ci/1"]);
+
+        let prio = Assignment::parse("prio=high").unwrap();
+        set_field(&mut items, &prio).unwrap();
+        assert_eq!(items[2].priority, Some(Priority::High));
+        let err = Assignment::parse("prio+=low").unwrap();
+        assert!(
+            set_field(&mut items, &err)
+                .unwrap_err()
+                .contains("priority=")
+        );
+    }
+
+    #[test]
+    fn empty_selections_and_unknown_fields_are_refused() {
+        let mut items = steps();
+        let set = Assignment::parse("ac=AC-7").unwrap();
+        assert_eq!(
+            set_field(&mut items[..1], &set),
+            Err("No steps in the selection".to_string())
+        );
+        assert_eq!(
+            set_field(&mut [], &set),
+            Err("No steps in the selection".to_string())
+        );
+
+        let err = Assignment::parse("owner=me").unwrap_err();
+        assert!(
+            err.starts_with("Unknown field 'owner', one of instructions"),
+            "{}",
+            err
+        );
+        assert!(err.contains("priority, estimate, author"), "{}", err);
+        assert_eq!(Assignment::parse("ac"), Err(USAGE.to_string()));
+        assert_eq!(Assignment::parse("+=x"), Err(USAGE.to_string()));
+    }
+
+    #[test]
+    fn moves_skip_empty_sources_and_can_append() {
+        let mut items = steps();
+        items[1].expected_results = "Signed in".to_string();
+        items[1].ac = "AC-1".to_string();
+        items[2].ac = "AC-2".to_string();
+
+        let append = Move::parse("expected->ac append").unwrap();
+        let changed = for_each_step(&mut items, |step| {
+            append.apply(step);
+            Ok(())
+        });
+        // the second step has no expected results, its ac stays
+        assert_eq!(changed, Ok(1));
+        assert_eq!(items[1].ac, "AC-1, Signed in");
+        assert_eq!(items[1].expected_results, "");
+        assert_eq!(items[2].ac, "AC-2");
+
+        let replace = Move::parse("ac->comment").unwrap();
+        assert!(!replace.append);
+        items[2].comment = "old".to_string();
+        for_each_step(&mut items, |step| {
+            replace.apply(step);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(items[2].comment, "AC-2");
+        assert_eq!(items[2].ac, "");
+
+        items[1].expected_results = "Home page".to_string();
+        items[1].ac = "AC-3".to_string();
+        for_each_step(&mut items[1..2], |step| {
+            swap_expected_ac(step);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            (items[1].expected_results.as_str(), items[1].ac.as_str()),
+            ("AC-3", "Home page")
+        );
+
+        assert_eq!(Move::parse("ac->ac"), Err("Both sides are ac".to_string()));
+        assert!(
+            Move::parse("status->ac")
+                .unwrap_err()
+                .contains("isn't text")
+        );
+        assert_eq!(Move::parse("ac comment"), Err(MOVE_USAGE.to_string()));
+        assert_eq!(Move::parse("ac->comment both"), Err(MOVE_USAGE.to_string()));
+    }
+}
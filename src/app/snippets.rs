@@ -0,0 +1,349 @@
+use super::{App, InsertDirection, MsgState, Window, overlay};
+use crate::{
+    config::Config, err_msg::WithErrMsg, fuzzy, placeholders, test_step::TestStep,
+    text_input::clean_paste, timestamp,
+};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::Stylize,
+    text::{Line, Text},
+    widgets::{Clear, Paragraph},
+};
+use std::collections::HashMap;
+
+// the picker never grows taller than this many matches
+const PICKER_ROWS: usize = 10;
+
+// the snippets sorted by name, the snippets window shows `steps` as its rows
+#[derive(Default)]
+pub(super) struct SnippetList {
+    pub names: Vec<String>,
+    pub steps: Vec<TestStep>,
+}
+
+pub(super) fn snippet_list(config: &Config) -> SnippetList {
+    let mut names: Vec<String> = config.snippets.keys().cloned().collect();
+    names.sort();
+    let steps = names
+        .iter()
+        .map(|name| config.snippets[name].clone())
+        .collect();
+    SnippetList { names, steps }
+}
+
+// `i s`, typing narrows the list
+#[derive(Default)]
+pub(super) struct Picker {
+    query: String,
+    selected: usize,
+}
+
+impl Picker {
+    // the query takes a paste as typed, returns the control characters dropped
+    pub(super) fn paste(&mut self, text: &str) -> usize {
+        let (text, dropped) = clean_paste(text, false);
+        self.query.push_str(&text);
+        self.selected = 0;
+        dropped
+    }
+}
+
+// only what makes the step reusable, not where or when it was last used
+fn reusable(step: &TestStep) -> TestStep {
+    TestStep {
+        id: 0,
+        created_at: None,
+        updated_at: None,
+        author: None,
+        status: None,
+        comment: String::new(),
+        review_notes: Vec::new(),
+        ..step.clone()
+    }
+}
+
+impl App {
+    fn picker_matches(&self, query: &str) -> Vec<String> {
+        fuzzy::rank(query, self.snippets.names.iter().map(String::as_str))
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn refresh_snippets(&mut self) {
+        self.snippets = snippet_list(&self.config);
+        if self.window == Window::Snippets {
+            self.clamp_to_visible();
+        }
+    }
+
+    // `:snippet [name]`, an exact name goes straight in, anything else opens the picker
+    pub(super) fn snippet_command(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        name: &str,
+    ) -> Result<MsgState, String> {
+        if self.config.snippets.contains_key(name) {
+            return self.insert_snippet(terminal, name);
+        }
+        self.open_snippet_picker(name)
+    }
+
+    pub(super) fn open_snippet_picker(&mut self, query: &str) -> Result<MsgState, String> {
+        if self.config.snippets.is_empty() {
+            return Err("No snippets yet, save one with :savesnippet <name>".to_string());
+        }
+        self.picker = Some(Picker {
+            query: query.to_string(),
+            selected: 0,
+        });
+        Ok(MsgState::Default)
+    }
+
+    // `:savesnippet [name]`, an existing snippet of that name is replaced
+    pub(super) fn save_snippet(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        name: &str,
+    ) -> Result<MsgState, String> {
+        let step = self
+            .state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+            .filter(|step| self.window == Window::Uat && step.is_step())
+            .map(reusable)
+            .with_err_msg(&"Select a step to save it as a snippet")?;
+        let name = match name {
+            "" => self.prompt(terminal, "Enter a snippet name")?,
+            name => name.to_string(),
+        };
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Snippets need a name".to_string());
+        }
+        let replaced = self.config.snippets.insert(name.clone(), step).is_some();
+        self.config.save_config()?;
+        self.refresh_snippets();
+        Ok(MsgState::DynamicMsg(format!(
+            "{} snippet '{}'",
+            if replaced { "Replaced" } else { "Saved" },
+            name
+        )))
+    }
+
+    // asks for a value for every {{placeholder}}, leaving any left blank as it was
+    pub(super) fn fill_placeholders(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        steps: &mut [TestStep],
+    ) -> Result<(), String> {
+        let mut values = HashMap::new();
+        for name in placeholders::names(steps) {
+            let value = self.prompt(terminal, &format!("Value for {{{{{}}}}}", name))?;
+            if !value.trim().is_empty() {
+                values.insert(name, value.trim().to_string());
+            }
+        }
+        placeholders::fill(steps, &values);
+        Ok(())
+    }
+
+    // a copy goes in below the cursor in the document
+    fn insert_snippet(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        name: &str,
+    ) -> Result<MsgState, String> {
+        if let Some(refusal) = self.readonly_refusal() {
+            return Ok(refusal);
+        }
+        let snippet = self
+            .config
+            .snippets
+            .get(name)
+            .cloned()
+            .with_err_msg(&format!("No snippet named '{}'", name))?;
+        let mut steps = vec![snippet];
+        self.fill_placeholders(terminal, &mut steps)?;
+        let now = timestamp::now();
+        let step = TestStep {
+            id: TestStep::next_id(&self.items),
+            created_at: Some(now),
+            updated_at: Some(now),
+            author: self.config.author(),
+            ..steps.remove(0)
+        };
+        self.switch_window(Window::Uat);
+        self.push_undo();
+        self.insert_items(InsertDirection::Down, vec![step]);
+        Ok(MsgState::DynamicMsg(format!("Inserted snippet '{}'", name)))
+    }
+
+    pub(super) fn handle_picker_keys(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        key: KeyEvent,
+    ) -> Result<MsgState, String> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        let count = match &self.picker {
+            Some(picker) => self.picker_matches(&picker.query).len(),
+            None => return Ok(MsgState::Default),
+        };
+        let Some(picker) = &mut self.picker else {
+            return Ok(MsgState::Default);
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let last = count.saturating_sub(1);
+        match key.code {
+            KeyCode::Esc => self.picker = None,
+            KeyCode::Down => picker.selected = (picker.selected + 1).min(last),
+            KeyCode::Char('j' | 'n') if ctrl => picker.selected = (picker.selected + 1).min(last),
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Char('k' | 'p') if ctrl => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                picker.query.pop();
+                picker.selected = 0;
+            }
+            KeyCode::Char(c) => {
+                picker.query.push(c);
+                picker.selected = 0;
+            }
+            KeyCode::Enter => {
+                let (query, selected) = (picker.query.clone(), picker.selected);
+                self.picker = None;
+                let matches = self.picker_matches(&query);
+                return Ok(MsgState::log_err_msg_or(
+                    match matches.get(selected.min(matches.len().saturating_sub(1))) {
+                        Some(name) => self.insert_snippet(terminal, &name.clone()),
+                        None => Err(format!("No snippet matches '{}'", query)),
+                    },
+                ));
+            }
+            _ => {}
+        }
+        Ok(MsgState::Default)
+    }
+
+    pub(super) fn render_picker(&self, frame: &mut Frame) {
+        let Some(picker) = &self.picker else {
+            return;
+        };
+        let matches = self.picker_matches(&picker.query);
+        let selected = picker.selected.min(matches.len().saturating_sub(1));
+        // the selection scrolls the list once it passes the bottom
+        let first = (selected + 1).saturating_sub(PICKER_ROWS);
+        let mut lines = vec![Line::from(format!("> {}▏", picker.query)), Line::from("")];
+        if matches.is_empty() {
+            lines.push(Line::from("no matches").dim());
+        }
+        for (idx, name) in matches.iter().enumerate().skip(first).take(PICKER_ROWS) {
+            let idx_in_list = self.snippets.names.iter().position(|n| n == name);
+            let first_line = idx_in_list
+                .map(|i| self.snippets.steps[i].instructions())
+                .unwrap_or_default();
+            let first_line = first_line.lines().next().unwrap_or("").to_string();
+            let line = Line::from(vec![name.clone().bold(), format!("  {}", first_line).dim()]);
+            lines.push(if idx == selected {
+                line.style(self.colors.selected_row_style())
+            } else {
+                line
+            });
+        }
+        let area = overlay::popup_area(frame.area(), &lines);
+        let popup = Paragraph::new(Text::from(lines))
+            .style(self.colors.info_style())
+            .block(self.colors.info_block().title(" Insert snippet "));
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup, area);
+    }
+
+    // `:snippets`
+    pub(super) fn open_snippets(&mut self) -> Result<MsgState, String> {
+        if self.config.snippets.is_empty() {
+            return Err("No snippets yet, save one with :savesnippet <name>".to_string());
+        }
+        self.switch_window(Window::Snippets);
+        Ok(MsgState::Default)
+    }
+
+    fn selected_snippet(&self) -> Result<String, String> {
+        self.state
+            .selected()
+            .and_then(|idx| self.snippets.names.get(idx))
+            .cloned()
+            .with_err_msg(&"No snippet is currently selected")
+    }
+
+    fn rename_snippet(&mut self, terminal: &mut DefaultTerminal) -> Result<MsgState, String> {
+        let old = self.selected_snippet()?;
+        let new = self.prompt(terminal, &format!("Rename '{}' to", old))?;
+        let new = new.trim().to_string();
+        if new.is_empty() || new == old {
+            return Ok(MsgState::Default);
+        }
+        if self.config.snippets.contains_key(&new) {
+            return Err(format!("A snippet named '{}' already exists", new));
+        }
+        let step = self
+            .config
+            .snippets
+            .remove(&old)
+            .with_err_msg(&"No snippet found with matching name")?;
+        self.config.snippets.insert(new.clone(), step);
+        self.config.save_config()?;
+        self.refresh_snippets();
+        if let Some(idx) = self.snippets.names.iter().position(|n| *n == new) {
+            self.state.select(Some(idx));
+        }
+        Ok(MsgState::DynamicMsg(format!(
+            "Renamed snippet '{}' to '{}'",
+            old, new
+        )))
+    }
+
+    fn delete_snippet(&mut self) -> Result<MsgState, String> {
+        let name = self.selected_snippet()?;
+        self.config.snippets.remove(&name);
+        self.config.save_config()?;
+        self.refresh_snippets();
+        if self.snippets.names.is_empty() {
+            self.switch_window(Window::Uat);
+        }
+        Ok(MsgState::DynamicMsg(format!("Deleted snippet '{}'", name)))
+    }
+
+    pub(super) fn handle_snippet_keys(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        key: KeyEvent,
+    ) -> Result<MsgState, String> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(MsgState::Default);
+        }
+        let res = match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.next_row();
+                Ok(MsgState::Default)
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.previous_row();
+                Ok(MsgState::Default)
+            }
+            KeyCode::Esc => {
+                self.switch_window(Window::Uat);
+                Ok(MsgState::Default)
+            }
+            KeyCode::Char('q') => return self.quit(),
+            KeyCode::Enter => self
+                .selected_snippet()
+                .and_then(|name| self.insert_snippet(terminal, &name)),
+            KeyCode::Char('r') => self.rename_snippet(terminal),
+            KeyCode::Char('d') => self.delete_snippet(),
+            _ => Ok(MsgState::Default),
+        };
+        Ok(MsgState::log_err_msg_or(res))
+    }
+}
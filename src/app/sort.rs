@@ -0,0 +1,216 @@
+use super::{App, MsgState};
+use crate::test_step::TestStep;
+use std::cmp::Ordering;
+use std::ops::Range;
+
+const USAGE: &str = "Usage: sort[!] instructions|status|priority|estimate [asc|desc]";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum SortKey {
+    Instructions,
+    Status,
+    Priority,
+    Estimate,
+}
+
+impl SortKey {
+    fn parse(input: &str) -> Result<SortKey, String> {
+        match input {
+            "instructions" | "name" | "" => Ok(SortKey::Instructions),
+            "status" => Ok(SortKey::Status),
+            "priority" | "prio" => Ok(SortKey::Priority),
+            "estimate" => Ok(SortKey::Estimate),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SortKey::Instructions => "instructions",
+            SortKey::Status => "status",
+            SortKey::Priority => "priority",
+            SortKey::Estimate => "estimate",
+        }
+    }
+
+    // steps without a status, priority or estimate always go last, high priority first
+    fn compare(self, a: &TestStep, b: &TestStep) -> Ordering {
+        match self {
+            SortKey::Instructions => a
+                .instructions()
+                .to_lowercase()
+                .cmp(&b.instructions().to_lowercase()),
+            SortKey::Status => (a.status.is_none(), a.status).cmp(&(b.status.is_none(), b.status)),
+            SortKey::Priority => {
+                (a.priority.is_none(), a.priority).cmp(&(b.priority.is_none(), b.priority))
+            }
+            SortKey::Estimate => (a.estimate_minutes.is_none(), a.estimate_minutes)
+                .cmp(&(b.estimate_minutes.is_none(), b.estimate_minutes)),
+        }
+    }
+}
+
+// the last `:sort`, shown in the header until another document is loaded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct ActiveSort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl ActiveSort {
+    // status, priority and estimate are shown beside the directions, so every key marks that column
+    pub(super) fn indicator(self) -> String {
+        let glyph = if self.descending { "▼" } else { "▲" };
+        match self.key {
+            SortKey::Instructions => glyph.to_string(),
+            key => format!("{} {}", glyph, key.name()),
+        }
+    }
+}
+
+// the rows between the section header at or above `idx` and the next one
+pub(super) fn section_range(items: &[TestStep], idx: usize) -> Range<usize> {
+    let start = items[..=idx.min(items.len().saturating_sub(1))]
+        .iter()
+        .rposition(|i| i.is_new_section)
+        .map_or(0, |header| header + 1);
+    let end = items[start..]
+        .iter()
+        .position(|i| i.is_new_section)
+        .map_or(items.len(), |next| start + next);
+    start..end
+}
+
+// sorts the steps in `items`, section headers and comments keep their positions and
+// the steps are dealt back into the slots between them. returns how many steps moved
+pub(super) fn sort_steps(items: &mut [TestStep], key: SortKey, descending: bool) -> usize {
+    let slots: Vec<usize> = (0..items.len()).filter(|i| items[*i].is_step()).collect();
+    let mut steps: Vec<TestStep> = slots.iter().map(|i| items[*i].clone()).collect();
+    steps.sort_by(|a, b| {
+        let ord = key.compare(a, b);
+        if descending { ord.reverse() } else { ord }
+    });
+    let mut moved = 0;
+    for (slot, step) in slots.into_iter().zip(steps) {
+        if items[slot] != step {
+            moved += 1;
+        }
+        items[slot] = step;
+    }
+    moved
+}
+
+impl App {
+    // `:sort` works on the visual selection or the current section, `:sort!` on everything
+    pub(super) fn sort_command(
+        &mut self,
+        args: &str,
+        everything: bool,
+    ) -> Result<MsgState, String> {
+        let mut args = args.split_whitespace();
+        let key = SortKey::parse(args.next().unwrap_or(""))?;
+        let descending = match args.next() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(_) => return Err(USAGE.to_string()),
+        };
+        if self.items.is_empty() {
+            return Err("Nothing to sort".to_string());
+        }
+        let range = if everything {
+            0..self.items.len()
+        } else if self.visual_anchor.is_some() {
+            let range = self
+                .selected_range()
+                .ok_or_else(|| "No item is currently selected".to_string())?;
+            *range.start()..range.end() + 1
+        } else {
+            section_range(&self.items, self.state.selected().unwrap_or(0))
+        };
+
+        let snapshot = self.items.clone();
+        let moved = sort_steps(&mut self.items[range], key, descending);
+        self.sorted_by = Some(ActiveSort { key, descending });
+        if moved == 0 {
+            return Ok(MsgState::DynamicMsg("Already sorted".to_string()));
+        }
+        self.push_undo_snapshot(snapshot);
+        self.visual_anchor = None;
+        Ok(MsgState::DynamicMsg(format!(
+            "Sorted, {} step{} moved",
+            moved,
+            if moved == 1 { "" } else { "s" }
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_step::{Priority, StepStatus};
+
+    fn names(items: &[TestStep]) -> Vec<String> {
+        items.iter().map(|i| i.instructions()).collect()
+    }
+
+    #[test]
+    fn headers_and_comments_stay_pinned() {
+        let mut items = vec![
+            TestStep::section("Setup"),
+            TestStep::new().with_instructions("c"),
+            TestStep::comment("note"),
+            TestStep::new().with_instructions("A"),
+            TestStep::new().with_instructions("b"),
+        ];
+        assert_eq!(sort_steps(&mut items, SortKey::Instructions, false), 3);
+        assert_eq!(names(&items), vec!["Setup", "A", "note", "b", "c"]);
+        assert_eq!(sort_steps(&mut items, SortKey::Instructions, true), 2);
+        assert_eq!(names(&items), vec!["Setup", "c", "note", "b", "A"]);
+    }
+
+    #[test]
+    fn missing_values_sort_last() {
+        let mut items = vec![
+            TestStep::new().with_instructions("none"),
+            TestStep {
+                status: Some(StepStatus::Fail),
+                ..TestStep::new().with_instructions("fail")
+            },
+            TestStep {
+                status: Some(StepStatus::Pass),
+                ..TestStep::new().with_instructions("pass")
+            },
+        ];
+        sort_steps(&mut items, SortKey::Status, false);
+        assert_eq!(names(&items), vec!["pass", "fail", "none"]);
+
+        items[0].priority = Some(Priority::Low);
+        items[2].priority = Some(Priority::High);
+        sort_steps(&mut items, SortKey::Priority, false);
+        assert_eq!(names(&items), vec!["none", "pass", "fail"]);
+    }
+
+    #[test]
+    fn indicator_names_the_key_unless_it_is_the_column() {
+        let sort = |key, descending| ActiveSort { key, descending }.indicator();
+        assert_eq!(sort(SortKey::Instructions, false), "▲");
+        assert_eq!(sort(SortKey::Status, true), "▼ status");
+        assert_eq!(sort(SortKey::Estimate, false), "▲ estimate");
+    }
+
+    #[test]
+    fn sections_end_at_the_next_header() {
+        let items = vec![
+            TestStep::new().with_instructions("intro"),
+            TestStep::section("one"),
+            TestStep::new().with_instructions("a"),
+            TestStep::new().with_instructions("b"),
+            TestStep::section("two"),
+            TestStep::new().with_instructions("c"),
+        ];
+        assert_eq!(section_range(&items, 0), 0..1);
+        assert_eq!(section_range(&items, 1), 2..4);
+        assert_eq!(section_range(&items, 3), 2..4);
+        assert_eq!(section_range(&items, 5), 5..6);
+    }
+}
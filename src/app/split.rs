@@ -0,0 +1,61 @@
+use super::{App, MsgState};
+use crate::{
+    edit_session::Target,
+    err_msg::WithErrMsg,
+    test_step::{DOCUMENT_SEPARATOR, TestStep},
+    timestamp,
+};
+use ratatui::DefaultTerminal;
+
+const SPLIT_HINT: &str =
+    "<!-- Put a line containing only --- where this step should be split in two. -->";
+
+impl App {
+    // edits the selected step, a separator line in the result turns it into several steps
+    pub(super) fn split_step(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<MsgState, String> {
+        let idx = self
+            .state
+            .selected()
+            .filter(|idx| *idx < self.items.len())
+            .with_err_msg(&"No item is currently selected")?;
+        let original = self.items[idx].clone();
+        let markdown = format!(
+            "{}\n\n{}",
+            SPLIT_HINT,
+            original.gen_markdown_in(self.config.use_ac, self.config.locale)
+        );
+
+        let Some(content) = self.open_editor(Target::Step(idx), markdown, terminal)? else {
+            return Ok(MsgState::DynamicMsg("No changes".to_string()));
+        };
+        let mut parts = TestStep::parse_split(&content, &self.config.heading_aliases)?;
+        if parts.is_empty() {
+            return Err("Edited step was empty".to_string());
+        }
+
+        let author = self.config.author();
+        let first_id = TestStep::next_id(&self.items);
+        parts[0].inherit_metadata(&original, author.clone());
+        for (id, part) in (first_id..).zip(parts.iter_mut().skip(1)) {
+            part.id = id;
+            part.created_at = Some(timestamp::now());
+            part.updated_at = part.created_at;
+            part.author = author.clone();
+        }
+
+        let count = parts.len();
+        self.push_undo();
+        self.items.splice(idx..=idx, parts);
+        self.state.select(Some(idx));
+        Ok(match count {
+            1 => MsgState::Default,
+            _ => MsgState::DynamicMsg(format!(
+                "Split step at '{}' into {}",
+                DOCUMENT_SEPARATOR, count
+            )),
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use super::{App, MsgState, overlay::Overlay};
+use crate::{test_step::TestStep, words};
+use ratatui::{style::Stylize, text::Line};
+use std::collections::BTreeMap;
+
+impl App {
+    // totals per field across every step, and the longest step to point at
+    fn word_lines(&self) -> Vec<Line<'static>> {
+        let steps: Vec<(usize, &TestStep)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.is_step())
+            .collect();
+        let names = ["Instructions", "Test Data", "Expected Results", "AC"];
+        let counts = |item: &TestStep| {
+            [
+                &item.instructions,
+                &item.test_data,
+                &item.expected_results,
+                &item.ac,
+            ]
+            .map(|field| words::word_count(field))
+        };
+        let per_step: Vec<(usize, [usize; 4])> = steps
+            .iter()
+            .map(|(idx, item)| (*idx, counts(item)))
+            .collect();
+        let mut lines = Vec::new();
+        let mut total = 0;
+        for (field, name) in names.iter().enumerate() {
+            let count: usize = per_step.iter().map(|(_, counts)| counts[field]).sum();
+            total += count;
+            lines.push(Line::from(format!(
+                "  {}: {}",
+                name,
+                words::describe(count)
+            )));
+        }
+        lines.push(Line::from(format!("  Total: {}", words::describe(total))));
+
+        if let Some((idx, count)) = per_step
+            .iter()
+            .map(|(idx, counts)| (*idx, counts.iter().sum::<usize>()))
+            .max_by_key(|(idx, count)| (*count, std::cmp::Reverse(*idx)))
+            .filter(|(_, count)| *count > 0)
+        {
+            let labels = TestStep::step_labels(&self.items, self.config.numbering_start);
+            lines.push(Line::from(format!(
+                "  Longest: step {} ({})",
+                labels[idx],
+                words::describe(count)
+            )));
+        }
+        lines
+    }
+
+    fn stats_lines(&self) -> Vec<Line<'static>> {
+        let steps = self.items.iter().filter(|i| i.is_step()).count();
+        let sections = self.items.iter().filter(|i| i.is_new_section).count();
+        let comments = self.items.iter().filter(|i| i.is_stepless_comment).count();
+        let estimate = TestStep::total_estimate(&self.items);
+
+        let mut lines = vec![
+            Line::from(format!("Steps: {}", steps)),
+            Line::from(format!("Sections: {}", sections)),
+            Line::from(format!("Comments: {}", comments)),
+            Line::from(format!("Estimated: {}", TestStep::format_minutes(estimate))),
+            Line::from(""),
+            Line::from("Words").bold(),
+        ];
+        lines.extend(self.word_lines());
+        lines.extend([Line::from(""), Line::from("Authors").bold()]);
+
+        let mut authors: BTreeMap<&str, usize> = BTreeMap::new();
+        for item in &self.items {
+            let author = item.author.as_deref().unwrap_or("(unknown)");
+            *authors.entry(author).or_default() += 1;
+        }
+        lines.extend(
+            authors
+                .into_iter()
+                .map(|(author, count)| Line::from(format!("  {}: {}", author, count))),
+        );
+
+        lines
+    }
+
+    pub(super) fn open_stats(&mut self) -> Result<MsgState, String> {
+        self.overlay = Some(Overlay::new("Stats", self.stats_lines()));
+        Ok(MsgState::Default)
+    }
+}
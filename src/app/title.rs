@@ -0,0 +1,40 @@
+use super::{App, Window};
+use ratatui::{Frame, layout::Rect, text::Line, widgets::Paragraph};
+
+impl App {
+    fn mode_name(&self) -> &'static str {
+        match self.window {
+            Window::Template => "TEMPLATES",
+            Window::Snippets => "SNIPPETS",
+            Window::Uat if self.reorder.is_some() => "MOVE",
+            Window::Uat if self.visual_anchor.is_some() => "VISUAL",
+            Window::Uat => "UAT",
+        }
+    }
+
+    pub(super) fn title_line(&self) -> String {
+        let document = self.document.as_deref().unwrap_or("[unsaved]");
+        let dirty = if self.dirty { "*" } else { "" };
+        let readonly = if self.readonly { " [RO]" } else { "" };
+        let project = match &self.config.project {
+            Some(project) => format!(" | project: {}", project.name),
+            None => String::new(),
+        };
+        let steps = self.items.iter().filter(|i| i.is_step()).count();
+        format!(
+            " uat_editor: {}{}{} | {} | {} step{}{}",
+            document,
+            dirty,
+            readonly,
+            self.mode_name(),
+            steps,
+            if steps == 1 { "" } else { "s" },
+            project
+        )
+    }
+
+    pub(super) fn render_title(&self, frame: &mut Frame, area: Rect) {
+        let title = Paragraph::new(Line::from(self.title_line())).style(self.colors.title_style());
+        frame.render_widget(title, area);
+    }
+}
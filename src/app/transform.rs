@@ -0,0 +1,57 @@
+use super::{App, MsgState};
+use crate::{err_msg::WithErrMsg, transform};
+
+impl App {
+    // `:transform <name>`, the result opens as a new buffer so the original stays as it
+    // was until it is reviewed, `:b#` flips between the two
+    pub(super) fn transform_command(&mut self, name: &str) -> Result<MsgState, String> {
+        if name.is_empty() {
+            let mut names: Vec<&str> = self
+                .config
+                .transformers
+                .keys()
+                .map(String::as_str)
+                .collect();
+            names.sort();
+            return Err(format!("Usage: transform {}", names.join("|")));
+        }
+        let transformer = self
+            .config
+            .transformers
+            .get(name)
+            .cloned()
+            .with_err_msg(&format!("No transformer named '{}' in the config", name))?;
+        if self.items.is_empty() {
+            return Err("Nothing to transform".to_string());
+        }
+        let transformed = transform::run(
+            &transformer,
+            &self.items,
+            self.config.use_ac,
+            self.config.author(),
+        );
+        if transformed.failures.len() == transformed.chunks {
+            return Err(format!(
+                "Transformer {} failed, {}",
+                name, transformed.failures[0]
+            ));
+        }
+        for failure in &transformed.failures {
+            self.messages.push(&format!(
+                "Transformer {}: {}, kept the original",
+                name, failure
+            ));
+        }
+        self.open_buffer(transformed.items);
+        Ok(MsgState::DynamicMsg(match transformed.failures.len() {
+            0 => format!(
+                "Transformed with {} into a new buffer, :b# for the original",
+                name
+            ),
+            n => format!(
+                "Transformed with {} into a new buffer, {} chunk(s) failed and kept the original, see :messages",
+                name, n
+            ),
+        }))
+    }
+}
@@ -0,0 +1,218 @@
+use super::{App, InsertDirection, MsgState, footer, overlay::Overlay};
+use crate::{archive, config, crypto, err_msg::WithErrMsg, test_step::TestStep, timestamp};
+use ratatui::{style::Stylize, text::Line};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, path::Path};
+
+const TRASH_LIMIT: usize = 50;
+const TRASH_FILE: &str = "trash.json";
+
+// one deletion, everything it removed together
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub(super) struct TrashEntry {
+    deleted_at: u64,
+    what: String,
+    steps: Vec<TestStep>,
+}
+
+// everything deleted this session, newest first. only the last deletion is in the
+// register, this is where the rest can still be found
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct Trash {
+    entries: VecDeque<TrashEntry>,
+}
+
+impl Trash {
+    fn push(&mut self, what: &str, steps: Vec<TestStep>) {
+        if steps.is_empty() {
+            return;
+        }
+        self.entries.push_front(TrashEntry {
+            deleted_at: timestamp::now(),
+            what: what.to_string(),
+            steps,
+        });
+        self.entries.truncate(TRASH_LIMIT);
+    }
+}
+
+// kept beside the backup with `persist_trash`, so it outlives the session
+fn trash_path() -> Result<String, String> {
+    Ok(format!("{}/{}", config::paths()?.backup_dir, TRASH_FILE))
+}
+
+// how a single deleted row is listed
+pub(super) fn describe(step: &TestStep) -> String {
+    let kind = if step.is_new_section {
+        "section"
+    } else if step.is_stepless_comment {
+        "comment"
+    } else {
+        "step"
+    };
+    let title = step.instructions.lines().next().unwrap_or("").trim();
+    format!("{} '{}'", kind, footer::fit_message(title, 40))
+}
+
+fn entry_line(entry: &TrashEntry) -> Line<'static> {
+    let age = timestamp::format_age(timestamp::now().saturating_sub(entry.deleted_at));
+    let count = entry.steps.len();
+    Line::from(vec![
+        format!("{:<10} ", age).dim(),
+        format!("{} ", entry.what).into(),
+        format!("({} item{})", count, if count == 1 { "" } else { "s" }).dim(),
+    ])
+}
+
+impl App {
+    // every deletion goes through here, `what` is how it's listed in :trash
+    pub(super) fn trash(&mut self, what: &str, steps: Vec<TestStep>) {
+        self.trash.push(what, steps);
+        self.save_trash();
+    }
+
+    fn save_trash(&mut self) {
+        if !self.config.persist_trash {
+            return;
+        }
+        let saved = trash_path().and_then(|path| {
+            let dir = config::paths()?.backup_dir;
+            std::fs::create_dir_all(&dir).with_err_msg(&format!("Failed to create {}", dir))?;
+            let json =
+                serde_json::to_string(&self.trash).with_err_msg(&"Failed to serialize trash")?;
+            crypto::write(&path, &json)
+        });
+        if let Err(err) = saved {
+            self.messages.push(&err);
+        }
+    }
+
+    // a missing or unreadable trash file just starts an empty trash
+    pub(super) fn load_trash(&mut self) {
+        if !self.config.persist_trash {
+            return;
+        }
+        if let Some(trash) = trash_path()
+            .ok()
+            .and_then(|path| crypto::read(&path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+        {
+            self.trash = trash;
+        }
+    }
+
+    // `:trash`
+    pub(super) fn open_trash(&mut self) -> Result<MsgState, String> {
+        if self.trash.entries.is_empty() {
+            return Err("Trash is empty".to_string());
+        }
+        let lines = self.trash.entries.iter().map(entry_line).collect();
+        let targets = (0..self.trash.entries.len()).collect();
+        let mut overlay = Overlay::picker("Trash, Enter restores below, d purges", lines, targets);
+        overlay.trash = true;
+        self.overlay = Some(overlay);
+        Ok(MsgState::Default)
+    }
+
+    // one undoable insert below the cursor, ids already in use get fresh ones
+    pub(super) fn restore_steps(&mut self, mut steps: Vec<TestStep>) {
+        let mut next_id = TestStep::next_id(&self.items);
+        for step in &mut steps {
+            if step.id == 0 || self.items.iter().any(|item| item.id == step.id) {
+                step.id = next_id;
+                next_id += 1;
+            }
+        }
+        self.push_undo();
+        self.insert_items(InsertDirection::Down, steps);
+    }
+
+    pub(super) fn restore_trash(&mut self, idx: usize) -> Result<MsgState, String> {
+        if let Some(refusal) = self.readonly_refusal() {
+            return Ok(refusal);
+        }
+        let entry = self
+            .trash
+            .entries
+            .remove(idx)
+            .with_err_msg(&"No trash entry selected")?;
+        let count = entry.steps.len();
+        self.restore_steps(entry.steps);
+        self.save_trash();
+        Ok(MsgState::DynamicMsg(format!(
+            "Restored {} ({} item{})",
+            entry.what,
+            count,
+            if count == 1 { "" } else { "s" }
+        )))
+    }
+
+    // ctrl+shift+d or :clear, the table is written to the backup directory before it goes so
+    // :restore-last-clear can bring it back whatever happened since
+    pub(super) fn clear_all(&mut self) -> Result<MsgState, String> {
+        let dir = config::paths()?.backup_dir;
+        let Some(path) = archive::archive(Path::new(&dir), &self.items, timestamp::now())? else {
+            return Ok(MsgState::DynamicMsg("Nothing to clear".to_string()));
+        };
+        let steps = self.items.iter().filter(|i| i.is_step()).count();
+        self.push_undo();
+        let items = std::mem::take(&mut self.items);
+        self.trash("the whole document", items);
+        Ok(MsgState::DynamicMsg(format!(
+            "Cleared {} step{} (archived to {})",
+            steps,
+            if steps == 1 { "" } else { "s" },
+            path
+        )))
+    }
+
+    // `:restore-last-clear`, fills an empty table or goes in below the cursor
+    pub(super) fn restore_last_clear(&mut self) -> Result<MsgState, String> {
+        let dir = config::paths()?.backup_dir;
+        let (path, items) = archive::latest(Path::new(&dir))?;
+        let count = items.len();
+        self.restore_steps(items);
+        Ok(MsgState::DynamicMsg(format!(
+            "Restored {} item{} from {}",
+            count,
+            if count == 1 { "" } else { "s" },
+            path
+        )))
+    }
+
+    // the overlay stays open on the next entry until the trash is empty
+    pub(super) fn purge_trash(&mut self, idx: usize) -> Result<MsgState, String> {
+        let entry = self
+            .trash
+            .entries
+            .remove(idx)
+            .with_err_msg(&"No trash entry selected")?;
+        self.save_trash();
+        self.overlay = None;
+        if !self.trash.entries.is_empty() {
+            self.open_trash()?;
+            if let Some(overlay) = &mut self.overlay {
+                overlay.scroll = idx.min(self.trash.entries.len() - 1) as u16;
+            }
+        }
+        Ok(MsgState::DynamicMsg(format!("Purged {}", entry.what)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_first_and_bounded() {
+        let mut trash = Trash::default();
+        trash.push("nothing", Vec::new());
+        assert!(trash.entries.is_empty());
+        for n in 0..TRASH_LIMIT + 5 {
+            trash.push(&format!("step {}", n), vec![TestStep::new()]);
+        }
+        assert_eq!(trash.entries.len(), TRASH_LIMIT);
+        assert_eq!(trash.entries[0].what, format!("step {}", TRASH_LIMIT + 4));
+        assert_eq!(trash.entries[TRASH_LIMIT - 1].what, "step 5");
+    }
+}
@@ -0,0 +1,130 @@
+use super::{App, MsgState};
+use ratatui::crossterm::event::KeyCode;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Align {
+    Center,
+    Top,
+    Bottom,
+}
+
+// the table offset that puts row `selected` at `align` when `rows` fit on screen,
+// clamped so the table never scrolls past either end
+pub(super) fn align_offset(selected: usize, len: usize, rows: usize, align: Align) -> usize {
+    let rows = rows.max(1);
+    let wanted = match align {
+        Align::Top => selected,
+        Align::Center => selected.saturating_sub((rows - 1) / 2),
+        Align::Bottom => selected.saturating_sub(rows - 1),
+    };
+    wanted.min(len.saturating_sub(rows))
+}
+
+// the smallest change to `offset` that shows row `selected` with `scrolloff` rows of
+// context on either side, given each row's height. when the margin can't fit the
+// selected row ends up at the top, and the end of the table never leaves empty space
+pub(super) fn scroll_into_view(
+    offset: usize,
+    selected: usize,
+    heights: &[u16],
+    viewport: u16,
+    scrolloff: usize,
+) -> usize {
+    let Some(last) = heights.len().checked_sub(1) else {
+        return 0;
+    };
+    let selected = selected.min(last);
+    let top = selected.saturating_sub(scrolloff);
+    let bottom = (selected + scrolloff).min(last);
+    let mut offset = offset.min(top);
+    let fits = |from: usize| {
+        heights[from..=bottom]
+            .iter()
+            .map(|h| *h as u32)
+            .sum::<u32>()
+            <= viewport as u32
+    };
+    while offset < selected && !fits(offset) {
+        offset += 1;
+    }
+    let rest = |from: usize| heights[from..].iter().map(|h| *h as u32).sum::<u32>();
+    while offset > 0 && rest(offset - 1) <= viewport as u32 {
+        offset -= 1;
+    }
+    offset
+}
+
+impl App {
+    // `zz`, `zt` and `zb`, the selection stays where it is
+    pub(super) fn handle_z_keys(&mut self, code: KeyCode) -> Result<MsgState, String> {
+        let align = match code {
+            KeyCode::Char('z') => Align::Center,
+            KeyCode::Char('t') => Align::Top,
+            KeyCode::Char('b') => Align::Bottom,
+            _ => return Ok(MsgState::Default),
+        };
+        // the table only holds the visible rows, so positions are within those
+        let visible = self.visible_indices();
+        let Some(selected) = self
+            .state
+            .selected()
+            .and_then(|i| visible.iter().position(|v| *v == i))
+        else {
+            return Ok(MsgState::Default);
+        };
+        *self.view_state.offset_mut() =
+            align_offset(selected, visible.len(), self.table_rows, align);
+        Ok(MsgState::Default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_align_in_the_middle_of_the_table() {
+        assert_eq!(align_offset(20, 50, 5, Align::Center), 18);
+        assert_eq!(align_offset(20, 50, 5, Align::Top), 20);
+        assert_eq!(align_offset(20, 50, 5, Align::Bottom), 16);
+        assert_eq!(align_offset(20, 50, 4, Align::Center), 19);
+    }
+
+    #[test]
+    fn offsets_follow_the_selection_over_variable_heights() {
+        let heights = [4, 4, 8, 2, 2, 4, 4, 6];
+        // already visible, nothing moves
+        assert_eq!(scroll_into_view(0, 1, &heights, 12, 0), 0);
+        // rows 2..=3 take 10, row 1 no longer fits on top of them
+        assert_eq!(scroll_into_view(0, 3, &heights, 12, 0), 2);
+        // scrolling up puts the selection at the top
+        assert_eq!(scroll_into_view(5, 2, &heights, 12, 0), 2);
+        // a taller row than the viewport just sits at the top
+        assert_eq!(scroll_into_view(0, 2, &heights, 6, 0), 2);
+        // offsets past a shrunk table come back
+        assert_eq!(scroll_into_view(20, 7, &heights, 12, 0), 6);
+        assert_eq!(scroll_into_view(3, 0, &[], 12, 0), 0);
+    }
+
+    #[test]
+    fn scrolloff_keeps_context_around_the_selection() {
+        let heights = [4; 10];
+        assert_eq!(scroll_into_view(0, 1, &heights, 12, 1), 0);
+        assert_eq!(scroll_into_view(0, 2, &heights, 12, 1), 1);
+        assert_eq!(scroll_into_view(0, 3, &heights, 12, 1), 2);
+        assert_eq!(scroll_into_view(5, 5, &heights, 12, 1), 4);
+        // context past the end of the table isn't needed
+        assert_eq!(scroll_into_view(7, 9, &heights, 12, 1), 7);
+        // too small for the margin, the selection wins
+        assert_eq!(scroll_into_view(0, 5, &heights, 4, 2), 5);
+    }
+
+    #[test]
+    fn edge_rows_clamp() {
+        assert_eq!(align_offset(1, 50, 5, Align::Center), 0);
+        assert_eq!(align_offset(2, 50, 5, Align::Bottom), 0);
+        assert_eq!(align_offset(48, 50, 5, Align::Top), 45);
+        assert_eq!(align_offset(3, 4, 5, Align::Top), 0);
+        assert_eq!(align_offset(0, 0, 0, Align::Center), 0);
+    }
+}
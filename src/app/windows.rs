@@ -0,0 +1,31 @@
+use super::{App, ITEM_HEIGHT, Window};
+
+// where the selection was in the window that isn't showing
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct Position {
+    selected: Option<usize>,
+    offset: usize,
+}
+
+impl App {
+    // each window keeps its own selection, clamped if its list shrank in the meantime
+    pub(super) fn switch_window(&mut self, window: Window) {
+        if self.window == window {
+            return;
+        }
+        let current = Position {
+            selected: self.state.selected(),
+            offset: self.view_state.offset(),
+        };
+        self.positions.insert(self.window, current);
+        let restored = self.positions.get(&window).copied().unwrap_or_default();
+        self.window = window;
+
+        let last = self.length_constraint().saturating_sub(1);
+        let selected = restored.selected.unwrap_or(0).min(last);
+        self.state.select(Some(selected));
+        self.scroll_state = self.scroll_state.position(selected * ITEM_HEIGHT);
+        *self.view_state.offset_mut() = restored.offset.min(last);
+        self.clamp_to_visible();
+    }
+}
@@ -0,0 +1,120 @@
+use super::{App, MsgState};
+use crate::{colors::Colors, config::Config};
+use ratatui::DefaultTerminal;
+use std::{path::Path, sync::Arc};
+
+const EDITOR_CANDIDATES: [&str; 4] = ["nvim", "vim", "nano", "code"];
+
+// the candidates that are somewhere on `path` (a $PATH style list)
+pub(super) fn detect_editors(path: &str, exists: impl Fn(&Path) -> bool) -> Vec<&'static str> {
+    EDITOR_CANDIDATES
+        .into_iter()
+        .filter(|editor| std::env::split_paths(path).any(|dir| exists(&dir.join(editor))))
+        .collect()
+}
+
+// a number picks from the list, anything else is taken as the editor command itself
+fn pick_editor(answer: &str, candidates: &[&str]) -> Option<String> {
+    let answer = answer.trim();
+    match answer.parse::<usize>() {
+        Ok(n) => candidates.get(n.checked_sub(1)?).map(|c| c.to_string()),
+        Err(_) if answer.is_empty() => candidates.first().map(|c| c.to_string()),
+        Err(_) => Some(answer.to_string()),
+    }
+}
+
+impl App {
+    // first run with no editor anywhere in the chain, asks for the few things that
+    // can't be guessed and writes the initial config
+    pub(super) fn run_wizard(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<MsgState, String> {
+        let path = std::env::var("PATH").unwrap_or_default();
+        let candidates = detect_editors(&path, Path::is_file);
+        let listed: Vec<String> = candidates
+            .iter()
+            .enumerate()
+            .map(|(idx, editor)| format!("{}) {}", idx + 1, editor))
+            .collect();
+        let question = if listed.is_empty() {
+            "Welcome to uat_editor! Which editor command should steps open in".to_string()
+        } else {
+            format!(
+                "Welcome to uat_editor! Pick an editor [{}] or type a command (enter for {})",
+                listed.join(" "),
+                candidates[0]
+            )
+        };
+        let editor = loop {
+            let answer = self.prompt(terminal, &question)?;
+            if let Some(editor) = pick_editor(&answer, &candidates) {
+                break editor;
+            }
+        };
+
+        let author = self.prompt(terminal, "Author name for new steps (enter to use $USER)")?;
+        let theme = self.prompt(
+            terminal,
+            "Theme, default or deuteranopia (enter for default)",
+        )?;
+
+        self.config.editor = Arc::new(editor);
+        self.config.author = Some(author.trim().to_string()).filter(|a| !a.is_empty());
+        self.config.theme.preset = Some(theme.trim().to_string()).filter(|t| !t.is_empty());
+        if let Ok(colors) = Colors::load(&self.config.theme, self.colors.plain) {
+            self.colors = colors;
+        } else {
+            self.config.theme.preset = None;
+        }
+        self.config.save_config()?;
+        self.needs_setup = false;
+        Ok(MsgState::DynamicMsg(format!(
+            "Wrote {}",
+            Config::path().unwrap_or_default()
+        )))
+    }
+
+    // first run without the wizard, says which editor steps will open in and why
+    pub(super) fn first_run_notice(&self) -> Option<MsgState> {
+        let path = Config::path().ok()?;
+        if self.config_error.is_some() || Path::new(&path).exists() {
+            return None;
+        }
+        let (editor, source) = self.config.editor_with_source()?;
+        Some(MsgState::DynamicMsg(format!(
+            "Steps open in {} (from {}), set editor in {} to change",
+            editor,
+            source.describe(),
+            path
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_editors_on_path() {
+        let exists = |p: &Path| matches!(p.to_str(), Some("/usr/bin/vim" | "/opt/bin/code"));
+        assert_eq!(
+            detect_editors("/usr/bin:/opt/bin", exists),
+            vec!["vim", "code"]
+        );
+        assert!(detect_editors("", exists).is_empty());
+    }
+
+    #[test]
+    fn picks_by_number_default_or_command() {
+        let candidates = ["vim", "nano"];
+        assert_eq!(pick_editor("2", &candidates), Some("nano".to_string()));
+        assert_eq!(pick_editor("", &candidates), Some("vim".to_string()));
+        assert_eq!(
+            pick_editor("hx --wait", &candidates),
+            Some("hx --wait".to_string())
+        );
+        assert_eq!(pick_editor("3", &candidates), None);
+        assert_eq!(pick_editor("", &[]), None);
+    }
+}
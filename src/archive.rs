@@ -0,0 +1,99 @@
+use crate::{embedding, err_msg::WithErrMsg, test_step::TestStep};
+use std::path::Path;
+
+// cleared tables are kept as cleared-<unix time>.json in the backup directory, the
+// oldest go once there are more than this
+const ARCHIVE_LIMIT: usize = 10;
+const PREFIX: &str = "cleared-";
+const SUFFIX: &str = ".json";
+
+// every archive in `dir` with its time, oldest first
+fn archives(dir: &Path) -> Vec<(u64, String)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut found: Vec<(u64, String)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stamp = name
+                .strip_prefix(PREFIX)?
+                .strip_suffix(SUFFIX)?
+                .parse()
+                .ok()?;
+            Some((stamp, entry.path().to_string_lossy().to_string()))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+// writes the table out before it is cleared, None when there was nothing to keep
+pub fn archive(dir: &Path, items: &[TestStep], now: u64) -> Result<Option<String>, String> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+    std::fs::create_dir_all(dir).with_err_msg(&format!("Failed to create {}", dir.display()))?;
+    // two clears in the same second don't overwrite each other, and the newest archive
+    // always sorts last
+    let stamp = archives(dir)
+        .last()
+        .map_or(now, |(last, _)| now.max(last + 1));
+    let path = dir
+        .join(format!("{}{}{}", PREFIX, stamp, SUFFIX))
+        .to_string_lossy()
+        .to_string();
+    embedding::save_file(&path, items)?;
+    let all = archives(dir);
+    for (_, old) in &all[..all.len().saturating_sub(ARCHIVE_LIMIT)] {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(Some(path))
+}
+
+// the most recently cleared table and where it was read from
+pub fn latest(dir: &Path) -> Result<(String, Vec<TestStep>), String> {
+    let (_, path) = archives(dir)
+        .pop()
+        .with_err_msg(&"No cleared table has been archived yet")?;
+    let items = embedding::load_file(&path)?;
+    Ok((path, items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "uat_editor_archive_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn clearing_an_empty_table_writes_nothing() {
+        let dir = dir("empty");
+        assert_eq!(archive(&dir, &[], 100), Ok(None));
+        assert!(!dir.exists());
+        assert!(latest(&dir).is_err());
+    }
+
+    #[test]
+    fn only_the_newest_archives_are_kept() {
+        let dir = dir("rotate");
+        for n in 0..ARCHIVE_LIMIT + 2 {
+            let items = vec![TestStep::new().with_instructions(&format!("step {}", n))];
+            // every clear in the same second still gets its own file
+            archive(&dir, &items, 100).unwrap();
+        }
+        assert_eq!(archives(&dir).len(), ARCHIVE_LIMIT);
+        let (path, items) = latest(&dir).unwrap();
+        assert!(path.ends_with(&format!("cleared-{}.json", 100 + ARCHIVE_LIMIT + 1)));
+        assert_eq!(items[0].instructions, format!("step {}", ARCHIVE_LIMIT + 1));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{crypto, embedding, err_msg::WithErrMsg, test_step::TestStep};
+use std::{io::Write, path::Path};
+
+const HTML: &str = "backup.html";
+// only there when the last html backup failed to read back, and newer than it
+const SIDECAR: &str = "backup.json";
+
+fn write_and_read_back(
+    tmp: &Path,
+    content: &str,
+    items: &[TestStep],
+    parse: fn(&str) -> Result<Vec<TestStep>, String>,
+) -> Result<(), String> {
+    let mut file =
+        std::fs::File::create(tmp).with_err_msg(&format!("Failed to open {}", tmp.display()))?;
+    file.write_all(&crypto::seal(content)?)
+        .and_then(|_| file.sync_all())
+        .with_err_msg(&format!("Failed to write {}", tmp.display()))?;
+    let read = crypto::read(&tmp.to_string_lossy())
+        .map_err(|err| format!("Failed to read back {}", err))?;
+    let parsed = parse(&read)?;
+    if embedding::canonical_json(&parsed)? != embedding::canonical_json(items)? {
+        return Err(format!("{} reads back different steps", tmp.display()));
+    }
+    Ok(())
+}
+
+// written beside `path` and read back before it replaces it, so whatever was there
+// stays until the new copy is known to load
+fn write_verified(
+    path: &Path,
+    content: &str,
+    items: &[TestStep],
+    parse: fn(&str) -> Result<Vec<TestStep>, String>,
+) -> Result<(), String> {
+    crypto::guard(path)?;
+    let tmp = path.with_extension("tmp");
+    if let Err(err) = write_and_read_back(&tmp, content, items, parse) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(err);
+    }
+    std::fs::rename(&tmp, path).with_err_msg(&format!("Failed to replace {}", path.display()))
+}
+
+// the html backup, or the json sidecar when the html doesn't read back. an error means
+// the html failed either way, the message says whether the sidecar saved the steps
+pub fn write(dir: &Path, html: &str, items: &[TestStep]) -> Result<(), String> {
+    std::fs::create_dir_all(dir).with_err_msg(&format!("Failed to create {}", dir.display()))?;
+    let html_err = match write_verified(&dir.join(HTML), html, items, embedding::extract) {
+        Ok(()) => {
+            let _ = std::fs::remove_file(dir.join(SIDECAR));
+            return Ok(());
+        }
+        Err(err) => err,
+    };
+    let sidecar = dir.join(SIDECAR);
+    let json = embedding::canonical_json(items)?;
+    match write_verified(&sidecar, &json, items, embedding::parse_document) {
+        Ok(()) => Err(format!(
+            "BACKUP FAILED TO VERIFY ({}), steps saved to {} instead",
+            html_err,
+            sidecar.display()
+        )),
+        Err(err) => Err(format!(
+            "BACKUP FAILED TO VERIFY ({}) and so did {} ({}), the last good backup is kept",
+            html_err,
+            sidecar.display(),
+            err
+        )),
+    }
+}
+
+// the sidecar when there is one, it was written after the html it stands in for
+fn latest(dir: &Path) -> std::path::PathBuf {
+    let sidecar = dir.join(SIDECAR);
+    if sidecar.exists() {
+        sidecar
+    } else {
+        dir.join(HTML)
+    }
+}
+
+pub fn load(dir: &Path) -> Result<Vec<TestStep>, String> {
+    embedding::load_file(&latest(dir).to_string_lossy())
+}
+
+// a backup that won't decrypt is left alone rather than replaced by an empty table
+pub fn is_encrypted(dir: &Path) -> bool {
+    crypto::is_encrypted_file(&latest(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("uat_editor_backup_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn steps(instructions: &str) -> Vec<TestStep> {
+        let mut items = vec![TestStep::new().with_instructions(instructions)];
+        TestStep::assign_ids(&mut items);
+        items
+    }
+
+    fn html(items: &[TestStep]) -> String {
+        format!(
+            "<table></table><!-- MDEMBEDDING:{} -->",
+            embedding::serialize_items(items).unwrap()
+        )
+    }
+
+    #[test]
+    fn a_backup_that_reads_back_replaces_the_old_one() {
+        let dir = dir("good");
+        let old = steps("Old");
+        write(&dir, &html(&old), &old).unwrap();
+        assert_eq!(load(&dir).unwrap()[0].instructions, "Old");
+
+        let new = steps("New");
+        write(&dir, &html(&new), &new).unwrap();
+        assert_eq!(load(&dir).unwrap()[0].instructions, "New");
+        assert!(!dir.join("backup.tmp").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_broken_backup_falls_back_to_the_sidecar() {
+        let dir = dir("broken");
+        let old = steps("Old");
+        write(&dir, &html(&old), &old).unwrap();
+
+        let new = steps("New");
+        let truncated = html(&new)[..30].to_string();
+        let err = write(&dir, &truncated, &new).unwrap_err();
+        assert!(err.starts_with("BACKUP FAILED TO VERIFY"), "{}", err);
+        assert!(err.contains("backup.json"), "{}", err);
+        // the old html is untouched, the sidecar is what loads
+        assert_eq!(
+            embedding::load_file(&dir.join(HTML).to_string_lossy()).unwrap()[0].instructions,
+            "Old"
+        );
+        assert_eq!(load(&dir).unwrap()[0].instructions, "New");
+
+        // the next good html backup retires the sidecar
+        write(&dir, &html(&new), &new).unwrap();
+        assert!(!dir.join(SIDECAR).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    app::App,
+    config::ColumnConfig,
+    embedding,
+    export::html::{self, HtmlOptions},
+    locale::Locale,
+    test_step::TestStep,
+};
+
+pub use crate::stress::{DEFAULT_SEED, generate};
+
+pub fn gen_html(items: &[TestStep]) -> Result<String, String> {
+    let columns = ColumnConfig::default();
+    let options = HtmlOptions {
+        columns: &columns,
+        use_ac: true,
+        show_ids: false,
+        max_image_bytes: 0,
+        locale: Locale::En,
+        markdown_extensions: &[],
+        numbering_start: 1,
+    };
+    html::gen_html(items, &options)
+}
+
+pub fn serialize_items(items: &[TestStep]) -> Result<String, String> {
+    embedding::serialize_items(items)
+}
+
+pub fn deserialize_items(serialized: &str) -> Result<Vec<TestStep>, String> {
+    embedding::deserialize_items(serialized)
+}
+
+// each item as the editor would see it, the input for parse_markdown
+pub fn markdown(items: &[TestStep]) -> Vec<String> {
+    items.iter().map(|item| item.gen_markdown(true)).collect()
+}
+
+pub fn parse_markdown(markdown: &str) -> Result<TestStep, String> {
+    TestStep::parse_markdown(markdown)
+}
+
+// every item in one markdown file split by "---", as a bulk import reads it
+pub fn document(items: &[TestStep]) -> String {
+    TestStep::gen_document(items, true)
+}
+
+pub fn parse_document(document: &str) -> Result<Vec<TestStep>, String> {
+    TestStep::parse_document(document)
+}
+
+// the table rows the tui draws every frame, built by an app that is never run
+pub struct Rows(App);
+
+impl Rows {
+    pub fn new(items: Vec<TestStep>) -> Result<Rows, String> {
+        App::headless(items).map(Rows)
+    }
+
+    pub fn build(&self) -> usize {
+        self.0.row_count()
+    }
+}
@@ -0,0 +1,414 @@
+use crate::{
+    app::AppOptions,
+    config::{self, Config, Overrides, TextLayout},
+    convert::{self, Conversion, DocFormat},
+    embedding,
+    err_msg::WithErrMsg,
+    export::{
+        Format,
+        ado::{AdoOptions, Grouping, MarkdownMode},
+        html::HtmlOptions,
+    },
+    schema,
+    serve::{Server, Snapshot},
+    stress,
+};
+
+const USAGE: &str = "usage: uat_editor [--config <path>] [--editor <cmd>] [--readonly] [--no-wizard] [--no-color] \
+                     [--exit-summary <path>] [--print-config] [export --format junit|ado|confluence|text|print|pdf [--group section|step] \
+                     [--markdown html|strip] [--layout outline|table] [--width <cols>] [--unicode] \
+                     [--embed] [--start <n>] [-o <output>] <input>] [serve [--bind <addr>] [--port <port>] <input>] \
+                     [fmt [--check] <file>...] [gen-stress [--steps <n>] [--seed <n>] -o <output>] \
+                     [schema] [convert --from html|json --to html|json --out-dir <dir> [--force] <input>...]";
+
+// how often `serve` checks whether the document changed on disk
+const RELOAD_POLL: std::time::Duration = std::time::Duration::from_millis(500);
+
+pub enum CliCommand {
+    Tui(AppOptions),
+    PrintConfig,
+    Export {
+        format: Format,
+        text: TextFlags,
+        // --start, the number of the first section
+        start: Option<usize>,
+        input: String,
+        output: Option<String>,
+    },
+    Serve {
+        input: String,
+        bind: String,
+        port: u16,
+    },
+    Fmt {
+        files: Vec<String>,
+        check: bool,
+    },
+    // a large generated document for benchmarks and manual stress testing
+    GenStress {
+        steps: usize,
+        seed: u64,
+        output: String,
+    },
+    // the json schema external tools can check their documents against
+    Schema,
+    Convert {
+        conversion: Conversion,
+        inputs: Vec<String>,
+    },
+}
+
+// plain text flags given on the command line, anything left unset comes from the config
+#[derive(Default)]
+pub struct TextFlags {
+    layout: Option<TextLayout>,
+    width: Option<usize>,
+    unicode: bool,
+}
+
+impl CliCommand {
+    // global flags come before the subcommand, the overrides apply to every command
+    pub fn parse(args: &[String]) -> Result<(CliCommand, Overrides), String> {
+        let mut overrides = Overrides::default();
+        let mut options = AppOptions::default();
+        let mut print_config = false;
+        let mut idx = 0;
+        while let Some(arg) = args.get(idx) {
+            match arg.as_str() {
+                "--config" => {
+                    idx += 1;
+                    overrides.config = Some(args.get(idx).with_err_msg(&USAGE)?.clone());
+                }
+                "--editor" => {
+                    idx += 1;
+                    overrides.editor = Some(args.get(idx).with_err_msg(&USAGE)?.clone());
+                }
+                "--readonly" | "-R" => options.readonly = true,
+                "--no-wizard" => options.no_wizard = true,
+                "--no-color" => options.no_color = true,
+                "--exit-summary" => {
+                    idx += 1;
+                    options.exit_summary = Some(args.get(idx).with_err_msg(&USAGE)?.clone());
+                }
+                "--print-config" => print_config = true,
+                _ => break,
+            }
+            idx += 1;
+        }
+        let command = match args.get(idx).map(String::as_str) {
+            None if print_config => CliCommand::PrintConfig,
+            None => CliCommand::Tui(options),
+            Some(subcommand) => CliCommand::parse_subcommand(subcommand, &args[idx..])?,
+        };
+        Ok((command, overrides))
+    }
+
+    fn parse_subcommand(subcommand: &str, args: &[String]) -> Result<CliCommand, String> {
+        match subcommand {
+            "export" => {
+                let mut format = None;
+                let mut output = None;
+                let mut input = None;
+                let mut ado = AdoOptions::default();
+                let mut text = TextFlags::default();
+                let mut embed = false;
+                let mut start = None;
+                let mut args = args[1..].iter();
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--format" | "-f" => {
+                            format = Some(args.next().with_err_msg(&USAGE)?.clone());
+                        }
+                        "--output" | "-o" => {
+                            output = Some(args.next().with_err_msg(&USAGE)?.clone());
+                        }
+                        "--group" => {
+                            ado.grouping = Grouping::parse(args.next().with_err_msg(&USAGE)?)?;
+                        }
+                        "--markdown" => {
+                            ado.markdown = MarkdownMode::parse(args.next().with_err_msg(&USAGE)?)?;
+                        }
+                        "--layout" => {
+                            text.layout =
+                                Some(TextLayout::parse(args.next().with_err_msg(&USAGE)?)?);
+                        }
+                        "--width" => {
+                            let width = args.next().with_err_msg(&USAGE)?;
+                            text.width = Some(
+                                width
+                                    .parse()
+                                    .with_err_msg(&format!("Invalid width: {}", width))?,
+                            );
+                        }
+                        "--unicode" => text.unicode = true,
+                        "--embed" => embed = true,
+                        "--start" => {
+                            start = Some(config::parse_numbering_start(
+                                args.next().with_err_msg(&USAGE)?,
+                            )?);
+                        }
+                        _ => input = Some(arg.clone()),
+                    }
+                }
+                let format = format.unwrap_or_else(|| "junit".to_string());
+                let input = input.with_err_msg(&USAGE)?;
+                let mut format = Format::parse(&format, ado, Default::default())?;
+                if let Format::Print(print) | Format::Pdf(_, print) = &mut format {
+                    print.source = Some(input.clone());
+                    print.embed = embed;
+                }
+                Ok(CliCommand::Export {
+                    format,
+                    text,
+                    start,
+                    input,
+                    output,
+                })
+            }
+            "serve" => {
+                let mut bind = config::default_serve_bind();
+                let mut port = config::default_serve_port();
+                let mut input = None;
+                let mut args = args[1..].iter();
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--bind" => bind = args.next().with_err_msg(&USAGE)?.clone(),
+                        "--port" | "-p" => {
+                            let val = args.next().with_err_msg(&USAGE)?;
+                            port = val
+                                .parse()
+                                .with_err_msg(&format!("Invalid port: {}", val))?;
+                        }
+                        _ => input = Some(arg.clone()),
+                    }
+                }
+                Ok(CliCommand::Serve {
+                    input: input.with_err_msg(&USAGE)?,
+                    bind,
+                    port,
+                })
+            }
+            "fmt" => {
+                let check = args[1..].iter().any(|arg| arg == "--check");
+                let files: Vec<String> = args[1..]
+                    .iter()
+                    .filter(|arg| *arg != "--check")
+                    .cloned()
+                    .collect();
+                if files.is_empty() {
+                    return Err(USAGE.to_string());
+                }
+                Ok(CliCommand::Fmt { files, check })
+            }
+            "gen-stress" => {
+                let mut steps = 5000;
+                let mut seed = stress::DEFAULT_SEED;
+                let mut output = None;
+                let mut args = args[1..].iter();
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--steps" => {
+                            let val = args.next().with_err_msg(&USAGE)?;
+                            steps = val
+                                .parse()
+                                .with_err_msg(&format!("Invalid step count: {}", val))?;
+                        }
+                        "--seed" => {
+                            let val = args.next().with_err_msg(&USAGE)?;
+                            seed = val
+                                .parse()
+                                .with_err_msg(&format!("Invalid seed: {}", val))?;
+                        }
+                        "--output" | "-o" => {
+                            output = Some(args.next().with_err_msg(&USAGE)?.clone());
+                        }
+                        _ => return Err(USAGE.to_string()),
+                    }
+                }
+                Ok(CliCommand::GenStress {
+                    steps,
+                    seed,
+                    output: output.with_err_msg(&USAGE)?,
+                })
+            }
+            "schema" if args.len() == 1 => Ok(CliCommand::Schema),
+            "convert" => {
+                let mut from = None;
+                let mut to = None;
+                let mut out_dir = None;
+                let mut force = false;
+                let mut inputs = Vec::new();
+                let mut args = args[1..].iter();
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--from" => {
+                            from = Some(DocFormat::parse(args.next().with_err_msg(&USAGE)?)?);
+                        }
+                        "--to" => to = Some(DocFormat::parse(args.next().with_err_msg(&USAGE)?)?),
+                        "--out-dir" => out_dir = Some(args.next().with_err_msg(&USAGE)?.clone()),
+                        "--force" => force = true,
+                        _ => inputs.push(arg.clone()),
+                    }
+                }
+                if inputs.is_empty() {
+                    return Err(USAGE.to_string());
+                }
+                Ok(CliCommand::Convert {
+                    conversion: Conversion {
+                        from: from.with_err_msg(&USAGE)?,
+                        to: to.with_err_msg(&USAGE)?,
+                        out_dir: out_dir.with_err_msg(&USAGE)?,
+                        force,
+                    },
+                    inputs,
+                })
+            }
+            _ => Err(USAGE.to_string()),
+        }
+    }
+
+    pub fn run(self) -> Result<(), String> {
+        match self {
+            CliCommand::Tui(_) => Ok(()),
+            CliCommand::PrintConfig => {
+                let paths = config::paths()?;
+                let config = Config::load_config()?;
+                println!("# config file: {}", paths.config);
+                println!("# backup dir: {}", paths.backup_dir);
+                if let Some(project) = &config.project {
+                    println!("# project config: {} ({})", project.path, project.name);
+                }
+                match config.editor_with_source() {
+                    Some((editor, source)) => {
+                        println!("# editor: {} (from {})", editor, source.describe())
+                    }
+                    None => println!("# editor: none found"),
+                }
+                let toml =
+                    toml::to_string(&config).with_err_msg(&"Failed to serialize config to toml")?;
+                print!("{}", toml);
+                Ok(())
+            }
+            // every file is looked at before failing, so a hook lists all of them at once
+            CliCommand::Fmt { files, check } => {
+                let mut changed = 0;
+                for file in &files {
+                    if embedding::format_file(file, check)? {
+                        changed += 1;
+                        println!(
+                            "{} {}",
+                            if check {
+                                "Would reformat"
+                            } else {
+                                "Reformatted"
+                            },
+                            file
+                        );
+                    }
+                }
+                if check && changed > 0 {
+                    return Err(format!(
+                        "{} of {} files need formatting",
+                        changed,
+                        files.len()
+                    ));
+                }
+                Ok(())
+            }
+            CliCommand::Schema => {
+                print!("{}", schema::SCHEMA);
+                Ok(())
+            }
+            // like fmt every file is tried, the exit status says whether any failed
+            CliCommand::Convert { conversion, inputs } => {
+                let inputs = convert::expand_inputs(&inputs, conversion.from)?;
+                if inputs.is_empty() {
+                    return Err("No files to convert".to_string());
+                }
+                let outcomes = convert::convert(&inputs, &conversion, &Config::load_layout())?;
+                print!("{}", convert::summary(&outcomes));
+                let failed: Vec<&str> = outcomes
+                    .iter()
+                    .filter(|outcome| outcome.result.is_err())
+                    .map(|outcome| outcome.input.as_str())
+                    .collect();
+                if !failed.is_empty() {
+                    return Err(format!(
+                        "{} of {} files failed: {}",
+                        failed.len(),
+                        outcomes.len(),
+                        failed.join(", ")
+                    ));
+                }
+                Ok(())
+            }
+            CliCommand::GenStress {
+                steps,
+                seed,
+                output,
+            } => {
+                embedding::save_file(&output, &stress::generate(steps, seed))?;
+                println!("Wrote {} steps to {}", steps, output);
+                Ok(())
+            }
+            CliCommand::Serve { input, bind, port } => {
+                let layout = Config::load_layout();
+                let snapshot = |items| Snapshot {
+                    items,
+                    columns: layout.columns.clone(),
+                    use_ac: layout.use_ac,
+                    show_ids: false,
+                    max_image_bytes: crate::images::DEFAULT_MAX_IMAGE_BYTES,
+                    locale: layout.locale,
+                    markdown_extensions: layout.markdown_extensions.clone(),
+                    numbering_start: layout.numbering_start,
+                };
+                let modified = || std::fs::metadata(&input).and_then(|m| m.modified()).ok();
+                let server = Server::start(&bind, port, snapshot(embedding::load_file(&input)?))?;
+                println!("Serving {} at {} (ctrl+c to stop)", input, server.url());
+                // edits saved to the file show up on the next refresh
+                let mut seen = modified();
+                loop {
+                    std::thread::sleep(RELOAD_POLL);
+                    let current = modified();
+                    if current != seen {
+                        seen = current;
+                        match embedding::load_file(&input) {
+                            Ok(items) => server.update(snapshot(items)),
+                            Err(err) => eprintln!("Failed to reload {}: {}", input, err),
+                        }
+                    }
+                }
+            }
+            CliCommand::Export {
+                format,
+                text,
+                start,
+                input,
+                output,
+            } => {
+                let items = embedding::load_file(&input)?;
+                let mut layout = Config::load_layout();
+                layout.numbering_start = start.unwrap_or(layout.numbering_start);
+                let plain_text = &mut layout.plain_text;
+                plain_text.layout = text.layout.unwrap_or(plain_text.layout);
+                plain_text.width = text.width.unwrap_or(plain_text.width);
+                plain_text.unicode |= text.unicode;
+                let format = match format {
+                    Format::Text(_) => Format::Text(layout.plain_text),
+                    Format::Pdf(_, print) => Format::Pdf(layout.pdf.clone(), print),
+                    format => format,
+                };
+                let options = HtmlOptions::from_layout(&layout);
+                match output {
+                    Some(path) => format.write(&items, &options, &path),
+                    None => {
+                        let exported = format.render(&items, &options)?;
+                        print!("{}", exported);
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
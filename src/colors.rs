@@ -1,3 +1,7 @@
+use crate::{
+    config::ThemeConfig,
+    test_step::{Priority, StepStatus, TestStep},
+};
 use ratatui::{
     style::{Color, Modifier, Style, Stylize},
     widgets::{Block, BorderType},
@@ -12,6 +16,25 @@ pub struct Colors {
     pub normal_row_color: Color,
     pub alt_row_color: Color,
     pub footer_border_color: Color,
+    pub title_bg: Color,
+    pub title_fg: Color,
+    pub visual_row_color: Color,
+    pub match_bg: Color,
+    pub match_fg: Color,
+    pub code_bg: Color,
+    pub pass_row_color: Color,
+    pub fail_row_color: Color,
+    pub blocked_row_color: Color,
+    // sections and comments fall back to the alternating row colors
+    pub section_row_color: Option<Color>,
+    pub comment_row_color: Option<Color>,
+    // NO_COLOR or --no-color, styles that need a color use modifiers instead
+    pub plain: bool,
+}
+
+// NO_COLOR set to anything but an empty string counts, see no-color.org
+pub fn no_color(flag: bool) -> bool {
+    flag || std::env::var_os("NO_COLOR").is_some_and(|val| !val.is_empty())
 }
 
 impl Colors {
@@ -26,9 +49,100 @@ impl Colors {
             normal_row_color: Color::Rgb(35, 33, 54),
             alt_row_color: Color::Rgb(57, 53, 82),
             footer_border_color: Color::Rgb(62, 143, 176),
+            title_bg: Color::Rgb(62, 143, 176),
+            title_fg: Color::Rgb(35, 33, 54),
+            visual_row_color: Color::Rgb(86, 82, 122),
+            match_bg: Color::Rgb(246, 193, 119),
+            match_fg: Color::Rgb(35, 33, 54),
+            code_bg: Color::Rgb(25, 23, 38),
+            pass_row_color: Color::Rgb(38, 70, 56),
+            fail_row_color: Color::Rgb(88, 38, 52),
+            blocked_row_color: Color::Rgb(84, 70, 38),
+            section_row_color: None,
+            comment_row_color: None,
+            plain: false,
+        }
+    }
+
+    // the terminal's own colors everywhere
+    pub const fn monochrome() -> Self {
+        Self {
+            buffer_bg: Color::Reset,
+            header_bg: Color::Reset,
+            header_fg: Color::Reset,
+            row_fg: Color::Reset,
+            selected_column_style_fg: Color::Reset,
+            selected_cell_style_fg: Color::Reset,
+            normal_row_color: Color::Reset,
+            alt_row_color: Color::Reset,
+            footer_border_color: Color::Reset,
+            title_bg: Color::Reset,
+            title_fg: Color::Reset,
+            visual_row_color: Color::Reset,
+            match_bg: Color::Reset,
+            match_fg: Color::Reset,
+            code_bg: Color::Reset,
+            pass_row_color: Color::Reset,
+            fail_row_color: Color::Reset,
+            blocked_row_color: Color::Reset,
+            section_row_color: None,
+            comment_row_color: None,
+            plain: true,
         }
     }
 
+    // blue/orange instead of green/red for pass/fail
+    pub const fn deuteranopia() -> Self {
+        Self {
+            pass_row_color: Color::Rgb(30, 60, 110),
+            fail_row_color: Color::Rgb(120, 70, 20),
+            blocked_row_color: Color::Rgb(80, 80, 96),
+            ..Self::new()
+        }
+    }
+
+    pub fn from_theme(theme: &ThemeConfig) -> Result<Self, String> {
+        let mut colors = match theme.preset.as_deref() {
+            None | Some("default") => Self::new(),
+            Some("deuteranopia") => Self::deuteranopia(),
+            Some(other) => {
+                return Err(format!(
+                    "Unknown theme preset: {} (expected default|deuteranopia)",
+                    other
+                ));
+            }
+        };
+        let parse = |name: &str, val: &Option<String>| -> Result<Option<Color>, String> {
+            val.as_deref()
+                .map(|val| {
+                    val.parse::<Color>()
+                        .map_err(|_| format!("Invalid theme color for {}: {}", name, val))
+                })
+                .transpose()
+        };
+        if let Some(color) = parse("pass", &theme.pass)? {
+            colors.pass_row_color = color;
+        }
+        if let Some(color) = parse("fail", &theme.fail)? {
+            colors.fail_row_color = color;
+        }
+        if let Some(color) = parse("blocked", &theme.blocked)? {
+            colors.blocked_row_color = color;
+        }
+        colors.section_row_color = parse("section", &theme.section)?.or(colors.section_row_color);
+        colors.comment_row_color = parse("comment", &theme.comment)?.or(colors.comment_row_color);
+        Ok(colors)
+    }
+
+    // the theme is still checked when `plain` leaves it unused
+    pub fn load(theme: &ThemeConfig, plain: bool) -> Result<Self, String> {
+        let colors = Self::from_theme(theme)?;
+        Ok(match plain {
+            true => Self::monochrome(),
+            false => colors,
+        })
+    }
+
     pub fn row_style(&self, i: usize) -> Style {
         let color = match i % 2 {
             0 => self.normal_row_color,
@@ -37,6 +151,43 @@ impl Colors {
         Style::new().fg(self.row_fg).bg(color)
     }
 
+    // the priority glyph in the # column, on whatever the row's background is
+    pub fn priority_style(&self, priority: Priority) -> Style {
+        match (self.plain, priority) {
+            (true, Priority::High) => Style::new().bold(),
+            (true, _) => Style::new(),
+            (false, Priority::High) => Style::new().fg(Color::Rgb(235, 111, 146)),
+            (false, Priority::Medium) => Style::new().fg(Color::Rgb(246, 193, 119)),
+            (false, Priority::Low) => Style::new().fg(Color::Rgb(156, 207, 216)),
+        }
+    }
+
+    pub fn step_row_style(&self, i: usize, step: &TestStep) -> Style {
+        if self.plain && step.is_new_section {
+            return self.row_style(i).bold();
+        }
+        let kind_color = if step.is_new_section {
+            self.section_row_color
+        } else if step.is_stepless_comment {
+            self.comment_row_color
+        } else {
+            None
+        };
+        match kind_color {
+            Some(color) => Style::new().fg(self.row_fg).bg(color),
+            None => self.status_row_style(i, step.status),
+        }
+    }
+
+    pub fn status_row_style(&self, i: usize, status: Option<StepStatus>) -> Style {
+        match status {
+            Some(StepStatus::Pass) => Style::new().fg(self.row_fg).bg(self.pass_row_color),
+            Some(StepStatus::Fail) => Style::new().fg(self.row_fg).bg(self.fail_row_color),
+            Some(StepStatus::Blocked) => Style::new().fg(self.row_fg).bg(self.blocked_row_color),
+            Some(StepStatus::Skipped) | None => self.row_style(i),
+        }
+    }
+
     pub fn header_style(&self) -> Style {
         Style::default()
             .fg(self.header_fg)
@@ -48,8 +199,40 @@ impl Colors {
         Style::default().add_modifier(Modifier::REVERSED)
     }
 
+    pub fn visual_row_style(&self) -> Style {
+        let style = Style::new().fg(self.row_fg).bg(self.visual_row_color);
+        match self.plain {
+            true => style.underlined(),
+            false => style,
+        }
+    }
+
+    // search and filter hits inside a cell or the details pane
+    pub fn match_style(&self) -> Style {
+        let style = Style::new().fg(self.match_fg).bg(self.match_bg);
+        match self.plain {
+            true => style.bold().underlined(),
+            false => style,
+        }
+    }
+
+    // lines inside a fenced code block
+    pub fn code_style(&self) -> Style {
+        Style::new().bg(self.code_bg)
+    }
+
+    // rows being moved, lifted out of the table
+    pub fn reorder_row_style(&self) -> Style {
+        self.visual_row_style()
+            .add_modifier(Modifier::BOLD | Modifier::ITALIC)
+    }
+
     pub fn selected_col_style(&self) -> Style {
-        Style::default().fg(self.selected_column_style_fg)
+        let style = Style::default().fg(self.selected_column_style_fg);
+        match self.plain {
+            true => style.bold(),
+            false => style,
+        }
     }
 
     pub fn selected_cell_style(&self) -> Style {
@@ -58,13 +241,82 @@ impl Colors {
             .fg(self.selected_cell_style_fg)
     }
 
+    pub fn title_style(&self) -> Style {
+        let style = Style::new().fg(self.title_fg).bg(self.title_bg).bold();
+        match self.plain {
+            true => style.reversed(),
+            false => style,
+        }
+    }
+
     pub fn info_style(&self) -> Style {
         Style::new().fg(self.row_fg).bg(self.buffer_bg)
     }
 
-    pub fn info_block(&self) -> Block {
+    pub fn info_block(&self) -> Block<'_> {
         Block::bordered()
             .border_type(BorderType::Double)
             .border_style(Style::new().fg(self.footer_border_color))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_overrides_apply_on_top_of_the_preset() {
+        let theme = ThemeConfig {
+            preset: Some("deuteranopia".to_string()),
+            fail: Some("#ff8000".to_string()),
+            section: Some("blue".to_string()),
+            ..ThemeConfig::default()
+        };
+        let colors = Colors::from_theme(&theme).unwrap();
+        assert_eq!(colors.pass_row_color, Colors::deuteranopia().pass_row_color);
+        assert_eq!(colors.fail_row_color, Color::Rgb(255, 128, 0));
+        assert_eq!(colors.section_row_color, Some(Color::Blue));
+        assert_eq!(colors.comment_row_color, None);
+
+        let bad = ThemeConfig {
+            pass: Some("not a color".to_string()),
+            ..ThemeConfig::default()
+        };
+        assert!(Colors::from_theme(&bad).is_err());
+    }
+
+    #[test]
+    fn plain_styles_use_no_colors() {
+        let colors = Colors::load(&ThemeConfig::default(), true).unwrap();
+        let fail = TestStep {
+            status: Some(StepStatus::Fail),
+            ..TestStep::new()
+        };
+        let styles = [
+            colors.step_row_style(1, &fail),
+            colors.header_style(),
+            colors.visual_row_style(),
+            colors.match_style(),
+            colors.title_style(),
+            colors.info_style(),
+        ];
+        for style in styles {
+            assert!(!matches!(style.fg, Some(Color::Rgb(..))), "{:?}", style);
+            assert!(!matches!(style.bg, Some(Color::Rgb(..))), "{:?}", style);
+        }
+        let section = colors.step_row_style(0, &TestStep::section("Login"));
+        assert!(section.add_modifier.contains(Modifier::BOLD));
+        assert!(
+            colors
+                .match_style()
+                .add_modifier
+                .contains(Modifier::UNDERLINED)
+        );
+        // a bad theme is still reported
+        let bad = ThemeConfig {
+            preset: Some("neon".to_string()),
+            ..ThemeConfig::default()
+        };
+        assert!(Colors::load(&bad, true).is_err());
+    }
+}
@@ -1,46 +1,937 @@
 use crate::err_msg::WithErrMsg;
-use crate::test_step::TestStep;
+use crate::images::DEFAULT_MAX_IMAGE_BYTES;
+use crate::import::mapping::Field;
+use crate::locale::Locale;
+use crate::test_step::{AliasTarget, TestStep, ret_false};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
-const CONFIG_PATH: &'static str = ".config/uat_editor/config.toml";
+const CONFIG_DIR: &str = ".config/uat_editor";
+const CONFIG_FILE: &str = "config.toml";
+const PROJECT_FILE: &str = ".uat_editor.toml";
+
+// the user config keys a project config may override, anything else in it is ignored
+const PROJECT_KEYS: &[&str] = &[
+    "columns",
+    "use_ac",
+    "show_ids",
+    "author",
+    "theme",
+    "tab_width",
+    "title_bar",
+    "max_image_bytes",
+    "locale",
+    "heading_aliases",
+    "markdown_extensions",
+    "numbering_start",
+];
+
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+// values given on the command line, set once at startup
+#[derive(Debug, Default, Clone)]
+pub struct Overrides {
+    pub config: Option<String>,
+    pub editor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paths {
+    pub config: String,
+    pub backup_dir: String,
+    // only set when the cli or environment overrides the config file's editor
+    pub editor: Option<String>,
+}
+
+pub fn set_overrides(overrides: Overrides) {
+    let _ = OVERRIDES.set(overrides);
+}
+
+// cli flags win over UAT_EDITOR_* variables, which win over the config file and the
+// defaults under $HOME
+pub fn resolve(cli: &Overrides, env: impl Fn(&str) -> Option<String>) -> Result<Paths, String> {
+    let home = || {
+        env("HOME")
+            .map(|home| format!("{}/{}", home, CONFIG_DIR))
+            .with_err_msg(&"EXPECTED HOME VARIABLE")
+    };
+    let config = match cli.config.clone().or_else(|| env("UAT_EDITOR_CONFIG")) {
+        Some(path) => path,
+        None => format!("{}/{}", home()?, CONFIG_FILE),
+    };
+    let backup_dir = match env("UAT_EDITOR_BACKUP_DIR") {
+        Some(dir) => dir,
+        None => home()?,
+    };
+    let editor = cli.editor.clone().or_else(|| env("UAT_EDITOR_EDITOR"));
+    Ok(Paths {
+        config,
+        backup_dir,
+        editor,
+    })
+}
+
+// tried in order when neither the config nor the environment names an editor
+const FALLBACK_EDITORS: [&str; 4] = ["nvim", "vim", "vi", "nano"];
+
+// where the editor in use was taken from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorSource {
+    Override,
+    Config,
+    Visual,
+    Editor,
+    Path,
+}
+
+impl EditorSource {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            EditorSource::Override => "--editor or UAT_EDITOR_EDITOR",
+            EditorSource::Config => "config",
+            EditorSource::Visual => "$VISUAL",
+            EditorSource::Editor => "$EDITOR",
+            EditorSource::Path => "found on PATH",
+        }
+    }
+}
+
+// every editor worth trying, best first: the override, the config, $VISUAL, $EDITOR and
+// whichever fallbacks are installed. blank and repeated commands are left out
+pub fn editor_chain(
+    overridden: Option<&str>,
+    configured: &str,
+    env: impl Fn(&str) -> Option<String>,
+    on_path: impl Fn(&str) -> bool,
+) -> Vec<(String, EditorSource)> {
+    let named = [
+        (overridden.map(str::to_string), EditorSource::Override),
+        (Some(configured.to_string()), EditorSource::Config),
+        (env("VISUAL"), EditorSource::Visual),
+        (env("EDITOR"), EditorSource::Editor),
+    ];
+    let installed = FALLBACK_EDITORS
+        .into_iter()
+        .filter(|editor| on_path(editor))
+        .map(|editor| (Some(editor.to_string()), EditorSource::Path));
+    let mut chain: Vec<(String, EditorSource)> = Vec::new();
+    for (editor, source) in named.into_iter().chain(installed) {
+        let Some(editor) = editor.map(|e| e.trim().to_string()) else {
+            continue;
+        };
+        if !editor.is_empty() && !chain.iter().any(|(seen, _)| *seen == editor) {
+            chain.push((editor, source));
+        }
+    }
+    chain
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+fn is_unset(editor: &Arc<String>) -> bool {
+    editor.trim().is_empty()
+}
+
+// the nearest .uat_editor.toml in `start` or one of its parents
+pub fn find_project(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+// tables are merged key by key so a project can set one column without repeating the rest
+fn merge_value(base: &mut toml::Value, over: &toml::Value) {
+    match (base, over) {
+        (toml::Value::Table(base), toml::Value::Table(over)) => {
+            for (key, value) in over {
+                match base.get_mut(key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over.clone(),
+    }
+}
+
+// merges the project's keys over the user's, returning the user's values for each key
+// that was overridden (None when the user never set it) so they can be restored on save
+pub fn merge_project(
+    user: &mut toml::Table,
+    project: &toml::Table,
+) -> Vec<(String, Option<toml::Value>)> {
+    let mut shadowed = Vec::new();
+    for key in PROJECT_KEYS {
+        let Some(value) = project.get(*key) else {
+            continue;
+        };
+        shadowed.push((key.to_string(), user.get(*key).cloned()));
+        match user.get_mut(*key) {
+            Some(existing) => merge_value(existing, value),
+            None => {
+                user.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+    shadowed
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct ProjectFile {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    templates: HashMap<String, Vec<TestStep>>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Project {
+    pub name: String,
+    pub path: String,
+    pub templates: HashMap<String, Vec<TestStep>>,
+    shadowed: Vec<(String, Option<toml::Value>)>,
+}
+
+pub fn paths() -> Result<Paths, String> {
+    let overrides = OVERRIDES.get().cloned().unwrap_or_default();
+    resolve(&overrides, |key| {
+        std::env::var(key).ok().filter(|val| !val.is_empty())
+    })
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ColumnConfig {
+    #[serde(default = "ret_false")]
+    pub test_data: bool,
+    #[serde(default = "ret_false")]
+    pub estimate: bool,
+    #[serde(default = "ret_false")]
+    pub author: bool,
+    // the priority beside pass/fail, every prioritised row carries its class either way
+    #[serde(default = "ret_false")]
+    pub priority: bool,
+}
+
+// semantic colors are "#rrggbb" or a color name, anything unset comes from the preset
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub preset: Option<String>,
+    #[serde(default)]
+    pub pass: Option<String>,
+    #[serde(default)]
+    pub fail: Option<String>,
+    #[serde(default)]
+    pub blocked: Option<String>,
+    #[serde(default)]
+    pub section: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    // status glyphs in the # column so status isn't shown by color alone
+    #[serde(default = "ret_false")]
+    pub glyphs: bool,
+}
+
+// what reversing a selection does with the section headers inside it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderPolicy {
+    #[default]
+    Error,
+    Skip,
+}
+
+// github flavored markdown the compiled html understands on top of commonmark
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownExtension {
+    Tables,
+    Tasklists,
+    Strikethrough,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextLayout {
+    #[default]
+    Outline,
+    Table,
+}
+
+impl TextLayout {
+    pub fn parse(val: &str) -> Result<TextLayout, String> {
+        match val {
+            "outline" => Ok(TextLayout::Outline),
+            "table" => Ok(TextLayout::Table),
+            _ => Err(format!("Unknown layout: {} (expected outline|table)", val)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct PlainTextConfig {
+    #[serde(default)]
+    pub layout: TextLayout,
+    #[serde(default = "default_text_width")]
+    pub width: usize,
+    // box-drawing borders for the table, off by default since some mail clients mangle them
+    #[serde(default = "ret_false")]
+    pub unicode: bool,
+}
+
+impl Default for PlainTextConfig {
+    fn default() -> Self {
+        PlainTextConfig {
+            layout: TextLayout::default(),
+            width: default_text_width(),
+            unicode: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PdfConfig {
+    // a known converter's name or a command with {input} and {output} placeholders,
+    // unset uses the first converter found on PATH
+    #[serde(default)]
+    pub converter: Option<String>,
+    #[serde(default = "default_pdf_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for PdfConfig {
+    fn default() -> Self {
+        PdfConfig {
+            converter: None,
+            timeout_secs: default_pdf_timeout_secs(),
+        }
+    }
+}
+
+// a command that gets the document as json on stdin and prints the exported text
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExporterConfig {
+    pub command: String,
+    // the output goes to the clipboard instead of a file
+    #[serde(default = "ret_false")]
+    pub clipboard: bool,
+    #[serde(default = "default_exporter_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+// a command that reads a file in some other tool's format and prints the document json.
+// `{path}` in the command is replaced with the file, without it the file comes on stdin
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ImporterConfig {
+    pub command: String,
+    #[serde(default = "default_exporter_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+// a command that rewrites the document, e.g. a translation script, for `:transform <name>`.
+// it gets the `---` separated markdown on stdin and prints the same shape back. with
+// `max_chars` set the document goes in chunks of whole steps no longer than that
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TransformerConfig {
+    pub command: String,
+    #[serde(default = "default_transformer_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+}
+
+// the parts of the config exports need, see Config::load_layout
+#[derive(Debug, Deserialize)]
+pub struct Layout {
+    #[serde(default)]
+    pub columns: ColumnConfig,
+    #[serde(default = "default_use_ac")]
+    pub use_ac: bool,
+    #[serde(default)]
+    pub plain_text: PlainTextConfig,
+    #[serde(default)]
+    pub pdf: PdfConfig,
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub markdown_extensions: Vec<MarkdownExtension>,
+    #[serde(default = "default_numbering_start")]
+    pub numbering_start: usize,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            columns: ColumnConfig::default(),
+            use_ac: default_use_ac(),
+            plain_text: PlainTextConfig::default(),
+            pdf: PdfConfig::default(),
+            locale: Locale::default(),
+            markdown_extensions: Vec::new(),
+            numbering_start: default_numbering_start(),
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub templates: HashMap<String, Vec<TestStep>>,
+    // single steps inserted with `i s` or `:snippet <name>`
+    #[serde(default)]
+    pub snippets: HashMap<String, TestStep>,
+    // left out or empty falls through to $VISUAL, $EDITOR and then FALLBACK_EDITORS
+    #[serde(default, skip_serializing_if = "is_unset")]
     pub editor: Arc<String>,
+    // from the cli or environment, never written back to the file
+    #[serde(skip)]
+    pub editor_override: Option<Arc<String>>,
+    // the .uat_editor.toml merged over this config, its templates are kept apart
+    #[serde(skip)]
+    pub project: Option<Project>,
+    #[serde(default)]
+    pub columns: ColumnConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default = "default_use_ac")]
+    pub use_ac: bool,
+    #[serde(default = "default_title_bar")]
+    pub title_bar: bool,
+    #[serde(default = "ret_false")]
+    pub show_ids: bool,
+    #[serde(default = "default_show_builtin_templates")]
+    pub show_builtin_templates: bool,
+    #[serde(default = "default_tab_width")]
+    pub tab_width: usize,
+    #[serde(default)]
+    pub reverse_headers: HeaderPolicy,
+    // en or fr, for the tui, the markdown headings and exported column titles
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub plain_text: PlainTextConfig,
+    #[serde(default)]
+    pub pdf: PdfConfig,
+    // `:export <name>` for formats this tool doesn't know
+    #[serde(default)]
+    pub exporters: HashMap<String, ExporterConfig>,
+    // where `:serve` listens, localhost unless the view should be reachable from elsewhere
+    #[serde(default = "default_serve_bind")]
+    pub serve_bind: String,
+    #[serde(default = "default_serve_port")]
+    pub serve_port: u16,
+    // run after every successful `$` with the compiled html on stdin
+    #[serde(default)]
+    pub post_compile_cmd: Option<String>,
+    #[serde(default = "default_exporter_timeout_secs")]
+    pub post_compile_timeout_secs: u64,
+    // `:import <name> <path>` for files from other tools
+    #[serde(default)]
+    pub importers: HashMap<String, ImporterConfig>,
+    // pipes the editor's stderr so a failure can show why, off by default since some
+    // terminal editors draw to it
+    #[serde(default = "ret_false")]
+    pub capture_editor_stderr: bool,
+    // keeps :trash in the backup directory between sessions
+    #[serde(default = "ret_false")]
+    pub persist_trash: bool,
+    // `:transform <name>` rewrites the document into a new buffer
+    #[serde(default)]
+    pub transformers: HashMap<String, TransformerConfig>,
+    // runs :normalize before every export from the tui
+    #[serde(default = "ret_false")]
+    pub normalize_on_export: bool,
+    // ]] past the last section goes back to the first, and [[ the other way
+    #[serde(default = "default_wrap_sections")]
+    pub wrap_sections: bool,
+    // rows of context kept above and below the selection
+    #[serde(default)]
+    pub scrolloff: usize,
+    #[serde(default = "ret_false")]
+    pub show_age: bool,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default = "default_max_image_bytes")]
+    pub max_image_bytes: u64,
+    // how many of the most used instruction lines a new step offers, commented out, in
+    // the editor. 0 leaves them out
+    #[serde(default)]
+    pub scaffold_lines: usize,
+    // fields longer than this are flagged by :validate, 0 turns the check off
+    #[serde(default = "default_max_step_words")]
+    pub max_step_words: usize,
+    #[serde(default)]
+    pub spellcheck_cmd: Option<String>,
+    #[serde(default)]
+    pub spellcheck_ignore: Vec<String>,
+    // more headings read back from the editor, e.g. "Test Steps" = "instructions". the
+    // targets are instructions, expected_results and ac
+    #[serde(default)]
+    pub heading_aliases: HashMap<String, AliasTarget>,
+    // tables, tasklists and strikethrough. off by default, so a "|" or "~~" typed in a
+    // step reaches the export as written
+    #[serde(default)]
+    pub markdown_extensions: Vec<MarkdownExtension>,
+    // compile and export a document with no steps without asking for the ! form
+    #[serde(default = "ret_false")]
+    pub allow_empty_export: bool,
+    // the columns picked when a spreadsheet was imported, keyed by its headers so the same
+    // export maps by itself next time
+    #[serde(default)]
+    pub import_mappings: HashMap<String, Vec<Field>>,
+    // asks terminals that support the kitty keyboard protocol to report every modifier,
+    // so ctrl+shift+d clears all there. off leaves the terminal as is and clear-all on :clear
+    #[serde(default = "default_keyboard_enhancement")]
+    pub keyboard_enhancement: bool,
+    // the number of the first section, so part 2 of a document split in two carries on
+    // from part 1. steps are numbered within their section, so 41 makes the first step
+    // 41.1 in the table, the compiled html and every export
+    #[serde(default = "default_numbering_start")]
+    pub numbering_start: usize,
+    // reads the clipboard back after a compile, some platforms cut long html short without
+    // an error. a short read keeps the full html beside the backups
+    #[serde(default = "default_verify_clipboard")]
+    pub verify_clipboard: bool,
+    // encrypts the backup, trash, archives and documents written with :w, with a
+    // passphrase from UAT_EDITOR_PASSPHRASE, passphrase_cmd or asked for at startup.
+    // encrypted files are read back whatever this is set to, given the passphrase
+    #[serde(default = "ret_false")]
+    pub encrypt: bool,
+    // prints the passphrase, e.g. `pass show uat_editor`
+    #[serde(default)]
+    pub passphrase_cmd: Option<String>,
+}
+
+fn default_max_image_bytes() -> u64 {
+    DEFAULT_MAX_IMAGE_BYTES
+}
+
+fn default_max_step_words() -> usize {
+    150
+}
+
+fn default_use_ac() -> bool {
+    true
+}
+
+fn default_title_bar() -> bool {
+    true
+}
+
+fn default_show_builtin_templates() -> bool {
+    true
+}
+
+fn default_wrap_sections() -> bool {
+    true
+}
+
+fn default_keyboard_enhancement() -> bool {
+    true
+}
+
+fn default_verify_clipboard() -> bool {
+    true
+}
+
+fn default_numbering_start() -> usize {
+    1
+}
+
+// as given to --start or :set start=
+pub fn parse_numbering_start(val: &str) -> Result<usize, String> {
+    match val.parse() {
+        Ok(start) if start > 0 => Ok(start),
+        _ => Err(format!("Invalid start: {} (expected a number from 1)", val)),
+    }
+}
+
+fn default_tab_width() -> usize {
+    4
+}
+
+fn default_text_width() -> usize {
+    72
+}
+
+fn default_pdf_timeout_secs() -> u64 {
+    60
+}
+
+fn default_exporter_timeout_secs() -> u64 {
+    30
+}
+
+// per chunk, translation tools can be slow
+fn default_transformer_timeout_secs() -> u64 {
+    120
+}
+
+pub fn default_serve_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+pub fn default_serve_port() -> u16 {
+    8080
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let editor = std::env::var("EDITOR")
-            .expect("EXPECTED EDITOR VARIABLE")
-            .into();
         Config {
             templates: HashMap::new(),
-            editor,
+            snippets: HashMap::new(),
+            // left empty so the environment and PATH are looked at, see editor_chain
+            editor: Arc::default(),
+            editor_override: paths().ok().and_then(|paths| paths.editor).map(Arc::new),
+            project: None,
+            columns: ColumnConfig::default(),
+            theme: ThemeConfig::default(),
+            use_ac: true,
+            title_bar: default_title_bar(),
+            show_ids: false,
+            show_builtin_templates: default_show_builtin_templates(),
+            tab_width: default_tab_width(),
+            reverse_headers: HeaderPolicy::default(),
+            locale: Locale::default(),
+            plain_text: PlainTextConfig::default(),
+            pdf: PdfConfig::default(),
+            exporters: HashMap::new(),
+            importers: HashMap::new(),
+            transformers: HashMap::new(),
+            persist_trash: false,
+            capture_editor_stderr: false,
+            serve_bind: default_serve_bind(),
+            serve_port: default_serve_port(),
+            post_compile_cmd: None,
+            post_compile_timeout_secs: default_exporter_timeout_secs(),
+            normalize_on_export: false,
+            wrap_sections: default_wrap_sections(),
+            scrolloff: 0,
+            show_age: false,
+            author: None,
+            max_image_bytes: DEFAULT_MAX_IMAGE_BYTES,
+            scaffold_lines: 0,
+            max_step_words: default_max_step_words(),
+            spellcheck_cmd: None,
+            spellcheck_ignore: Vec::new(),
+            heading_aliases: HashMap::new(),
+            markdown_extensions: Vec::new(),
+            allow_empty_export: false,
+            keyboard_enhancement: default_keyboard_enhancement(),
+            numbering_start: default_numbering_start(),
+            verify_clipboard: default_verify_clipboard(),
+            encrypt: false,
+            passphrase_cmd: None,
+            import_mappings: HashMap::new(),
         }
     }
 }
 
 impl Config {
+    pub fn author(&self) -> Option<String> {
+        self.author
+            .clone()
+            .or_else(|| std::env::var("USER").ok())
+            .filter(|author| !author.trim().is_empty())
+    }
+
+    pub fn path() -> Result<String, String> {
+        paths().map(|paths| paths.config)
+    }
+
+    pub fn editors(&self) -> Vec<(String, EditorSource)> {
+        editor_chain(
+            self.editor_override.as_deref().map(String::as_str),
+            &self.editor,
+            |name| std::env::var(name).ok(),
+            on_path,
+        )
+    }
+
+    // the first of `editors`, empty when there is none at all and the tui runs its
+    // setup wizard
+    pub fn editor(&self) -> Arc<String> {
+        self.editor_with_source()
+            .map(|(editor, _)| Arc::new(editor))
+            .unwrap_or_default()
+    }
+
+    pub fn editor_with_source(&self) -> Option<(String, EditorSource)> {
+        self.editors().into_iter().next()
+    }
+
+    // a missing file means defaults, one that can't be read or parsed is an error so a
+    // typo never quietly turns into an empty config that the next save writes out
     pub fn load_config() -> Result<Config, String> {
-        let home = std::env::var("HOME").with_err_msg(&"EXPECTED HOME VARIABLE")?;
-        let path = format!("{}/{}", home, CONFIG_PATH);
-        Ok(match std::fs::read_to_string(path) {
-            Ok(content) => match toml::from_str(&content) {
-                Ok(config) => config,
-                Err(_) => Config::default(),
-            },
-            Err(_) => Config::default(),
-        })
+        let path = Config::path()?;
+        let config = match std::fs::read_to_string(&path) {
+            Ok(content) => Config::parse(&path, &content)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(err) => return Err(format!("Failed to read {}: {}", path, err)),
+        };
+        match std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_project(&dir))
+        {
+            Some(project) => config.with_project(&project),
+            None => Ok(config),
+        }
+    }
+
+    // project wins over the user config for PROJECT_KEYS
+    pub fn with_project(self, path: &Path) -> Result<Config, String> {
+        let display = path.display().to_string();
+        let content =
+            std::fs::read_to_string(path).with_err_msg(&format!("Failed to read {}", display))?;
+        let project_table: toml::Table = toml::from_str(&content)
+            .map_err(|err| format!("Failed to parse {}: {}", display, err))?;
+        let project_file: ProjectFile = toml::from_str(&content)
+            .map_err(|err| format!("Failed to parse {}: {}", display, err))?;
+
+        let mut table =
+            toml::Table::try_from(&self).with_err_msg(&"Failed to serialize config to toml")?;
+        let shadowed = merge_project(&mut table, &project_table);
+        let mut config: Config = table
+            .try_into()
+            .map_err(|err| format!("Invalid value in {}: {}", display, err))?;
+
+        let dir_name = path
+            .parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string());
+        config.editor_override = self.editor_override;
+        config.project = Some(Project {
+            name: project_file
+                .name
+                .or(dir_name)
+                .unwrap_or_else(|| "project".to_string()),
+            path: display,
+            templates: project_file.templates,
+            shadowed,
+        });
+        Ok(config)
+    }
+
+    // what gets written to the user's file, with any project overrides taken back out
+    fn user_toml(&self) -> Result<String, String> {
+        let mut table =
+            toml::Table::try_from(self).with_err_msg(&"Failed to serialize config to toml")?;
+        if let Some(project) = &self.project {
+            for (key, original) in &project.shadowed {
+                match original {
+                    Some(value) => table.insert(key.clone(), value.clone()),
+                    None => table.remove(key),
+                };
+            }
+        }
+        toml::to_string(&table).with_err_msg(&"Failed to serialize config to toml")
+    }
+
+    fn parse(path: &str, content: &str) -> Result<Config, String> {
+        let mut config: Config =
+            toml::from_str(content).map_err(|err| format!("Failed to parse {}: {}", path, err))?;
+        config.editor_override = paths().ok().and_then(|paths| paths.editor).map(Arc::new);
+        Ok(config)
+    }
+
+    // moves a config that failed to parse out of the way so saving works again
+    pub fn backup_broken() -> Result<String, String> {
+        let path = Config::path()?;
+        let broken = format!("{}.broken", path);
+        std::fs::rename(&path, &broken)
+            .with_err_msg(&format!("Failed to move {} to {}", path, broken))?;
+        Ok(broken)
+    }
+
+    // just the layout and export settings, for callers that don't need (or have) an
+    // editor configured
+    pub fn load_layout() -> Layout {
+        let read_table = |path: &Path| {
+            std::fs::read_to_string(path)
+                .ok()
+                .and_then(|content| toml::from_str::<toml::Table>(&content).ok())
+        };
+        let mut table = Config::path()
+            .ok()
+            .and_then(|path| read_table(Path::new(&path)))
+            .unwrap_or_default();
+        if let Some(project) = std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_project(&dir))
+            .and_then(|path| read_table(&path))
+        {
+            merge_project(&mut table, &project);
+        }
+        table.try_into::<Layout>().unwrap_or_default()
     }
 
     pub fn save_config(&self) -> Result<(), String> {
-        let home = std::env::var("HOME").with_err_msg(&"EXPECTED HOME VARIABLE")?;
-        let toml = toml::to_string(self).with_err_msg(&"Failed to serialize config to toml")?;
-        let path = format!("{}/{}", home, CONFIG_PATH);
-        std::fs::write(path, toml).with_err_msg(&"Failed to write config to toml")
+        let path = Config::path()?;
+        if let Ok(existing) = std::fs::read_to_string(&path) {
+            Config::parse(&path, &existing).map_err(|err| {
+                format!(
+                    "Refusing to overwrite, fix it or :backupconfig first. {}",
+                    err
+                )
+            })?;
+        }
+        std::fs::write(path, self.user_toml()?).with_err_msg(&"Failed to write config to toml")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_errors_name_the_file_and_line() {
+        let content = "editor = \"vim\"\n[templates\n";
+        let err = Config::parse("config.toml", content).unwrap_err();
+        assert!(err.starts_with("Failed to parse config.toml"));
+        assert!(err.contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn cli_beats_env_beats_defaults() {
+        let env = |key: &str| match key {
+            "HOME" => Some("/home/qa".to_string()),
+            "UAT_EDITOR_CONFIG" => Some("/etc/uat/config.toml".to_string()),
+            "UAT_EDITOR_BACKUP_DIR" => Some("/tmp/uat".to_string()),
+            "UAT_EDITOR_EDITOR" => Some("nano".to_string()),
+            _ => None,
+        };
+        let cli = Overrides {
+            config: Some("ci.toml".to_string()),
+            editor: Some("vim".to_string()),
+        };
+        assert_eq!(
+            resolve(&cli, env).unwrap(),
+            Paths {
+                config: "ci.toml".to_string(),
+                backup_dir: "/tmp/uat".to_string(),
+                editor: Some("vim".to_string()),
+            }
+        );
+
+        let from_env = resolve(&Overrides::default(), env).unwrap();
+        assert_eq!(from_env.config, "/etc/uat/config.toml");
+        assert_eq!(from_env.editor, Some("nano".to_string()));
+
+        let home_only = |key: &str| (key == "HOME").then(|| "/home/qa".to_string());
+        assert_eq!(
+            resolve(&Overrides::default(), home_only).unwrap(),
+            Paths {
+                config: "/home/qa/.config/uat_editor/config.toml".to_string(),
+                backup_dir: "/home/qa/.config/uat_editor".to_string(),
+                editor: None,
+            }
+        );
+        assert!(resolve(&Overrides::default(), |_| None).is_err());
+    }
+
+    #[test]
+    fn project_keys_win_and_tables_merge() {
+        let mut user: toml::Table = toml::from_str(
+            "editor = \"vim\"\nauthor = \"me\"\n[columns]\ntest_data = true\n[templates]\n",
+        )
+        .unwrap();
+        let project: toml::Table = toml::from_str(
+            "author = \"client qa\"\neditor = \"nano\"\nshow_ids = true\nnumbering_start = 2\n[columns]\nestimate = true\n",
+        )
+        .unwrap();
+        let shadowed = merge_project(&mut user, &project);
+
+        assert_eq!(user["author"].as_str(), Some("client qa"));
+        assert_eq!(user["show_ids"].as_bool(), Some(true));
+        // part 2 of a split document numbers on from the project
+        assert_eq!(user["numbering_start"].as_integer(), Some(2));
+        // not a project key, the user's editor stays
+        assert_eq!(user["editor"].as_str(), Some("vim"));
+        assert_eq!(user["columns"]["test_data"].as_bool(), Some(true));
+        assert_eq!(user["columns"]["estimate"].as_bool(), Some(true));
+        assert_eq!(
+            shadowed,
+            vec![
+                (
+                    "columns".to_string(),
+                    Some(
+                        toml::from_str("test_data = true")
+                            .map(toml::Value::Table)
+                            .unwrap()
+                    )
+                ),
+                ("show_ids".to_string(), None),
+                ("author".to_string(), Some(toml::Value::from("me"))),
+                ("numbering_start".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn project_values_are_not_saved_to_the_user_config() {
+        let dir = std::env::temp_dir().join(format!("uat_editor_project_{}", std::process::id()));
+        let nested = dir.join("client/feature");
+        std::fs::create_dir_all(&nested).unwrap();
+        let project_path = dir.join(PROJECT_FILE);
+        std::fs::write(
+            &project_path,
+            "name = \"acme\"\nauthor = \"acme qa\"\n[templates]\nsmoke = []\n",
+        )
+        .unwrap();
+        assert_eq!(find_project(&nested), Some(project_path.clone()));
+
+        let user: Config = toml::from_str("editor = \"vim\"\n[templates]\n").unwrap();
+        let config = user.with_project(&project_path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(config.author.as_deref(), Some("acme qa"));
+        let project = config.project.as_ref().unwrap();
+        assert_eq!(project.name, "acme");
+        assert!(project.templates.contains_key("smoke"));
+        assert!(config.templates.is_empty());
+        let saved = config.user_toml().unwrap();
+        assert!(!saved.contains("acme"), "{}", saved);
+    }
+
+    #[test]
+    fn editor_chain_goes_flag_config_env_then_path() {
+        let env = |name: &str| match name {
+            "VISUAL" => Some("code".to_string()),
+            "EDITOR" => Some("vim".to_string()),
+            _ => None,
+        };
+        let on_path = |editor: &str| matches!(editor, "vim" | "nano");
+        let chain = editor_chain(Some("hx"), " ", env, on_path);
+        assert_eq!(
+            chain,
+            vec![
+                ("hx".to_string(), EditorSource::Override),
+                ("code".to_string(), EditorSource::Visual),
+                ("vim".to_string(), EditorSource::Editor),
+                ("nano".to_string(), EditorSource::Path),
+            ]
+        );
+        let chain = editor_chain(None, "micro", |_| None, on_path);
+        assert_eq!(chain[0], ("micro".to_string(), EditorSource::Config));
+        assert!(editor_chain(None, "", |_| None, |_| false).is_empty());
+    }
+
+    #[test]
+    fn editor_override_is_not_saved() {
+        let mut config: Config = toml::from_str("editor = \"nano\"\n[templates]\n").unwrap();
+        config.editor_override = Some(Arc::new("vim".to_string()));
+        assert_eq!(config.editor().as_str(), "vim");
+        assert!(
+            toml::to_string(&config)
+                .unwrap()
+                .contains("editor = \"nano\"")
+        );
     }
 }
@@ -0,0 +1,301 @@
+use crate::{
+    config::Layout,
+    crypto, embedding,
+    err_msg::WithErrMsg,
+    export::html::{self, HtmlOptions},
+    test_step::TestStep,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+// what `convert` reads and writes: compiled html carrying an embedding, or a json document
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocFormat {
+    Html,
+    Json,
+}
+
+impl DocFormat {
+    pub fn parse(name: &str) -> Result<DocFormat, String> {
+        match name {
+            "html" => Ok(DocFormat::Html),
+            "json" => Ok(DocFormat::Json),
+            _ => Err(format!(
+                "Unknown document format: {} (expected html|json)",
+                name
+            )),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            DocFormat::Html => "html",
+            DocFormat::Json => "json",
+        }
+    }
+
+    fn read(self, content: &str) -> Result<Vec<TestStep>, String> {
+        match self {
+            DocFormat::Html => embedding::extract(content),
+            DocFormat::Json => embedding::parse_document(content),
+        }
+    }
+
+    // html is rendered with the current columns and template, like :compile
+    fn render(self, items: &[TestStep], layout: &Layout) -> Result<String, String> {
+        match self {
+            DocFormat::Html => html::gen_html(items, &HtmlOptions::from_layout(layout)),
+            DocFormat::Json => embedding::canonical_json(items),
+        }
+    }
+}
+
+pub struct Conversion {
+    pub from: DocFormat,
+    pub to: DocFormat,
+    pub out_dir: String,
+    pub force: bool,
+}
+
+// one input, Ok is the file written and how many rows it has
+pub struct Outcome {
+    pub input: String,
+    pub result: Result<(String, usize), String>,
+}
+
+// a directory stands for the files in it with the --from extension, in name order
+pub fn expand_inputs(inputs: &[String], from: DocFormat) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if !Path::new(input).is_dir() {
+            files.push(input.clone());
+            continue;
+        }
+        let entries =
+            std::fs::read_dir(input).with_err_msg(&format!("Failed to read {}", input))?;
+        let mut found: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path.extension().and_then(|ext| ext.to_str()) == Some(from.extension())
+            })
+            .collect();
+        found.sort();
+        files.extend(found.iter().map(|path| path.to_string_lossy().to_string()));
+    }
+    Ok(files)
+}
+
+fn output_path(input: &str, conversion: &Conversion) -> String {
+    let stem = Path::new(input)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| input.to_string());
+    Path::new(&conversion.out_dir)
+        .join(format!("{}.{}", stem, conversion.to.extension()))
+        .to_string_lossy()
+        .to_string()
+}
+
+fn convert_file(
+    input: &str,
+    output: &str,
+    conversion: &Conversion,
+    layout: &Layout,
+) -> Result<usize, String> {
+    if !conversion.force && Path::new(output).exists() {
+        return Err(format!(
+            "{} already exists, pass --force to overwrite it",
+            output
+        ));
+    }
+    let content = crypto::read(input)?;
+    let items = conversion.from.read(&content)?;
+    let rendered = conversion.to.render(&items, layout)?;
+    std::fs::write(output, rendered).with_err_msg(&format!("Failed to write {}", output))?;
+    Ok(items.len())
+}
+
+// every input is tried, a failure never stops the rest
+pub fn convert(
+    inputs: &[String],
+    conversion: &Conversion,
+    layout: &Layout,
+) -> Result<Vec<Outcome>, String> {
+    std::fs::create_dir_all(&conversion.out_dir)
+        .with_err_msg(&format!("Failed to create {}", conversion.out_dir))?;
+    // two inputs with the same name would write one output, even with --force only the
+    // first gets it
+    let mut claimed: HashMap<String, &str> = HashMap::new();
+    let outcomes = inputs
+        .iter()
+        .map(|input| {
+            let output = output_path(input, conversion);
+            let result = match claimed.get(&output) {
+                Some(first) => Err(format!("{} is already written from {}", output, first)),
+                None => {
+                    claimed.insert(output.clone(), input);
+                    convert_file(input, &output, conversion, layout).map(|rows| (output, rows))
+                }
+            };
+            Outcome {
+                input: input.clone(),
+                result,
+            }
+        })
+        .collect();
+    Ok(outcomes)
+}
+
+pub fn summary(outcomes: &[Outcome]) -> String {
+    let width = outcomes
+        .iter()
+        .map(|outcome| outcome.input.chars().count())
+        .chain(["INPUT".len()])
+        .max()
+        .unwrap_or(0);
+    let mut out = format!(
+        "{:<6}  {:<width$}  RESULT\n",
+        "STATUS",
+        "INPUT",
+        width = width
+    );
+    for outcome in outcomes {
+        let (status, result) = match &outcome.result {
+            Ok((output, rows)) => ("ok", format!("{} ({} rows)", output, rows)),
+            Err(err) => ("failed", err.clone()),
+        };
+        out.push_str(&format!(
+            "{:<6}  {:<width$}  {}\n",
+            status,
+            outcome.input,
+            result,
+            width = width
+        ));
+    }
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| outcome.result.is_err())
+        .count();
+    out.push_str(&format!(
+        "{} files: {} converted, {} failed\n",
+        outcomes.len(),
+        outcomes.len() - failed,
+        failed
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "uat_editor_convert_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn conversion(from: DocFormat, to: DocFormat, out_dir: &Path, force: bool) -> Conversion {
+        Conversion {
+            from,
+            to,
+            out_dir: out_dir.to_string_lossy().to_string(),
+            force,
+        }
+    }
+
+    #[test]
+    fn html_backups_convert_to_json_and_back() {
+        let root = dir("round_trip");
+        let mut items = vec![
+            TestStep::section("Login"),
+            TestStep::new().with_instructions("Open app"),
+        ];
+        TestStep::assign_ids(&mut items);
+        let layout = Layout::default();
+        let compiled = DocFormat::Html.render(&items, &layout).unwrap();
+        std::fs::write(root.join("a.html"), &compiled).unwrap();
+        std::fs::write(root.join("broken.html"), "<table></table>").unwrap();
+        std::fs::write(root.join("notes.txt"), "skipped").unwrap();
+
+        let inputs = expand_inputs(&[root.to_string_lossy().to_string()], DocFormat::Html).unwrap();
+        assert_eq!(inputs.len(), 2);
+        let json_dir = root.join("json");
+        let to_json = conversion(DocFormat::Html, DocFormat::Json, &json_dir, false);
+        let outcomes = convert(&inputs, &to_json, &layout).unwrap();
+        assert_eq!(outcomes[0].result.as_ref().unwrap().1, 2);
+        assert_eq!(
+            outcomes[1].result,
+            Err("Could not find MDEMBEDDING marker".to_string())
+        );
+        let written = embedding::load_file(&json_dir.join("a.json").to_string_lossy()).unwrap();
+        assert_eq!(written[1].instructions, "Open app");
+
+        let html_dir = root.join("html");
+        let to_html = conversion(DocFormat::Json, DocFormat::Html, &html_dir, false);
+        let json = vec![json_dir.join("a.json").to_string_lossy().to_string()];
+        assert!(convert(&json, &to_html, &layout).unwrap()[0].result.is_ok());
+        let rendered = std::fs::read_to_string(html_dir.join("a.html")).unwrap();
+        assert_eq!(rendered, compiled);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn existing_outputs_need_force() {
+        let root = dir("force");
+        let items = vec![TestStep::new().with_instructions("Open app")];
+        let input = root.join("a.json");
+        embedding::save_file(&input.to_string_lossy(), &items).unwrap();
+        std::fs::write(root.join("a.html"), "keep me").unwrap();
+        let inputs = vec![
+            input.to_string_lossy().to_string(),
+            input.to_string_lossy().to_string(),
+        ];
+        let layout = Layout::default();
+
+        let outcomes = convert(
+            &inputs,
+            &conversion(DocFormat::Json, DocFormat::Html, &root, false),
+            &layout,
+        )
+        .unwrap();
+        assert!(
+            outcomes[0]
+                .result
+                .as_ref()
+                .unwrap_err()
+                .contains("pass --force")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("a.html")).unwrap(),
+            "keep me"
+        );
+
+        let outcomes = convert(
+            &inputs,
+            &conversion(DocFormat::Json, DocFormat::Html, &root, true),
+            &layout,
+        )
+        .unwrap();
+        assert!(outcomes[0].result.is_ok());
+        assert!(
+            outcomes[1]
+                .result
+                .as_ref()
+                .unwrap_err()
+                .contains("is already written from")
+        );
+        let table = summary(&outcomes);
+        assert!(table.starts_with("STATUS  INPUT"));
+        assert!(table.ends_with("2 files: 1 converted, 1 failed\n"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{config::Config, err_msg::WithErrMsg, shell};
+use argon2::Argon2;
+use chacha20poly1305::{
+    Key, KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, OsRng, rand_core::RngCore},
+};
+use ratatui::crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal,
+};
+use std::{
+    io::{IsTerminal, Write},
+    path::Path,
+    sync::OnceLock,
+};
+
+// every encrypted file starts with this, anything else is read as plaintext
+const MAGIC: &[u8] = b"UATENC1\n";
+const SALT_LEN: usize = 16;
+const CHECK_LEN: usize = 8;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + CHECK_LEN + NONCE_LEN;
+
+static PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+// a missing or unreadable file isn't
+pub fn is_encrypted_file(path: &Path) -> bool {
+    std::fs::read(path).is_ok_and(|bytes| is_encrypted(&bytes))
+}
+
+// the first half keys the cipher, the rest is kept in the header so a wrong passphrase
+// can be told apart from a damaged file
+fn derive(passphrase: &str, salt: &[u8]) -> Result<([u8; 32], [u8; CHECK_LEN]), String> {
+    let mut out = [0u8; 32 + CHECK_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut out)
+        .map_err(|err| format!("Failed to derive a key: {}", err))?;
+    let mut key = [0u8; 32];
+    let mut check = [0u8; CHECK_LEN];
+    key.copy_from_slice(&out[..32]);
+    check.copy_from_slice(&out[32..]);
+    Ok((key, check))
+}
+
+// MAGIC, salt, check, nonce, then the ciphertext with its tag
+pub fn encrypt(passphrase: &str, plaintext: &str) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    let (key, check) = derive(passphrase, &salt)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+        .with_err_msg(&"Failed to encrypt")?;
+    let mut out = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&check);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+// the key for `bytes` when `passphrase` is the one they were sealed with, checked against
+// the header before anything is decrypted
+fn key_for(passphrase: &str, bytes: &[u8]) -> Result<[u8; 32], String> {
+    if !is_encrypted(bytes) {
+        return Err("Not an encrypted uat_editor file".to_string());
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err("Encrypted file is cut short".to_string());
+    }
+    let (salt, rest) = bytes[MAGIC.len()..].split_at(SALT_LEN);
+    let (key, check) = derive(passphrase, salt)?;
+    if check != rest[..CHECK_LEN] {
+        return Err("Wrong passphrase".to_string());
+    }
+    Ok(key)
+}
+
+pub fn decrypt(passphrase: &str, bytes: &[u8]) -> Result<String, String> {
+    let key = key_for(passphrase, bytes)?;
+    let (nonce, ciphertext) = bytes[HEADER_LEN - NONCE_LEN..].split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .with_err_msg(&"Encrypted file is corrupted, the passphrase matched")?;
+    String::from_utf8(plaintext).with_err_msg(&"Encrypted file does not hold text")
+}
+
+fn unlocked() -> Option<&'static str> {
+    PASSPHRASE.get().map(String::as_str)
+}
+
+// with `encrypt` on: UAT_EDITOR_PASSPHRASE, then passphrase_cmd, then a prompt when
+// `interactive` and there is a terminal to ask on
+pub fn unlock(config: &Config, interactive: bool) -> Result<(), String> {
+    if !config.encrypt || unlocked().is_some() {
+        return Ok(());
+    }
+    let passphrase = match (
+        std::env::var("UAT_EDITOR_PASSPHRASE"),
+        &config.passphrase_cmd,
+    ) {
+        (Ok(passphrase), _) => passphrase,
+        (Err(_), Some(cmd)) => shell::filter(cmd, "")
+            .map_err(|err| format!("passphrase_cmd failed: {}", err))?
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+        (Err(_), None) if interactive && std::io::stdin().is_terminal() => prompt()?,
+        (Err(_), None) => {
+            return Err("encrypt is on, set UAT_EDITOR_PASSPHRASE or passphrase_cmd".to_string());
+        }
+    };
+    if passphrase.is_empty() {
+        return Err("The passphrase can't be empty".to_string());
+    }
+    let _ = PASSPHRASE.set(passphrase);
+    Ok(())
+}
+
+// on the plain terminal before the tui starts, nothing is echoed
+fn prompt() -> Result<String, String> {
+    print!("Passphrase for encrypted files: ");
+    let _ = std::io::stdout().flush();
+    terminal::enable_raw_mode().with_err_msg(&"Failed to read the passphrase")?;
+    let mut passphrase = String::new();
+    let answer = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(_) => break Err("Failed to read the passphrase".to_string()),
+        };
+        match key.code {
+            KeyCode::Enter => break Ok(()),
+            KeyCode::Esc => break Err("No passphrase given".to_string()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err("No passphrase given".to_string());
+            }
+            KeyCode::Backspace => {
+                passphrase.pop();
+            }
+            KeyCode::Char(c) => passphrase.push(c),
+            _ => {}
+        }
+    };
+    let _ = terminal::disable_raw_mode();
+    println!();
+    answer.map(|_| passphrase)
+}
+
+// what goes on disk for `text`, encrypted once a passphrase is unlocked
+pub fn seal(text: &str) -> Result<Vec<u8>, String> {
+    match unlocked() {
+        Some(passphrase) => encrypt(passphrase, text),
+        None => Ok(text.as_bytes().to_vec()),
+    }
+}
+
+// an encrypted file is never replaced by plaintext, nor sealed again under a passphrase
+// it wasn't written with. either takes moving the file aside
+pub fn guard(path: &Path) -> Result<(), String> {
+    guard_with(unlocked(), path)
+}
+
+fn guard_with(passphrase: Option<&str>, path: &Path) -> Result<(), String> {
+    let Some(bytes) = std::fs::read(path).ok().filter(|bytes| is_encrypted(bytes)) else {
+        return Ok(());
+    };
+    match passphrase {
+        Some(passphrase) => key_for(passphrase, &bytes)
+            .map(|_| ())
+            .map_err(|err| format!("{}: {}, it is left as it is", path.display(), err)),
+        None => Err(format!(
+            "{} is encrypted, it takes encrypt = true and the passphrase to write it",
+            path.display()
+        )),
+    }
+}
+
+// plaintext files read as they are, encrypted ones are decrypted
+pub fn read(path: &str) -> Result<String, String> {
+    let bytes = std::fs::read(path).with_err_msg(&format!("Failed to read {}", path))?;
+    if !is_encrypted(&bytes) {
+        return String::from_utf8(bytes).with_err_msg(&format!("Failed to read {}", path));
+    }
+    match unlocked() {
+        Some(passphrase) => decrypt(passphrase, &bytes).map_err(|err| format!("{}: {}", path, err)),
+        None => Err(format!(
+            "{} is encrypted, it takes encrypt = true and the passphrase to read it",
+            path
+        )),
+    }
+}
+
+pub fn write(path: &str, text: &str) -> Result<(), String> {
+    guard(Path::new(path))?;
+    std::fs::write(path, seal(text)?).with_err_msg(&format!("Failed to write {}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrong_passphrases_and_damage_are_told_apart() {
+        let sealed = encrypt("hunter2", "{\"version\":2}").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_eq!(decrypt("hunter2", &sealed).unwrap(), "{\"version\":2}");
+        // a fresh salt and nonce every time
+        assert_ne!(encrypt("hunter2", "{\"version\":2}").unwrap(), sealed);
+
+        assert_eq!(decrypt("hunter3", &sealed).unwrap_err(), "Wrong passphrase");
+        let mut damaged = sealed.clone();
+        *damaged.last_mut().unwrap() ^= 1;
+        assert!(
+            decrypt("hunter2", &damaged)
+                .unwrap_err()
+                .contains("corrupted")
+        );
+        assert_eq!(
+            decrypt("hunter2", &sealed[..HEADER_LEN - 1]).unwrap_err(),
+            "Encrypted file is cut short"
+        );
+        assert!(decrypt("hunter2", b"{}").is_err());
+    }
+
+    #[test]
+    fn a_wrong_passphrase_never_writes_over_an_encrypted_file() {
+        let path = std::env::temp_dir().join(format!("uat_editor_guard_{}", std::process::id()));
+        let sealed = encrypt("hunter2", "[]").unwrap();
+        std::fs::write(&path, &sealed).unwrap();
+
+        let err = guard_with(Some("hunter3"), &path).unwrap_err();
+        assert!(err.contains("Wrong passphrase"), "{}", err);
+        assert!(guard_with(None, &path).is_err());
+        assert_eq!(guard_with(Some("hunter2"), &path), Ok(()));
+        assert_eq!(std::fs::read(&path).unwrap(), sealed);
+
+        // plaintext and missing files are free to write
+        std::fs::write(&path, "[]").unwrap();
+        assert_eq!(guard_with(Some("hunter3"), &path), Ok(()));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(guard_with(None, &path), Ok(()));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::test_step::TestStep;
+
+// steps are matched up by id, a step whose id is new was added and one whose id is gone
+// was removed. labels are the step numbers in whichever document the step is in
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Added {
+        label: String,
+        title: String,
+    },
+    Removed {
+        label: String,
+        title: String,
+    },
+    Modified {
+        label: String,
+        title: String,
+        fields: Vec<FieldChange>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+fn kind(item: &TestStep) -> String {
+    if item.is_new_section {
+        "section"
+    } else if item.is_stepless_comment {
+        "comment"
+    } else {
+        "step"
+    }
+    .to_string()
+}
+
+fn fields(item: &TestStep) -> [(&'static str, String); 10] {
+    [
+        ("Kind", kind(item)),
+        ("Instructions", item.instructions()),
+        ("Test Data", item.test_data()),
+        ("Expected Results", item.expected_results()),
+        ("AC", item.ac()),
+        (
+            "Estimate",
+            item.estimate_minutes
+                .map(TestStep::format_minutes)
+                .unwrap_or_default(),
+        ),
+        (
+            "Status",
+            item.status
+                .map(|s| s.label().to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "Priority",
+            item.priority
+                .map(|p| p.label().to_string())
+                .unwrap_or_default(),
+        ),
+        ("Comment", item.comment.trim().to_string()),
+        ("Links", item.links.join("\n")),
+    ]
+}
+
+// lines only in the old value and lines only in the new one, in order. good enough for
+// the short fields of a step, a moved line shows as removed and added
+fn field_change(field: &'static str, old: &str, new: &str) -> Option<FieldChange> {
+    if old == new {
+        return None;
+    }
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    Some(FieldChange {
+        field,
+        removed: old_lines
+            .iter()
+            .filter(|line| !new_lines.contains(line))
+            .map(|line| line.to_string())
+            .collect(),
+        added: new_lines
+            .iter()
+            .filter(|line| !old_lines.contains(line))
+            .map(|line| line.to_string())
+            .collect(),
+    })
+}
+
+fn title(item: &TestStep) -> String {
+    item.instructions().lines().next().unwrap_or("").to_string()
+}
+
+// in the order of the new document, removed steps last
+pub fn diff(old: &[TestStep], new: &[TestStep], first_section: usize) -> Vec<Change> {
+    let old_labels = TestStep::step_labels(old, first_section);
+    let new_labels = TestStep::step_labels(new, first_section);
+    let mut changes = Vec::new();
+    for (item, label) in new.iter().zip(&new_labels) {
+        let Some(before) = old.iter().find(|o| o.id == item.id) else {
+            changes.push(Change::Added {
+                label: label.clone(),
+                title: title(item),
+            });
+            continue;
+        };
+        let fields: Vec<FieldChange> = fields(before)
+            .into_iter()
+            .zip(fields(item))
+            .filter_map(|((field, old), (_, new))| field_change(field, &old, &new))
+            .collect();
+        if !fields.is_empty() {
+            changes.push(Change::Modified {
+                label: label.clone(),
+                title: title(item),
+                fields,
+            });
+        }
+    }
+    for (item, label) in old.iter().zip(&old_labels) {
+        if !new.iter().any(|n| n.id == item.id) {
+            changes.push(Change::Removed {
+                label: label.clone(),
+                title: title(item),
+            });
+        }
+    }
+    changes
+}
+
+// e.g. "2 added, 1 removed, 3 modified"
+pub fn describe(changes: &[Change]) -> String {
+    let count = |f: fn(&Change) -> bool| changes.iter().filter(|c| f(c)).count();
+    format!(
+        "{} added, {} removed, {} modified",
+        count(|c| matches!(c, Change::Added { .. })),
+        count(|c| matches!(c, Change::Removed { .. })),
+        count(|c| matches!(c, Change::Modified { .. })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_step::StepStatus;
+
+    fn step(id: u64, instructions: &str) -> TestStep {
+        TestStep {
+            id,
+            ..TestStep::new().with_instructions(instructions)
+        }
+    }
+
+    #[test]
+    fn steps_are_matched_by_id() {
+        let old = vec![step(1, "Open"), step(2, "Sign in"), step(3, "Log out")];
+        let mut changed = step(2, "Sign in\nwith sso");
+        changed.status = Some(StepStatus::Pass);
+        let new = vec![step(1, "Open"), step(4, "Reset password"), changed];
+
+        let changes = diff(&old, &new, 1);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Added {
+                    label: "1.2".to_string(),
+                    title: "Reset password".to_string(),
+                },
+                Change::Modified {
+                    label: "1.3".to_string(),
+                    title: "Sign in".to_string(),
+                    fields: vec![
+                        FieldChange {
+                            field: "Instructions",
+                            removed: vec![],
+                            added: vec!["with sso".to_string()],
+                        },
+                        FieldChange {
+                            field: "Status",
+                            removed: vec![],
+                            added: vec!["Pass".to_string()],
+                        },
+                    ],
+                },
+                Change::Removed {
+                    label: "1.3".to_string(),
+                    title: "Log out".to_string(),
+                },
+            ]
+        );
+        assert_eq!(describe(&changes), "1 added, 1 removed, 1 modified");
+    }
+
+    #[test]
+    fn identical_documents_have_no_changes() {
+        let items = vec![TestStep::section("Login"), step(2, "Open")];
+        assert!(diff(&items, &items, 1).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::err_msg::WithErrMsg;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+const PREFIX: &str = "uat_editor-";
+const SUFFIX: &str = ".md";
+// where every edit went before sessions had their own file
+const LEGACY_FILE: &str = "uat_editor.md";
+// without /proc to ask, a file this old is assumed to be from a crashed run
+const ORPHAN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+static NEXT_SESSION: AtomicUsize = AtomicUsize::new(0);
+
+// what the edited markdown goes back into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Step(usize),
+    NewStep,
+}
+
+impl Target {
+    pub fn describe(&self) -> String {
+        match self {
+            Target::Step(idx) => format!("row {}", idx + 1),
+            Target::NewStep => "a new step".to_string(),
+        }
+    }
+}
+
+fn hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+// one trip through the external editor. the file is named for this process and
+// session so two edits never share it, and it is removed when the session is dropped
+#[derive(Debug)]
+pub struct EditSession {
+    path: PathBuf,
+    target: Target,
+    original: u64,
+}
+
+impl EditSession {
+    pub fn create(dir: &Path, target: Target, content: &str) -> Result<EditSession, String> {
+        let session = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!(
+            "{}{}-{}{}",
+            PREFIX,
+            std::process::id(),
+            session,
+            SUFFIX
+        ));
+        std::fs::write(&path, content)
+            .with_err_msg(&format!("Failed to write {}", path.display()))?;
+        Ok(EditSession {
+            path,
+            target,
+            original: hash(content),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    // None when the file was left as it was written, so there is nothing to parse
+    pub fn read_back(&self) -> Result<Option<String>, String> {
+        let content = std::fs::read_to_string(&self.path).with_err_msg(&format!(
+            "Failed to read edits from {}",
+            self.path.display()
+        ))?;
+        Ok((hash(&content) != self.original).then_some(content))
+    }
+}
+
+impl Drop for EditSession {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// the pid an edit file was written by, None for files that aren't ours
+fn owner(name: &str) -> Option<u32> {
+    name.strip_prefix(PREFIX)?
+        .strip_suffix(SUFFIX)?
+        .split_once('-')?
+        .0
+        .parse()
+        .ok()
+}
+
+fn is_orphan(path: &Path, pid: u32) -> bool {
+    if pid == std::process::id() {
+        return false;
+    }
+    let proc = Path::new("/proc");
+    if proc.is_dir() {
+        return !proc.join(pid.to_string()).exists();
+    }
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > ORPHAN_AGE)
+}
+
+// edit files left behind by runs that crashed or were killed mid edit, returns how
+// many were removed
+pub fn clean_orphans(dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let orphan = match owner(&name) {
+            Some(pid) => is_orphan(&entry.path(), pid),
+            None => name == LEGACY_FILE,
+        };
+        if orphan && std::fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "uat_editor_sessions_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn untouched_files_read_back_as_none() {
+        let dir = dir("read_back");
+        let first = EditSession::create(&dir, Target::Step(3), "# Instructions\n").unwrap();
+        let second = EditSession::create(&dir, Target::NewStep, "# Instructions\n").unwrap();
+        assert_ne!(first.path(), second.path());
+        assert_eq!(first.read_back(), Ok(None));
+        std::fs::write(first.path(), "# Instructions\nOpen app\n").unwrap();
+        assert_eq!(
+            first.read_back(),
+            Ok(Some("# Instructions\nOpen app\n".to_string()))
+        );
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn only_files_from_dead_runs_are_cleaned() {
+        let dir = dir("orphans");
+        let live = EditSession::create(&dir, Target::NewStep, "").unwrap();
+        // pid 0 is never a user process
+        std::fs::write(dir.join("uat_editor-0-0.md"), "").unwrap();
+        std::fs::write(dir.join(LEGACY_FILE), "").unwrap();
+        std::fs::write(dir.join("notes.md"), "").unwrap();
+        if Path::new("/proc").is_dir() {
+            assert_eq!(clean_orphans(&dir), 2);
+            assert!(live.path().exists());
+            assert!(dir.join("notes.md").exists());
+        }
+        drop(live);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{crypto, err_msg::WithErrMsg, schema, test_step::TestStep};
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+pub const MDEMBEDDING: &str = "MDEMBEDDING";
+
+// bump this with a migration below whenever a change alters what existing fields mean,
+// along with the version in document.schema.json.
+// v1: the bare array of steps
+// v2: { version, items, meta } envelope
+pub const DOCUMENT_VERSION: u64 = 2;
+
+#[derive(Serialize, Deserialize, Default)]
+struct Meta {
+    #[serde(default)]
+    generator: String,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u64,
+    items: &'a [TestStep],
+    meta: Meta,
+}
+
+fn migrate_v1(items: Value) -> Value {
+    json!({ "version": 2, "items": items, "meta": {} })
+}
+
+pub fn document_json(items: &[TestStep]) -> Result<String, String> {
+    let envelope = Envelope {
+        version: DOCUMENT_VERSION,
+        items,
+        meta: Meta {
+            generator: "uat_editor".to_string(),
+        },
+    };
+    serde_json::to_string(&envelope).with_err_msg(&"Failed to serialize items")
+}
+
+// the on-disk form `:w` and `fmt` write: pretty printed, keys sorted (serde_json's map is
+// ordered) and a trailing newline, so the same document always gives the same bytes and a
+// changed step shows up as a change to its own block in a diff
+pub fn canonical_json(items: &[TestStep]) -> Result<String, String> {
+    let envelope = Envelope {
+        version: DOCUMENT_VERSION,
+        items,
+        meta: Meta {
+            generator: "uat_editor".to_string(),
+        },
+    };
+    let value = serde_json::to_value(&envelope).with_err_msg(&"Failed to serialize items")?;
+    let json = serde_json::to_string_pretty(&value).with_err_msg(&"Failed to serialize items")?;
+    Ok(json + "\n")
+}
+
+// encrypted when `encrypt` is on, see crypto::write
+pub fn save_file(path: &str, items: &[TestStep]) -> Result<(), String> {
+    crypto::write(path, &canonical_json(items)?)
+}
+
+// rewrites a json document in the canonical form, Ok(true) when that changed the file.
+// with `check` set nothing is written, for pre-commit hooks
+pub fn format_file(path: &str, check: bool) -> Result<bool, String> {
+    if crypto::is_encrypted_file(std::path::Path::new(path)) {
+        return Err(format!(
+            "{} is encrypted, fmt only rewrites plain json",
+            path
+        ));
+    }
+    let content =
+        std::fs::read_to_string(path).with_err_msg(&format!("Failed to read {}", path))?;
+    if serde_json::from_str::<Value>(&content).is_err() {
+        return Err(format!("{} is not a json document", path));
+    }
+    let canonical = canonical_json(&parse_document(&content)?)?;
+    if canonical == content {
+        return Ok(false);
+    }
+    if !check {
+        std::fs::write(path, canonical).with_err_msg(&format!("Failed to write {}", path))?;
+    }
+    Ok(true)
+}
+
+// accepts every document version this build knows about and migrates it forward
+pub fn parse_document(json: &str) -> Result<Vec<TestStep>, String> {
+    let document: Value =
+        serde_json::from_str(json).with_err_msg(&"Failed to convert json to items data")?;
+    document_items(migrate(document)?)
+}
+
+// parse_document for documents other tools wrote, checked against the schema first so
+// a mistake is reported by field name
+pub fn parse_checked(json: &str) -> Result<Vec<TestStep>, String> {
+    let document: Value =
+        serde_json::from_str(json).with_err_msg(&"Failed to convert json to items data")?;
+    let document = migrate(document)?;
+    schema::validate(&document)?;
+    document_items(document)
+}
+
+fn migrate(mut document: Value) -> Result<Value, String> {
+    loop {
+        let version = match &document {
+            Value::Array(_) => 1,
+            Value::Object(fields) => fields
+                .get("version")
+                .and_then(Value::as_u64)
+                .with_err_msg(&"Document is missing its version")?,
+            _ => return Err("Document is neither a step list nor an envelope".to_string()),
+        };
+        if version > DOCUMENT_VERSION {
+            return Err(format!(
+                "Document was created by a newer uat_editor (v{}); please upgrade",
+                version
+            ));
+        }
+        document = match version {
+            1 => migrate_v1(document),
+            _ => return Ok(document),
+        };
+    }
+}
+
+fn document_items(mut document: Value) -> Result<Vec<TestStep>, String> {
+    // item by item so a bad document (a hand written importer's output, say) is pointed
+    // at the first step that doesn't fit
+    let items = match document.get_mut("items").map(Value::take) {
+        Some(Value::Array(items)) => items,
+        _ => return Err("Document items are missing or not a list".to_string()),
+    };
+    let mut items = items
+        .into_iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            serde_json::from_value(item).map_err(|err| format!("Item {} is invalid: {}", idx, err))
+        })
+        .collect::<Result<Vec<TestStep>, String>>()?;
+    TestStep::assign_ids(&mut items);
+    Ok(items)
+}
+
+pub fn serialize_items(items: &[TestStep]) -> Result<String, String> {
+    Ok(BASE64_STANDARD.encode(document_json(items)?))
+}
+
+pub fn deserialize_items(serialized_items: &str) -> Result<Vec<TestStep>, String> {
+    let err_msg = format!(
+        "Failed to deserialize base64 items, found: {}",
+        serialized_items
+    );
+
+    let items_json = BASE64_STANDARD
+        .decode(serialized_items)
+        .with_err_msg(&err_msg)?;
+
+    let items_json =
+        String::from_utf8(items_json).with_err_msg(&"Failed to convert byte string to String")?;
+
+    parse_document(&items_json)
+}
+
+// pulls the base64 payload out of compiled html (or anything else containing the marker),
+// the payload ends at the first non base64 character so quotes and cdata both work.
+// the embedding is always written after the table, so the last marker wins over any
+// copy of the marker text inside a step
+pub fn extract(context: &str) -> Result<Vec<TestStep>, String> {
+    let marker = format!("{}:", MDEMBEDDING);
+    let idx = context
+        .rfind(&marker)
+        .with_err_msg(&"Could not find MDEMBEDDING marker")?;
+
+    let context = context.split_at(idx + marker.len()).1;
+
+    let idx = context
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=')))
+        .unwrap_or(context.len());
+
+    deserialize_items(context.split_at(idx).0)
+}
+
+// documents on disk are either json (any version) or html carrying an embedding
+pub fn parse_file_content(content: &str) -> Result<Vec<TestStep>, String> {
+    if serde_json::from_str::<Value>(content).is_ok() {
+        parse_document(content)
+    } else {
+        extract(content)
+    }
+}
+
+pub fn load_file(path: &str) -> Result<Vec<TestStep>, String> {
+    parse_file_content(&crypto::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // payloads as each version of the tool wrote them
+    const V1: &str = r#"[{"is_stepless_comment":false,"is_new_section":false,"instructions":"Open app","expected_results":"Opens","ac":"AC1"}]"#;
+    const V2: &str = r#"{"version":2,"items":[{"id":7,"is_stepless_comment":false,"is_new_section":false,"instructions":"Open app","test_data":"","expected_results":"Opens","ac":"AC1","status":"pass","comment":""}],"meta":{"generator":"uat_editor"}}"#;
+
+    #[test]
+    fn loads_v1_bare_array() {
+        let items = deserialize_items(&BASE64_STANDARD.encode(V1)).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].instructions, "Open app");
+        assert_eq!(items[0].id, 1);
+    }
+
+    #[test]
+    fn loads_v2_envelope() {
+        let items = deserialize_items(&BASE64_STANDARD.encode(V2)).unwrap();
+        assert_eq!(items[0].id, 7);
+        assert_eq!(items[0].ac, "AC1");
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let future = r#"{"version":99,"items":[],"meta":{}}"#;
+        let err = parse_document(future).unwrap_err();
+        assert!(err.contains("newer uat_editor (v99)"), "{}", err);
+    }
+
+    #[test]
+    fn invalid_items_are_reported_by_index() {
+        let bad = r#"{"version":2,"items":[
+            {"is_stepless_comment":false,"is_new_section":false,"instructions":"ok","expected_results":"","ac":""},
+            {"is_stepless_comment":false,"is_new_section":false,"instructions":"ok"}
+        ]}"#;
+        let err = parse_document(bad).unwrap_err();
+        assert!(
+            err.starts_with("Item 1 is invalid: missing field `expected_results`"),
+            "{}",
+            err
+        );
+        let err = parse_document(r#"{"version":2,"items":{}}"#).unwrap_err();
+        assert_eq!(err, "Document items are missing or not a list");
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys_one_field_per_line() {
+        let json = canonical_json(&[TestStep::new().with_instructions("Open app")]).unwrap();
+        assert!(json.starts_with("{\n  \"items\": [\n    {\n      \"ac\": \"\",\n"));
+        assert!(json.ends_with("  \"version\": 2\n}\n"), "{}", json);
+        let keys: Vec<&str> = json
+            .lines()
+            .filter(|line| line.starts_with("      \""))
+            .map(|line| line.trim().split('"').nth(1).unwrap())
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    fn writes_current_version() {
+        let json = document_json(&[TestStep::new()]).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], DOCUMENT_VERSION);
+    }
+}
@@ -0,0 +1,287 @@
+pub mod ado;
+pub mod confluence;
+pub mod external;
+pub mod html;
+pub mod junit;
+pub mod pdf;
+pub mod text;
+
+use self::{
+    ado::AdoOptions,
+    html::{HtmlOptions, PrintOptions},
+};
+use crate::{
+    config::{ColumnConfig, PdfConfig, PlainTextConfig},
+    err_msg::WithErrMsg,
+    images::DEFAULT_MAX_IMAGE_BYTES,
+    locale::{Key, Locale},
+    test_step::TestStep,
+};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+pub const FORMAT_NAMES: &str = "junit|ado|confluence|text|print|pdf";
+
+pub enum Format {
+    Junit,
+    Ado(AdoOptions),
+    Confluence,
+    Text(PlainTextConfig),
+    Print(PrintOptions),
+    Pdf(PdfConfig, PrintOptions),
+}
+
+impl Format {
+    pub fn parse(name: &str, ado: AdoOptions, text: PlainTextConfig) -> Result<Format, String> {
+        match name {
+            "junit" => Ok(Format::Junit),
+            "ado" => Ok(Format::Ado(ado)),
+            "confluence" => Ok(Format::Confluence),
+            "text" => Ok(Format::Text(text)),
+            "print" => Ok(Format::Print(PrintOptions::default())),
+            "pdf" => Ok(Format::Pdf(PdfConfig::default(), PrintOptions::default())),
+            _ => Err(format!(
+                "Unknown export format: {} (expected {})",
+                name, FORMAT_NAMES
+            )),
+        }
+    }
+
+    // as given to --format
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Junit => "junit",
+            Format::Ado(_) => "ado",
+            Format::Confluence => "confluence",
+            Format::Text(_) => "text",
+            Format::Print(_) => "print",
+            Format::Pdf(..) => "pdf",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Format::Junit => "JUnit",
+            Format::Ado(_) => "Azure DevOps CSV",
+            Format::Confluence => "Confluence storage format",
+            Format::Text(_) => "Plain text",
+            Format::Print(_) => "Printable HTML",
+            Format::Pdf(..) => "PDF",
+        }
+    }
+
+    pub fn default_path(&self) -> &'static str {
+        match self {
+            Format::Junit => "uat_results.xml",
+            Format::Ado(_) => "uat_cases.csv",
+            Format::Confluence => "uat_confluence.xml",
+            Format::Text(_) => "uat.txt",
+            Format::Print(_) => "uat_print.html",
+            Format::Pdf(..) => "uat.pdf",
+        }
+    }
+
+    pub fn render(&self, items: &[TestStep], options: &HtmlOptions) -> Result<String, String> {
+        let first_section = options.numbering_start;
+        match self {
+            Format::Junit => Ok(junit::export(items, first_section)),
+            Format::Ado(ado) => Ok(ado::export(items, *ado, first_section)),
+            Format::Confluence => confluence::export(
+                items,
+                options.columns,
+                options.use_ac,
+                options.locale,
+                first_section,
+            ),
+            Format::Text(text) => Ok(text::export(
+                items,
+                *text,
+                options.use_ac,
+                options.locale,
+                first_section,
+            )),
+            Format::Print(print) => print_html(items, options, print),
+            Format::Pdf(..) => {
+                Err("A PDF can only be written to a file, pass -o <path>".to_string())
+            }
+        }
+    }
+
+    // pdfs are written by their converter, everything else is rendered then saved
+    pub fn write(
+        &self,
+        items: &[TestStep],
+        options: &HtmlOptions,
+        path: &str,
+    ) -> Result<(), String> {
+        match self {
+            Format::Pdf(pdf, print) => {
+                let backend = pdf::backend(pdf)?;
+                let html = print_html(items, options, print)?;
+                backend.write_pdf(&html, std::path::Path::new(path))
+            }
+            _ => std::fs::write(path, self.render(items, options)?)
+                .with_err_msg(&format!("Failed to write {}", path)),
+        }
+    }
+}
+
+// ids are for the editor and images are always inlined in full, whatever the options say
+fn print_html(
+    items: &[TestStep],
+    options: &HtmlOptions,
+    print: &PrintOptions,
+) -> Result<String, String> {
+    let options = HtmlOptions {
+        show_ids: false,
+        max_image_bytes: DEFAULT_MAX_IMAGE_BYTES,
+        ..*options
+    };
+    html::gen_print_html(items, &options, print)
+}
+
+pub enum HtmlColumn {
+    Step,
+    Pass,
+    Priority,
+    Action,
+    TestData,
+    ExpectedResults,
+    Comments,
+    Estimate,
+    Author,
+    Ac,
+}
+
+impl HtmlColumn {
+    pub fn class(&self) -> &'static str {
+        match self {
+            HtmlColumn::Step => "step-td",
+            HtmlColumn::Pass => "pass-td",
+            HtmlColumn::Priority => "priority-td",
+            HtmlColumn::Action => "action-td",
+            HtmlColumn::TestData => "test-data-td",
+            HtmlColumn::ExpectedResults => "expected-result-td",
+            HtmlColumn::Comments => "comments-td",
+            HtmlColumn::Estimate => "estimate-td",
+            HtmlColumn::Author => "author-td",
+            HtmlColumn::Ac => "ac-td",
+        }
+    }
+
+    pub fn title(&self, locale: Locale) -> &'static str {
+        locale.text(match self {
+            HtmlColumn::Step => Key::ColumnStep,
+            HtmlColumn::Pass => Key::ColumnPass,
+            HtmlColumn::Priority => Key::Priority,
+            HtmlColumn::Action => Key::ColumnAction,
+            HtmlColumn::TestData => Key::TestData,
+            HtmlColumn::ExpectedResults => Key::ExpectedResults,
+            HtmlColumn::Comments => Key::ColumnComments,
+            HtmlColumn::Estimate => Key::ColumnEstimate,
+            HtmlColumn::Author => Key::ColumnAuthor,
+            HtmlColumn::Ac => Key::ColumnAc,
+        })
+    }
+
+    // the compiled table layout, optional columns come from the config
+    pub fn columns(config: &ColumnConfig, use_ac: bool) -> Vec<HtmlColumn> {
+        let mut columns = vec![HtmlColumn::Step, HtmlColumn::Pass];
+        if config.priority {
+            columns.push(HtmlColumn::Priority);
+        }
+        columns.push(HtmlColumn::Action);
+        if config.test_data {
+            columns.push(HtmlColumn::TestData);
+        }
+        columns.push(HtmlColumn::ExpectedResults);
+        columns.push(HtmlColumn::Comments);
+        if config.estimate {
+            columns.push(HtmlColumn::Estimate);
+        }
+        if config.author {
+            columns.push(HtmlColumn::Author);
+        }
+        if use_ac {
+            columns.push(HtmlColumn::Ac);
+        }
+        columns
+    }
+}
+
+pub struct Section<'a> {
+    pub name: String,
+    pub cases: Vec<(String, &'a TestStep)>,
+}
+
+// groups steps under the section that precedes them, comment rows are dropped
+pub fn sections(items: &[TestStep], first_section: usize) -> Vec<Section<'_>> {
+    let labels = TestStep::step_labels(items, first_section);
+    let mut sections: Vec<Section> = Vec::new();
+    for (item, label) in items.iter().zip(labels) {
+        if item.is_new_section {
+            let title = first_line(&item.instructions);
+            let name = if title.is_empty() {
+                format!("Section {}", sections.len() + 1)
+            } else {
+                title
+            };
+            sections.push(Section {
+                name,
+                cases: Vec::new(),
+            });
+        } else if item.is_step() {
+            if sections.is_empty() {
+                sections.push(Section {
+                    name: "UAT".to_string(),
+                    cases: Vec::new(),
+                });
+            }
+            if let Some(section) = sections.last_mut() {
+                section.cases.push((label, item));
+            }
+        }
+    }
+    sections
+}
+
+pub fn escape_xml(val: &str) -> String {
+    val.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// the first non-empty line of a field, used wherever a step needs a short name
+pub fn first_line(val: &str) -> String {
+    val.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("")
+        .to_string()
+}
+
+// markdown flattened to the text a reader sees, list items keep a dash
+pub fn strip_markdown(markdown: &str) -> String {
+    let mut out = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(text) | Event::Code(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                out.push_str(&text)
+            }
+            Event::SoftBreak | Event::HardBreak => out.push('\n'),
+            Event::Start(Tag::Item) => out.push_str("- "),
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item) => out.push('\n'),
+            _ => {}
+        }
+    }
+    out.trim_end().to_string()
+}
+
+pub fn escape_csv(val: &str) -> String {
+    if val.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", val.replace('"', "\"\""))
+    } else {
+        val.to_string()
+    }
+}
@@ -0,0 +1,209 @@
+use super::{escape_csv, escape_xml, first_line, sections, strip_markdown};
+use crate::test_step::TestStep;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+const HEADER: [&str; 6] = [
+    "ID",
+    "Work Item Type",
+    "Title",
+    "Test Step",
+    "Step Action",
+    "Step Expected",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Grouping {
+    #[default]
+    Section,
+    Step,
+}
+
+impl Grouping {
+    pub fn parse(val: &str) -> Result<Grouping, String> {
+        match val {
+            "section" => Ok(Grouping::Section),
+            "step" => Ok(Grouping::Step),
+            _ => Err(format!("Unknown grouping: {} (expected section|step)", val)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MarkdownMode {
+    // the subset of html ado renders inside step fields
+    #[default]
+    Html,
+    Strip,
+}
+
+impl MarkdownMode {
+    pub fn parse(val: &str) -> Result<MarkdownMode, String> {
+        match val {
+            "html" => Ok(MarkdownMode::Html),
+            "strip" => Ok(MarkdownMode::Strip),
+            _ => Err(format!(
+                "Unknown markdown mode: {} (expected html|strip)",
+                val
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdoOptions {
+    pub grouping: Grouping,
+    pub markdown: MarkdownMode,
+}
+
+fn to_ado_html(markdown: &str) -> String {
+    let mut out = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Paragraph) => out.push_str("<P>"),
+            Event::End(TagEnd::Paragraph) => out.push_str("</P>"),
+            Event::Start(Tag::Heading { .. }) => out.push_str("<P><B>"),
+            Event::End(TagEnd::Heading(_)) => out.push_str("</B></P>"),
+            Event::Start(Tag::Strong) => out.push_str("<B>"),
+            Event::End(TagEnd::Strong) => out.push_str("</B>"),
+            Event::Start(Tag::Emphasis) => out.push_str("<I>"),
+            Event::End(TagEnd::Emphasis) => out.push_str("</I>"),
+            Event::Start(Tag::List(None)) => out.push_str("<UL>"),
+            Event::End(TagEnd::List(false)) => out.push_str("</UL>"),
+            Event::Start(Tag::List(Some(_))) => out.push_str("<OL>"),
+            Event::End(TagEnd::List(true)) => out.push_str("</OL>"),
+            Event::Start(Tag::Item) => out.push_str("<LI>"),
+            Event::End(TagEnd::Item) => out.push_str("</LI>"),
+            Event::Start(Tag::CodeBlock(_)) => out.push_str("<PRE>"),
+            Event::End(TagEnd::CodeBlock) => out.push_str("</PRE>"),
+            Event::Code(text) => out.push_str(&format!("<CODE>{}</CODE>", escape_xml(&text))),
+            Event::SoftBreak | Event::HardBreak => out.push_str("<BR/>"),
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                out.push_str(&escape_xml(&text))
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn field(markdown: &str, mode: MarkdownMode) -> String {
+    match mode {
+        MarkdownMode::Html => to_ado_html(markdown),
+        MarkdownMode::Strip => strip_markdown(markdown),
+    }
+}
+
+fn action(item: &TestStep, mode: MarkdownMode) -> String {
+    let mut action = field(&item.instructions, mode);
+    if item.has_test_data() {
+        let test_data = field(&item.test_data, mode);
+        match mode {
+            MarkdownMode::Html => action.push_str(&format!("<P><B>Test Data</B></P>{}", test_data)),
+            MarkdownMode::Strip => action.push_str(&format!("\nTest Data:\n{}", test_data)),
+        }
+    }
+    action
+}
+
+fn row(cols: [&str; 6]) -> String {
+    let cols: Vec<String> = cols.iter().map(|c| escape_csv(c)).collect();
+    format!("{}\r\n", cols.join(","))
+}
+
+// ado's bulk import wants the case on its own row followed by one row per step
+// with the title left blank, so a blank title means "same test case"
+pub fn export(items: &[TestStep], options: AdoOptions, first_section: usize) -> String {
+    let mut csv = row(HEADER);
+    for section in sections(items, first_section) {
+        match options.grouping {
+            Grouping::Section => {
+                csv.push_str(&row(["", "Test Case", &section.name, "", "", ""]));
+                for (idx, (_, item)) in section.cases.iter().enumerate() {
+                    csv.push_str(&row([
+                        "",
+                        "",
+                        "",
+                        &(idx + 1).to_string(),
+                        &action(item, options.markdown),
+                        &field(&item.expected_results, options.markdown),
+                    ]));
+                }
+            }
+            Grouping::Step => {
+                for (label, item) in &section.cases {
+                    let title = format!(
+                        "{} - {} {}",
+                        section.name,
+                        label,
+                        first_line(&strip_markdown(&item.instructions))
+                    );
+                    csv.push_str(&row(["", "Test Case", title.trim_end(), "", "", ""]));
+                    csv.push_str(&row([
+                        "",
+                        "",
+                        "",
+                        "1",
+                        &action(item, options.markdown),
+                        &field(&item.expected_results, options.markdown),
+                    ]));
+                }
+            }
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Vec<TestStep> {
+        let section = TestStep::section("Login");
+        let mut first = TestStep::new();
+        first.instructions = "Open **login**, page".to_string();
+        first.expected_results = "Form \"shown\"".to_string();
+        let mut second = TestStep::new();
+        second.instructions = "- enter user\n- enter pass".to_string();
+        second.test_data = "admin".to_string();
+        second.expected_results = "Signed in".to_string();
+        vec![section, first, second]
+    }
+
+    #[test]
+    fn one_case_per_section() {
+        let csv = export(&fixture(), AdoOptions::default(), 1);
+        let expected = concat!(
+            "ID,Work Item Type,Title,Test Step,Step Action,Step Expected\r\n",
+            ",Test Case,Login,,,\r\n",
+            ",,,1,\"<P>Open <B>login</B>, page</P>\",<P>Form &quot;shown&quot;</P>\r\n",
+            ",,,2,<UL><LI>enter user</LI><LI>enter pass</LI></UL><P><B>Test Data</B></P><P>admin</P>,<P>Signed in</P>\r\n",
+        );
+        assert_eq!(csv, expected);
+    }
+
+    #[test]
+    fn code_keeps_its_lines() {
+        let html = to_ado_html("Run `make`:\n\n```sh\nmake clean\n  make <all>\n```");
+        assert_eq!(
+            html,
+            "<P>Run <CODE>make</CODE>:</P><PRE>make clean\n  make &lt;all&gt;\n</PRE>"
+        );
+    }
+
+    #[test]
+    fn one_case_per_step_stripped() {
+        let options = AdoOptions {
+            grouping: Grouping::Step,
+            markdown: MarkdownMode::Strip,
+        };
+        let csv = export(&fixture(), options, 1);
+        let expected = concat!(
+            "ID,Work Item Type,Title,Test Step,Step Action,Step Expected\r\n",
+            ",Test Case,\"Login - 1.2 Open login, page\",,,\r\n",
+            ",,,1,\"Open login, page\",\"Form \"\"shown\"\"\"\r\n",
+            ",Test Case,Login - 1.3 - enter user,,,\r\n",
+            ",,,1,\"- enter user\n- enter pass\nTest Data:\nadmin\",Signed in\r\n",
+        );
+        assert_eq!(csv, expected);
+    }
+}
@@ -0,0 +1,184 @@
+use super::{HtmlColumn, escape_xml};
+use crate::{config::ColumnConfig, embedding, locale::Locale, test_step::TestStep};
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+// the embedding lives in a code macro (inside an expand so it stays out of the way)
+// because confluence keeps macro bodies verbatim through edits, unlike attributes
+pub const EMBEDDING_MACRO_TITLE: &str = "uat_editor data";
+
+fn cdata(val: &str) -> String {
+    format!("<![CDATA[{}]]>", val.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+fn code_macro(language: &str, body: &str) -> String {
+    let language = if language.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<ac:parameter ac:name=\"language\">{}</ac:parameter>",
+            escape_xml(language)
+        )
+    };
+    format!(
+        "<ac:structured-macro ac:name=\"code\">{}<ac:plain-text-body>{}</ac:plain-text-body></ac:structured-macro>",
+        language,
+        cdata(body)
+    )
+}
+
+// markdown to storage format: plain tags pass through as xhtml, code blocks and
+// images become their macro equivalents and raw html is kept as text
+pub fn to_storage(markdown: &str) -> String {
+    let mut events = Vec::new();
+    let mut code: Option<(String, String)> = None;
+    let mut in_image = false;
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code = Some((language, String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, body)) = code.take() {
+                    events.push(Event::Html(code_macro(&language, &body).into()));
+                }
+            }
+            Event::Text(text) if code.is_some() => {
+                if let Some((_, body)) = code.as_mut() {
+                    body.push_str(&text);
+                }
+            }
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                in_image = true;
+                events.push(Event::Html(
+                    format!(
+                        "<ac:image><ri:url ri:value=\"{}\"/></ac:image>",
+                        escape_xml(&dest_url)
+                    )
+                    .into(),
+                ));
+            }
+            Event::End(TagEnd::Image) => in_image = false,
+            _ if in_image => {}
+            Event::Html(html) | Event::InlineHtml(html) => events.push(Event::Text(html)),
+            event => events.push(event),
+        }
+    }
+    let mut out = String::new();
+    pulldown_cmark::html::push_html(&mut out, events.into_iter());
+    out
+}
+
+fn links_markdown(links: &[String]) -> String {
+    let links: Vec<String> = links
+        .iter()
+        .enumerate()
+        .map(|(idx, link)| {
+            let (title, url) = TestStep::link_parts(link);
+            let title = title
+                .map(str::to_string)
+                .unwrap_or(format!("[{}]", idx + 1));
+            format!("[{}](<{}>)", title.replace(['[', ']'], ""), url)
+        })
+        .collect();
+    if links.is_empty() {
+        String::new()
+    } else {
+        format!("\n\n{}", links.join(" "))
+    }
+}
+
+fn cell(item: &TestStep, label: &str, column: &HtmlColumn) -> String {
+    match column {
+        HtmlColumn::Step if item.is_new_section => format!("<strong>{}</strong>", label),
+        HtmlColumn::Step => label.to_string(),
+        HtmlColumn::Pass => item.status.map(|s| s.label()).unwrap_or("").to_string(),
+        HtmlColumn::Priority => item.priority.map(|p| p.label()).unwrap_or("").to_string(),
+        HtmlColumn::Action => {
+            let mut action = item.instructions();
+            action.push_str(&links_markdown(&item.links));
+            to_storage(&action)
+        }
+        HtmlColumn::TestData => to_storage(&item.test_data()),
+        HtmlColumn::ExpectedResults => to_storage(&item.expected_results()),
+        HtmlColumn::Comments => escape_xml(item.comment.trim()),
+        HtmlColumn::Estimate => item
+            .estimate_minutes
+            .filter(|_| item.is_step())
+            .map(TestStep::format_minutes)
+            .unwrap_or_default(),
+        HtmlColumn::Author => escape_xml(item.author.as_deref().unwrap_or("")),
+        HtmlColumn::Ac => to_storage(&item.ac()),
+    }
+}
+
+pub fn export(
+    items: &[TestStep],
+    columns: &ColumnConfig,
+    use_ac: bool,
+    locale: Locale,
+    first_section: usize,
+) -> Result<String, String> {
+    let columns = HtmlColumn::columns(columns, use_ac);
+    let labels = TestStep::step_labels(items, first_section);
+
+    let mut out = String::from("<table><colgroup>");
+    out.push_str(&"<col/>".repeat(columns.len()));
+    out.push_str("</colgroup><tbody><tr>");
+    for column in &columns {
+        out.push_str(&format!("<th><p>{}</p></th>", column.title(locale)));
+    }
+    out.push_str("</tr>");
+    for (item, label) in items.iter().zip(labels) {
+        out.push_str("<tr>");
+        for column in &columns {
+            out.push_str(&format!("<td>{}</td>", cell(item, &label, column)));
+        }
+        out.push_str("</tr>");
+    }
+    out.push_str("</tbody></table>");
+
+    out.push_str(&format!(
+        "<ac:structured-macro ac:name=\"expand\"><ac:parameter ac:name=\"title\">{}</ac:parameter><ac:rich-text-body><p><em>Loaded back by uat_editor, keep this code block intact.</em></p>{}</ac:rich-text-body></ac:structured-macro>",
+        EMBEDDING_MACRO_TITLE,
+        code_macro(
+            "",
+            &format!(
+                "{}:{}",
+                embedding::MDEMBEDDING,
+                embedding::serialize_items(items)?
+            )
+        )
+    ));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedding_survives_in_code_macro() {
+        let mut step = TestStep::new();
+        step.instructions = "Run\n\n```sh\necho ]]> <b>\n```".to_string();
+        let items = vec![step];
+        let out = export(&items, &ColumnConfig::default(), true, Locale::En, 1).unwrap();
+        assert!(out.contains("<ac:parameter ac:name=\"language\">sh</ac:parameter>"));
+        assert!(out.contains("<![CDATA[echo ]]]]><![CDATA[> <b>\n]]>"));
+
+        let loaded = embedding::extract(&out).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].instructions, items[0].instructions);
+    }
+
+    #[test]
+    fn headers_follow_the_locale() {
+        let items = vec![TestStep::new().with_instructions("Ouvrir")];
+        let out = export(&items, &ColumnConfig::default(), true, Locale::Fr, 1).unwrap();
+        assert!(out.contains("<th><p>Résultats attendus</p></th><th><p>Commentaires</p></th>"));
+        assert!(!out.contains("Expected Results"));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    config::ExporterConfig,
+    embedding,
+    shell::{self, ShellOutput},
+    test_step::TestStep,
+};
+use std::time::Duration;
+
+// feeds the document json (the same envelope the embedding holds) to the exporter
+pub fn run(exporter: &ExporterConfig, items: &[TestStep]) -> Result<ShellOutput, String> {
+    let document = embedding::document_json(items)?;
+    shell::run_timeout(
+        &exporter.command,
+        &document,
+        Duration::from_secs(exporter.timeout_secs),
+    )
+}
+
+// the exported text, or the reason the exporter gave for failing
+pub fn check(name: &str, output: &ShellOutput) -> Result<String, String> {
+    if output.status.success() {
+        return Ok(output.stdout.clone());
+    }
+    let line = output.first_stderr_line();
+    Err(
+        format!("Exporter {} failed ({}) {}", name, output.status, line)
+            .trim_end()
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exporter(command: &str, timeout_secs: u64) -> ExporterConfig {
+        ExporterConfig {
+            command: command.to_string(),
+            clipboard: false,
+            timeout_secs,
+        }
+    }
+
+    #[test]
+    fn document_goes_in_and_stdout_comes_back() {
+        let items = vec![TestStep::new().with_instructions("Open app")];
+        let output = run(&exporter("cat", 10), &items).unwrap();
+        let exported = check("stub", &output).unwrap();
+        let loaded = embedding::parse_document(&exported).unwrap();
+        assert_eq!(loaded[0].instructions, "Open app");
+        assert!(exported.starts_with("{\"version\":2,"));
+    }
+
+    #[test]
+    fn failures_report_the_first_stderr_line() {
+        let output = run(&exporter("echo; echo bad input >&2; exit 2", 10), &[]).unwrap();
+        assert_eq!(
+            check("stub", &output).unwrap_err(),
+            "Exporter stub failed (exit status: 2) bad input"
+        );
+        let err = run(&exporter("sleep 10", 1), &[]).err().unwrap();
+        assert_eq!(err, "Command timed out after 1s: sleep 10");
+    }
+}
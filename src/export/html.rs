@@ -0,0 +1,527 @@
+use super::HtmlColumn;
+use crate::{
+    config::{ColumnConfig, Config, Layout, MarkdownExtension},
+    embedding::{self, MDEMBEDDING},
+    images,
+    locale::Locale,
+    test_step::TestStep,
+    timestamp,
+};
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use std::collections::BTreeSet;
+
+enum RowType {
+    SectionStart,
+    SectionMid,
+    SectionEnd,
+    Comment,
+}
+
+enum ColType {
+    First,
+    Middle,
+    Last,
+}
+
+pub fn escape_html(val: &str) -> String {
+    val.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub struct HtmlOptions<'a> {
+    pub columns: &'a ColumnConfig,
+    pub use_ac: bool,
+    pub show_ids: bool,
+    pub max_image_bytes: u64,
+    pub locale: Locale,
+    pub markdown_extensions: &'a [MarkdownExtension],
+    // the number of the first section, see Config::numbering_start
+    pub numbering_start: usize,
+}
+
+impl<'a> HtmlOptions<'a> {
+    pub fn from_config(config: &'a Config) -> HtmlOptions<'a> {
+        HtmlOptions {
+            columns: &config.columns,
+            use_ac: config.use_ac,
+            show_ids: config.show_ids,
+            max_image_bytes: config.max_image_bytes,
+            locale: config.locale,
+            markdown_extensions: &config.markdown_extensions,
+            numbering_start: config.numbering_start,
+        }
+    }
+
+    // outside the tui, where there are no ids to show and no config limit on images
+    pub fn from_layout(layout: &'a Layout) -> HtmlOptions<'a> {
+        HtmlOptions {
+            columns: &layout.columns,
+            use_ac: layout.use_ac,
+            show_ids: false,
+            max_image_bytes: images::DEFAULT_MAX_IMAGE_BYTES,
+            locale: layout.locale,
+            markdown_extensions: &layout.markdown_extensions,
+            numbering_start: layout.numbering_start,
+        }
+    }
+}
+
+// the standalone document `export --format print` writes
+#[derive(Debug, Clone, Default)]
+pub struct PrintOptions {
+    // where the steps came from, shown on the cover when known
+    pub source: Option<String>,
+    // keeps the embedding so the printout's html can still be loaded back
+    pub embed: bool,
+}
+
+fn col_border(col_type: &ColType) -> &'static str {
+    match *col_type {
+        ColType::First => "border-left: 2px solid black;",
+        ColType::Middle => "",
+        ColType::Last => "border-right: 2px solid black;",
+    }
+}
+
+fn row_border(row_type: &RowType) -> &'static str {
+    match *row_type {
+        RowType::SectionStart => "border-top: 2px solid black;",
+        RowType::SectionMid => "",
+        RowType::SectionEnd => "border-bottom: 2px solid black;",
+        RowType::Comment => "border-top: 2px solid black; border-bottom: 2px solid black;",
+    }
+}
+
+fn open_td(out: &mut String, class: &str, row_type: &RowType, col_type: &ColType) {
+    for part in [
+        "<td class=\"",
+        class,
+        "\" style=\"border: 1px solid black;",
+        col_border(col_type),
+        row_border(row_type),
+        "\">",
+    ] {
+        out.push_str(part);
+    }
+}
+
+fn build_td(out: &mut String, class: &str, val: &str, row_type: &RowType, col_type: &ColType) {
+    open_td(out, class, row_type, col_type);
+    out.push_str(val);
+    out.push_str("</td>");
+}
+
+fn md_options(extensions: &[MarkdownExtension]) -> Options {
+    extensions
+        .iter()
+        .fold(Options::empty(), |options, extension| {
+            options
+                | match extension {
+                    MarkdownExtension::Tables => Options::ENABLE_TABLES,
+                    MarkdownExtension::Tasklists => Options::ENABLE_TASKLISTS,
+                    MarkdownExtension::Strikethrough => Options::ENABLE_STRIKETHROUGH,
+                }
+        })
+}
+
+// code blocks get a class of their own so the stylesheet can set them in monospace
+// without the cell's font rule winning
+fn code_block(event: Event) -> Event {
+    match event {
+        Event::Start(Tag::CodeBlock(kind)) => {
+            let language = match kind {
+                CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(escape_html),
+                CodeBlockKind::Indented => None,
+            };
+            let open = match language {
+                Some(language) => format!(
+                    "<pre class=\"code-block\"><code class=\"language-{}\">",
+                    language
+                ),
+                None => "<pre class=\"code-block\"><code>".to_string(),
+            };
+            Event::Html(open.into())
+        }
+        Event::End(TagEnd::CodeBlock) => Event::Html("</code></pre>\n".into()),
+        event => event,
+    }
+}
+
+fn parse_td(
+    out: &mut String,
+    options: Options,
+    max_image_bytes: u64,
+    class: &str,
+    s: &str,
+    row_type: &RowType,
+    col_type: &ColType,
+) {
+    let parser = Parser::new_ext(s, options).map(code_block);
+    let events = images::inline_images(parser, None, max_image_bytes);
+    open_td(out, class, row_type, col_type);
+    pulldown_cmark::html::push_html(out, events.into_iter());
+    out.push_str("</td>");
+}
+
+pub fn last_was_comment(items: &[TestStep], current_idx: usize) -> bool {
+    if current_idx == 0 {
+        false
+    } else {
+        items
+            .get(current_idx - 1)
+            .map(|i| i.is_stepless_comment)
+            .unwrap_or(false)
+    }
+}
+
+pub fn is_end_of_section(items: &[TestStep], current_idx: usize) -> bool {
+    items
+        .get(current_idx + 1)
+        .map(|i| i.is_new_section)
+        .unwrap_or(true)
+}
+
+// links ride along in the action cell as a trailing paragraph of anchors
+fn links_markdown(links: &[String]) -> String {
+    if links.is_empty() {
+        return String::new();
+    }
+    let anchors: Vec<String> = links
+        .iter()
+        .enumerate()
+        .map(|(idx, link)| {
+            let (title, url) = TestStep::link_parts(link);
+            let title = title
+                .map(str::to_string)
+                .unwrap_or(format!("[{}]", idx + 1));
+            format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(url),
+                escape_html(&title)
+            )
+        })
+        .collect();
+    format!("\n\n{}", anchors.join(" "))
+}
+
+fn col_type(idx: usize, len: usize) -> ColType {
+    if idx == 0 {
+        ColType::First
+    } else if idx + 1 == len {
+        ColType::Last
+    } else {
+        ColType::Middle
+    }
+}
+
+fn build_th(out: &mut String, column: &HtmlColumn, col_type: &ColType, locale: Locale) {
+    let added_borders = match *col_type {
+        ColType::First => " border-left: 2px solid black;",
+        ColType::Middle => "",
+        ColType::Last => " border-right: 2px solid black;",
+    };
+    for part in [
+        "<td class=\"",
+        column.class(),
+        "\" style=\"border: 1px solid #000000; border-top: 2px solid black; border-bottom: 2px solid black;",
+        added_borders,
+        "\">\n<p><strong><span>",
+        column.title(locale),
+        "</span></strong></p>\n</td>\n",
+    ] {
+        out.push_str(part);
+    }
+}
+
+// the header cells, shared by the compiled and print documents
+fn write_header(out: &mut String, options: &HtmlOptions) {
+    let columns = HtmlColumn::columns(options.columns, options.use_ac);
+    for (idx, column) in columns.iter().enumerate() {
+        build_th(out, column, &col_type(idx, columns.len()), options.locale);
+    }
+}
+
+// enough room that the document is written without the string growing: markdown comes
+// out about half again as long as html and every cell carries its inline style
+fn capacity(items: &[TestStep], columns: usize) -> usize {
+    const CELL_MARKUP: usize = 96;
+    items
+        .iter()
+        .map(|item| {
+            let text = item.instructions.len()
+                + item.test_data.len()
+                + item.expected_results.len()
+                + item.ac.len()
+                + item.comment.len()
+                + item.links.iter().map(String::len).sum::<usize>();
+            text * 2 + columns * CELL_MARKUP
+        })
+        .sum()
+}
+
+// the body rows, shared by the compiled and print documents
+fn write_rows(out: &mut String, items: &[TestStep], options: &HtmlOptions) {
+    let columns = HtmlColumn::columns(options.columns, options.use_ac);
+    let md_options = md_options(options.markdown_extensions);
+    let max_image_bytes = options.max_image_bytes;
+    let labels = TestStep::step_labels(items, options.numbering_start);
+    for (idx, item) in items.iter().enumerate() {
+        let row_type = if item.is_new_section {
+            RowType::SectionStart
+        } else if item.is_stepless_comment {
+            RowType::Comment
+        } else if idx == 0 {
+            RowType::SectionStart
+        } else if idx == 1 {
+            if last_was_comment(items, idx) {
+                RowType::SectionStart
+            } else {
+                RowType::SectionMid
+            }
+        } else {
+            if is_end_of_section(items, idx) {
+                RowType::SectionEnd
+            } else {
+                RowType::SectionMid
+            }
+        };
+        let i = if options.show_ids {
+            format!("{}<br><small>#{}</small>", labels[idx], item.id)
+        } else {
+            labels[idx].clone()
+        };
+        match item.priority {
+            Some(priority) => out.push_str(&format!("<tr class=\"{}\">", priority.class())),
+            None => out.push_str("<tr>"),
+        }
+        for (col_idx, column) in columns.iter().enumerate() {
+            let col_type = col_type(col_idx, columns.len());
+            let class = column.class();
+            let markdown = |out: &mut String, markdown: &str| {
+                parse_td(
+                    out,
+                    md_options,
+                    max_image_bytes,
+                    class,
+                    markdown,
+                    &row_type,
+                    &col_type,
+                )
+            };
+            match column {
+                HtmlColumn::Step => build_td(out, class, i.as_str(), &row_type, &col_type),
+                HtmlColumn::Pass => {
+                    let status = item.status.map(|s| s.label()).unwrap_or("");
+                    build_td(out, class, status, &row_type, &col_type)
+                }
+                HtmlColumn::Priority => {
+                    let priority = item.priority.map(|p| p.label()).unwrap_or("");
+                    build_td(out, class, priority, &row_type, &col_type)
+                }
+                HtmlColumn::Comments => {
+                    let comment = escape_html(item.comment.trim());
+                    build_td(out, class, comment.as_str(), &row_type, &col_type)
+                }
+                HtmlColumn::Action => {
+                    let mut action = item.instructions();
+                    action.push_str(&links_markdown(&item.links));
+                    markdown(out, &action)
+                }
+                HtmlColumn::TestData => markdown(out, &item.test_data()),
+                HtmlColumn::ExpectedResults => markdown(out, &item.expected_results()),
+                HtmlColumn::Estimate => {
+                    let estimate = item
+                        .estimate_minutes
+                        .filter(|_| item.is_step())
+                        .map(TestStep::format_minutes)
+                        .unwrap_or_default();
+                    build_td(out, class, estimate.as_str(), &row_type, &col_type)
+                }
+                HtmlColumn::Author => {
+                    let author = escape_html(item.author.as_deref().unwrap_or(""));
+                    build_td(out, class, author.as_str(), &row_type, &col_type)
+                }
+                HtmlColumn::Ac => markdown(out, &item.ac()),
+            }
+        }
+        out.push_str("</tr>");
+    }
+}
+
+fn write_embedding(out: &mut String, serialized: &str) {
+    for part in [
+        "<div class=\"md-embedding\" id=\"",
+        MDEMBEDDING,
+        ":",
+        serialized,
+        "\"></div>",
+    ] {
+        out.push_str(part);
+    }
+}
+
+// writes `template` into `out` with each "{}" in it replaced by what `part` writes for
+// that placeholder's index. unlike format! the table and embedding are never copied
+// into an argument first
+fn fill(out: &mut String, template: &str, mut part: impl FnMut(&mut String, usize)) {
+    let mut pieces = template.split("{}").enumerate().peekable();
+    while let Some((idx, piece)) = pieces.next() {
+        out.push_str(piece);
+        if pieces.peek().is_some() {
+            part(out, idx);
+        }
+    }
+}
+
+// the compiled document: the styled table followed by the embedding that loads it back
+pub fn gen_html(items: &[TestStep], options: &HtmlOptions) -> Result<String, String> {
+    let serialized = embedding::serialize_items(items)?;
+    let columns = HtmlColumn::columns(options.columns, options.use_ac).len();
+    let template = include_str!("../template.html");
+    let style = include_str!("../style.css");
+    let mut out = String::with_capacity(
+        template.len() + style.len() + capacity(items, columns) + serialized.len(),
+    );
+    fill(&mut out, template, |out, idx| match idx {
+        0 => out.push_str(style),
+        1 => write_header(out, options),
+        2 => write_rows(out, items, options),
+        _ => write_embedding(out, &serialized),
+    });
+    Ok(out)
+}
+
+// what a printout leads with in place of a title page
+fn cover(items: &[TestStep], print: &PrintOptions) -> String {
+    let steps = items.iter().filter(|i| i.is_step()).count();
+    let sections = items.iter().filter(|i| i.is_new_section).count();
+    let authors: BTreeSet<&str> = items.iter().filter_map(|i| i.author.as_deref()).collect();
+    let mut fields = vec![
+        ("Printed", timestamp::format_date(timestamp::now())),
+        ("Steps", steps.to_string()),
+        ("Sections", sections.to_string()),
+        (
+            "Estimated",
+            TestStep::format_minutes(TestStep::total_estimate(items)),
+        ),
+    ];
+    if !authors.is_empty() {
+        fields.push((
+            "Authors",
+            authors.into_iter().collect::<Vec<_>>().join(", "),
+        ));
+    }
+    if let Some(source) = &print.source {
+        fields.push(("Source", source.clone()));
+    }
+    let fields: String = fields
+        .iter()
+        .map(|(name, value)| format!("<dt>{}</dt><dd>{}</dd>", name, escape_html(value)))
+        .collect();
+    format!("<h1>User Acceptance Test</h1>\n<dl>{}</dl>\n", fields)
+}
+
+// a standalone page for printing: plain black on white, rows kept whole and the header
+// repeated on every page
+pub fn gen_print_html(
+    items: &[TestStep],
+    options: &HtmlOptions,
+    print: &PrintOptions,
+) -> Result<String, String> {
+    let serialized = match print.embed {
+        true => Some(embedding::serialize_items(items)?),
+        false => None,
+    };
+    let columns = HtmlColumn::columns(options.columns, options.use_ac).len();
+    let template = include_str!("../print_template.html");
+    let style = include_str!("../print.css");
+    let mut out = String::with_capacity(
+        template.len()
+            + style.len()
+            + capacity(items, columns)
+            + serialized.as_ref().map_or(0, String::len),
+    );
+    fill(&mut out, template, |out, idx| match idx {
+        0 => out.push_str("User Acceptance Test"),
+        1 => out.push_str(style),
+        2 => out.push_str(&cover(items, print)),
+        3 => write_header(out, options),
+        4 => write_rows(out, items, options),
+        _ => {
+            if let Some(serialized) = &serialized {
+                write_embedding(out, serialized)
+            }
+        }
+    });
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{stress, test_step::Priority};
+
+    #[test]
+    fn capacity_covers_the_whole_document() {
+        let columns = ColumnConfig {
+            test_data: true,
+            estimate: true,
+            author: true,
+            priority: true,
+        };
+        let options = HtmlOptions {
+            columns: &columns,
+            use_ac: true,
+            show_ids: true,
+            max_image_bytes: 0,
+            locale: Locale::En,
+            markdown_extensions: &[],
+            numbering_start: 1,
+        };
+        for seed in [stress::DEFAULT_SEED, 7] {
+            let items = stress::generate(500, seed);
+            let html = gen_html(&items, &options).unwrap();
+            let reserved = include_str!("../template.html").len()
+                + include_str!("../style.css").len()
+                + capacity(&items, HtmlColumn::columns(&columns, true).len())
+                + embedding::serialize_items(&items).unwrap().len();
+            assert!(html.len() <= reserved, "{} > {}", html.len(), reserved);
+        }
+    }
+
+    #[test]
+    fn prioritised_rows_carry_a_class_and_the_column_is_optional() {
+        let mut high = TestStep::new().with_instructions("Pay");
+        high.priority = Some(Priority::High);
+        let mut low = TestStep::new().with_instructions("Change avatar");
+        low.priority = Some(Priority::Low);
+        let items = vec![high, TestStep::new().with_instructions("Browse"), low];
+        let plain = ColumnConfig::default();
+        let with_priority = ColumnConfig {
+            priority: true,
+            ..ColumnConfig::default()
+        };
+        let options = |columns| HtmlOptions {
+            columns,
+            use_ac: true,
+            show_ids: false,
+            max_image_bytes: 0,
+            locale: Locale::En,
+            markdown_extensions: &[],
+            numbering_start: 1,
+        };
+
+        let html = gen_html(&items, &options(&plain)).unwrap();
+        assert_eq!(html.matches("<tr class=\"priority-high\">").count(), 1);
+        assert_eq!(html.matches("<tr class=\"priority-low\">").count(), 1);
+        assert!(!html.contains("priority-medium\">"));
+        assert!(!html.contains(">High<"));
+
+        let html = gen_html(&items, &options(&with_priority)).unwrap();
+        assert!(html.contains(">Priority<"));
+        assert!(html.contains(">High<") && html.contains(">Low<"));
+        assert_eq!(html.matches("<tr class=\"priority-").count(), 2);
+    }
+}
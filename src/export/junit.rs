@@ -0,0 +1,126 @@
+use super::{escape_xml, first_line, sections};
+use crate::test_step::{StepStatus, TestStep};
+
+fn case_name(label: &str, item: &TestStep) -> String {
+    let id = if item.id != 0 {
+        format!(" [#{}]", item.id)
+    } else {
+        String::new()
+    };
+    format!("{}{} {}", label, id, first_line(&item.instructions))
+}
+
+fn testcase(suite: &str, label: &str, item: &TestStep) -> String {
+    let name = escape_xml(&case_name(label, item));
+    let classname = escape_xml(suite);
+    let comment = escape_xml(item.comment.trim());
+    let body = match item.status {
+        Some(StepStatus::Pass) => String::new(),
+        Some(StepStatus::Fail) => format!(
+            "\n      <failure message=\"{}\">{}</failure>\n    ",
+            comment,
+            escape_xml(item.expected_results.trim())
+        ),
+        Some(StepStatus::Blocked) | Some(StepStatus::Skipped) => {
+            format!("\n      <skipped message=\"{}\"/>\n    ", comment)
+        }
+        None => "\n      <skipped message=\"not executed\"/>\n    ".to_string(),
+    };
+    format!(
+        "    <testcase name=\"{}\" classname=\"{}\">{}</testcase>\n",
+        name, classname, body
+    )
+}
+
+fn counts<'a>(cases: impl Iterator<Item = &'a TestStep>) -> (usize, usize, usize) {
+    cases.fold((0, 0, 0), |(tests, failures, skipped), item| {
+        match item.status {
+            Some(StepStatus::Fail) => (tests + 1, failures + 1, skipped),
+            Some(StepStatus::Pass) => (tests + 1, failures, skipped),
+            _ => (tests + 1, failures, skipped + 1),
+        }
+    })
+}
+
+pub fn export(items: &[TestStep], first_section: usize) -> String {
+    let suites = sections(items, first_section);
+    let (tests, failures, skipped) =
+        counts(suites.iter().flat_map(|s| s.cases.iter().map(|(_, i)| *i)));
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"uat\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">\n",
+        tests, failures, skipped
+    ));
+    for suite in &suites {
+        let (tests, failures, skipped) = counts(suite.cases.iter().map(|(_, i)| *i));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">\n",
+            escape_xml(&suite.name),
+            tests,
+            failures,
+            skipped
+        ));
+        for (label, item) in &suite.cases {
+            xml.push_str(&testcase(&suite.name, label, item));
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(instructions: &str, status: Option<StepStatus>, comment: &str) -> TestStep {
+        let mut step = TestStep::new();
+        step.instructions = instructions.to_string();
+        step.status = status;
+        step.comment = comment.to_string();
+        step
+    }
+
+    #[test]
+    fn export_parses_as_junit() {
+        let section = TestStep::section("Login & <auth>");
+        let mut items = vec![
+            section,
+            step("Open page", Some(StepStatus::Pass), ""),
+            step("Submit \"form\"", Some(StepStatus::Fail), "500 <error>"),
+            step("Logout", Some(StepStatus::Blocked), "env down"),
+            step("Cleanup", None, ""),
+        ];
+        TestStep::assign_ids(&mut items);
+
+        let xml = export(&items, 1);
+        let doc = roxmltree::Document::parse(&xml).expect("valid xml");
+        let root = doc.root_element();
+        assert_eq!(root.tag_name().name(), "testsuites");
+        assert_eq!(root.attribute("tests"), Some("4"));
+        assert_eq!(root.attribute("failures"), Some("1"));
+        assert_eq!(root.attribute("skipped"), Some("2"));
+
+        let suite = root
+            .children()
+            .find(|n| n.has_tag_name("testsuite"))
+            .expect("testsuite");
+        assert_eq!(suite.attribute("name"), Some("Login & <auth>"));
+
+        let cases: Vec<_> = suite
+            .children()
+            .filter(|n| n.has_tag_name("testcase"))
+            .collect();
+        assert_eq!(cases.len(), 4);
+        assert!(cases[1].attribute("name").unwrap().contains("[#3]"));
+        let failure = cases[1]
+            .children()
+            .find(|n| n.has_tag_name("failure"))
+            .expect("failure");
+        assert_eq!(failure.attribute("message"), Some("500 <error>"));
+        assert!(cases[2].children().any(|n| n.has_tag_name("skipped")));
+        assert!(cases[3].children().any(|n| n.has_tag_name("skipped")));
+        assert!(cases[0].children().all(|n| !n.is_element()));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{config::PdfConfig, err_msg::WithErrMsg, shell};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+// tried in order when no converter is configured
+const KNOWN: &[(&str, &[&str])] = &[
+    (
+        "wkhtmltopdf",
+        &[
+            "--quiet",
+            "--enable-local-file-access",
+            "{input}",
+            "{output}",
+        ],
+    ),
+    ("weasyprint", &["{input}", "{output}"]),
+    ("chromium", CHROME_ARGS),
+    ("chromium-browser", CHROME_ARGS),
+    ("google-chrome", CHROME_ARGS),
+];
+
+const CHROME_ARGS: &[&str] = &[
+    "--headless",
+    "--disable-gpu",
+    "--no-pdf-header-footer",
+    "--print-to-pdf={output}",
+    "{input}",
+];
+
+// anything that turns the print html into a pdf, the shell converters are the only one
+// for now but a native writer only has to implement this
+pub trait PdfBackend {
+    fn write_pdf(&self, html: &str, output: &Path) -> Result<(), String>;
+}
+
+enum Invocation {
+    Program(PathBuf, Vec<String>),
+    // a user command, run through `sh -c`
+    Shell(String),
+}
+
+pub struct ShellConverter {
+    name: String,
+    invocation: Invocation,
+    timeout: Duration,
+}
+
+// removed again when dropped, however the conversion went
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn create(extension: &str, contents: &str) -> Result<TempFile, String> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "uat_editor_{}_{}.{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            extension
+        ));
+        std::fs::write(&path, contents)
+            .with_err_msg(&format!("Failed to write {}", path.display()))?;
+        Ok(TempFile(path))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn find_on_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+fn quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "'\\''"))
+}
+
+fn fill(arg: &str, input: &str, output: &str) -> String {
+    arg.replace("{input}", input).replace("{output}", output)
+}
+
+impl ShellConverter {
+    fn known(name: &str, args: &[&str], timeout: Duration) -> Option<ShellConverter> {
+        let program = find_on_path(name)?;
+        Some(ShellConverter {
+            name: name.to_string(),
+            invocation: Invocation::Program(program, args.iter().map(|a| a.to_string()).collect()),
+            timeout,
+        })
+    }
+
+    fn command(&self, input: &Path, output: &Path) -> Command {
+        match &self.invocation {
+            Invocation::Program(program, args) => {
+                let (input, output) = (input.display().to_string(), output.display().to_string());
+                let mut command = Command::new(program);
+                command.args(args.iter().map(|arg| fill(arg, &input, &output)));
+                command
+            }
+            Invocation::Shell(cmd) => {
+                let mut command = Command::new("sh");
+                command
+                    .arg("-c")
+                    .arg(fill(cmd, &quote(input), &quote(output)));
+                command
+            }
+        }
+    }
+}
+
+impl PdfBackend for ShellConverter {
+    fn write_pdf(&self, html: &str, output: &Path) -> Result<(), String> {
+        let input = TempFile::create("html", html)?;
+        // converters happily leave an old file in place when they fail
+        let _ = std::fs::remove_file(output);
+        let (mut child, stderr) = shell::spawn_quiet(&mut self.command(&input.0, output))
+            .map_err(|_| format!("Failed to start {}", self.name))?;
+        let status = shell::wait_timeout(&mut child, self.timeout)?.with_err_msg(&format!(
+            "{} timed out after {}s",
+            self.name,
+            self.timeout.as_secs()
+        ))?;
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            let line = stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+            return Err(format!("{} failed ({}) {}", self.name, status, line)
+                .trim_end()
+                .to_string());
+        }
+        if !output.is_file() {
+            return Err(format!(
+                "{} finished without writing {}",
+                self.name,
+                output.display()
+            ));
+        }
+        Ok(())
+    }
+}
+
+// the configured converter, or the first known one on PATH
+pub fn backend(config: &PdfConfig) -> Result<Box<dyn PdfBackend>, String> {
+    let timeout = Duration::from_secs(config.timeout_secs);
+    match config.converter.as_deref().map(str::trim) {
+        Some(name) if !name.contains(' ') => {
+            let args = KNOWN
+                .iter()
+                .find(|(known, _)| *known == name)
+                .map(|(_, args)| *args)
+                .with_err_msg(&format!(
+                    "Unknown pdf converter: {} (use a command with {{input}} and {{output}})",
+                    name
+                ))?;
+            ShellConverter::known(name, args, timeout)
+                .map(|converter| Box::new(converter) as Box<dyn PdfBackend>)
+                .with_err_msg(&format!("{} is not on PATH", name))
+        }
+        Some(cmd) => Ok(Box::new(ShellConverter {
+            name: cmd.split_whitespace().next().unwrap_or(cmd).to_string(),
+            invocation: Invocation::Shell(cmd.to_string()),
+            timeout,
+        })),
+        None => KNOWN
+            .iter()
+            .find_map(|(name, args)| ShellConverter::known(name, args, timeout))
+            .map(|converter| Box::new(converter) as Box<dyn PdfBackend>)
+            .with_err_msg(&format!(
+                "No pdf converter found on PATH (tried {}), set pdf.converter in the config",
+                KNOWN
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn converter(cmd: &str, timeout_secs: u64) -> Box<dyn PdfBackend> {
+        backend(&PdfConfig {
+            converter: Some(cmd.to_string()),
+            timeout_secs,
+        })
+        .unwrap()
+    }
+
+    fn output(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("uat_editor_pdf_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn custom_commands_get_quoted_paths() {
+        let path = output("it's.pdf");
+        converter("cp {input} {output}", 10)
+            .write_pdf("<p>hi</p>", &path)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "<p>hi</p>");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn failures_and_timeouts_are_errors() {
+        let path = output("fail.pdf");
+        let err = converter("echo broken >&2; exit 3", 10)
+            .write_pdf("", &path)
+            .unwrap_err();
+        assert_eq!(err, "echo failed (exit status: 3) broken");
+        let err = converter("true {output}", 10)
+            .write_pdf("", &path)
+            .unwrap_err();
+        assert!(err.starts_with("true finished without writing"), "{}", err);
+        let started = Instant::now();
+        let err = converter("sleep 10", 1).write_pdf("", &path).unwrap_err();
+        assert_eq!(err, "sleep timed out after 1s");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        let config = PdfConfig {
+            converter: Some("prince".to_string()),
+            timeout_secs: 1,
+        };
+        assert!(backend(&config).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use super::{sections, strip_markdown};
+use crate::{
+    config::{PlainTextConfig, TextLayout},
+    locale::{Key, Locale},
+    test_step::TestStep,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// text columns never get narrower than this, however tight the width is
+const MIN_COLUMN_WIDTH: usize = 8;
+const MAX_AC_WIDTH: usize = 12;
+
+struct Borders {
+    horizontal: char,
+    vertical: char,
+    // left, middle and right for the top, separator and bottom rules
+    top: [char; 3],
+    middle: [char; 3],
+    bottom: [char; 3],
+}
+
+const ASCII: Borders = Borders {
+    horizontal: '-',
+    vertical: '|',
+    top: ['+', '+', '+'],
+    middle: ['+', '+', '+'],
+    bottom: ['+', '+', '+'],
+};
+
+const UNICODE: Borders = Borders {
+    horizontal: '─',
+    vertical: '│',
+    top: ['┌', '┬', '┐'],
+    middle: ['├', '┼', '┤'],
+    bottom: ['└', '┴', '┘'],
+};
+
+// greedy word wrap by display width. words too long for a line are left whole unless
+// `hard` is set, an outline can let a url run past the margin but a table cell can't
+fn wrap(text: &str, width: usize, hard: bool) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let pieces = if hard && word.width() > width {
+                split_word(word, width)
+            } else {
+                vec![word.to_string()]
+            };
+            for piece in pieces {
+                if !line.is_empty() && line.width() + 1 + piece.width() > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&piece);
+            }
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+fn split_word(word: &str, width: usize) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    for c in word.chars() {
+        let current = pieces.last_mut().expect("starts with a piece");
+        if !current.is_empty() && current.width() + c.width().unwrap_or(0) > width {
+            pieces.push(String::new());
+        }
+        pieces.last_mut().expect("starts with a piece").push(c);
+    }
+    pieces
+}
+
+fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())))
+}
+
+fn heading(name: &str, width: usize) -> String {
+    let underline = "=".repeat(name.width().clamp(1, width.max(1)));
+    format!("{}\n{}\n\n", name, underline)
+}
+
+// `prefix` starts the first line, the rest line up under the text after it
+fn hanging(out: &mut String, indent: usize, prefix: &str, markdown: &str, width: usize) {
+    let hang = indent + prefix.width();
+    let text = strip_markdown(markdown);
+    let lines = wrap(
+        &text,
+        width.saturating_sub(hang).max(MIN_COLUMN_WIDTH),
+        false,
+    );
+    for (idx, line) in lines.iter().enumerate() {
+        let lead = if idx == 0 {
+            format!("{}{}", " ".repeat(indent), prefix)
+        } else {
+            " ".repeat(hang)
+        };
+        out.push_str(format!("{}{}", lead, line).trim_end());
+        out.push('\n');
+    }
+}
+
+fn outline(
+    items: &[TestStep],
+    options: PlainTextConfig,
+    use_ac: bool,
+    first_section: usize,
+) -> String {
+    let mut out = String::new();
+    for section in sections(items, first_section) {
+        out.push_str(&heading(&section.name, options.width));
+        for (label, item) in &section.cases {
+            let prefix = format!("{}. ", label);
+            let indent = prefix.width();
+            hanging(&mut out, 0, &prefix, &item.instructions, options.width);
+            if item.has_test_data() {
+                hanging(&mut out, indent, "Data: ", &item.test_data, options.width);
+            }
+            if !item.expected_results.trim().is_empty() {
+                hanging(
+                    &mut out,
+                    indent,
+                    "Expect: ",
+                    &item.expected_results,
+                    options.width,
+                );
+            }
+            if use_ac && !item.ac.trim().is_empty() {
+                hanging(&mut out, indent, "AC: ", &item.ac, options.width);
+            }
+            out.push('\n');
+        }
+    }
+    out.trim_end().to_string() + "\n"
+}
+
+fn rule(borders: &Borders, ends: [char; 3], widths: &[usize]) -> String {
+    let cells: Vec<String> = widths
+        .iter()
+        .map(|w| borders.horizontal.to_string().repeat(w + 2))
+        .collect();
+    format!(
+        "{}{}{}\n",
+        ends[0],
+        cells.join(&ends[1].to_string()),
+        ends[2]
+    )
+}
+
+fn table_row(borders: &Borders, cells: &[Vec<String>], widths: &[usize]) -> String {
+    let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+    let mut out = String::new();
+    for line in 0..height {
+        out.push(borders.vertical);
+        for (cell, width) in cells.iter().zip(widths) {
+            let text = cell.get(line).map(String::as_str).unwrap_or("");
+            out.push_str(&format!(" {} {}", pad(text, *width), borders.vertical));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// the label and ac columns are as wide as their content, the text columns split the rest
+fn table(
+    items: &[TestStep],
+    options: PlainTextConfig,
+    use_ac: bool,
+    locale: Locale,
+    first_section: usize,
+) -> String {
+    let borders = if options.unicode { &UNICODE } else { &ASCII };
+    let sections = sections(items, first_section);
+    let cases = || sections.iter().flat_map(|s| s.cases.iter());
+    let test_data = cases().any(|(_, item)| item.has_test_data());
+
+    let mut titles = vec!["#", locale.text(Key::Instructions)];
+    if test_data {
+        titles.push(locale.text(Key::TestData));
+    }
+    titles.push(locale.text(Key::ExpectedResults));
+    let label_width = cases().map(|(label, _)| label.width()).fold(1, usize::max);
+    let ac_width = cases()
+        .map(|(_, item)| strip_markdown(&item.ac).width())
+        .fold(2, usize::max)
+        .min(MAX_AC_WIDTH);
+    let fixed = label_width + if use_ac { ac_width } else { 0 };
+    let text_columns = titles.len() - 1;
+    let columns = titles.len() + usize::from(use_ac);
+    let text_width = (options.width.saturating_sub(3 * columns + 1 + fixed) / text_columns)
+        .max(MIN_COLUMN_WIDTH);
+    let mut widths = vec![label_width];
+    widths.extend(std::iter::repeat_n(text_width, text_columns));
+    if use_ac {
+        titles.push("AC");
+        widths.push(ac_width);
+    }
+
+    let header: Vec<Vec<String>> = titles
+        .iter()
+        .zip(&widths)
+        .map(|(title, width)| wrap(title, *width, true))
+        .collect();
+    let mut out = String::new();
+    for section in &sections {
+        out.push_str(&heading(&section.name, options.width));
+        out.push_str(&rule(borders, borders.top, &widths));
+        out.push_str(&table_row(borders, &header, &widths));
+        for (label, item) in &section.cases {
+            out.push_str(&rule(borders, borders.middle, &widths));
+            let mut fields = vec![item.instructions.as_str()];
+            if test_data {
+                fields.push(&item.test_data);
+            }
+            fields.push(&item.expected_results);
+            if use_ac {
+                fields.push(&item.ac);
+            }
+            let mut cells = vec![vec![label.clone()]];
+            cells.extend(
+                fields
+                    .iter()
+                    .zip(&widths[1..])
+                    .map(|(field, width)| wrap(&strip_markdown(field), *width, true)),
+            );
+            out.push_str(&table_row(borders, &cells, &widths));
+        }
+        out.push_str(&rule(borders, borders.bottom, &widths));
+        out.push('\n');
+    }
+    out.trim_end().to_string() + "\n"
+}
+
+// for pasting into emails and tickets that strip all formatting
+pub fn export(
+    items: &[TestStep],
+    options: PlainTextConfig,
+    use_ac: bool,
+    locale: Locale,
+    first_section: usize,
+) -> String {
+    match options.layout {
+        TextLayout::Outline => outline(items, options, use_ac, first_section),
+        TextLayout::Table => table(items, options, use_ac, locale, first_section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Vec<TestStep> {
+        let mut first = TestStep::new().with_instructions("Open the **login** page");
+        first.expected_results = "The form is shown with a username and password field".to_string();
+        first.ac = "1".to_string();
+        let mut second = TestStep::new().with_instructions("- enter user\n- enter pass");
+        second.test_data = "admin".to_string();
+        second.expected_results = "Signed in".to_string();
+        vec![TestStep::section("Login"), first, second]
+    }
+
+    #[test]
+    fn outline_wraps_under_a_hanging_indent() {
+        let options = PlainTextConfig {
+            width: 40,
+            ..PlainTextConfig::default()
+        };
+        let expected = concat!(
+            "Login\n",
+            "=====\n",
+            "\n",
+            "1.2. Open the login page\n",
+            "     Expect: The form is shown with a\n",
+            "             username and password field\n",
+            "     AC: 1\n",
+            "\n",
+            "1.3. - enter user\n",
+            "     - enter pass\n",
+            "     Data: admin\n",
+            "     Expect: Signed in\n",
+        );
+        assert_eq!(export(&fixture(), options, true, Locale::En, 1), expected);
+    }
+
+    #[test]
+    fn ascii_table() {
+        let options = PlainTextConfig {
+            layout: TextLayout::Table,
+            width: 60,
+            unicode: false,
+        };
+        let expected = concat!(
+            "Login\n",
+            "=====\n",
+            "\n",
+            "+-----+----------------+----------------+----------------+\n",
+            "| #   | Instructions   | Test Data      | Expected       |\n",
+            "|     |                |                | Results        |\n",
+            "+-----+----------------+----------------+----------------+\n",
+            "| 1.2 | Open the login |                | The form is    |\n",
+            "|     | page           |                | shown with a   |\n",
+            "|     |                |                | username and   |\n",
+            "|     |                |                | password field |\n",
+            "+-----+----------------+----------------+----------------+\n",
+            "| 1.3 | - enter user   | admin          | Signed in      |\n",
+            "|     | - enter pass   |                |                |\n",
+            "+-----+----------------+----------------+----------------+\n",
+        );
+        assert_eq!(export(&fixture(), options, false, Locale::En, 1), expected);
+    }
+
+    #[test]
+    fn unicode_borders_are_optional() {
+        let options = PlainTextConfig {
+            layout: TextLayout::Table,
+            width: 60,
+            unicode: true,
+        };
+        let table = export(&fixture(), options, true, Locale::En, 1);
+        assert!(table.contains("┌─────┬"));
+        assert!(table.contains("┼────┤\n"));
+        assert!(table.ends_with("┴────┘\n"));
+        assert!(table.lines().all(|line| line.width() <= 60));
+    }
+
+    #[test]
+    fn long_words_only_break_in_tables() {
+        let url = "https://example.com/a/very/long/path";
+        assert_eq!(wrap(&format!("see {}", url), 10, false), vec!["see", url]);
+        assert_eq!(
+            wrap(url, 12, true),
+            vec!["https://exam", "ple.com/a/ve", "ry/long/path"]
+        );
+        assert_eq!(wrap("", 10, false), vec![""]);
+    }
+}
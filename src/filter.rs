@@ -0,0 +1,168 @@
+use crate::{
+    err_msg::WithErrMsg,
+    query::{self, Field, Term},
+    test_step::{Priority, TestStep},
+    timestamp, validate,
+};
+use regex::Regex;
+use std::collections::HashSet;
+use std::ops::Range;
+
+pub enum Predicate {
+    OlderThan(u64),
+    Author(String),
+    // prio:none matches the steps without one
+    Priority(Option<Priority>),
+    Duplicate,
+    // in one field or (None) any of them
+    Text(Option<Field>, Regex),
+}
+
+// what predicates can look at besides the step itself
+struct Context {
+    now: u64,
+    duplicates: HashSet<usize>,
+}
+
+impl Predicate {
+    fn parse(term: Term) -> Result<Predicate, String> {
+        let Some(key) = term.key else {
+            if term.value.eq_ignore_ascii_case("dup") {
+                return Ok(Predicate::Duplicate);
+            }
+            return Ok(Predicate::Text(None, query::matcher(&term.value)?));
+        };
+        let val = term.value.as_str();
+        if let Some(field) = Field::parse(&key) {
+            return Ok(Predicate::Text(Some(field), query::matcher(val)?));
+        }
+
+        match key.as_str() {
+            "older-than" => {
+                let secs = timestamp::parse_duration(val)
+                    .with_err_msg(&format!("Invalid duration: {}", val))?;
+                Ok(Predicate::OlderThan(secs))
+            }
+            "author" => Ok(Predicate::Author(val.to_lowercase())),
+            "prio" | "priority" => Ok(Predicate::Priority(Priority::parse(val)?)),
+            _ => Err(format!(
+                "Unknown prefix {}: (expected i: d: e: ac: author: prio: or older-than:)",
+                key
+            )),
+        }
+    }
+
+    fn matches(&self, idx: usize, step: &TestStep, context: &Context) -> bool {
+        match self {
+            // steps from before timestamps existed count as stale
+            Predicate::OlderThan(secs) => step
+                .updated_at
+                .or(step.created_at)
+                .map(|t| context.now.saturating_sub(t) > *secs)
+                .unwrap_or(true),
+            Predicate::Duplicate => context.duplicates.contains(&idx),
+            Predicate::Priority(priority) => step.priority == *priority,
+            Predicate::Text(field, needle) => {
+                let fields = match field {
+                    Some(Field::Instructions) => vec![&step.instructions],
+                    Some(Field::TestData) => vec![&step.test_data],
+                    Some(Field::Expected) => vec![&step.expected_results],
+                    Some(Field::Ac) => vec![&step.ac],
+                    None => vec![
+                        &step.instructions,
+                        &step.test_data,
+                        &step.expected_results,
+                        &step.ac,
+                        &step.comment,
+                    ],
+                };
+                fields.iter().any(|text| needle.is_match(text))
+            }
+            Predicate::Author(author) => step
+                .author
+                .as_ref()
+                .map(|a| a.to_lowercase() == *author)
+                .unwrap_or(false),
+        }
+    }
+}
+
+pub struct Filter {
+    pub query: String,
+    predicates: Vec<Predicate>,
+}
+
+impl Filter {
+    pub fn parse(query: &str) -> Result<Filter, String> {
+        let predicates = query::parse(query)?
+            .into_iter()
+            .map(Predicate::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Filter {
+            query: query.trim().to_string(),
+            predicates,
+        })
+    }
+
+    // byte ranges in `text` matched by the text terms that apply to `field`, None being
+    // a field only unscoped terms search (the comment)
+    pub fn match_ranges(&self, field: Option<Field>, text: &str) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = self
+            .predicates
+            .iter()
+            .filter_map(|p| match p {
+                Predicate::Text(scope, regex) if scope.is_none() || *scope == field => Some(regex),
+                _ => None,
+            })
+            .flat_map(|regex| regex.find_iter(text).map(|m| m.range()))
+            .filter(|range| !range.is_empty())
+            .collect();
+        ranges.sort_by_key(|range| range.start);
+        ranges
+    }
+
+    // indices of the items that match every predicate
+    pub fn matching(&self, items: &[TestStep]) -> Vec<usize> {
+        let context = Context {
+            now: timestamp::now(),
+            duplicates: validate::duplicate_groups(items)
+                .into_iter()
+                .flatten()
+                .collect(),
+        };
+        items
+            .iter()
+            .enumerate()
+            .filter(|(idx, step)| {
+                self.predicates
+                    .iter()
+                    .all(|p| p.matches(*idx, step, &context))
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prio_matches_a_priority_or_none() {
+        let mut items: Vec<TestStep> = ["Pay", "Browse", "Change avatar"]
+            .into_iter()
+            .map(|text| TestStep::new().with_instructions(text))
+            .collect();
+        items[0].priority = Some(Priority::High);
+        items[2].priority = Some(Priority::Low);
+        let matching = |query: &str| Filter::parse(query).unwrap().matching(&items);
+
+        assert_eq!(matching("prio:high"), vec![0]);
+        assert_eq!(matching("priority:3"), vec![2]);
+        assert_eq!(matching("prio:none"), vec![1]);
+        assert_eq!(matching("prio:low avatar"), vec![2]);
+        assert!(matching("prio:medium").is_empty());
+        assert!(Filter::parse("prio:urgent").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+// a case-insensitive subsequence match, higher is better. letters starting a word and
+// consecutive ones score extra, and the query as one piece beats it spread out, so
+// "cache" ranks "clear cache" over "c-a-c-h-e"
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    let query = query.trim().to_lowercase();
+    let lowered = candidate.to_lowercase();
+    let candidate: Vec<char> = lowered.chars().collect();
+    let mut score = 0;
+    if !query.is_empty() && lowered.contains(&query) {
+        score += 10 * query.chars().count() as i64;
+    }
+    let mut at = 0;
+    let mut last: Option<usize> = None;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (at..candidate.len()).find(|&i| candidate[i] == q)?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last = Some(found);
+        at = found + 1;
+    }
+    // shorter names win a tie
+    Some(score * 100 - candidate.len() as i64)
+}
+
+// the candidates that match, best first
+pub fn rank<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut scored: Vec<(i64, &str)> = candidates
+        .into_iter()
+        .filter_map(|c| score(query, c).map(|s| (s, c)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsequences_match_and_words_rank_first() {
+        assert!(score("xyz", "clear cache").is_none());
+        assert_eq!(
+            rank(
+                "cache",
+                ["check audit log", "c-a-c-h-e", "clear cache", "hard reload"]
+            ),
+            vec!["clear cache", "c-a-c-h-e"]
+        );
+        assert_eq!(
+            rank("", ["b", "a"]),
+            vec!["a", "b"],
+            "an empty query keeps everything"
+        );
+    }
+}
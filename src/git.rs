@@ -0,0 +1,106 @@
+use crate::err_msg::WithErrMsg;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// the nearest directory at or above `path` holding a .git (a directory, or a file for
+// worktrees and submodules)
+pub fn repo_root(path: &Path) -> Option<PathBuf> {
+    let start = if path.is_dir() { path } else { path.parent()? };
+    let start = std::fs::canonicalize(start).ok()?;
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+// the file as committed in HEAD, with the usual ways that can fail put in plain words
+pub fn show_head(path: &str) -> Result<String, String> {
+    let file = std::fs::canonicalize(path).with_err_msg(&format!("Failed to find {}", path))?;
+    let root =
+        repo_root(&file).with_err_msg(&format!("{} is not inside a git repository", path))?;
+    // git wants forward slashes in the object name on every platform
+    let relative = file
+        .strip_prefix(&root)
+        .with_err_msg(&format!("{} is outside of {}", path, root.display()))?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&root)
+        .arg("show")
+        .arg(format!("HEAD:{}", relative))
+        .output()
+        .with_err_msg(&"Failed to run git, is it installed?")?;
+    if output.status.success() {
+        return String::from_utf8(output.stdout)
+            .map_err(|_| format!("HEAD:{} is binary, not a document", relative));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(if stderr.contains("invalid object name 'HEAD'") {
+        "The repository has no commits yet".to_string()
+    } else if stderr.contains("exists on disk, but not in") || stderr.contains("does not exist in")
+    {
+        format!(
+            "{} is not committed yet (untracked or new in the index)",
+            relative
+        )
+    } else {
+        let line = stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+        format!("git show HEAD:{} failed {}", relative, line)
+            .trim_end()
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    #[test]
+    fn reads_the_committed_version() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("uat_editor_git_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        let file = dir.join("docs/uat.json");
+        let path = file.to_str().unwrap();
+        std::fs::write(&file, "old").unwrap();
+
+        let err = show_head(path).unwrap_err();
+        assert!(err.ends_with("is not inside a git repository"), "{}", err);
+        git(&dir, &["init", "-q"]);
+        assert_eq!(
+            show_head(path).unwrap_err(),
+            "The repository has no commits yet"
+        );
+        git(&dir, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        assert_eq!(
+            show_head(path).unwrap_err(),
+            "docs/uat.json is not committed yet (untracked or new in the index)"
+        );
+        git(&dir, &["add", "docs/uat.json"]);
+        git(&dir, &["commit", "-q", "-m", "doc"]);
+        std::fs::write(&file, "new").unwrap();
+        assert_eq!(show_head(path).unwrap(), "old");
+        assert_eq!(repo_root(&file), std::fs::canonicalize(&dir).ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
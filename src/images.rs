@@ -0,0 +1,148 @@
+use base64::prelude::*;
+use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_MAX_IMAGE_BYTES: u64 = 2 * 1024 * 1024;
+
+pub fn is_local(dest: &str) -> bool {
+    !dest.contains("://") && !dest.starts_with("data:")
+}
+
+// relative paths resolve against the document's directory, falling back to the cwd
+pub fn resolve(dest: &str, base_dir: Option<&Path>) -> PathBuf {
+    let path = Path::new(dest);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    match base_dir {
+        Some(base) => base.join(path),
+        None => std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "image/png",
+    }
+}
+
+pub enum ImageProblem {
+    Missing(PathBuf),
+    TooLarge(PathBuf, u64),
+}
+
+impl ImageProblem {
+    pub fn message(&self) -> String {
+        match self {
+            ImageProblem::Missing(path) => format!("image not found: {}", path.display()),
+            ImageProblem::TooLarge(path, size) => format!(
+                "image exceeds size cap ({} bytes): {}",
+                size,
+                path.display()
+            ),
+        }
+    }
+}
+
+pub fn check(dest: &str, base_dir: Option<&Path>, max_bytes: u64) -> Result<PathBuf, ImageProblem> {
+    let path = resolve(dest, base_dir);
+    let size = std::fs::metadata(&path)
+        .map(|m| m.len())
+        .map_err(|_| ImageProblem::Missing(path.clone()))?;
+    if size > max_bytes {
+        return Err(ImageProblem::TooLarge(path, size));
+    }
+    Ok(path)
+}
+
+fn data_uri(dest: &str, base_dir: Option<&Path>, max_bytes: u64) -> Option<String> {
+    let path = check(dest, base_dir, max_bytes).ok()?;
+    let bytes = std::fs::read(&path).ok()?;
+    Some(format!(
+        "data:{};base64,{}",
+        mime_type(&path),
+        BASE64_STANDARD.encode(bytes)
+    ))
+}
+
+// every local image path referenced by markdown in the text
+pub fn local_images(md: &str) -> Vec<String> {
+    pulldown_cmark::Parser::new(md)
+        .filter_map(|event| match event {
+            Event::Start(Tag::Image { dest_url, .. }) if is_local(&dest_url) => {
+                Some(dest_url.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn escape_attr(val: &str) -> String {
+    val.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+// swaps local images for inline data uris, missing or oversized files are left as links
+pub fn inline_images<'a>(
+    events: impl Iterator<Item = Event<'a>>,
+    base_dir: Option<&Path>,
+    max_bytes: u64,
+) -> Vec<Event<'a>> {
+    let mut output = Vec::new();
+    let mut pending: Option<(String, String)> = None;
+    for event in events {
+        match (&mut pending, event) {
+            (None, Event::Start(Tag::Image { dest_url, .. }))
+                if is_local(&dest_url)
+                    && let Some(uri) = data_uri(&dest_url, base_dir, max_bytes) =>
+            {
+                pending = Some((uri, String::new()));
+            }
+            (Some((_, alt)), Event::Text(text)) => alt.push_str(&text),
+            (Some((uri, alt)), Event::End(TagEnd::Image)) => {
+                output.push(Event::Html(CowStr::from(format!(
+                    "<img src=\"{}\" alt=\"{}\" style=\"max-width: 100%;\">",
+                    uri,
+                    escape_attr(alt)
+                ))));
+                pending = None;
+            }
+            (Some(_), _) => {}
+            (None, event) => output.push(event),
+        }
+    }
+    output
+}
+
+// renders "![caption](path)" as just the caption with a marker for the tui
+pub fn caption_markers(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("![") {
+        let after = &rest[start + 2..];
+        let Some(close) = after.find("](") else {
+            break;
+        };
+        let Some(end) = after[close + 2..].find(')') else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        output.push_str("▣ ");
+        output.push_str(&after[..close]);
+        rest = &after[close + 2 + end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
@@ -0,0 +1,5 @@
+pub mod external;
+pub mod html;
+pub mod loose;
+pub mod mapping;
+pub mod sheet;
@@ -0,0 +1,80 @@
+use crate::{config::ImporterConfig, embedding, err_msg::WithErrMsg, shell, test_step::TestStep};
+use std::time::Duration;
+
+fn quote(path: &str) -> String {
+    format!("'{}'", path.replace('\'', "'\\''"))
+}
+
+// runs the importer on `path` and checks what it printed against the document schema
+pub fn run(name: &str, importer: &ImporterConfig, path: &str) -> Result<Vec<TestStep>, String> {
+    let (cmd, input) = if importer.command.contains("{path}") {
+        (
+            importer.command.replace("{path}", &quote(path)),
+            String::new(),
+        )
+    } else {
+        let input =
+            std::fs::read_to_string(path).with_err_msg(&format!("Failed to read {}", path))?;
+        (importer.command.clone(), input)
+    };
+    let output = shell::run_timeout(&cmd, &input, Duration::from_secs(importer.timeout_secs))?;
+    if !output.status.success() {
+        let line = output.first_stderr_line();
+        return Err(
+            format!("Importer {} failed ({}) {}", name, output.status, line)
+                .trim_end()
+                .to_string(),
+        );
+    }
+    if output.stdout.trim().is_empty() {
+        return Err(format!("Importer {} printed nothing", name));
+    }
+    embedding::parse_checked(&output.stdout)
+        .map_err(|err| format!("Importer {} output: {}", name, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn importer(command: &str) -> ImporterConfig {
+        ImporterConfig {
+            command: command.to_string(),
+            timeout_secs: 10,
+        }
+    }
+
+    fn input(name: &str, contents: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("uat_editor_import_{}_{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn reads_from_a_path_or_stdin() {
+        let document = embedding::document_json(&[TestStep::new().with_instructions("a")]).unwrap();
+        let path = input("it's.json", &document);
+        let by_path = run("stub", &importer("cat {path}"), &path).unwrap();
+        let by_stdin = run("stub", &importer("cat"), &path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(by_path[0].instructions, "a");
+        assert_eq!(by_path, by_stdin);
+    }
+
+    #[test]
+    fn bad_output_points_at_the_item() {
+        let path = input(
+            "bad.json",
+            r#"{"version":2,"items":[{"id":"x","instructions":"a","expected_results":"","ac":""}]}"#,
+        );
+        let err = run("stub", &importer("cat"), &path).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            err,
+            "Importer stub output: items[0].id: expected integer, found string"
+        );
+        let err = run("stub", &importer("echo nope >&2; exit 1 {path}"), "").unwrap_err();
+        assert_eq!(err, "Importer stub failed (exit status: 1) nope");
+    }
+}
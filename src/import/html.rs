@@ -0,0 +1,592 @@
+use super::mapping::{self, Field};
+use crate::test_step::{StepStatus, TestStep};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open { name: String, attrs: String },
+    Close(String),
+    Text(String),
+}
+
+fn attr(attrs: &str, key: &str) -> Option<String> {
+    let lower = attrs.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(pos) = lower[search..].find(key) {
+        let start = search + pos;
+        search = start + key.len();
+        let boundary = start == 0 || lower[..start].ends_with(char::is_whitespace);
+        let rest = lower[search..].trim_start();
+        if !boundary || !rest.starts_with('=') {
+            continue;
+        }
+        let value = attrs[attrs.len() - rest.len() + 1..].trim_start();
+        return Some(match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or("").to_string(),
+            _ => value
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or("")
+                .to_string(),
+        });
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find('&') {
+        out.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// a forgiving tag scanner, good enough for the tables editors put on the clipboard
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    let mut skip_until: Option<String> = None;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            if skip_until.is_none() {
+                tokens.push(Token::Text(rest.to_string()));
+            }
+            break;
+        };
+        if lt > 0 && skip_until.is_none() {
+            tokens.push(Token::Text(rest[..lt].to_string()));
+        }
+        rest = &rest[lt..];
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map(|end| &rest[end + 3..]).unwrap_or("");
+            continue;
+        }
+
+        let mut quote = None;
+        let end = rest.char_indices().skip(1).find(|(_, c)| match quote {
+            Some(q) => {
+                if *c == q {
+                    quote = None;
+                }
+                false
+            }
+            None if matches!(c, '"' | '\'') => {
+                quote = Some(*c);
+                false
+            }
+            None => *c == '>',
+        });
+        let Some((end, _)) = end else {
+            if skip_until.is_none() {
+                tokens.push(Token::Text(rest.to_string()));
+            }
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_lowercase();
+        if let Some(skip) = &skip_until {
+            if closing && *skip == name {
+                skip_until = None;
+            }
+            continue;
+        }
+        if closing {
+            tokens.push(Token::Close(name));
+        } else if name == "script" || name == "style" {
+            skip_until = Some(name);
+        } else {
+            tokens.push(Token::Open {
+                name,
+                attrs: tag[name_end..].to_string(),
+            });
+        }
+    }
+    tokens
+}
+
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div" | "li" | "ul" | "ol" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "tr" | "td"
+    )
+}
+
+fn plain_text(tokens: &[Token]) -> String {
+    let text: String = tokens
+        .iter()
+        .map(|t| match t {
+            Token::Text(text) => decode_entities(text),
+            Token::Open { name, .. } if is_block(name) || name == "br" => " ".to_string(),
+            _ => String::new(),
+        })
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn end_block(out: &mut String) {
+    let trimmed = out.trim_end_matches([' ', '\n']).len();
+    out.truncate(trimmed);
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+}
+
+// cell html back to markdown, structure markdown can't hold (nested tables) is an
+// error so the caller can fall back to plain text for the row
+fn to_markdown(tokens: &[Token]) -> Result<String, String> {
+    let mut out = String::new();
+    let mut lists: Vec<Option<usize>> = Vec::new();
+    let mut hrefs: Vec<String> = Vec::new();
+    for token in tokens {
+        match token {
+            Token::Text(text) => {
+                let text = decode_entities(text);
+                let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if text.starts_with(char::is_whitespace)
+                    && !out.is_empty()
+                    && !out.ends_with([' ', '\n'])
+                {
+                    out.push(' ');
+                }
+                out.push_str(&words);
+                if text.ends_with(char::is_whitespace) && !words.is_empty() {
+                    out.push(' ');
+                }
+            }
+            Token::Open { name, attrs } => match name.as_str() {
+                "table" => return Err("nested table".to_string()),
+                "p" | "div" => end_block(&mut out),
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    end_block(&mut out);
+                    let level = name[1..].parse().unwrap_or(1);
+                    out.push_str(&"#".repeat(level));
+                    out.push(' ');
+                }
+                "br" => {
+                    let trimmed = out.trim_end_matches(' ').len();
+                    out.truncate(trimmed);
+                    out.push_str("  \n");
+                }
+                "strong" | "b" => out.push_str("**"),
+                "em" | "i" => out.push('*'),
+                "code" => out.push('`'),
+                "s" | "del" | "strike" => out.push_str("~~"),
+                "ul" | "ol" => {
+                    if lists.is_empty() {
+                        end_block(&mut out);
+                    }
+                    lists.push((name == "ol").then_some(0));
+                }
+                "li" => {
+                    let trimmed = out.trim_end_matches(' ').len();
+                    out.truncate(trimmed);
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                    match lists.last_mut() {
+                        Some(Some(n)) => {
+                            *n += 1;
+                            out.push_str(&format!("{}. ", n));
+                        }
+                        _ => out.push_str("- "),
+                    }
+                }
+                "a" => {
+                    hrefs.push(attr(attrs, "href").unwrap_or_default());
+                    out.push('[');
+                }
+                "img" => {
+                    let alt = attr(attrs, "alt").unwrap_or_default();
+                    let src = attr(attrs, "src").unwrap_or_default();
+                    out.push_str(&format!("![{}]({})", alt, src));
+                }
+                _ => {}
+            },
+            Token::Close(name) => match name.as_str() {
+                "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => end_block(&mut out),
+                "strong" | "b" => out.push_str("**"),
+                "em" | "i" => out.push('*'),
+                "code" => out.push('`'),
+                "s" | "del" | "strike" => out.push_str("~~"),
+                "ul" | "ol" => {
+                    lists.pop();
+                    if lists.is_empty() {
+                        end_block(&mut out);
+                    }
+                }
+                "a" => {
+                    let href = hrefs.pop().unwrap_or_default();
+                    out.push_str(&format!("]({})", href));
+                }
+                _ => {}
+            },
+        }
+    }
+    Ok(out.trim().to_string())
+}
+
+enum Content {
+    Html(Vec<Token>),
+    // a spreadsheet cell, already the markdown it will import as
+    Text(String),
+}
+
+struct Cell {
+    content: Content,
+    is_header: bool,
+    colspan: usize,
+}
+
+impl Cell {
+    fn push(&mut self, token: &Token) {
+        if let Content::Html(tokens) = &mut self.content {
+            tokens.push(token.clone());
+        }
+    }
+
+    fn text(&self) -> String {
+        match &self.content {
+            Content::Html(tokens) => plain_text(tokens),
+            Content::Text(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        }
+    }
+
+    fn markdown(&self) -> Result<String, String> {
+        match &self.content {
+            Content::Html(tokens) => to_markdown(tokens),
+            Content::Text(text) => Ok(text.trim().to_string()),
+        }
+    }
+}
+
+pub struct Table {
+    pub headers: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+pub struct Imported {
+    pub items: Vec<TestStep>,
+    pub warnings: Vec<String>,
+}
+
+impl Table {
+    // the first top level table in the document, nested tables stay inside their cell
+    pub fn parse(html: &str) -> Result<Table, String> {
+        let tokens = tokenize(html);
+        let start = tokens
+            .iter()
+            .position(|t| matches!(t, Token::Open { name, .. } if name == "table"))
+            .ok_or("No <table> found in the HTML")?;
+
+        let mut rows: Vec<Vec<Cell>> = Vec::new();
+        let mut cell: Option<Cell> = None;
+        let flush = |cell: &mut Option<Cell>, rows: &mut Vec<Vec<Cell>>| {
+            if let Some(done) = cell.take() {
+                match rows.last_mut() {
+                    Some(row) => row.push(done),
+                    None => rows.push(vec![done]),
+                }
+            }
+        };
+        let mut depth = 0;
+        for token in &tokens[start..] {
+            match token {
+                Token::Open { name, .. } if name == "table" => depth += 1,
+                Token::Close(name) if name == "table" => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+            let nested = depth > 1 || matches!(token, Token::Close(name) if name == "table");
+            if nested {
+                if let Some(cell) = cell.as_mut() {
+                    cell.push(token);
+                }
+                continue;
+            }
+            match token {
+                Token::Open { name, .. } if name == "tr" => {
+                    flush(&mut cell, &mut rows);
+                    rows.push(Vec::new());
+                }
+                Token::Open { name, attrs } if name == "td" || name == "th" => {
+                    flush(&mut cell, &mut rows);
+                    cell = Some(Cell {
+                        content: Content::Html(Vec::new()),
+                        is_header: name == "th",
+                        colspan: attr(attrs, "colspan")
+                            .and_then(|c| c.parse().ok())
+                            .unwrap_or(1),
+                    });
+                }
+                Token::Close(name) if matches!(name.as_str(), "td" | "th" | "tr") => {
+                    flush(&mut cell, &mut rows);
+                }
+                token => {
+                    if let Some(cell) = cell.as_mut() {
+                        cell.push(token);
+                    }
+                }
+            }
+        }
+        flush(&mut cell, &mut rows);
+        rows.retain(|row| !row.is_empty());
+        if rows.is_empty() {
+            return Err("The table has no rows".to_string());
+        }
+
+        let first = &rows[0];
+        let is_header = first.iter().all(|c| c.is_header)
+            || first
+                .iter()
+                .filter(|c| Field::guess(&c.text()).is_some())
+                .count()
+                >= 2;
+        let headers = if is_header {
+            rows.remove(0).iter().map(Cell::text).collect()
+        } else {
+            let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+            (1..=width).map(|n| format!("Column {}", n)).collect()
+        };
+        Ok(Table { headers, rows })
+    }
+
+    // csv and spreadsheet rows, the first row is always the headers. blank rows are
+    // dropped and every row keeps its width, so none reads as a section heading
+    pub fn from_rows(rows: Vec<Vec<String>>) -> Result<Table, String> {
+        let mut rows = rows
+            .into_iter()
+            .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+        let headers: Vec<String> = rows
+            .next()
+            .ok_or("The sheet has no rows")?
+            .iter()
+            .map(|header| header.trim().to_string())
+            .collect();
+        let rows = rows
+            .map(|row| {
+                row.into_iter()
+                    .map(|text| Cell {
+                        content: Content::Text(text),
+                        is_header: false,
+                        colspan: 1,
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(Table { headers, rows })
+    }
+
+    pub fn guess_mapping(&self) -> Vec<Field> {
+        mapping::guess(&self.headers)
+    }
+
+    // the first few non-empty values under a header, for telling columns apart
+    pub fn samples(&self, column: usize, count: usize) -> Vec<String> {
+        self.rows
+            .iter()
+            .filter(|row| row.len() > 1 || self.headers.len() == 1)
+            .filter_map(|row| {
+                let mut col = 0;
+                row.iter().find(|cell| {
+                    col += cell.colspan.max(1);
+                    col > column
+                })
+            })
+            .map(Cell::text)
+            .filter(|text| !text.is_empty())
+            .take(count)
+            .collect()
+    }
+
+    pub fn describe_mapping(&self, mapping: &[Field]) -> String {
+        self.headers
+            .iter()
+            .zip(mapping)
+            .enumerate()
+            .map(|(idx, (header, field))| format!("{}:{}={}", idx + 1, header, field.name()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // overrides like "2=expected 3=ac" on top of the guessed mapping
+    pub fn apply_overrides(&self, mapping: &mut [Field], overrides: &str) -> Result<(), String> {
+        for term in overrides.split_whitespace() {
+            let (col, field) = term
+                .split_once('=')
+                .ok_or(format!("Expected <column>=<field>, found: {}", term))?;
+            let slot = col
+                .parse::<usize>()
+                .ok()
+                .and_then(|col| col.checked_sub(1))
+                .and_then(|idx| mapping.get_mut(idx))
+                .ok_or(format!("No column {}", col))?;
+            *slot = Field::parse(field)?;
+        }
+        Ok(())
+    }
+
+    pub fn import(&self, mapping: &[Field]) -> Imported {
+        let mut items = Vec::new();
+        let mut warnings = Vec::new();
+        for (row_idx, row) in self.rows.iter().enumerate() {
+            // a lone cell in a wider table reads as a section heading
+            if row.len() == 1 && self.headers.len() > 1 {
+                let title = row[0].markdown().unwrap_or(row[0].text());
+                items.push(TestStep::section(&title));
+                continue;
+            }
+
+            let cells = row
+                .iter()
+                .map(Cell::markdown)
+                .collect::<Result<Vec<String>, String>>()
+                .unwrap_or_else(|err| {
+                    warnings.push(format!(
+                        "Row {}: imported as plain text ({})",
+                        row_idx + 1,
+                        err
+                    ));
+                    row.iter().map(Cell::text).collect()
+                });
+
+            let mut step = TestStep::new();
+            let mut col = 0;
+            for (text, cell) in cells.into_iter().zip(row) {
+                let field = mapping.get(col).copied().unwrap_or(Field::Ignore);
+                col += cell.colspan.max(1);
+                let target = match field {
+                    Field::Instructions => &mut step.instructions,
+                    Field::TestData => &mut step.test_data,
+                    Field::ExpectedResults => &mut step.expected_results,
+                    Field::Ac => &mut step.ac,
+                    Field::Comment => &mut step.comment,
+                    Field::Status => {
+                        match StepStatus::parse(&text) {
+                            Ok(status) => step.status = status,
+                            Err(_) => warnings.push(format!(
+                                "Row {}: unknown status '{}' ignored",
+                                row_idx + 1,
+                                text
+                            )),
+                        }
+                        continue;
+                    }
+                    Field::Ignore => continue,
+                };
+                if !target.is_empty() && !text.is_empty() {
+                    target.push_str("\n\n");
+                }
+                target.push_str(&text);
+            }
+            if !step.is_blank() {
+                items.push(step);
+            }
+        }
+        TestStep::assign_ids(&mut items);
+        Imported { items, warnings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFLUENCE_TABLE: &str = r#"<p>UAT</p>
+<table class="confluenceTable"><colgroup><col/><col/><col/><col/></colgroup><tbody>
+<tr><th><p>Step</p></th><th><p>Action</p></th><th><p>Expected Result</p></th><th>AC #</th></tr>
+<tr><td colspan="4"><strong>Login</strong></td></tr>
+<tr><td>1.1</td><td><p>Open the <b>login</b> page</p><ul><li>as admin</li><li>as guest</li></ul></td>
+<td>Form&nbsp;shown<br/>fast</td><td>AC-1</td></tr>
+<tr><td>1.2</td><td>Check<table><tr><td>nested</td></tr></table></td><td>ok</td><td></td></tr>
+</tbody></table>"#;
+
+    #[test]
+    fn imports_hand_written_table() {
+        let table = Table::parse(CONFLUENCE_TABLE).unwrap();
+        let mapping = table.guess_mapping();
+        assert_eq!(
+            mapping,
+            vec![
+                Field::Ignore,
+                Field::Instructions,
+                Field::ExpectedResults,
+                Field::Ac
+            ]
+        );
+
+        let imported = table.import(&mapping);
+        assert_eq!(imported.items.len(), 3);
+        assert!(imported.items[0].is_new_section);
+        assert_eq!(imported.items[0].instructions, "**Login**");
+        assert_eq!(
+            imported.items[1].instructions,
+            "Open the **login** page\n\n- as admin\n- as guest"
+        );
+        assert_eq!(imported.items[1].expected_results, "Form shown  \nfast");
+        assert_eq!(imported.items[1].ac, "AC-1");
+        assert_eq!(imported.items[2].instructions, "Check nested");
+        assert_eq!(imported.warnings.len(), 1);
+    }
+
+    #[test]
+    fn overrides_replace_guesses() {
+        let table = Table::parse("<table><tr><td>a</td><td>b</td></tr></table>").unwrap();
+        let mut mapping = table.guess_mapping();
+        assert_eq!(mapping, vec![Field::Instructions, Field::ExpectedResults]);
+        table.apply_overrides(&mut mapping, "2=ac").unwrap();
+        let imported = table.import(&mapping);
+        assert_eq!(imported.items[0].ac, "b");
+    }
+
+    #[test]
+    fn attributes_after_multibyte_values() {
+        let attrs = "title=\"İSTANBUL İİ\" COLSPAN=\"3\"";
+        assert_eq!(attr(attrs, "colspan"), Some("3".to_string()));
+        assert_eq!(attr(attrs, "title"), Some("İSTANBUL İİ".to_string()));
+    }
+}
@@ -0,0 +1,357 @@
+use crate::test_step::TestStep;
+
+// steps as people type them in chat: numbered or bulleted lists, with the expected result
+// after an arrow, an "expect" or a "should" clause. anything the heuristics get wrong is
+// caught in the preview before it's inserted
+
+const BULLETS: [&str; 8] = ["- [ ]", "- [x]", "* [ ]", "-", "*", "+", "•", "◦"];
+const ARROWS: [&str; 4] = ["→", "->", "=>", "⇒"];
+const EXPECT_WORDS: [&str; 4] = ["expect", "expects", "expected", "expecting"];
+// what comes between "expect" and the result itself
+const EXPECT_FILLERS: [&str; 4] = ["result", "results", "that", "to see"];
+// where the clause holding a "should" starts
+const CLAUSE_BREAKS: [&str; 8] = ["\n", ". ", "; ", ", ", " - ", " — ", " – ", " and "];
+const SEPARATORS: &[char] = &['—', '–', '-', ',', ';', ':'];
+// left dangling when an expectation is cut off the end
+const CONJUNCTIONS: [&str; 2] = [" and", " then"];
+const HEADING_WORDS: usize = 6;
+
+// a marker only counts with a space after it, "-5" or "1.5" aren't items
+fn after_marker(rest: &str) -> Option<&str> {
+    rest.starts_with(char::is_whitespace)
+        .then(|| rest.trim_start())
+}
+
+// Some(rest) when `line` opens a list item: "1.", "2)", "(3)", "Step 4:", or a bullet
+fn list_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    for bullet in BULLETS {
+        if let Some(rest) = line.strip_prefix(bullet).and_then(after_marker) {
+            return Some(rest);
+        }
+    }
+    let numbered = match line.get(..5) {
+        Some(step) if step.eq_ignore_ascii_case("step ") => line[5..].trim_start(),
+        _ => line.strip_prefix('(').unwrap_or(line),
+    };
+    let digits = numbered.len()
+        - numbered
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    if digits == 0 || digits > 3 {
+        return None;
+    }
+    let rest = &numbered[digits..];
+    rest.strip_prefix(['.', ')', ':'])
+        .or_else(|| rest.strip_prefix(" -"))
+        .and_then(after_marker)
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+// "Login:" on a line of its own or a markdown heading, a section between the lists. a
+// sentence ending in a colon is chatter, not a heading
+fn heading(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let title = match line.strip_prefix('#') {
+        Some(rest) => rest.trim_start_matches('#').trim(),
+        None => {
+            let title = line.strip_suffix(':')?.trim();
+            if title.contains([',', '.']) || title.split_whitespace().count() > HEADING_WORDS {
+                return None;
+            }
+            title
+        }
+    };
+    (!title.is_empty()).then_some(title)
+}
+
+// the words of `text` with their byte offsets
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric() || c == '\'', start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                words.push((from, &text[from..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn is_any(word: &str, list: &[&str]) -> bool {
+    list.iter()
+        .any(|candidate| word.eq_ignore_ascii_case(candidate))
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn tidy(instructions: &str, expected: &str) -> (String, String) {
+    let mut instructions =
+        instructions.trim_end_matches(|c: char| c.is_whitespace() || SEPARATORS.contains(&c));
+    for conjunction in CONJUNCTIONS {
+        let cut = instructions.len().saturating_sub(conjunction.len());
+        if instructions
+            .get(cut..)
+            .is_some_and(|end| end.eq_ignore_ascii_case(conjunction))
+        {
+            instructions = instructions[..cut]
+                .trim_end_matches(|c: char| c.is_whitespace() || SEPARATORS.contains(&c));
+        }
+    }
+    let expected =
+        expected.trim_start_matches(|c: char| c.is_whitespace() || SEPARATORS.contains(&c));
+    (instructions.to_string(), capitalize(expected.trim_end()))
+}
+
+// instructions and expected results out of one item, the whole item is instructions when
+// nothing in it reads like an expectation
+pub fn split_expected(text: &str) -> (String, String) {
+    let text = text.trim();
+    let arrow = ARROWS
+        .iter()
+        .filter_map(|arrow| text.find(arrow).map(|at| (at, arrow.len())))
+        .min();
+    if let Some((at, len)) = arrow
+        && at > 0
+    {
+        return tidy(&text[..at], &text[at + len..]);
+    }
+    let words = words(text);
+    for (idx, (at, word)) in words.iter().enumerate() {
+        if is_any(word, &EXPECT_WORDS) {
+            // "expected result:", "expect that", "expect to see"
+            let mut end = at + word.len();
+            for (next_at, next) in &words[idx + 1..] {
+                // the "see" of "to see"
+                if *next_at < end {
+                    continue;
+                }
+                let between = &text[end..*next_at];
+                let filler = is_any(next, &EXPECT_FILLERS)
+                    || (next.eq_ignore_ascii_case("to")
+                        && text[*next_at..].to_lowercase().starts_with("to see"));
+                if !filler || between.contains(|c: char| !c.is_whitespace() && c != ':') {
+                    break;
+                }
+                end = match next.eq_ignore_ascii_case("to") {
+                    true => next_at + "to see".len(),
+                    false => next_at + next.len(),
+                };
+            }
+            return tidy(&text[..*at], &text[end..]);
+        }
+        if word.eq_ignore_ascii_case("should") {
+            let Some((start, len)) = CLAUSE_BREAKS
+                .iter()
+                .filter_map(|clause| text[..*at].rfind(clause).map(|found| (found, clause.len())))
+                .max()
+            else {
+                break;
+            };
+            return tidy(&text[..start], &text[start + len..]);
+        }
+    }
+    (text.to_string(), String::new())
+}
+
+fn step(text: &str) -> TestStep {
+    let (instructions, expected) = split_expected(text);
+    let mut step = TestStep::new().with_instructions(&instructions);
+    step.expected_results = expected;
+    step
+}
+
+// one step per list item, lines under an item belong to it. with no list in the text
+// every line is a step
+pub fn parse(text: &str) -> Result<Vec<TestStep>, String> {
+    let text = text.replace("\r\n", "\n");
+    let lines: Vec<&str> = text.lines().collect();
+    let top = lines
+        .iter()
+        .filter(|line| list_item(line).is_some())
+        .map(|line| indent(line))
+        .min();
+
+    let mut steps = Vec::new();
+    let mut current: Option<String> = None;
+    let mut after_blank = true;
+    for line in &lines {
+        if line.trim().is_empty() {
+            after_blank = true;
+            continue;
+        }
+        let item = match top {
+            Some(top) if indent(line) <= top => list_item(line),
+            Some(_) => None,
+            None => Some(line.trim()),
+        };
+        let section = match (top, item) {
+            (None, _) => heading(line),
+            (Some(_), None) if current.is_none() || after_blank => heading(line),
+            _ => None,
+        }
+        .or_else(|| {
+            line.trim_start()
+                .starts_with('#')
+                .then(|| heading(line))
+                .flatten()
+        });
+        after_blank = false;
+        if let Some(title) = section {
+            steps.extend(current.take().map(|text| step(&text)));
+            steps.push(TestStep::section(title));
+            continue;
+        }
+        match (item, &mut current) {
+            (Some(item), _) => {
+                steps.extend(current.replace(item.to_string()).map(|text| step(&text)));
+            }
+            (None, Some(text)) => {
+                text.push('\n');
+                text.push_str(line.trim());
+            }
+            // chatter before the first item
+            (None, None) => {}
+        }
+    }
+    steps.extend(current.map(|text| step(&text)));
+    if !steps.iter().any(TestStep::is_step) {
+        return Err("No list items or lines to make steps from".to_string());
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(text: &str) -> Vec<(String, String)> {
+        parse(text)
+            .unwrap()
+            .iter()
+            .map(|step| match step.is_new_section {
+                true => (format!("# {}", step.instructions), String::new()),
+                false => (step.instructions.clone(), step.expected_results.clone()),
+            })
+            .collect()
+    }
+
+    fn owned(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expectations_are_split_off() {
+        let cases = [
+            ("click save — expect a toast", ("click save", "A toast")),
+            (
+                "click save -> toast appears",
+                ("click save", "Toast appears"),
+            ),
+            (
+                "Open settings → Settings page loads",
+                ("Open settings", "Settings page loads"),
+            ),
+            (
+                "log out, you should land on the login page",
+                ("log out", "You should land on the login page"),
+            ),
+            (
+                "Submit the form. Expected result: a confirmation email",
+                ("Submit the form.", "A confirmation email"),
+            ),
+            (
+                "enter a wrong password and expect to see an error",
+                ("enter a wrong password", "An error"),
+            ),
+            (
+                "refresh the page; the cart should still have 2 items",
+                ("refresh the page", "The cart should still have 2 items"),
+            ),
+            ("Expected: nothing happens", ("", "Nothing happens")),
+            // no clause to cut at, and words that only contain a keyword
+            ("Dashboard should load", ("Dashboard should load", "")),
+            (
+                "handle the unexpected popup",
+                ("handle the unexpected popup", ""),
+            ),
+            ("open the page", ("open the page", "")),
+        ];
+        for (input, (instructions, expected)) in cases {
+            assert_eq!(
+                split_expected(input),
+                (instructions.to_string(), expected.to_string()),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn slack_lists_become_steps() {
+        let paste = "hey, here are the steps for the release check:\n\n1. open the page\n2. click save — expect a toast\n3) sign out\n   you should see the login page again\n";
+        assert_eq!(
+            pairs(paste),
+            owned(&[
+                ("open the page", ""),
+                ("click save", "A toast"),
+                ("sign out", "You should see the login page again"),
+            ])
+        );
+
+        let bullets = "• open the app\n• tap Login → login form shows\n    - with a valid user\n• close the app";
+        assert_eq!(
+            pairs(bullets),
+            owned(&[
+                ("open the app", ""),
+                ("tap Login", "Login form shows\n- with a valid user"),
+                ("close the app", ""),
+            ])
+        );
+
+        let sections = "Login:\n1. open /login\n2. sign in => dashboard\n\n## Checkout\nStep 1: add an item\nStep 2: pay, the order should be confirmed\r\n";
+        assert_eq!(
+            pairs(sections),
+            owned(&[
+                ("# Login", ""),
+                ("open /login", ""),
+                ("sign in", "Dashboard"),
+                ("# Checkout", ""),
+                ("add an item", ""),
+                ("pay", "The order should be confirmed"),
+            ])
+        );
+    }
+
+    #[test]
+    fn text_without_a_list_is_a_step_per_line() {
+        assert_eq!(
+            pairs("open the page\nclick save, a toast should show\n\n"),
+            owned(&[("open the page", ""), ("click save", "A toast should show")])
+        );
+        // a year or a long number isn't an item marker
+        assert_eq!(list_item("2024. was a year"), None);
+        assert_eq!(list_item("12345) nope"), None);
+        assert_eq!(list_item("-dash without space"), None);
+        assert_eq!(list_item("3 eggs"), None);
+        assert_eq!(list_item("(2) second"), Some("second"));
+        assert!(parse("\n  \n").is_err());
+        assert!(parse("Login:\n").is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Instructions,
+    TestData,
+    #[serde(rename = "expected", alias = "expected_results")]
+    ExpectedResults,
+    Ac,
+    Status,
+    Comment,
+    Ignore,
+}
+
+// in the order the mapping overlay lists them
+pub const FIELDS: [Field; 7] = [
+    Field::Instructions,
+    Field::ExpectedResults,
+    Field::Ac,
+    Field::TestData,
+    Field::Status,
+    Field::Comment,
+    Field::Ignore,
+];
+
+// a table with no column for these can't make a step
+const REQUIRED: [Field; 2] = [Field::Instructions, Field::ExpectedResults];
+
+impl Field {
+    pub fn parse(name: &str) -> Result<Field, String> {
+        match name.to_lowercase().as_str() {
+            "instructions" | "action" => Ok(Field::Instructions),
+            "test_data" | "data" => Ok(Field::TestData),
+            "expected" | "expected_results" => Ok(Field::ExpectedResults),
+            "ac" => Ok(Field::Ac),
+            "status" => Ok(Field::Status),
+            "comment" => Ok(Field::Comment),
+            "ignore" | "-" => Ok(Field::Ignore),
+            _ => Err(format!(
+                "Unknown field: {} (expected instructions|test_data|expected|ac|status|comment|ignore)",
+                name
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Instructions => "instructions",
+            Field::TestData => "test_data",
+            Field::ExpectedResults => "expected",
+            Field::Ac => "ac",
+            Field::Status => "status",
+            Field::Comment => "comment",
+            Field::Ignore => "ignore",
+        }
+    }
+
+    pub fn guess(header: &str) -> Option<Field> {
+        let header = header.to_lowercase();
+        let has_word = |word: &str| {
+            header
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|w| w == word)
+        };
+        if header.contains("expected") || header.contains("result") {
+            Some(Field::ExpectedResults)
+        } else if header.contains("acceptance") || has_word("ac") {
+            Some(Field::Ac)
+        } else if header.contains("data") {
+            Some(Field::TestData)
+        } else if header.contains("pass") || header.contains("status") {
+            Some(Field::Status)
+        } else if header.contains("comment") || header.contains("note") {
+            Some(Field::Comment)
+        } else if header.contains("action")
+            || header.contains("instruction")
+            || header.contains("description")
+        {
+            Some(Field::Instructions)
+        } else if matches!(header.trim(), "step" | "#" | "no." | "no") {
+            Some(Field::Ignore)
+        } else if header.contains("step") {
+            Some(Field::Instructions)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn guess(headers: &[String]) -> Vec<Field> {
+    let mut mapping: Vec<Field> = headers
+        .iter()
+        .map(|h| Field::guess(h).unwrap_or(Field::Ignore))
+        .collect();
+    // nothing recognisable, assume action, expected and ac in order
+    if !mapping.contains(&Field::Instructions) {
+        let mut defaults = [Field::Instructions, Field::ExpectedResults, Field::Ac].into_iter();
+        for field in mapping.iter_mut().filter(|f| **f == Field::Ignore) {
+            match defaults.next() {
+                Some(default) => *field = default,
+                None => break,
+            }
+        }
+    }
+    mapping
+}
+
+// the key a mapping is remembered under in config, the same spreadsheet exported again
+// matches even if a header's case or spacing changed
+pub fn fingerprint(headers: &[String]) -> String {
+    headers
+        .iter()
+        .map(|header| {
+            header
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+// the mapping to start from and whether it can be used without asking: one remembered
+// for these headers, or a guess that recognised every header and covers the required
+// fields
+pub fn resolve(headers: &[String], remembered: &HashMap<String, Vec<Field>>) -> (Vec<Field>, bool) {
+    if let Some(mapping) = remembered
+        .get(&fingerprint(headers))
+        .filter(|mapping| mapping.len() == headers.len())
+    {
+        return (mapping.clone(), true);
+    }
+    let mapping = guess(headers);
+    let recognised = headers.iter().all(|h| Field::guess(h).is_some());
+    let confident = recognised && check(&mapping).is_ok();
+    (mapping, confident)
+}
+
+pub fn check(mapping: &[Field]) -> Result<(), String> {
+    let missing: Vec<&str> = REQUIRED
+        .iter()
+        .filter(|field| !mapping.contains(field))
+        .map(Field::name)
+        .collect();
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(format!("No column is mapped to {}", missing.join(" or "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn known_headers_map_without_asking() {
+        let known = headers(&["#", "Action", "Expected Result", "AC"]);
+        let (mapping, confident) = resolve(&known, &HashMap::new());
+        assert_eq!(
+            mapping,
+            vec![
+                Field::Ignore,
+                Field::Instructions,
+                Field::ExpectedResults,
+                Field::Ac
+            ]
+        );
+        assert!(confident);
+
+        // recognised but nothing to put in expected
+        let (_, confident) = resolve(&headers(&["Action", "Notes"]), &HashMap::new());
+        assert!(!confident);
+        let (mapping, confident) = resolve(&headers(&["Was", "Soll"]), &HashMap::new());
+        assert_eq!(mapping, vec![Field::Instructions, Field::ExpectedResults]);
+        assert!(!confident);
+    }
+
+    #[test]
+    fn remembered_mapping_wins() {
+        let sheet = headers(&["Was", "Soll", "Owner"]);
+        let remembered = HashMap::from([(
+            "was | soll | owner".to_string(),
+            vec![Field::Instructions, Field::ExpectedResults, Field::Ignore],
+        )]);
+        let (mapping, confident) = resolve(&sheet, &remembered);
+        assert_eq!(mapping[2], Field::Ignore);
+        assert!(confident);
+        assert_eq!(
+            fingerprint(&headers(&[" WAS", "Soll ", "owner"])),
+            fingerprint(&sheet)
+        );
+        // a column added since means asking again
+        let wider = headers(&["Was", "Soll", "Owner", "Due"]);
+        assert!(!resolve(&wider, &remembered).1);
+    }
+
+    #[test]
+    fn required_fields_block_the_import() {
+        assert_eq!(
+            check(&[Field::Instructions, Field::ExpectedResults]),
+            Ok(())
+        );
+        assert_eq!(
+            check(&[Field::Instructions, Field::Ac]),
+            Err("No column is mapped to expected".to_string())
+        );
+        assert_eq!(
+            check(&[Field::Ignore]),
+            Err("No column is mapped to instructions or expected".to_string())
+        );
+    }
+
+    #[test]
+    fn fields_are_stored_by_name() {
+        for field in FIELDS {
+            let stored = serde_json::to_string(&field).unwrap();
+            assert_eq!(stored, format!("\"{}\"", field.name()));
+            assert_eq!(Field::parse(field.name()), Ok(field));
+        }
+        let old: Field = serde_json::from_str("\"expected_results\"").unwrap();
+        assert_eq!(old, Field::ExpectedResults);
+    }
+}
@@ -0,0 +1,143 @@
+use super::html::Table;
+use crate::err_msg::WithErrMsg;
+use calamine::{Reader, open_workbook_auto};
+use std::path::Path;
+
+const WORKBOOK_EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+pub fn is_sheet(path: &str) -> bool {
+    let ext = extension(path);
+    ext == "csv" || ext == "tsv" || WORKBOOK_EXTENSIONS.contains(&ext.as_str())
+}
+
+// whichever of , ; and tab the header line has most of, spreadsheets in some locales
+// write ; by default
+fn delimiter(csv: &str) -> char {
+    let header = csv.lines().next().unwrap_or("");
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d).count())
+        .filter(|d| header.contains(*d))
+        .unwrap_or(',')
+}
+
+// rfc 4180: quoted fields may hold the delimiter, newlines and "" for a quote
+pub fn parse_csv(csv: &str, delimiter: char) -> Vec<Vec<String>> {
+    let csv = csv.strip_prefix('\u{feff}').unwrap_or(csv);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+// the first worksheet, dates and numbers as the spreadsheet displays them
+fn read_workbook(path: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook =
+        open_workbook_auto(path).with_err_msg(&format!("Failed to open {}", path))?;
+    let name = workbook
+        .sheet_names()
+        .into_iter()
+        .next()
+        .ok_or(format!("{} has no sheets", path))?;
+    let range = workbook
+        .worksheet_range(&name)
+        .with_err_msg(&format!("Failed to read sheet {} of {}", name, path))?;
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect())
+}
+
+pub fn read(path: &str) -> Result<Table, String> {
+    let rows = match extension(path).as_str() {
+        "csv" | "tsv" => {
+            let csv =
+                std::fs::read_to_string(path).with_err_msg(&format!("Failed to read {}", path))?;
+            parse_csv(&csv, delimiter(&csv))
+        }
+        _ => read_workbook(path)?,
+    };
+    Table::from_rows(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::mapping::{self, Field};
+
+    #[test]
+    fn quoted_fields_keep_newlines_and_quotes() {
+        let csv = "\u{feff}Action;Expected\r\n\"Open \"\"Settings\"\"\n1. tap gear\";shown; twice\r\n\r\n";
+        assert_eq!(delimiter(csv), ';');
+        assert_eq!(
+            parse_csv(csv, ';'),
+            vec![
+                vec!["Action", "Expected"],
+                vec!["Open \"Settings\"\n1. tap gear", "shown", " twice"],
+                vec![""],
+            ]
+        );
+        assert_eq!(parse_csv("a,b", ','), vec![vec!["a", "b"]]);
+        assert_eq!(delimiter("one column\nx"), ',');
+    }
+
+    #[test]
+    fn csv_rows_import_as_steps() {
+        let csv = "Was,Soll,Owner\n\"Open app\n- as admin\",Home shown,qa\n,,\n";
+        let table = Table::from_rows(parse_csv(csv, ',')).unwrap();
+        assert_eq!(table.headers, vec!["Was", "Soll", "Owner"]);
+        assert_eq!(table.samples(2, 3), vec!["qa"]);
+        let mut fields = mapping::guess(&table.headers);
+        fields[2] = Field::Ignore;
+        let imported = table.import(&fields);
+        assert_eq!(imported.items.len(), 1);
+        assert_eq!(imported.items[0].instructions, "Open app\n- as admin");
+        assert_eq!(imported.items[0].expected_results, "Home shown");
+    }
+
+    #[test]
+    fn reads_the_first_worksheet() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/steps.xlsx");
+        assert!(is_sheet(path));
+        let table = read(path).unwrap();
+        assert_eq!(
+            table.headers,
+            vec!["Step", "Action", "Expected Result", "Est"]
+        );
+        let imported = table.import(&table.guess_mapping());
+        assert_eq!(imported.items.len(), 2);
+        assert_eq!(imported.items[1].instructions, "Log in\nas admin");
+        assert_eq!(imported.items[1].expected_results, "Dashboard");
+    }
+}
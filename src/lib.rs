@@ -0,0 +1,75 @@
+mod app;
+mod archive;
+mod backup;
+// entry points for benches/, not a stable api
+#[doc(hidden)]
+pub mod bench;
+mod cli;
+mod colors;
+mod config;
+mod convert;
+mod crypto;
+mod diff;
+mod edit_session;
+mod embedding;
+mod err_msg;
+mod export;
+mod filter;
+mod fuzzy;
+mod git;
+mod images;
+mod import;
+mod locale;
+mod normalize;
+mod placeholders;
+mod query;
+mod review;
+mod scaffold;
+mod schema;
+mod serve;
+mod shell;
+mod signals;
+mod spellcheck;
+mod stress;
+mod test_step;
+#[cfg(test)]
+mod tests;
+mod text_input;
+mod timestamp;
+mod transform;
+mod validate;
+mod words;
+
+use crate::{app::App, cli::CliCommand, signals::Signals};
+
+// the binary only calls this, the crate is a library so benches/ can link it
+pub fn run() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, overrides) = CliCommand::parse(&args)?;
+    config::set_overrides(overrides);
+    // a broken config is reported by whatever loads it next
+    let config = config::Config::load_config().ok();
+    let options = match command {
+        CliCommand::Tui(options) => options,
+        command => {
+            // an encrypted file read without a passphrase says so where it's read
+            if let Some(config) = &config {
+                let _ = crypto::unlock(config, false);
+            }
+            return command.run();
+        }
+    };
+    if let Some(config) = &config {
+        crypto::unlock(config, true)?;
+    }
+
+    let signals = Signals::install();
+    let terminal = ratatui::init();
+    let app_result = App::new(signals.clone(), options).map(|mut app| app.run(terminal));
+    ratatui::restore();
+    eprintln!("Final App State: {:#?}", app_result);
+    if let Some(code) = signals.exit_code() {
+        std::process::exit(code);
+    }
+    Ok(())
+}
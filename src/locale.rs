@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+
+// the language of the tui's fixed strings, the markdown headings steps are edited under
+// and the exported column titles. step content is never translated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+pub const LOCALES: [Locale; 2] = [Locale::En, Locale::Fr];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    // markdown headings, without the leading "# "
+    Instructions,
+    NewSection,
+    CommentSection,
+    TestData,
+    ExpectedResults,
+    Ac,
+    Links,
+    Estimate,
+    // exported column titles
+    ColumnStep,
+    ColumnPass,
+    ColumnAction,
+    ColumnComments,
+    ColumnEstimate,
+    ColumnAuthor,
+    ColumnAc,
+    // the tui table
+    TestDirections,
+    TemplateName,
+    Status,
+    Priority,
+    Comment,
+    // footer
+    Compiled,
+    Yanked,
+    Loaded,
+    HintQuit,
+    HintMoveUpDown,
+    HintEdit,
+    HintCompile,
+    HintCopyPlainText,
+    HintLoadClipboard,
+    HintInsertAboveBelow,
+    HintBlankAboveBelow,
+    HintDeleteToRegister,
+    HintPasteAboveBelow,
+    HintTemplates,
+    HintCommand,
+    HintDetails,
+    HintPeek,
+    HintMove,
+    HintClearAll,
+    HintDrop,
+    HintCancel,
+    HintMoveTopBottom,
+    HintSave,
+    HintNewLine,
+    HintStartEndOfLine,
+    HintBack,
+    HintLoad,
+    HintSaveAsTemplate,
+    HintSaveBuiltinAsMine,
+    HintInsertBelowCursor,
+    HintRename,
+    HintDelete,
+}
+
+// every key, for the completeness test
+#[cfg(test)]
+const KEYS: [Key; 52] = [
+    Key::Instructions,
+    Key::NewSection,
+    Key::CommentSection,
+    Key::TestData,
+    Key::ExpectedResults,
+    Key::Ac,
+    Key::Links,
+    Key::Estimate,
+    Key::ColumnStep,
+    Key::ColumnPass,
+    Key::ColumnAction,
+    Key::ColumnComments,
+    Key::ColumnEstimate,
+    Key::ColumnAuthor,
+    Key::ColumnAc,
+    Key::TestDirections,
+    Key::TemplateName,
+    Key::Status,
+    Key::Priority,
+    Key::Comment,
+    Key::Compiled,
+    Key::Yanked,
+    Key::Loaded,
+    Key::HintQuit,
+    Key::HintMoveUpDown,
+    Key::HintEdit,
+    Key::HintCompile,
+    Key::HintCopyPlainText,
+    Key::HintLoadClipboard,
+    Key::HintInsertAboveBelow,
+    Key::HintBlankAboveBelow,
+    Key::HintDeleteToRegister,
+    Key::HintPasteAboveBelow,
+    Key::HintTemplates,
+    Key::HintCommand,
+    Key::HintDetails,
+    Key::HintPeek,
+    Key::HintMove,
+    Key::HintClearAll,
+    Key::HintDrop,
+    Key::HintCancel,
+    Key::HintMoveTopBottom,
+    Key::HintSave,
+    Key::HintNewLine,
+    Key::HintStartEndOfLine,
+    Key::HintBack,
+    Key::HintLoad,
+    Key::HintSaveAsTemplate,
+    Key::HintSaveBuiltinAsMine,
+    Key::HintInsertBelowCursor,
+    Key::HintRename,
+    Key::HintDelete,
+];
+
+const EN: &[(Key, &str)] = &[
+    (Key::Instructions, "Instructions"),
+    (Key::NewSection, "New Section"),
+    (Key::CommentSection, "Comment Section"),
+    (Key::TestData, "Test Data"),
+    (Key::ExpectedResults, "Expected Results"),
+    (Key::Ac, "AC"),
+    (Key::Links, "Links"),
+    (Key::Estimate, "Estimate"),
+    (Key::ColumnStep, "Step"),
+    (Key::ColumnPass, "Pass/Fail"),
+    (Key::ColumnAction, "Action"),
+    (Key::ColumnComments, "Comments"),
+    (Key::ColumnEstimate, "Est."),
+    (Key::ColumnAuthor, "Author"),
+    (Key::ColumnAc, "Acceptance Criteria #"),
+    (Key::TestDirections, "Test Directions"),
+    (Key::TemplateName, "Template Name"),
+    (Key::Status, "Status"),
+    (Key::Priority, "Priority"),
+    (Key::Comment, "Comment"),
+    (Key::Compiled, "COMPILED HTML COPIED TO CLIPBOARD"),
+    (Key::Yanked, "YANKED TO REGISTER"),
+    (Key::Loaded, "LOADED CONTEXT FROM CLIPBOARD"),
+    (Key::HintQuit, "quit"),
+    (Key::HintMoveUpDown, "move up/down"),
+    (Key::HintEdit, "edit"),
+    (Key::HintCompile, "export"),
+    (Key::HintCopyPlainText, "copy plain text"),
+    (Key::HintLoadClipboard, "load from clipboard"),
+    (Key::HintInsertAboveBelow, "insert above/below"),
+    (Key::HintBlankAboveBelow, "blank above/below"),
+    (Key::HintDeleteToRegister, "delete to reg"),
+    (Key::HintPasteAboveBelow, "paste above/below"),
+    (Key::HintTemplates, "templates & config"),
+    (Key::HintCommand, "command"),
+    (Key::HintDetails, "details"),
+    (Key::HintPeek, "peek"),
+    (Key::HintMove, "move"),
+    (Key::HintClearAll, "clear all"),
+    (Key::HintDrop, "drop"),
+    (Key::HintCancel, "cancel"),
+    (Key::HintMoveTopBottom, "move to top/bottom"),
+    (Key::HintSave, "save"),
+    (Key::HintNewLine, "new line"),
+    (Key::HintStartEndOfLine, "start/end of line"),
+    (Key::HintBack, "back"),
+    (Key::HintLoad, "load"),
+    (Key::HintSaveAsTemplate, "save current table as template"),
+    (Key::HintSaveBuiltinAsMine, "save built-in as mine"),
+    (Key::HintInsertBelowCursor, "insert below cursor"),
+    (Key::HintRename, "rename"),
+    (Key::HintDelete, "delete"),
+];
+
+const FR: &[(Key, &str)] = &[
+    (Key::Instructions, "Instructions"),
+    (Key::NewSection, "Nouvelle section"),
+    (Key::CommentSection, "Section de commentaire"),
+    (Key::TestData, "Données de test"),
+    (Key::ExpectedResults, "Résultats attendus"),
+    (Key::Ac, "CA"),
+    (Key::Links, "Liens"),
+    (Key::Estimate, "Estimation"),
+    (Key::ColumnStep, "Étape"),
+    (Key::ColumnPass, "Réussi/Échoué"),
+    (Key::ColumnAction, "Action"),
+    (Key::ColumnComments, "Commentaires"),
+    (Key::ColumnEstimate, "Est."),
+    (Key::ColumnAuthor, "Auteur"),
+    (Key::ColumnAc, "Critère d'acceptation n°"),
+    (Key::TestDirections, "Directives de test"),
+    (Key::TemplateName, "Nom du modèle"),
+    (Key::Status, "Statut"),
+    (Key::Priority, "Priorité"),
+    (Key::Comment, "Commentaire"),
+    (Key::Compiled, "HTML COMPILÉ COPIÉ DANS LE PRESSE-PAPIERS"),
+    (Key::Yanked, "COPIÉ DANS LE REGISTRE"),
+    (Key::Loaded, "CONTENU CHARGÉ DEPUIS LE PRESSE-PAPIERS"),
+    (Key::HintQuit, "quitter"),
+    (Key::HintMoveUpDown, "monter/descendre"),
+    (Key::HintEdit, "modifier"),
+    (Key::HintCompile, "exporter"),
+    (Key::HintCopyPlainText, "copier en texte brut"),
+    (Key::HintLoadClipboard, "charger du presse-papiers"),
+    (Key::HintInsertAboveBelow, "insérer au-dessus/dessous"),
+    (Key::HintBlankAboveBelow, "vide au-dessus/dessous"),
+    (Key::HintDeleteToRegister, "supprimer vers registre"),
+    (Key::HintPasteAboveBelow, "coller au-dessus/dessous"),
+    (Key::HintTemplates, "modèles et config"),
+    (Key::HintCommand, "commande"),
+    (Key::HintDetails, "détails"),
+    (Key::HintPeek, "aperçu"),
+    (Key::HintMove, "déplacer"),
+    (Key::HintClearAll, "tout effacer"),
+    (Key::HintDrop, "déposer"),
+    (Key::HintCancel, "annuler"),
+    (Key::HintMoveTopBottom, "aller en haut/bas"),
+    (Key::HintSave, "enregistrer"),
+    (Key::HintNewLine, "nouvelle ligne"),
+    (Key::HintStartEndOfLine, "début/fin de ligne"),
+    (Key::HintBack, "retour"),
+    (Key::HintLoad, "charger"),
+    (
+        Key::HintSaveAsTemplate,
+        "enregistrer le tableau comme modèle",
+    ),
+    (Key::HintSaveBuiltinAsMine, "copier le modèle intégré"),
+    (Key::HintInsertBelowCursor, "insérer sous le curseur"),
+    (Key::HintRename, "renommer"),
+    (Key::HintDelete, "supprimer"),
+];
+
+impl Locale {
+    fn table(self) -> &'static [(Key, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Fr => FR,
+        }
+    }
+
+    // a key missing from a locale falls back to english rather than showing nothing
+    pub fn text(self, key: Key) -> &'static str {
+        [self.table(), EN]
+            .into_iter()
+            .flatten()
+            .find(|(k, _)| *k == key)
+            .map_or("", |(_, text)| text)
+    }
+}
+
+// the same key in every locale, for reading back text written in any of them
+pub fn all(key: Key) -> impl Iterator<Item = &'static str> {
+    LOCALES.into_iter().map(move |locale| locale.text(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_is_in_every_locale() {
+        for locale in LOCALES {
+            let table = locale.table();
+            for key in KEYS {
+                let found = table.iter().filter(|(k, _)| *k == key).count();
+                assert_eq!(found, 1, "{:?} has {} entries for {:?}", locale, found, key);
+            }
+            assert_eq!(table.len(), KEYS.len(), "{:?} has unknown keys", locale);
+            assert!(table.iter().all(|(_, text)| !text.trim().is_empty()));
+        }
+    }
+
+    #[test]
+    fn headings_differ_between_locales() {
+        // parse_markdown tells the kinds of heading apart in every locale at once, so no
+        // heading may read as a different heading in another locale
+        let headings = [
+            Key::Instructions,
+            Key::NewSection,
+            Key::CommentSection,
+            Key::TestData,
+            Key::ExpectedResults,
+            Key::Ac,
+            Key::Links,
+            Key::Estimate,
+        ];
+        for (idx, key) in headings.iter().enumerate() {
+            for other in &headings[idx + 1..] {
+                for text in all(*key) {
+                    assert!(
+                        all(*other).all(|o| !o.eq_ignore_ascii_case(text)),
+                        "{:?} and {:?} share {}",
+                        key,
+                        other,
+                        text
+                    );
+                }
+            }
+        }
+    }
+}
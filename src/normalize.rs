@@ -0,0 +1,127 @@
+use crate::test_step::TestStep;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub removed: usize,
+    pub fields: usize,
+    pub titles: usize,
+}
+
+impl Summary {
+    pub fn is_empty(&self) -> bool {
+        *self == Summary::default()
+    }
+}
+
+// trailing whitespace goes from every line and any run of blank lines becomes a single
+// one, a field that's only whitespace ends up empty
+pub fn clean_field(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+// first letter of every word upper cased, the rest left alone so acronyms survive
+pub fn title_case(title: &str) -> String {
+    title
+        .split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// a step with nothing in it at all, not even a status, priority or comment
+fn is_empty_step(item: &TestStep) -> bool {
+    item.is_blank()
+        && item.status.is_none()
+        && item.priority.is_none()
+        && item.comment.trim().is_empty()
+        && item.links.is_empty()
+}
+
+pub fn normalize(items: &mut Vec<TestStep>, title_case_sections: bool) -> Summary {
+    let mut summary = Summary::default();
+    let before = items.len();
+    items.retain(|item| !is_empty_step(item));
+    summary.removed = before - items.len();
+
+    for item in items.iter_mut() {
+        for field in [
+            &mut item.instructions,
+            &mut item.test_data,
+            &mut item.expected_results,
+            &mut item.ac,
+            &mut item.comment,
+        ] {
+            let cleaned = clean_field(field);
+            if cleaned != *field {
+                *field = cleaned;
+                summary.fields += 1;
+            }
+        }
+        if title_case_sections && item.is_new_section {
+            let titled = title_case(&item.instructions);
+            if titled != item.instructions {
+                item.instructions = titled;
+                summary.titles += 1;
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_lose_trailing_space_and_extra_blank_lines() {
+        assert_eq!(clean_field("a  \n\n\n\nb\t\n"), "a\n\nb");
+        assert_eq!(clean_field("a\n\nb"), "a\n\nb");
+        assert_eq!(clean_field("  \n \n"), "");
+        assert_eq!(clean_field("  indented"), "  indented");
+    }
+
+    #[test]
+    fn titles_keep_acronyms() {
+        assert_eq!(title_case("login via SSO"), "Login Via SSO");
+        assert_eq!(title_case("über  setup"), "Über  Setup");
+    }
+
+    #[test]
+    fn empty_steps_are_removed_and_counted() {
+        let mut items = vec![
+            TestStep::section("setup"),
+            TestStep::new().with_instructions("   \n "),
+            TestStep::new().with_instructions("log in  "),
+            TestStep::comment(""),
+        ];
+        let summary = normalize(&mut items, true);
+        assert_eq!(
+            summary,
+            Summary {
+                removed: 1,
+                fields: 1,
+                titles: 1
+            }
+        );
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].instructions, "Setup");
+        assert_eq!(items[1].instructions, "log in");
+        assert!(normalize(&mut items, true).is_empty());
+    }
+}
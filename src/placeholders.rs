@@ -0,0 +1,88 @@
+use crate::test_step::TestStep;
+use std::collections::HashMap;
+
+// `{{name}}` markers in a template or snippet, filled in when it is inserted
+fn scan(text: &str, mut found: impl FnMut(&str, std::ops::Range<usize>)) {
+    let mut rest = 0;
+    while let Some(start) = text[rest..].find("{{").map(|i| rest + i) {
+        let Some(end) = text[start + 2..].find("}}").map(|i| start + 2 + i) else {
+            return;
+        };
+        let name = text[start + 2..end].trim();
+        if !name.is_empty() && !name.contains(['{', '\n']) {
+            found(name, start..end + 2);
+            rest = end + 2;
+        } else {
+            rest = start + 2;
+        }
+    }
+}
+
+fn fields(step: &mut TestStep) -> [&mut String; 5] {
+    [
+        &mut step.instructions,
+        &mut step.test_data,
+        &mut step.expected_results,
+        &mut step.ac,
+        &mut step.comment,
+    ]
+}
+
+// every distinct placeholder name, in the order they first appear
+pub fn names(steps: &[TestStep]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for step in steps {
+        let texts = [
+            &step.instructions,
+            &step.test_data,
+            &step.expected_results,
+            &step.ac,
+            &step.comment,
+        ];
+        for text in texts {
+            scan(text, |name, _| {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            });
+        }
+    }
+    names
+}
+
+// placeholders without a value are left as they were
+pub fn fill(steps: &mut [TestStep], values: &HashMap<String, String>) {
+    for step in steps {
+        for field in fields(step) {
+            let mut ranges = Vec::new();
+            scan(field, |name, range| {
+                if let Some(value) = values.get(name) {
+                    ranges.push((range, value.clone()));
+                }
+            });
+            for (range, value) in ranges.into_iter().rev() {
+                field.replace_range(range, &value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_found_once_in_order() {
+        let mut step = TestStep::new().with_instructions("Open {{ page }} as {{user}}");
+        step.expected_results = "{{page}} loads, {{}} and {{ nope".to_string();
+        assert_eq!(names(&[step]), vec!["page", "user"]);
+    }
+
+    #[test]
+    fn fill_replaces_known_names_only() {
+        let mut steps = vec![TestStep::new().with_instructions("Open {{page}} as {{ user }}")];
+        let values = HashMap::from([("user".to_string(), "admin".to_string())]);
+        fill(&mut steps, &values);
+        assert_eq!(steps[0].instructions, "Open {{page}} as admin");
+    }
+}
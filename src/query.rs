@@ -0,0 +1,150 @@
+use regex::{Regex, RegexBuilder};
+
+// the search and filter syntax: space separated terms that all have to match, each
+// either plain text or `key:value`. quotes keep spaces (or a colon) inside one term
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub key: Option<String>,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Instructions,
+    TestData,
+    Expected,
+    Ac,
+}
+
+impl Field {
+    // None when the key isn't a field, callers decide if it means something else
+    pub fn parse(key: &str) -> Option<Field> {
+        match key {
+            "i" | "instructions" => Some(Field::Instructions),
+            "d" | "data" => Some(Field::TestData),
+            "e" | "expected" => Some(Field::Expected),
+            "ac" => Some(Field::Ac),
+            _ => None,
+        }
+    }
+}
+
+// a value wrapped in slashes is a regex, anything else is matched literally. both
+// ignore case unless the pattern turns it back on with (?-i). the regex crate runs in
+// linear time and caps compiled size, so a bad pattern errors rather than hangs
+pub fn matcher(value: &str) -> Result<Regex, String> {
+    let pattern = match value.strip_prefix('/').and_then(|v| v.strip_suffix('/')) {
+        Some(pattern) if !pattern.is_empty() => pattern.to_string(),
+        _ => regex::escape(value),
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|err| format!("Invalid regex {}: {}", value, err))
+}
+
+fn quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    let mut value = String::new();
+    for c in chars.by_ref() {
+        if c == '"' {
+            return Ok(value);
+        }
+        value.push(c);
+    }
+    Err("Unterminated quote in query".to_string())
+}
+
+pub fn parse(query: &str) -> Result<Vec<Term>, String> {
+    let mut terms = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c == '"' {
+            terms.push(Term {
+                key: None,
+                value: quoted(&mut chars)?,
+            });
+            continue;
+        }
+        let mut word = c.to_string();
+        let mut key = None;
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            if c == ':' && key.is_none() {
+                key = Some(std::mem::take(&mut word).to_lowercase());
+                if chars.next_if_eq(&'"').is_some() {
+                    word = quoted(&mut chars)?;
+                    break;
+                }
+                continue;
+            }
+            word.push(c);
+        }
+        terms.push(Term { key, value: word });
+    }
+    Ok(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(key: Option<&str>, value: &str) -> Term {
+        Term {
+            key: key.map(str::to_string),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn plain_and_keyed_terms() {
+        assert_eq!(
+            parse("login  I:Button ac:AC-3").unwrap(),
+            vec![
+                term(None, "login"),
+                term(Some("i"), "Button"),
+                term(Some("ac"), "AC-3"),
+            ]
+        );
+        assert_eq!(parse("   ").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn quotes_keep_spaces_and_colons() {
+        assert_eq!(
+            parse("e:\"toast appears\" \"http://x\" a:b:c").unwrap(),
+            vec![
+                term(Some("e"), "toast appears"),
+                term(None, "http://x"),
+                term(Some("a"), "b:c"),
+            ]
+        );
+        assert!(parse("e:\"never closed").is_err());
+        assert!(parse("\"never closed").is_err());
+    }
+
+    #[test]
+    fn slashes_make_a_regex() {
+        let order = matcher("/ORD-\\d+/").unwrap();
+        assert!(order.is_match("see ord-1234"));
+        assert!(!order.is_match("ORD-x"));
+        assert!(!matcher("/(?-i)ORD-\\d+/").unwrap().is_match("ord-1"));
+        // literal text is escaped, a lone slash isn't a regex
+        assert!(matcher("a.b").unwrap().is_match("A.B"));
+        assert!(!matcher("a.b").unwrap().is_match("axb"));
+        assert!(matcher("/").unwrap().is_match("a/b"));
+        assert!(
+            matcher("/(unclosed/")
+                .unwrap_err()
+                .starts_with("Invalid regex")
+        );
+    }
+
+    #[test]
+    fn fields_by_short_or_long_name() {
+        assert_eq!(Field::parse("e"), Some(Field::Expected));
+        assert_eq!(Field::parse("instructions"), Some(Field::Instructions));
+        assert_eq!(Field::parse("t"), None);
+    }
+}
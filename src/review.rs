@@ -0,0 +1,250 @@
+use crate::{
+    err_msg::WithErrMsg,
+    export::strip_markdown,
+    test_step::{ReviewNote, TestStep},
+};
+use regex::Regex;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+// "step 12: ...", "#12 - ...", "1.3: ..." with an optional list bullet in front
+static KEYED_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:[-*+]\s+)?(?:step\s*)?#?(\d+(?:\.\d+)?)\s*[:\-–]\s*(.*)$")
+        .expect("valid regex")
+});
+
+// a bare number is a step id, a dotted one is the step number as compiled
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub key: String,
+    pub text: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Applied {
+    pub attached: usize,
+    // already on the step, resolved or not
+    pub skipped: usize,
+    pub unmatched: Vec<String>,
+}
+
+fn key_of(value: &str) -> String {
+    let value = value.trim();
+    let value = match value.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("step") => &value[4..],
+        _ => value,
+    };
+    value.trim().trim_start_matches('#').to_string()
+}
+
+fn parse_json(content: &str) -> Result<Vec<Annotation>, String> {
+    let value: Value =
+        serde_json::from_str(content).with_err_msg(&"Review notes are not valid json")?;
+    let map = value
+        .as_object()
+        .with_err_msg(&"Review notes json should map step ids to comments")?;
+    let mut annotations = Vec::new();
+    for (key, notes) in map {
+        let texts: Vec<&str> = match notes {
+            Value::String(text) => vec![text],
+            Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+            _ => return Err(format!("Review notes for {} should be text or a list", key)),
+        };
+        annotations.extend(texts.into_iter().map(|text| Annotation {
+            key: key_of(key),
+            text: text.trim().to_string(),
+        }));
+    }
+    Ok(annotations)
+}
+
+// one note per keyed line, unkeyed lines continue the note above them. headings and
+// anything before the first key are ignored
+fn parse_markdown(content: &str) -> Vec<Annotation> {
+    let mut annotations: Vec<Annotation> = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("# ") || trimmed.starts_with("##") {
+            continue;
+        }
+        if let Some(caps) = KEYED_LINE.captures(trimmed) {
+            annotations.push(Annotation {
+                key: caps[1].to_string(),
+                text: caps[2].trim().to_string(),
+            });
+        } else if let Some(last) = annotations.last_mut() {
+            if !last.text.is_empty() {
+                last.text.push('\n');
+            }
+            last.text.push_str(trimmed);
+        }
+    }
+    annotations
+}
+
+pub fn parse(content: &str) -> Result<Vec<Annotation>, String> {
+    let annotations = if content.trim_start().starts_with('{') {
+        parse_json(content)?
+    } else {
+        parse_markdown(content)
+    };
+    Ok(annotations
+        .into_iter()
+        .filter(|a| !a.text.is_empty())
+        .collect())
+}
+
+fn find(items: &[TestStep], labels: &[String], key: &str) -> Option<usize> {
+    if key.contains('.') {
+        return labels
+            .iter()
+            .zip(items)
+            .position(|(label, item)| label == key && item.is_step());
+    }
+    let id: u64 = key.parse().ok()?;
+    items.iter().position(|item| item.id == id)
+}
+
+// re-importing the same shared file only adds what's new
+pub fn apply(
+    items: &mut [TestStep],
+    annotations: Vec<Annotation>,
+    first_section: usize,
+) -> Applied {
+    let labels = TestStep::step_labels(items, first_section);
+    let mut applied = Applied::default();
+    for annotation in annotations {
+        let Some(idx) = find(items, &labels, &annotation.key) else {
+            if !applied.unmatched.contains(&annotation.key) {
+                applied.unmatched.push(annotation.key);
+            }
+            continue;
+        };
+        let notes = &mut items[idx].review_notes;
+        if notes.iter().any(|note| note.text == annotation.text) {
+            applied.skipped += 1;
+            continue;
+        }
+        notes.push(ReviewNote {
+            text: annotation.text,
+            resolved: false,
+        });
+        applied.attached += 1;
+    }
+    applied
+}
+
+// the unresolved notes as markdown, keyed the way `parse` reads them back
+pub fn summary(items: &[TestStep], first_section: usize) -> String {
+    let labels = TestStep::step_labels(items, first_section);
+    let mut out = String::from("# Unresolved review notes\n");
+    for (item, label) in items.iter().zip(&labels) {
+        let notes: Vec<&ReviewNote> = item.review_notes.iter().filter(|n| !n.resolved).collect();
+        if notes.is_empty() {
+            continue;
+        }
+        let title = strip_markdown(&item.instructions());
+        let title = title.lines().next().unwrap_or("");
+        out.push_str(format!("\n## {} {}", label, title).trim_end());
+        out.push_str("\n\n");
+        for note in notes {
+            let mut lines = note.text.lines();
+            out.push_str(&format!(
+                "- step {}: {}\n",
+                item.id,
+                lines.next().unwrap_or("")
+            ));
+            for line in lines {
+                out.push_str(&format!("  {}\n", line));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items() -> Vec<TestStep> {
+        let mut items = vec![
+            TestStep::section("Login"),
+            TestStep::new().with_instructions("Open the **login** page"),
+            TestStep::new().with_instructions("Sign in"),
+        ];
+        TestStep::assign_ids(&mut items);
+        items
+    }
+
+    #[test]
+    fn markdown_lines_are_keyed_by_id_or_number() {
+        let notes = parse(concat!(
+            "# Review from Sam\n",
+            "\n",
+            "step 2: the toast copy is wrong\n",
+            "- #3 - button is disabled\n",
+            "  until the form is filled\n",
+            "1.3: needs a screenshot\n",
+        ))
+        .unwrap();
+        let keys: Vec<&str> = notes.iter().map(|n| n.key.as_str()).collect();
+        assert_eq!(keys, vec!["2", "3", "1.3"]);
+        assert_eq!(
+            notes[1].text,
+            "button is disabled\nuntil the form is filled"
+        );
+    }
+
+    #[test]
+    fn json_maps_ids_to_one_or_more_comments() {
+        let notes = parse(r#"{"step 2": "typo", "1.3": ["a", "b"]}"#).unwrap();
+        let keys: Vec<&str> = notes.iter().map(|n| n.key.as_str()).collect();
+        assert_eq!(keys, vec!["1.3", "1.3", "2"]);
+        assert!(parse(r#"{"2": 5}"#).is_err());
+    }
+
+    #[test]
+    fn apply_reports_unmatched_and_skips_repeats() {
+        let mut items = items();
+        let notes = parse("2: typo\n1.3: slow\n9: gone\n1.9: gone too\n").unwrap();
+        let applied = apply(&mut items, notes.clone(), 1);
+        assert_eq!(applied.attached, 2);
+        assert_eq!(applied.unmatched, vec!["9", "1.9"]);
+        assert_eq!(items[2].review_notes[0].text, "slow");
+
+        items[1].review_notes[0].resolved = true;
+        let again = apply(&mut items, notes, 1);
+        assert_eq!((again.attached, again.skipped), (0, 2));
+        assert_eq!(items[1].unresolved_notes(), 0);
+    }
+
+    #[test]
+    fn summary_lists_unresolved_notes_in_the_import_format() {
+        let mut items = items();
+        apply(
+            &mut items,
+            parse("2: typo\n2: fixed already\n3: two\nlines\n").unwrap(),
+            1,
+        );
+        items[1].review_notes[1].resolved = true;
+        let summary = summary(&items, 1);
+        assert_eq!(
+            summary,
+            concat!(
+                "# Unresolved review notes\n",
+                "\n",
+                "## 1.2 Open the login page\n",
+                "\n",
+                "- step 2: typo\n",
+                "\n",
+                "## 1.3 Sign in\n",
+                "\n",
+                "- step 3: two\n",
+                "  lines\n",
+            )
+        );
+        let reparsed = parse(&summary).unwrap();
+        assert_eq!(reparsed.len(), 2);
+        assert_eq!(reparsed[1].text, "two\nlines");
+    }
+}
@@ -0,0 +1,138 @@
+use crate::test_step::TestStep;
+use std::collections::HashMap;
+
+// frequently used lines offered in the editor for a new step. each one is commented out
+// with PREFIX, deleting the prefix keeps the line and anything still commented is
+// dropped when the step is read back
+pub const START: &str = "%% ---- frequently used lines, delete the \"%% \" to use one ----";
+pub const END: &str = "%% ---- end of frequently used lines ----";
+pub const PREFIX: &str = "%% ";
+
+// lines seen at least this often are worth suggesting
+const MIN_USES: usize = 2;
+
+// the `limit` most common instruction lines, most used first and ties in the order
+// they were first seen
+pub fn frequent_lines<'a>(
+    steps: impl IntoIterator<Item = &'a TestStep>,
+    limit: usize,
+) -> Vec<String> {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    let lines = steps
+        .into_iter()
+        .filter(|step| step.is_step())
+        .flat_map(|step| step.instructions.lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    for (order, line) in lines.enumerate() {
+        counts.entry(line.to_string()).or_insert((0, order)).0 += 1;
+    }
+    let mut lines: Vec<(String, (usize, usize))> = counts
+        .into_iter()
+        .filter(|(_, (uses, _))| *uses >= MIN_USES)
+        .collect();
+    lines.sort_by(|a, b| b.1.0.cmp(&a.1.0).then(a.1.1.cmp(&b.1.1)));
+    lines
+        .into_iter()
+        .take(limit)
+        .map(|(line, _)| line)
+        .collect()
+}
+
+// the block goes right under the first heading so an uncommented line lands in the
+// instructions, taking the place of the empty line a blank step has there
+pub fn insert(markdown: &str, lines: &[String]) -> String {
+    if lines.is_empty() {
+        return markdown.to_string();
+    }
+    let (heading, rest) = markdown.split_once('\n').unwrap_or((markdown, ""));
+    let rest = rest.strip_prefix('\n').unwrap_or(rest);
+    let block: Vec<String> = std::iter::once(START.to_string())
+        .chain(lines.iter().map(|line| format!("{}{}", PREFIX, line)))
+        .chain(std::iter::once(END.to_string()))
+        .collect();
+    format!("{}\n{}\n{}", heading, block.join("\n"), rest)
+}
+
+// removes the block markers and every line still commented out inside the block. a
+// deleted end marker runs the block to the end, lines outside it are never touched
+pub fn strip(markdown: &str) -> String {
+    let mut inside = false;
+    let mut out = Vec::new();
+    for line in markdown.lines() {
+        match line.trim_end() {
+            START => inside = true,
+            END => inside = false,
+            line if inside && (line.starts_with(PREFIX) || line == PREFIX.trim_end()) => {}
+            _ => out.push(line),
+        }
+    }
+    let mut out = out.join("\n");
+    if markdown.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(instructions: &str) -> TestStep {
+        TestStep::new().with_instructions(instructions)
+    }
+
+    #[test]
+    fn only_repeated_lines_are_suggested() {
+        let steps = vec![
+            step("Log in\nClear cache and hard-reload"),
+            step("Check audit log entry"),
+            step("  Clear cache and hard-reload  \nCheck audit log entry"),
+            step("Clear cache and hard-reload"),
+            TestStep::section("Log in"),
+        ];
+        assert_eq!(
+            frequent_lines(&steps, 5),
+            vec!["Clear cache and hard-reload", "Check audit log entry"]
+        );
+        assert_eq!(frequent_lines(&steps, 1).len(), 1);
+    }
+
+    #[test]
+    fn untouched_scaffold_never_reaches_the_step() {
+        let markdown = insert(
+            &TestStep::new().gen_markdown(true),
+            &["Clear cache".to_string(), "Check audit log".to_string()],
+        );
+        assert!(markdown.starts_with(&format!("# Instructions\n{}\n%% Clear cache\n", START)));
+        let parsed = TestStep::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed, TestStep::new());
+    }
+
+    #[test]
+    fn uncommented_lines_are_kept() {
+        let markdown = insert(
+            &TestStep::new().gen_markdown(true),
+            &["Clear cache".to_string(), "Check audit log".to_string()],
+        )
+        .replace("%% Check audit log", "Check audit log");
+        let parsed = TestStep::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.instructions, "Check audit log");
+    }
+
+    #[test]
+    fn a_missing_end_marker_still_strips_the_block() {
+        let markdown = format!(
+            "# Instructions\nOpen app\n{}\n%% Clear cache\n%%\n\n# Expected Results\nOpens\n%% not from us\n",
+            START
+        );
+        let parsed = TestStep::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.instructions, "Open app");
+        assert_eq!(parsed.expected_results, "Opens");
+        // without a start marker nothing is stripped, %% can be real step text
+        let plain =
+            TestStep::parse_markdown("# Instructions\n%% 50%% off\n\n# Expected Results\nok")
+                .unwrap();
+        assert_eq!(plain.instructions, "%% 50%% off");
+    }
+}
@@ -0,0 +1,241 @@
+use serde_json::Value;
+
+// the json schema for the current document version, printed by `uat_editor schema`. it
+// is hand written, the tests below keep it in step with TestStep and DOCUMENT_VERSION
+pub const SCHEMA: &str = include_str!("document.schema.json");
+
+// just the parts of json schema document.schema.json uses, anything else in it is a
+// test failure rather than a rule silently skipped
+#[cfg(test)]
+const KEYWORDS: [&str; 14] = [
+    "$schema",
+    "$id",
+    "title",
+    "description",
+    "$defs",
+    "$ref",
+    "type",
+    "const",
+    "enum",
+    "minimum",
+    "required",
+    "properties",
+    "additionalProperties",
+    "items",
+];
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn field(path: &str, name: &str) -> String {
+    match path {
+        "" => name.to_string(),
+        path => format!("{}.{}", path, name),
+    }
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let at = if path.is_empty() { "document" } else { path };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .ok_or_else(|| format!("Schema reference {} not found", reference))?;
+        return check(root, target, value, path);
+    }
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            types => types.as_str().into_iter().collect(),
+        };
+        let found = kind(value);
+        if !types
+            .iter()
+            .any(|t| *t == found || (*t == "number" && found == "integer"))
+        {
+            return Err(format!(
+                "{}: expected {}, found {}",
+                at,
+                types.join(" or "),
+                found
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const").filter(|expected| *expected != value) {
+        return Err(format!("{}: expected {}, found {}", at, expected, value));
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        return Err(format!(
+            "{}: expected one of {}, found {}",
+            at,
+            allowed.join(", "),
+            value
+        ));
+    }
+    if let (Some(minimum), Some(number)) = (
+        schema.get("minimum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) && number < minimum
+    {
+        return Err(format!(
+            "{}: {} is below the minimum {}",
+            at, number, minimum
+        ));
+    }
+    if let Value::Object(fields) = value {
+        let required = schema.get("required").and_then(Value::as_array);
+        for name in required.into_iter().flatten().filter_map(Value::as_str) {
+            if !fields.contains_key(name) {
+                return Err(format!("{}: missing", field(path, name)));
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+        for (name, value) in fields {
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(property) => check(root, property, value, &field(path, name))?,
+                None if closed => return Err(format!("{}: unknown field", field(path, name))),
+                None => {}
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (idx, item) in items.iter().enumerate() {
+            check(root, item_schema, item, &format!("{}[{}]", at, idx))?;
+        }
+    }
+    Ok(())
+}
+
+// a current version document against SCHEMA, the error names the first field that
+// doesn't fit, e.g. "items[3].status: expected one of ..."
+pub fn validate(document: &Value) -> Result<(), String> {
+    let schema: Value = serde_json::from_str(SCHEMA)
+        .map_err(|err| format!("The built-in document schema is invalid: {}", err))?;
+    check(&schema, &schema, document, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        embedding::{self, DOCUMENT_VERSION},
+        stress,
+        test_step::{Priority, ReviewNote, StepStatus, TestStep},
+    };
+
+    fn schema() -> Value {
+        serde_json::from_str(SCHEMA).unwrap()
+    }
+
+    fn keywords_are_supported(schema: &Value) {
+        let Value::Object(fields) = schema else {
+            return;
+        };
+        for (key, value) in fields {
+            assert!(
+                KEYWORDS.contains(&key.as_str()),
+                "unsupported keyword {}",
+                key
+            );
+            match key.as_str() {
+                "properties" | "$defs" => {
+                    for definition in value.as_object().unwrap().values() {
+                        keywords_are_supported(definition);
+                    }
+                }
+                "items" => keywords_are_supported(value),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn schema_tracks_the_document_version() {
+        let schema = schema();
+        keywords_are_supported(&schema);
+        assert_eq!(schema["properties"]["version"]["const"], DOCUMENT_VERSION);
+        assert_eq!(
+            schema["$id"],
+            format!("urn:uat_editor:document:v{}", DOCUMENT_VERSION)
+        );
+    }
+
+    #[test]
+    fn every_step_field_is_in_the_schema() {
+        let mut step = TestStep::new().with_instructions("Open app");
+        step.estimate_minutes = Some(5);
+        step.created_at = Some(1);
+        step.updated_at = Some(2);
+        step.author = Some("qa".to_string());
+        step.links = vec!["Spec | https://example.com".to_string()];
+        step.status = Some(StepStatus::Blocked);
+        step.priority = Some(Priority::High);
+        step.review_notes = vec![ReviewNote {
+            text: "why?".to_string(),
+            resolved: true,
+        }];
+        let document: Value =
+            serde_json::from_str(&embedding::document_json(&[step]).unwrap()).unwrap();
+        assert_eq!(validate(&document), Ok(()));
+        let written = document["items"][0].as_object().unwrap();
+        let properties = schema()["$defs"]["step"]["properties"].clone();
+        assert_eq!(written.len(), properties.as_object().unwrap().len());
+    }
+
+    #[test]
+    fn written_documents_validate() {
+        for items in [stress::generate(300, stress::DEFAULT_SEED), Vec::new()] {
+            let document: Value =
+                serde_json::from_str(&embedding::canonical_json(&items).unwrap()).unwrap();
+            assert_eq!(validate(&document), Ok(()));
+        }
+    }
+
+    #[test]
+    fn errors_name_the_field() {
+        let cases = [
+            (
+                r#"{"version":2,"items":[{"instructions":"a","expected_results":"b","ac":"","status":"done"}]}"#,
+                r#"items[0].status: expected one of "pass", "fail", "blocked", "skipped", null, found "done""#,
+            ),
+            (
+                r#"{"version":2,"items":[{"instructions":"a","expected_results":"b"}]}"#,
+                "items[0].ac: missing",
+            ),
+            (
+                r#"{"version":2,"items":[{"instructions":"a","expected_results":"b","ac":"","instruction":"c"}]}"#,
+                "items[0].instruction: unknown field",
+            ),
+            (
+                r#"{"version":2,"items":[{"id":-1,"instructions":"a","expected_results":"b","ac":""}]}"#,
+                "items[0].id: -1 is below the minimum 0",
+            ),
+            (
+                r#"{"version":2,"items":[{"instructions":"a","expected_results":"b","ac":"","review_notes":[{"text":1}]}]}"#,
+                "items[0].review_notes[0].text: expected string, found integer",
+            ),
+            (
+                r#"{"version":3,"items":[]}"#,
+                "version: expected 2, found 3",
+            ),
+            (r#"[]"#, "document: expected object, found array"),
+        ];
+        for (document, err) in cases {
+            let document: Value = serde_json::from_str(document).unwrap();
+            assert_eq!(validate(&document), Err(err.to_string()));
+        }
+    }
+}
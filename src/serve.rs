@@ -0,0 +1,200 @@
+use crate::{
+    config::{ColumnConfig, MarkdownExtension},
+    embedding,
+    err_msg::WithErrMsg,
+    export::html::{self, HtmlOptions},
+    locale::Locale,
+    test_step::TestStep,
+};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+const ACCEPT_POLL: Duration = Duration::from_millis(50);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// what the server renders from, replaced whenever the items change
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    pub items: Vec<TestStep>,
+    pub columns: ColumnConfig,
+    pub use_ac: bool,
+    pub show_ids: bool,
+    pub max_image_bytes: u64,
+    pub locale: Locale,
+    pub markdown_extensions: Vec<MarkdownExtension>,
+    pub numbering_start: usize,
+}
+
+impl Snapshot {
+    fn page(&self) -> Result<String, String> {
+        let options = HtmlOptions {
+            columns: &self.columns,
+            use_ac: self.use_ac,
+            show_ids: self.show_ids,
+            max_image_bytes: self.max_image_bytes,
+            locale: self.locale,
+            markdown_extensions: &self.markdown_extensions,
+            numbering_start: self.numbering_start,
+        };
+        // the compiled html is a fragment for pasting, a browser wants to know the charset
+        Ok(format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>UAT</title>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+            html::gen_html(&self.items, &options)?
+        ))
+    }
+}
+
+// a read-only view of the document for screen sharing, plain http on a background thread
+pub struct Server {
+    addr: SocketAddr,
+    snapshot: Arc<Mutex<Snapshot>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    pub fn start(bind: &str, port: u16, snapshot: Snapshot) -> Result<Server, String> {
+        let listener = TcpListener::bind((bind, port))
+            .with_err_msg(&format!("Failed to listen on {}:{}", bind, port))?;
+        listener
+            .set_nonblocking(true)
+            .with_err_msg(&"Failed to configure the listener")?;
+        let addr = listener
+            .local_addr()
+            .with_err_msg(&"Failed to read the listening address")?;
+        let snapshot = Arc::new(Mutex::new(snapshot));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let snapshot = snapshot.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let _ = respond(stream, &snapshot);
+                        }
+                        Err(_) => std::thread::sleep(ACCEPT_POLL),
+                    }
+                }
+            })
+        };
+        Ok(Server {
+            addr,
+            snapshot,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    pub fn update(&self, snapshot: Snapshot) {
+        if let Ok(mut current) = self.snapshot.lock() {
+            *current = snapshot;
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn respond(stream: TcpStream, snapshot: &Mutex<Snapshot>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // the headers aren't needed, they're read so the client sees a clean close
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let snapshot = snapshot
+        .lock()
+        .map(|snapshot| snapshot.clone())
+        .unwrap_or_default();
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/") => match snapshot.page() {
+            Ok(page) => ("200 OK", "text/html; charset=utf-8", page),
+            Err(err) => ("500 Internal Server Error", "text/plain", err),
+        },
+        ("GET", "/json") => match embedding::document_json(&snapshot.items) {
+            Ok(json) => ("200 OK", "application/json", json),
+            Err(err) => ("500 Internal Server Error", "text/plain", err),
+        },
+        ("GET", _) => ("404 Not Found", "text/plain", "Not found".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Read only".to_string(),
+        ),
+    };
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn get(server: &Server, request: &str) -> String {
+        let mut stream = TcpStream::connect(server.addr).unwrap();
+        write!(stream, "{}\r\nHost: localhost\r\n\r\n", request).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_the_latest_snapshot() {
+        let snapshot = Snapshot {
+            items: vec![TestStep::new().with_instructions("first")],
+            ..Snapshot::default()
+        };
+        let server = Server::start("127.0.0.1", 0, snapshot.clone()).unwrap();
+        let page = get(&server, "GET / HTTP/1.1");
+        assert!(page.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(page.contains("first"));
+
+        server.update(Snapshot {
+            items: vec![TestStep::new().with_instructions("second")],
+            ..snapshot
+        });
+        let json = get(&server, "GET /json HTTP/1.1");
+        let body = json.split("\r\n\r\n").nth(1).unwrap();
+        let items = embedding::parse_document(body).unwrap();
+        assert_eq!(items[0].instructions, "second");
+
+        assert!(get(&server, "GET /nope HTTP/1.1").starts_with("HTTP/1.1 404"));
+        assert!(get(&server, "POST / HTTP/1.1").starts_with("HTTP/1.1 405"));
+        assert!(server.url().starts_with("http://127.0.0.1:"));
+    }
+}
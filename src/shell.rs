@@ -0,0 +1,175 @@
+use crate::err_msg::WithErrMsg;
+use std::{
+    io::{Read, Write},
+    process::{Child, Command, ExitStatus, Stdio},
+    time::{Duration, Instant},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct ShellOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ShellOutput {
+    pub fn first_stderr_line(&self) -> String {
+        self.stderr
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("")
+            .to_string()
+    }
+}
+
+// runs the command through `sh -c` with the input on stdin
+pub fn run(cmd: &str, input: &str) -> Result<ShellOutput, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_err_msg(&format!("Failed to spawn command: {}", cmd))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .with_err_msg(&"Failed to open command stdin")?;
+    let input = input.to_string();
+    // written from a thread so a chatty command can't deadlock on a full stdout pipe
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child
+        .wait_with_output()
+        .with_err_msg(&format!("Failed to wait on command: {}", cmd))?;
+    let _ = writer.join();
+
+    Ok(ShellOutput {
+        status: output.status,
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
+// waits up to `timeout` for the child, killing it and returning None once that passes
+pub fn wait_timeout(child: &mut Child, timeout: Duration) -> Result<Option<ExitStatus>, String> {
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(Some(status)),
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(err) => return Err(format!("Failed to wait on command: {}", err)),
+        }
+    }
+}
+
+// drained from a thread so a chatty command can't block on a full pipe
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut text);
+        }
+        text
+    })
+}
+
+// like run, but gives up on a command still going after `timeout`
+pub fn run_timeout(cmd: &str, input: &str, timeout: Duration) -> Result<ShellOutput, String> {
+    run_timeout_env(cmd, input, timeout, &[])
+}
+
+pub fn run_timeout_env(
+    cmd: &str,
+    input: &str,
+    timeout: Duration,
+    env: &[(&str, String)],
+) -> Result<ShellOutput, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .envs(env.iter().map(|(key, val)| (*key, val)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_err_msg(&format!("Failed to spawn command: {}", cmd))?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .with_err_msg(&"Failed to open command stdin")?;
+    let input = input.to_string();
+    std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let status = wait_timeout(&mut child, timeout)?.with_err_msg(&format!(
+        "Command timed out after {}s: {}",
+        timeout.as_secs(),
+        cmd
+    ))?;
+    Ok(ShellOutput {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+// stderr only, for commands whose output goes to a file of their own
+pub fn spawn_quiet(
+    command: &mut Command,
+) -> Result<(Child, std::thread::JoinHandle<String>), String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_err_msg(&"Failed to spawn command")?;
+    let stderr = read_in_background(child.stderr.take());
+    Ok((child, stderr))
+}
+
+// like run, but a non-zero exit becomes an error carrying the first line of stderr
+pub fn filter(cmd: &str, input: &str) -> Result<String, String> {
+    let output = run(cmd, input)?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let line = output.first_stderr_line();
+        if line.is_empty() {
+            Err(format!("Command failed: {}", output.status))
+        } else {
+            Err(line)
+        }
+    }
+}
+
+// hands a file to the platform's default application, without waiting for it to close
+pub fn open(path: &str) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let mut child = command
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_err_msg(&format!("Failed to open {}", path))?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
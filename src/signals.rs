@@ -0,0 +1,43 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+// termination signals are only recorded here, the event loop notices them on its next
+// poll and shuts down through the same backup path as a normal quit. on windows a
+// console close can't be intercepted without extra bindings, ctrl+c arrives as a key
+#[derive(Clone, Default)]
+pub struct Signals {
+    received: Arc<AtomicUsize>,
+}
+
+impl Signals {
+    pub fn install() -> Signals {
+        let signals = Signals::default();
+        #[cfg(unix)]
+        for signal in [
+            signal_hook::consts::SIGINT,
+            signal_hook::consts::SIGTERM,
+            signal_hook::consts::SIGHUP,
+        ] {
+            let _ = signal_hook::flag::register_usize(
+                signal,
+                Arc::clone(&signals.received),
+                signal as usize,
+            );
+        }
+        signals
+    }
+
+    pub fn received(&self) -> Option<i32> {
+        match self.received.load(Ordering::Relaxed) {
+            0 => None,
+            signal => Some(signal as i32),
+        }
+    }
+
+    // shell convention for a process ended by a signal
+    pub fn exit_code(&self) -> Option<i32> {
+        self.received().map(|signal| 128 + signal)
+    }
+}
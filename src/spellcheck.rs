@@ -0,0 +1,13 @@
+use crate::shell;
+
+// feeds the text to a command like `aspell list` and reads back one flagged word per line
+pub fn flagged_words(cmd: &str, text: &str) -> Result<Vec<String>, String> {
+    let stdout =
+        shell::filter(cmd, text).map_err(|err| format!("Spell check command failed: {}", err))?;
+
+    Ok(stdout
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
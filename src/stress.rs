@@ -0,0 +1,179 @@
+use crate::test_step::{ReviewNote, StepStatus, TestStep};
+
+// a big document that looks like a real one: sections of uneven length, the odd comment,
+// fields from empty to a screenful, markdown and unicode. the same seed always gives the
+// same document so benchmark runs compare
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+const WORDS: [&str; 32] = [
+    "open",
+    "the",
+    "login",
+    "page",
+    "enter",
+    "valid",
+    "credentials",
+    "and",
+    "submit",
+    "form",
+    "check",
+    "dashboard",
+    "shows",
+    "latest",
+    "orders",
+    "settings",
+    "Öffnen",
+    "Anmeldung",
+    "führen",
+    "naïve",
+    "café",
+    "résumé",
+    "登录",
+    "设置",
+    "確認",
+    "ΣΊΣΥΦΟΣ",
+    "İstanbul",
+    "🚀",
+    "✅",
+    "**bold**",
+    "`code`",
+    "_emphasis_",
+];
+const SECTIONS: [&str; 6] = [
+    "Login",
+    "Checkout",
+    "Einstellungen",
+    "Rapports mensuels",
+    "管理画面",
+    "Accessibility ♿",
+];
+const AUTHORS: [&str; 4] = ["qa", "Zoë", "José", "李雷"];
+
+// xorshift64*, plenty for fixture data and no dependency
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // in lo..=hi
+    fn range(&mut self, lo: usize, hi: usize) -> usize {
+        lo + (self.next() % (hi - lo + 1) as u64) as usize
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.range(1, 100) <= percent
+    }
+
+    fn pick<'a>(&mut self, from: &[&'a str]) -> &'a str {
+        from[self.range(0, from.len() - 1)]
+    }
+}
+
+fn sentence(rng: &mut Rng) -> String {
+    let words: Vec<&str> = (0..rng.range(3, 14)).map(|_| rng.pick(&WORDS)).collect();
+    words.join(" ")
+}
+
+// some fields are one line, some a list and a few run long enough to wrap many times
+fn text(rng: &mut Rng, max_lines: usize) -> String {
+    let lines = rng.range(1, max_lines);
+    let list = lines > 1 && rng.chance(40);
+    (0..lines)
+        .map(|n| match list {
+            true => format!("{}. {}", n + 1, sentence(rng)),
+            false => sentence(rng),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn step(rng: &mut Rng) -> TestStep {
+    let mut step = TestStep::new().with_instructions(&text(rng, 6));
+    if rng.chance(30) {
+        step.test_data = text(rng, 3);
+    }
+    if rng.chance(90) {
+        step.expected_results = text(rng, 4);
+    }
+    if rng.chance(50) {
+        step.ac = format!("AC-{}", rng.range(1, 400));
+    }
+    if rng.chance(20) {
+        step.links = vec![format!(
+            "Spec | https://example.com/spec/{}",
+            rng.range(1, 99)
+        )];
+    }
+    if rng.chance(40) {
+        step.estimate_minutes = Some(rng.range(1, 45) as u32);
+    }
+    step.status = match rng.range(0, 9) {
+        0..=3 => None,
+        4..=6 => Some(StepStatus::Pass),
+        7 => Some(StepStatus::Fail),
+        8 => Some(StepStatus::Blocked),
+        _ => Some(StepStatus::Skipped),
+    };
+    if rng.chance(10) {
+        step.comment = sentence(rng);
+    }
+    if rng.chance(5) {
+        step.review_notes = vec![ReviewNote {
+            text: sentence(rng),
+            resolved: rng.chance(50),
+        }];
+    }
+    step
+}
+
+// `steps` rows in all, sections and comments included
+pub fn generate(steps: usize, seed: u64) -> Vec<TestStep> {
+    let mut rng = Rng::new(seed);
+    let mut items = Vec::with_capacity(steps);
+    let mut section_left = 0;
+    let mut time = 1_700_000_000;
+    while items.len() < steps {
+        let mut item = if section_left == 0 {
+            section_left = rng.range(8, 40);
+            TestStep::section(rng.pick(&SECTIONS))
+        } else if rng.chance(3) {
+            TestStep::comment(&sentence(&mut rng))
+        } else {
+            section_left -= 1;
+            step(&mut rng)
+        };
+        time += rng.range(1, 600) as u64;
+        item.id = items.len() as u64 + 1;
+        item.author = Some(rng.pick(&AUTHORS).to_string());
+        item.created_at = Some(time);
+        item.updated_at = Some(time + rng.range(0, 86_400) as u64);
+        items.push(item);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_document() {
+        let items = generate(300, DEFAULT_SEED);
+        assert_eq!(items.len(), 300);
+        assert_eq!(items, generate(300, DEFAULT_SEED));
+        assert_ne!(items, generate(300, DEFAULT_SEED + 1));
+        assert!(items[0].is_new_section);
+        assert!(items.iter().any(|item| item.is_stepless_comment));
+        let ids: Vec<u64> = items.iter().map(|item| item.id).collect();
+        assert_eq!(ids, (1..=300).collect::<Vec<u64>>());
+    }
+}
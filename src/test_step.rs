@@ -1,134 +1,1291 @@
-use crate::err_msg::WithErrMsg;
+use crate::{
+    locale::{self, Key, Locale},
+    scaffold, timestamp,
+};
 use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::HashMap};
+
+pub const DOCUMENT_SEPARATOR: &str = "---";
+
+// a line opening or closing a fenced code block, as its fence character and length
+fn fence(line: &str) -> Option<(char, usize)> {
+    let line = line.trim_start();
+    let c = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|x| *x == c).count();
+    (len >= 3).then_some((c, len))
+}
+
+// for each line, whether it belongs to a fenced code block, fences included. whatever
+// is inside one is never a heading or a step separator
+pub fn code_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<bool> {
+    let mut open: Option<(char, usize)> = None;
+    lines
+        .into_iter()
+        .map(|line| {
+            match (open, fence(line)) {
+                (None, Some(fence)) => open = Some(fence),
+                // a closing fence is at least as long as the opening one and has no info
+                // string after it
+                (Some((c, len)), Some((close, close_len)))
+                    if close == c && close_len >= len && line.trim().chars().all(|x| x == c) =>
+                {
+                    open = None;
+                    return true;
+                }
+                (None, None) => return false,
+                _ => {}
+            }
+            true
+        })
+        .collect()
+}
+
+// compares the way to_lowercase would without allocating, plain ascii headings take
+// the fast path
+fn eq_caseless(a: &str, b: &str) -> bool {
+    if a.is_ascii() && b.is_ascii() {
+        return a.eq_ignore_ascii_case(b);
+    }
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+// blank lines around a field are dropped but the first line keeps its indent, which
+// can be what makes it an indented code block
+fn trim_block(text: &str) -> String {
+    let start = text
+        .lines()
+        .take_while(|line| line.trim().is_empty())
+        .map(|line| line.len() + 1)
+        .sum::<usize>();
+    text.get(start..).unwrap_or("").trim_end().to_string()
+}
 
 // defaults to false for backwards compatibility
 pub fn ret_false() -> bool {
     false
 }
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Pass,
+    Fail,
+    Blocked,
+    Skipped,
+}
+
+impl StepStatus {
+    pub fn parse(input: &str) -> Result<Option<StepStatus>, String> {
+        match input.trim().to_lowercase().as_str() {
+            "pass" | "passed" => Ok(Some(StepStatus::Pass)),
+            "fail" | "failed" => Ok(Some(StepStatus::Fail)),
+            "blocked" => Ok(Some(StepStatus::Blocked)),
+            "skip" | "skipped" => Ok(Some(StepStatus::Skipped)),
+            "none" | "" => Ok(None),
+            other => Err(format!("Unknown status: {}", other)),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StepStatus::Pass => "Pass",
+            StepStatus::Fail => "Fail",
+            StepStatus::Blocked => "Blocked",
+            StepStatus::Skipped => "Skipped",
+        }
+    }
+
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            StepStatus::Pass => "✓",
+            StepStatus::Fail => "✗",
+            StepStatus::Blocked => "⊘",
+            StepStatus::Skipped => "–",
+        }
+    }
+
+    // none -> pass -> fail -> blocked -> skipped -> none
+    pub fn cycle(status: Option<StepStatus>) -> Option<StepStatus> {
+        match status {
+            None => Some(StepStatus::Pass),
+            Some(StepStatus::Pass) => Some(StepStatus::Fail),
+            Some(StepStatus::Fail) => Some(StepStatus::Blocked),
+            Some(StepStatus::Blocked) => Some(StepStatus::Skipped),
+            Some(StepStatus::Skipped) => None,
+        }
+    }
+}
+
+// how much is at risk if the step fails, reviewers go through the high ones first.
+// declared in sort order, so ascending puts high first
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl Priority {
+    pub fn parse(input: &str) -> Result<Option<Priority>, String> {
+        match input.trim().to_lowercase().as_str() {
+            "high" | "h" | "1" => Ok(Some(Priority::High)),
+            "medium" | "med" | "m" | "2" => Ok(Some(Priority::Medium)),
+            "low" | "l" | "3" => Ok(Some(Priority::Low)),
+            "none" | "0" | "" => Ok(None),
+            other => Err(format!(
+                "Unknown priority: {} (expected high|medium|low|none)",
+                other
+            )),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::High => "High",
+            Priority::Medium => "Medium",
+            Priority::Low => "Low",
+        }
+    }
+
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            Priority::High => "▲",
+            Priority::Medium => "■",
+            Priority::Low => "▼",
+        }
+    }
+
+    // the class on the compiled html row, for tinting by css
+    pub fn class(&self) -> &'static str {
+        match self {
+            Priority::High => "priority-high",
+            Priority::Medium => "priority-medium",
+            Priority::Low => "priority-low",
+        }
+    }
+}
+
+// a reviewer's comment attached with `:review import`, kept once resolved so the same
+// note imported again isn't reopened
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReviewNote {
+    pub text: String,
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TestStep {
+    // 0 means the step has not been assigned an id yet
+    #[serde(default)]
+    pub id: u64,
     #[serde(default = "ret_false")]
     pub is_stepless_comment: bool,
     #[serde(default = "ret_false")]
     pub is_new_section: bool,
     pub instructions: String,
+    #[serde(default)]
+    pub test_data: String,
     pub expected_results: String,
     pub ac: String,
+    #[serde(default)]
+    pub estimate_minutes: Option<u32>,
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    #[serde(default)]
+    pub updated_at: Option<u64>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub links: Vec<String>,
+    #[serde(default)]
+    pub status: Option<StepStatus>,
+    // left out when unset, so documents from before priorities keep their bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub review_notes: Vec<ReviewNote>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Heading {
+    Instructions,
+    NewSection,
+    CommentSection,
+    TestData,
+    ExpectedResults,
+    Ac,
+    Links,
+    Estimate,
+}
+
+const HEADINGS: [Heading; 8] = [
+    Heading::Instructions,
+    Heading::NewSection,
+    Heading::CommentSection,
+    Heading::TestData,
+    Heading::ExpectedResults,
+    Heading::Ac,
+    Heading::Links,
+    Heading::Estimate,
+];
+
+// the sections a heading alias can stand in for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasTarget {
+    Instructions,
+    ExpectedResults,
+    Ac,
+}
+
+impl AliasTarget {
+    fn heading(self) -> Heading {
+        match self {
+            AliasTarget::Instructions => Heading::Instructions,
+            AliasTarget::ExpectedResults => Heading::ExpectedResults,
+            AliasTarget::Ac => Heading::Ac,
+        }
+    }
+}
+
+// what people write instead of the canonical headings, heading_aliases in the config
+// adds to these and wins where both name the same heading
+pub const DEFAULT_ALIASES: [(&str, AliasTarget); 14] = [
+    ("steps", AliasTarget::Instructions),
+    ("step", AliasTarget::Instructions),
+    ("test steps", AliasTarget::Instructions),
+    ("action", AliasTarget::Instructions),
+    ("actions", AliasTarget::Instructions),
+    ("expected", AliasTarget::ExpectedResults),
+    ("expected result", AliasTarget::ExpectedResults),
+    ("expected outcome", AliasTarget::ExpectedResults),
+    ("result", AliasTarget::ExpectedResults),
+    ("results", AliasTarget::ExpectedResults),
+    ("acceptance criteria", AliasTarget::Ac),
+    ("acceptance criterion", AliasTarget::Ac),
+    ("acs", AliasTarget::Ac),
+    ("criteria", AliasTarget::Ac),
+];
+
+impl Heading {
+    fn key(self) -> Key {
+        match self {
+            Heading::Instructions => Key::Instructions,
+            Heading::NewSection => Key::NewSection,
+            Heading::CommentSection => Key::CommentSection,
+            Heading::TestData => Key::TestData,
+            Heading::ExpectedResults => Key::ExpectedResults,
+            Heading::Ac => Key::Ac,
+            Heading::Links => Key::Links,
+            Heading::Estimate => Key::Estimate,
+        }
+    }
+
+    // headings written in any locale are read back, so a document edited under one
+    // locale still opens under another. the bool is set when the heading was an alias
+    fn parse(line: &str, aliases: &HashMap<String, AliasTarget>) -> Option<(Heading, bool)> {
+        let title = line.trim_end().strip_prefix("# ")?.trim();
+        let canonical = HEADINGS
+            .into_iter()
+            .find(|heading| locale::all(heading.key()).any(|text| eq_caseless(text, title)));
+        if let Some(heading) = canonical {
+            return Some((heading, false));
+        }
+        aliases
+            .iter()
+            .find(|(alias, _)| eq_caseless(alias.trim(), title))
+            .map(|(_, target)| *target)
+            .or_else(|| {
+                DEFAULT_ALIASES
+                    .iter()
+                    .find(|(alias, _)| eq_caseless(alias, title))
+                    .map(|(_, target)| *target)
+            })
+            .map(|target| (target.heading(), true))
+    }
+
+    // the three opening headings all fill the instructions, so a step has one of them
+    fn slot(self) -> Heading {
+        match self {
+            Heading::NewSection | Heading::CommentSection => Heading::Instructions,
+            heading => heading,
+        }
+    }
+
+    // the heading that opens a step decides what kind of row it is
+    fn kind(self) -> Option<StepKind> {
+        match self {
+            Heading::Instructions => Some(StepKind::Step),
+            Heading::NewSection => Some(StepKind::Section),
+            Heading::CommentSection => Some(StepKind::Comment),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    Step,
+    Section,
+    Comment,
+}
+
+impl Default for TestStep {
+    fn default() -> Self {
+        TestStep::new()
+    }
 }
 
 impl TestStep {
-    pub fn new(is_stepless_comment: bool, is_new_section: bool) -> TestStep {
+    // a plain numbered step
+    pub fn new() -> TestStep {
         TestStep {
-            is_stepless_comment,
-            is_new_section,
+            id: 0,
+            is_stepless_comment: false,
+            is_new_section: false,
             instructions: String::new(),
+            test_data: String::new(),
             expected_results: String::new(),
             ac: String::new(),
+            estimate_minutes: None,
+            created_at: None,
+            updated_at: None,
+            author: None,
+            links: Vec::new(),
+            status: None,
+            priority: None,
+            comment: String::new(),
+            review_notes: Vec::new(),
+        }
+    }
+
+    pub fn section(title: &str) -> TestStep {
+        TestStep {
+            is_new_section: true,
+            ..TestStep::new()
+        }
+        .with_instructions(title)
+    }
+
+    pub fn comment(text: &str) -> TestStep {
+        TestStep {
+            is_stepless_comment: true,
+            ..TestStep::new()
         }
+        .with_instructions(text)
+    }
+
+    pub fn blank(kind: StepKind) -> TestStep {
+        match kind {
+            StepKind::Step => TestStep::new(),
+            StepKind::Section => TestStep::section(""),
+            StepKind::Comment => TestStep::comment(""),
+        }
+    }
+
+    // a step with nothing written in it yet, e.g. a placeholder inserted without the editor
+    pub fn is_blank(&self) -> bool {
+        self.is_step()
+            && [
+                &self.instructions,
+                &self.test_data,
+                &self.expected_results,
+                &self.ac,
+            ]
+            .iter()
+            .all(|field| field.trim().is_empty())
+    }
+
+    // folds `lower` into this step, each field separated by a blank line. flags, id,
+    // status and priority stay with this step
+    pub fn merge(&mut self, lower: &TestStep) {
+        let join = |upper: &mut String, lower: &str| {
+            let lower = lower.trim();
+            if lower.is_empty() {
+                return;
+            }
+            let trimmed = upper.trim_end().len();
+            upper.truncate(trimmed);
+            if !upper.trim().is_empty() {
+                upper.push_str("\n\n");
+            }
+            upper.push_str(lower);
+        };
+        join(&mut self.instructions, &lower.instructions);
+        join(&mut self.test_data, &lower.test_data);
+        join(&mut self.expected_results, &lower.expected_results);
+        join(&mut self.ac, &lower.ac);
+        join(&mut self.comment, &lower.comment);
+        self.links.extend(lower.links.iter().cloned());
+        self.review_notes.extend(lower.review_notes.iter().cloned());
+        self.estimate_minutes = match (self.estimate_minutes, lower.estimate_minutes) {
+            (Some(upper), Some(lower)) => Some(upper.saturating_add(lower)),
+            (upper, lower) => upper.or(lower),
+        };
     }
 
-    pub fn ref_array(&self) -> [String; 3] {
-        [self.instructions(), self.expected_results(), self.ac()]
+    pub fn with_instructions(mut self, instructions: &str) -> TestStep {
+        self.instructions = instructions.to_string();
+        self
+    }
+
+    pub fn ref_array(&self, show_test_data: bool) -> Vec<String> {
+        if show_test_data {
+            vec![
+                self.instructions(),
+                self.test_data(),
+                self.expected_results(),
+                self.ac(),
+            ]
+        } else {
+            vec![self.instructions(), self.expected_results(), self.ac()]
+        }
     }
 
     pub fn instructions(&self) -> String {
-        self.instructions.trim().to_string()
+        trim_block(&self.instructions)
     }
 
-    pub fn expected_results(&self) -> String {
-        self.expected_results.trim().to_string()
+    pub fn test_data(&self) -> String {
+        trim_block(&self.test_data)
     }
 
-    pub fn ac(&self) -> String {
-        self.ac.trim().to_string()
-    }
-
-    pub fn parse_markdown(input: &String) -> Result<TestStep, String> {
-        let lower = input.to_lowercase();
-
-        let instructions;
-        let expected_results;
-        let ac;
-
-        let comment_section_str = "# comment section";
-        let new_section_str = "# new section";
-        let instructions_str = "# instructions";
-
-        let maybe_new_section = lower.find(new_section_str).map(|idx| (idx, true, false));
-        let maybe_comment_section = lower
-            .find(comment_section_str)
-            .map(|idx| (idx, false, true));
-
-        let (idx, is_new_section, is_stepless_comment) = match maybe_new_section {
-            Some(t) => t,
-            None => match maybe_comment_section {
-                Some(t) => t,
-                None => (
-                    lower
-                        .find(instructions_str)
-                        .with_err_msg(&"Failed to find instructions")?,
-                    false,
-                    false,
-                ),
-            },
+    pub fn has_test_data(&self) -> bool {
+        !self.test_data.trim().is_empty()
+    }
+
+    // the step numbers as they appear in the compiled html, sections counted from
+    // `first_section` so a document split in parts can carry on where the last one ended
+    pub fn step_labels(items: &[TestStep], first_section: usize) -> Vec<String> {
+        let last_was_comment = |idx: usize| {
+            idx != 0
+                && items
+                    .get(idx - 1)
+                    .map(|i| i.is_stepless_comment)
+                    .unwrap_or(false)
         };
+        let is_end_of_section =
+            |idx: usize| items.get(idx + 1).map(|i| i.is_new_section).unwrap_or(true);
+
+        let mut labels = Vec::with_capacity(items.len());
+        let mut section_idx = first_section;
+        let mut step_idx = 1;
+        for (idx, item) in items.iter().enumerate() {
+            let starts_section = (item.is_new_section && !last_was_comment(idx))
+                || (item.is_stepless_comment && is_end_of_section(idx));
+            if starts_section && idx != 0 && idx != 1 {
+                section_idx += 1;
+                step_idx = 1;
+            }
+            labels.push(if item.is_stepless_comment && idx == 0 {
+                String::new()
+            } else if item.is_stepless_comment {
+                format!("{}", section_idx)
+            } else {
+                format!("{}.{}", section_idx, step_idx)
+            });
+            if !item.is_stepless_comment {
+                step_idx += 1;
+            }
+        }
+        labels
+    }
 
-        let offset = if is_new_section {
-            new_section_str.len()
-        } else if is_stepless_comment {
-            comment_section_str.len()
+    pub fn next_id(items: &[TestStep]) -> u64 {
+        items.iter().map(|i| i.id).max().unwrap_or(0) + 1
+    }
+
+    // gives every unassigned or duplicated id a fresh one, keeping the first holder
+    pub fn assign_ids(items: &mut [TestStep]) {
+        let mut next = Self::next_id(items);
+        let mut seen = std::collections::HashSet::new();
+        for item in items.iter_mut() {
+            if item.id == 0 || !seen.insert(item.id) {
+                item.id = next;
+                seen.insert(next);
+                next += 1;
+            }
+        }
+    }
+
+    // whitespace-only and formatting tweaks don't count as rewriting a step
+    pub fn is_substantial_edit(&self, other: &TestStep) -> bool {
+        fn normalize(val: &str) -> String {
+            val.split_whitespace().collect::<Vec<_>>().join(" ")
+        }
+        normalize(&self.instructions) != normalize(&other.instructions)
+            || normalize(&self.test_data) != normalize(&other.test_data)
+            || normalize(&self.expected_results) != normalize(&other.expected_results)
+            || normalize(&self.ac) != normalize(&other.ac)
+    }
+
+    // keeps identity and history of the step this one replaces
+    pub fn inherit_metadata(&mut self, old: &TestStep, author: Option<String>) {
+        self.id = old.id;
+        self.status = old.status;
+        self.priority = old.priority;
+        self.comment = old.comment.clone();
+        self.review_notes = old.review_notes.clone();
+        self.created_at = old.created_at;
+        self.updated_at = Some(timestamp::now());
+        self.author = if old.is_substantial_edit(self) {
+            author.or(old.author.clone())
         } else {
-            instructions_str.len()
+            old.author.clone()
         };
+    }
+
+    pub fn is_step(&self) -> bool {
+        !self.is_new_section && !self.is_stepless_comment
+    }
+
+    pub fn unresolved_notes(&self) -> usize {
+        self.review_notes.iter().filter(|n| !n.resolved).count()
+    }
+
+    // sections and comments never carry an estimate into the totals
+    pub fn total_estimate<'a, I: IntoIterator<Item = &'a TestStep>>(items: I) -> u32 {
+        items
+            .into_iter()
+            .filter(|i| i.is_step())
+            .filter_map(|i| i.estimate_minutes)
+            .fold(0, u32::saturating_add)
+    }
 
-        let input = input.split_at(idx + offset).1;
+    pub fn format_minutes(minutes: u32) -> String {
+        match (minutes / 60, minutes % 60) {
+            (0, m) => format!("{}m", m),
+            (h, 0) => format!("{}h", h),
+            (h, m) => format!("{}h{}m", h, m),
+        }
+    }
 
-        let lower = input.to_lowercase();
+    // accepts "5", "5m", "5 min", "2h", "1h30m" and "1h 30m"
+    pub fn parse_estimate(input: &str) -> Option<u32> {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return None;
+        }
+        if let Ok(minutes) = input.parse::<u32>() {
+            return Some(minutes);
+        }
 
-        let idx = lower
-            .find("# expected results")
-            .with_err_msg(&"Could not find expected results section")?;
+        let mut total: u32 = 0;
+        let mut digits = String::new();
+        let mut found_unit = false;
+        for c in input.chars() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+            } else if c == 'h' || c == 'm' {
+                if digits.is_empty() {
+                    // trailing letters of "min"/"hr"
+                    continue;
+                }
+                let val: u32 = digits.parse().ok()?;
+                let minutes = if c == 'h' { val.checked_mul(60)? } else { val };
+                total = total.checked_add(minutes)?;
+                digits.clear();
+                found_unit = true;
+            } else if c.is_whitespace() || c == 'i' || c == 'n' || c == 'r' || c == 's' {
+                continue;
+            } else {
+                return None;
+            }
+        }
 
-        let split = input.split_at(idx);
-        let splitb = input.split_at(idx + 19);
-        instructions = split.0.to_string();
-        let input = splitb.1;
-        let lower = input.to_lowercase();
+        if !digits.is_empty() || !found_unit {
+            None
+        } else {
+            Some(total)
+        }
+    }
 
-        let idx = lower
-            .find("# ac")
-            .with_err_msg(&"Could not find ac section")?;
+    // pulls a trailing "[5m]" off of the instructions
+    fn split_estimate_suffix(instructions: &str) -> (String, Option<u32>) {
+        let trimmed = instructions.trim_end();
+        if let Some(idx) = trimmed.rfind('[').filter(|_| trimmed.ends_with(']')) {
+            let (rest, suffix) = trimmed.split_at(idx);
+            let estimate = Self::parse_estimate(&suffix[1..suffix.len() - 1]);
+            if estimate.is_some() {
+                return (rest.to_string(), estimate);
+            }
+        }
+        (instructions.to_string(), None)
+    }
 
-        let split = input.split_at(idx);
-        let splitb = input.split_at(idx + 5);
-        expected_results = split.0.to_string();
-        ac = splitb.1.to_string();
+    pub fn expected_results(&self) -> String {
+        trim_block(&self.expected_results)
+    }
 
-        let data = TestStep {
-            is_stepless_comment,
-            is_new_section,
-            instructions,
-            expected_results,
-            ac,
+    pub fn ac(&self) -> String {
+        trim_block(&self.ac)
+    }
+
+    pub fn parse_markdown(input: &str) -> Result<TestStep, String> {
+        Self::parse_sections(input, false, &HashMap::new())
+    }
+
+    // parse_markdown with the heading_aliases from the config on top of DEFAULT_ALIASES
+    pub fn parse_markdown_with(
+        input: &str,
+        aliases: &HashMap<String, AliasTarget>,
+    ) -> Result<TestStep, String> {
+        Self::parse_sections(input, false, aliases)
+    }
+
+    // like parse_markdown, but a chunk cut out of the middle of a step is accepted:
+    // text before any heading reads as instructions and a missing expected results
+    // section comes back empty
+    pub fn parse_partial(
+        input: &str,
+        aliases: &HashMap<String, AliasTarget>,
+    ) -> Result<TestStep, String> {
+        Self::parse_sections(input, true, aliases)
+    }
+
+    // headings are matched a line at a time, so crlf endings, stray whitespace and
+    // multibyte text can't shift where one section ends and the next begins
+    fn parse_sections(
+        input: &str,
+        lenient: bool,
+        aliases: &HashMap<String, AliasTarget>,
+    ) -> Result<TestStep, String> {
+        // most input has neither carriage returns nor a scaffold, so is read in place
+        let mut input = Cow::Borrowed(input);
+        if input.contains('\r') {
+            input = Cow::Owned(input.replace("\r\n", "\n").replace('\r', "\n"));
+        }
+        if input.contains(scaffold::START) {
+            input = Cow::Owned(scaffold::strip(&input));
+        }
+        let mut sections: Vec<(Option<Heading>, Vec<&str>)> = vec![(None, Vec::new())];
+        // every heading line, to catch an alias doubling up with another heading
+        let mut titles: Vec<(Heading, bool, &str)> = Vec::new();
+        let code = code_lines(input.lines());
+        for (line, in_code) in input.lines().zip(code) {
+            let heading = Heading::parse(line, aliases).filter(|_| !in_code);
+            if let Some((heading, alias)) = heading {
+                titles.push((heading, alias, line.trim()));
+            }
+            // only the first heading of each kind starts a section, a later one is a
+            // markdown heading the step itself contains
+            match heading.filter(|(heading, _)| {
+                titles
+                    .iter()
+                    .filter(|(other, ..)| other.slot() == heading.slot())
+                    .count()
+                    == 1
+            }) {
+                Some((heading, _)) => sections.push((Some(heading), Vec::new())),
+                None => {
+                    if let Some((_, lines)) = sections.last_mut() {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+
+        for (idx, (heading, alias, title)) in titles.iter().enumerate() {
+            let clash = titles[idx + 1..].iter().find(|(other, other_alias, _)| {
+                other.slot() == heading.slot() && (*alias || *other_alias)
+            });
+            if let Some((_, _, other)) = clash {
+                return Err(format!(
+                    "Headings \"{}\" and \"{}\" both mean {}, keep only one",
+                    title,
+                    other,
+                    Locale::En.text(heading.key())
+                ));
+            }
+        }
+
+        let start = sections
+            .iter()
+            .find_map(|(heading, _)| heading.and_then(Heading::kind));
+        let kind = match start {
+            Some(kind) => kind,
+            None if lenient => StepKind::Step,
+            None => return Err("Failed to find instructions".to_string()),
         };
+        let has_expected = sections
+            .iter()
+            .any(|(heading, _)| *heading == Some(Heading::ExpectedResults));
+        if !has_expected && !lenient {
+            return Err("Could not find expected results section".to_string());
+        }
+
+        let mut data = TestStep::blank(kind);
+        let mut estimate = None;
+        let append = |field: &mut String, text: String| {
+            if !field.is_empty() && !text.is_empty() {
+                field.push('\n');
+            }
+            field.push_str(&text);
+        };
+        for (heading, lines) in &sections {
+            let text = Self::section_text(lines);
+            match heading {
+                // text above the first heading is ignored, unless this
+                // is a fragment with no step heading at all
+                None if start.is_none() => append(&mut data.instructions, text),
+                None => {}
+                Some(Heading::Instructions | Heading::NewSection | Heading::CommentSection) => {
+                    append(&mut data.instructions, text)
+                }
+                Some(Heading::TestData) => append(&mut data.test_data, text),
+                Some(Heading::ExpectedResults) => append(&mut data.expected_results, text),
+                Some(Heading::Ac) => append(&mut data.ac, text),
+                Some(Heading::Links) => data.links.extend(
+                    lines
+                        .iter()
+                        .map(|l| l.trim().to_string())
+                        .filter(|l| !l.is_empty()),
+                ),
+                Some(Heading::Estimate) => estimate = estimate.or(Self::parse_estimate(&text)),
+            }
+        }
+        let (instructions, estimate_minutes) = match estimate {
+            Some(estimate) => (data.instructions, Some(estimate)),
+            None => Self::split_estimate_suffix(&data.instructions),
+        };
+        data.instructions = instructions;
+        data.estimate_minutes = estimate_minutes;
 
         Ok(data)
     }
 
-    pub fn gen_markdown(&self) -> String {
+    // a link is either a bare url or "title | url"
+    pub fn link_parts(link: &str) -> (Option<&str>, &str) {
+        match link.split_once('|') {
+            Some((title, url)) => (Some(title.trim()), url.trim()),
+            None => (None, link.trim()),
+        }
+    }
+
+    // the lines under a heading, minus the single blank line gen_markdown puts on
+    // either side of each section
+    fn section_text(lines: &[&str]) -> String {
+        let mut lines = lines;
+        if lines.first().is_some_and(|l| l.trim().is_empty()) {
+            lines = &lines[1..];
+        }
+        if lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines = &lines[..lines.len() - 1];
+        }
+        lines.join("\n")
+    }
+
+    // without use_ac the ac heading is left out, unless the step already has ac content
+    // that would otherwise be lost on the next edit
+    pub fn gen_markdown(&self, use_ac: bool) -> String {
+        self.gen_markdown_in(use_ac, Locale::En)
+    }
+
+    // the step as the editor shows it, under headings in `locale`
+    pub fn gen_markdown_in(&self, use_ac: bool, locale: Locale) -> String {
+        let heading = |key| format!("# {}", locale.text(key));
         let pre_str = if self.is_new_section {
-            "# New Section"
+            heading(Key::NewSection)
         } else if self.is_stepless_comment {
-            "# Comment Section"
+            heading(Key::CommentSection)
+        } else {
+            heading(Key::Instructions)
+        };
+        let ac = if use_ac || !self.ac.trim().is_empty() {
+            format!("\n\n{}\n{}", heading(Key::Ac), self.ac())
         } else {
-            "# Instructions"
+            String::new()
         };
         format!(
-            "{}\n{}\n\n# Expected Results\n{}\n\n# AC\n{}",
+            "{}\n{}\n\n{}\n{}\n\n{}\n{}{}\n\n{}\n{}\n\n{}\n{}",
             pre_str,
             self.instructions(),
+            heading(Key::TestData),
+            self.test_data(),
+            heading(Key::ExpectedResults),
             self.expected_results(),
-            self.ac()
+            ac,
+            heading(Key::Links),
+            self.links.join("\n"),
+            heading(Key::Estimate),
+            self.estimate_minutes
+                .map(Self::format_minutes)
+                .unwrap_or_default()
         )
     }
+
+    // several steps as one markdown document, split on lines holding only "---"
+    pub fn gen_document(items: &[TestStep], use_ac: bool) -> String {
+        items
+            .iter()
+            .map(|i| i.gen_markdown(use_ac))
+            .collect::<Vec<_>>()
+            .join(&format!("\n\n{}\n\n", DOCUMENT_SEPARATOR))
+    }
+
+    // the steps of a document as slices of it, nothing is copied
+    fn split_chunks(input: &str) -> Vec<&str> {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        let mut at = 0;
+        let code = code_lines(input.lines());
+        for (line, in_code) in input.split_inclusive('\n').zip(code) {
+            if line.trim() == DOCUMENT_SEPARATOR && !in_code {
+                chunks.push(&input[start..at]);
+                start = at + line.len();
+            }
+            at += line.len();
+        }
+        chunks.push(&input[start..]);
+        chunks.retain(|c| !c.trim().is_empty());
+        chunks
+    }
+
+    pub fn parse_document(input: &str) -> Result<Vec<TestStep>, String> {
+        Self::split_chunks(input)
+            .iter()
+            .enumerate()
+            .map(|(idx, c)| {
+                TestStep::parse_markdown(c).map_err(|err| format!("Step {}: {}", idx + 1, err))
+            })
+            .collect()
+    }
+
+    // a step edited with separator lines in it comes back as several steps, with none
+    // it is held to the same headings as any other edit
+    pub fn parse_split(
+        input: &str,
+        aliases: &HashMap<String, AliasTarget>,
+    ) -> Result<Vec<TestStep>, String> {
+        match Self::split_chunks(input).as_slice() {
+            [] => Ok(Vec::new()),
+            [step] => Self::parse_markdown_with(step, aliases).map(|step| vec![step]),
+            parts => parts
+                .iter()
+                .enumerate()
+                .map(|(idx, c)| {
+                    TestStep::parse_partial(c, aliases)
+                        .map_err(|err| format!("Part {}: {}", idx + 1, err))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constructors_set_the_right_flags() {
+        let step = TestStep::new();
+        assert!(!step.is_new_section && !step.is_stepless_comment);
+        assert!(step.is_step());
+        assert_eq!(step, TestStep::default());
+
+        let section = TestStep::section("Login");
+        assert!(section.is_new_section && !section.is_stepless_comment);
+        assert_eq!(section.instructions, "Login");
+
+        let comment = TestStep::comment("Note");
+        assert!(comment.is_stepless_comment && !comment.is_new_section);
+        assert_eq!(comment.instructions, "Note");
+    }
+
+    #[test]
+    fn priorities_parse_from_names_and_numbers() {
+        let cases = [
+            ("high", Some(Priority::High)),
+            ("H", Some(Priority::High)),
+            ("1", Some(Priority::High)),
+            ("med", Some(Priority::Medium)),
+            ("2", Some(Priority::Medium)),
+            (" low ", Some(Priority::Low)),
+            ("3", Some(Priority::Low)),
+            ("none", None),
+            ("0", None),
+            ("", None),
+        ];
+        for (input, priority) in cases {
+            assert_eq!(Priority::parse(input), Ok(priority), "{}", input);
+        }
+        assert_eq!(
+            Priority::parse("urgent"),
+            Err("Unknown priority: urgent (expected high|medium|low|none)".to_string())
+        );
+    }
+
+    #[test]
+    fn payloads_from_before_priorities_load_without_one() {
+        let old = r#"{"instructions":"Open app","expected_results":"It opens","ac":""}"#;
+        let mut step: TestStep = serde_json::from_str(old).unwrap();
+        assert_eq!(step.priority, None);
+        assert!(!serde_json::to_string(&step).unwrap().contains("priority"));
+
+        step.priority = Some(Priority::High);
+        let json = serde_json::to_string(&step).unwrap();
+        assert!(json.contains(r#""priority":"high""#), "{}", json);
+        let read: TestStep = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.priority, Some(Priority::High));
+    }
+
+    #[test]
+    fn numbering_start_offsets_the_section_counter() {
+        let items = vec![
+            TestStep::section("Login"),
+            TestStep::new().with_instructions("Open app"),
+            TestStep::new().with_instructions("Sign in"),
+            TestStep::section("Settings"),
+            TestStep::new().with_instructions("Open settings"),
+            TestStep::comment("Done"),
+        ];
+        assert_eq!(
+            TestStep::step_labels(&items, 1),
+            vec!["1.1", "1.2", "1.3", "2.1", "2.2", "3"]
+        );
+        // steps restart at 1 in each section, only the section number moves
+        assert_eq!(
+            TestStep::step_labels(&items, 41),
+            vec!["41.1", "41.2", "41.3", "42.1", "42.2", "43"]
+        );
+    }
+
+    #[test]
+    fn blank_kinds_round_trip_through_markdown() {
+        for kind in [StepKind::Step, StepKind::Section, StepKind::Comment] {
+            let blank = TestStep::blank(kind);
+            let parsed = TestStep::parse_markdown(&blank.gen_markdown(true)).unwrap();
+            assert_eq!(parsed.is_new_section, blank.is_new_section, "{:?}", kind);
+            assert_eq!(
+                parsed.is_stepless_comment, blank.is_stepless_comment,
+                "{:?}",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn every_default_alias_reads_as_its_heading() {
+        for (alias, target) in DEFAULT_ALIASES {
+            let heading = alias.to_uppercase();
+            let markdown = match target {
+                AliasTarget::Instructions => {
+                    format!("# {}\nOpen app\n# Expected Results\nOK", heading)
+                }
+                AliasTarget::ExpectedResults => {
+                    format!("# Instructions\nOpen app\n# {}\nOK", heading)
+                }
+                AliasTarget::Ac => format!(
+                    "# Instructions\nOpen app\n# Expected Results\nOK\n# {}\nAC-1",
+                    heading
+                ),
+            };
+            let step = TestStep::parse_markdown(&markdown)
+                .unwrap_or_else(|err| panic!("{}: {}", alias, err));
+            assert_eq!(
+                (step.instructions.as_str(), step.expected_results.as_str()),
+                ("Open app", "OK"),
+                "{}",
+                alias
+            );
+            if target == AliasTarget::Ac {
+                assert_eq!(step.ac, "AC-1", "{}", alias);
+            }
+        }
+        // the canonical headings are still what gets written
+        let written = TestStep::parse_markdown("# Steps\nOpen app\n# Result\nOK")
+            .unwrap()
+            .gen_markdown(true);
+        assert!(written.starts_with("# Instructions\nOpen app\n"));
+        assert!(written.contains("# Expected Results\nOK"));
+    }
+
+    #[test]
+    fn configured_aliases_and_clashing_headings() {
+        let aliases = HashMap::from([("Vorgehen".to_string(), AliasTarget::Instructions)]);
+        let step =
+            TestStep::parse_markdown_with("# vorgehen\nÖffnen\n# Expected\nOK", &aliases).unwrap();
+        assert_eq!(step.instructions, "Öffnen");
+        assert!(TestStep::parse_markdown("# Vorgehen\nÖffnen\n# Expected\nOK").is_err());
+        let err = TestStep::parse_markdown(
+            "# Instructions\nOpen\n# Steps\nClick\n# Expected Results\nOK",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "Headings \"# Instructions\" and \"# Steps\" both mean Instructions, keep only one"
+        );
+        let err =
+            TestStep::parse_markdown("# Steps\nOpen\n# Expected\nOK\n# Result\nOK").unwrap_err();
+        assert!(err.contains("both mean Expected Results"), "{}", err);
+    }
+
+    #[test]
+    fn french_headings_read_back_the_same_step() {
+        let mut step = TestStep::new().with_instructions("Ouvrir la page");
+        step.test_data = "utilisateur: zoë".to_string();
+        step.expected_results = "La page s'affiche".to_string();
+        step.ac = "CA-1".to_string();
+        step.estimate_minutes = Some(5);
+        let french = step.gen_markdown_in(true, Locale::Fr);
+        assert!(french.contains("# Résultats attendus\nLa page s'affiche"));
+        assert_eq!(TestStep::parse_markdown(&french).unwrap(), step);
+        let section =
+            TestStep::parse_markdown("# NOUVELLE SECTION\nConnexion\n# Résultats attendus\n")
+                .unwrap();
+        assert!(section.is_new_section);
+        // english and french headings can be mixed in one step
+        let mixed = TestStep::parse_markdown(
+            "# Instructions\nOuvrir\n\n# Expected Results\nOK\n# CA\nCA-2",
+        )
+        .unwrap();
+        assert_eq!(
+            (mixed.expected_results.as_str(), mixed.ac.as_str()),
+            ("OK", "CA-2")
+        );
+    }
+
+    #[test]
+    fn fenced_code_round_trips_with_heading_lines_inside() {
+        let mut step = TestStep::new().with_instructions(
+            "Run the script:\n\n```sh\n# Expected Results\n#!/bin/sh\n  echo \"# AC\"\n---\n```\n\nthen check",
+        );
+        step.expected_results = "~~~~\n# Instructions\n~~~\nstill code\n~~~~".to_string();
+        step.ac = "AC-7".to_string();
+        let markdown = step.gen_markdown(true);
+        assert_eq!(TestStep::parse_markdown(&markdown).unwrap(), step);
+        let document = TestStep::gen_document(&[step.clone(), step.clone()], true);
+        assert_eq!(
+            TestStep::parse_document(&document).unwrap(),
+            vec![step.clone(), step]
+        );
+        assert_eq!(
+            code_lines(["a", "```", "# x", "```", "# y", "```rust", "````"]),
+            vec![false, true, true, true, false, true, true]
+        );
+    }
+
+    #[test]
+    fn heading_keywords_inside_a_step_stay_content() {
+        let input = concat!(
+            "# Instructions\n",
+            "Type # AC into the box, then # Expected Results\n",
+            " # Test Data\n",
+            "```\n# Expected Results\n```\n",
+            "# Expected Results\n",
+            "Saved\n",
+            "# Expected results\n",
+            "Shown as a heading\n",
+            "# AC\n",
+            "AC-3\n",
+            "# Instructions\n",
+        );
+        let step = TestStep::parse_markdown(input).unwrap();
+        assert_eq!(
+            step.instructions,
+            "Type # AC into the box, then # Expected Results\n # Test Data\n```\n# Expected Results\n```"
+        );
+        assert_eq!(step.test_data, "");
+        assert_eq!(
+            step.expected_results,
+            "Saved\n# Expected results\nShown as a heading"
+        );
+        assert_eq!(step.ac, "AC-3\n# Instructions");
+        // a section can't be reopened as a comment further down either
+        let section = TestStep::parse_markdown(
+            "# New Section\nLogin\n# Comment Section\n# Expected Results\n",
+        )
+        .unwrap();
+        assert!(section.is_new_section && !section.is_stepless_comment);
+        assert_eq!(section.instructions, "Login\n# Comment Section");
+    }
+
+    #[test]
+    fn split_in_the_middle_of_instructions() {
+        let input =
+            "# Instructions\nfirst half\n---\nsecond half\n# Expected Results\nworks\n# AC\nAC1\n";
+        let parts = TestStep::parse_split(input, &HashMap::new()).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].instructions.trim(), "first half");
+        assert!(parts[0].expected_results.trim().is_empty());
+        assert_eq!(parts[1].instructions.trim(), "second half");
+        assert_eq!(parts[1].expected_results.trim(), "works");
+        assert_eq!(parts[1].ac.trim(), "AC1");
+
+        // without a separator it's a normal edit, the headings are still required
+        let unsplit = "# Instructions\nfirst half\n";
+        assert_eq!(
+            TestStep::parse_split(unsplit, &HashMap::new()),
+            TestStep::parse_markdown(unsplit).map(|step| vec![step])
+        );
+        assert!(TestStep::parse_split(unsplit, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn merge_joins_fields_and_keeps_flags() {
+        let mut upper = TestStep::comment("note");
+        upper.expected_results = "first\n".to_string();
+        let mut lower = TestStep::new().with_instructions("more");
+        lower.expected_results = "second".to_string();
+        lower.ac = "AC2".to_string();
+        upper.merge(&lower);
+        assert!(upper.is_stepless_comment);
+        assert_eq!(upper.instructions, "note\n\nmore");
+        assert_eq!(upper.expected_results, "first\n\nsecond");
+        assert_eq!(upper.ac, "AC2");
+
+        // estimates add up, stopping at the largest u32
+        upper.estimate_minutes = Some(u32::MAX);
+        lower.estimate_minutes = Some(5);
+        upper.merge(&lower);
+        assert_eq!(upper.estimate_minutes, Some(u32::MAX));
+    }
+
+    #[test]
+    fn ac_section_is_optional() {
+        let mut step = TestStep::new().with_instructions("Open");
+        step.expected_results = "Opens".to_string();
+        step.links = vec!["https://example.com".to_string()];
+        step.estimate_minutes = Some(5);
+        let markdown = step.gen_markdown(false);
+        assert!(!markdown.contains("# AC"));
+
+        let parsed = TestStep::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.expected_results.trim(), "Opens");
+        assert_eq!(parsed.links, step.links);
+        assert_eq!(parsed.estimate_minutes, Some(5));
+        assert!(parsed.ac.trim().is_empty());
+
+        step.ac = "AC1".to_string();
+        assert!(step.gen_markdown(false).contains("# AC"));
+    }
+
+    #[test]
+    fn estimates_too_large_for_minutes_are_rejected() {
+        assert_eq!(TestStep::parse_estimate("1h 30m"), Some(90));
+        assert_eq!(TestStep::parse_estimate("99999999h"), None);
+        assert_eq!(TestStep::parse_estimate("4294967295m 1m"), None);
+        assert_eq!(TestStep::parse_estimate("99999999999"), None);
+
+        // each parses, the total stops at the largest u32 instead of wrapping
+        let mut step = TestStep::new().with_instructions("Wait");
+        step.estimate_minutes = TestStep::parse_estimate("4294967295m");
+        assert_eq!(TestStep::total_estimate(&[step.clone(), step]), u32::MAX);
+    }
+
+    fn arbitrary_step(
+        kind: u8,
+        instructions: String,
+        test_data: String,
+        expected_results: String,
+        ac: String,
+        links: Vec<String>,
+        estimate: Option<u16>,
+    ) -> Option<TestStep> {
+        let fields = [&instructions, &test_data, &expected_results, &ac];
+        let texts = fields.into_iter().chain(links.iter());
+        if texts
+            .flat_map(|t| t.split(['\n', '\r']))
+            .any(|l| Heading::parse(l, &HashMap::new()).is_some())
+        {
+            return None;
+        }
+        let mut step = TestStep::blank(match kind % 3 {
+            0 => StepKind::Step,
+            1 => StepKind::Section,
+            _ => StepKind::Comment,
+        });
+        step.instructions = instructions;
+        step.test_data = test_data;
+        step.expected_results = expected_results;
+        step.ac = ac;
+        step.links = links;
+        step.estimate_minutes = estimate.map(u32::from);
+        Some(step)
+    }
+
+    #[test]
+    fn gen_then_parse_is_a_fixed_point() {
+        #[allow(clippy::too_many_arguments)]
+        fn property(
+            kind: u8,
+            use_ac: bool,
+            instructions: String,
+            test_data: String,
+            expected_results: String,
+            ac: String,
+            links: Vec<String>,
+            estimate: Option<u16>,
+        ) -> quickcheck::TestResult {
+            let step = arbitrary_step(
+                kind,
+                instructions,
+                test_data,
+                expected_results,
+                ac,
+                links,
+                estimate,
+            );
+            let Some(step) = step else {
+                return quickcheck::TestResult::discard();
+            };
+            let once = TestStep::parse_markdown(&step.gen_markdown(use_ac)).unwrap();
+            let markdown = once.gen_markdown(use_ac);
+            let twice = TestStep::parse_markdown(&markdown).unwrap();
+            let crlf = TestStep::parse_markdown(&markdown.replace('\n', "\r\n")).unwrap();
+            quickcheck::TestResult::from_bool(once == twice && crlf == twice)
+        }
+        quickcheck::QuickCheck::new().quickcheck(
+            property
+                as fn(
+                    u8,
+                    bool,
+                    String,
+                    String,
+                    String,
+                    String,
+                    Vec<String>,
+                    Option<u16>,
+                ) -> quickcheck::TestResult,
+        );
+    }
+
+    #[test]
+    fn crlf_and_trailing_newlines_are_tolerated() {
+        let markdown = "# Instructions\r\nOpen\r\n\r\n# Expected Results\r\nOpens\r\n\r\n\r\n";
+        let parsed = TestStep::parse_markdown(markdown).unwrap();
+        assert_eq!(parsed.instructions, "Open");
+        assert_eq!(parsed.expected_results, "Opens\n");
+    }
+
+    #[test]
+    fn multibyte_text_next_to_headings() {
+        let markdown = concat!(
+            "İİİ preamble\n",
+            "# INSTRUCTIONS\n",
+            "İstanbul 🚀\n",
+            "# Test Data\n",
+            "テストデータ\n",
+            "# Expected Results\n",
+            "ΣΊΣΥΦΟΣ 👍🏽\n",
+            "# AC\n",
+            "验收标准\n",
+            "# Links\n",
+            "🔗 | https://例え.jp\n",
+        );
+        let parsed = TestStep::parse_markdown(markdown).unwrap();
+        assert_eq!(parsed.instructions, "İstanbul 🚀");
+        assert_eq!(parsed.test_data, "テストデータ");
+        assert_eq!(parsed.expected_results, "ΣΊΣΥΦΟΣ 👍🏽");
+        assert_eq!(parsed.ac, "验收标准");
+        assert_eq!(parsed.links, vec!["🔗 | https://例え.jp".to_string()]);
+
+        let again = TestStep::parse_markdown(&parsed.gen_markdown(true)).unwrap();
+        assert_eq!(again, parsed);
+    }
 }
@@ -0,0 +1,249 @@
+// round trips between the stored items, the compiled html and the embedding loaded back
+// out of it. golden files pin the compiled html so template changes show up in review,
+// regenerate them with UPDATE_GOLDEN=1 cargo test
+use crate::{
+    config::{ColumnConfig, MarkdownExtension},
+    embedding,
+    export::html::{self, HtmlOptions, PrintOptions},
+    locale::Locale,
+    stress,
+    test_step::TestStep,
+};
+use std::path::PathBuf;
+
+const FIXTURES: [&str; 6] = [
+    "unicode",
+    "markup",
+    "embedding_text",
+    "long_cells",
+    "gfm",
+    "code",
+];
+
+fn repo_path(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+fn fixture(name: &str) -> Vec<TestStep> {
+    let path = repo_path(&format!("tests/fixtures/{}.json", name));
+    let content = std::fs::read_to_string(&path).expect("fixture exists");
+    serde_json::from_str(&content).expect("fixture parses")
+}
+
+fn compile(items: &[TestStep]) -> String {
+    compile_with(items, &[])
+}
+
+fn compile_with(items: &[TestStep], markdown_extensions: &[MarkdownExtension]) -> String {
+    let columns = ColumnConfig::default();
+    let options = HtmlOptions {
+        columns: &columns,
+        use_ac: true,
+        show_ids: false,
+        max_image_bytes: 0,
+        locale: Locale::En,
+        markdown_extensions,
+        numbering_start: 1,
+    };
+    html::gen_html(items, &options).expect("html compiles")
+}
+
+fn check_golden(name: &str, compiled: &str) {
+    let path = repo_path(&format!("tests/golden/{}.html", name));
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        std::fs::write(&path, compiled).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing golden file {}", path.display()));
+    assert!(
+        compiled == golden,
+        "compiled html for {} differs from {}, rerun with UPDATE_GOLDEN=1 if intended",
+        name,
+        path.display()
+    );
+}
+
+#[test]
+fn embedding_round_trip() {
+    for name in FIXTURES {
+        let items = fixture(name);
+        let serialized = embedding::serialize_items(&items).unwrap();
+        assert_eq!(
+            embedding::deserialize_items(&serialized).unwrap(),
+            items,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn compiled_html_round_trip() {
+    for name in FIXTURES {
+        let items = fixture(name);
+        let loaded = embedding::extract(&compile(&items)).unwrap();
+        assert_eq!(loaded, items, "{}", name);
+    }
+}
+
+// code blocks come back from the editor as written and compile to a styled <pre>
+#[test]
+fn code_blocks_survive_the_editor_and_compile() {
+    let items = fixture("code");
+    for item in &items {
+        let mut edited = TestStep::parse_markdown(&item.gen_markdown(true)).unwrap();
+        edited.id = item.id;
+        assert_eq!(&edited, item);
+    }
+    let compiled = compile(&items);
+    assert!(compiled.contains(
+        "<pre class=\"code-block\"><code class=\"language-sh\"># as the app user\npsql -c 'TRUNCATE orders;' &lt;&lt;EOF\n  \\q\nEOF\n</code></pre>"
+    ));
+    assert!(
+        compiled.contains(
+            "<pre class=\"code-block\"><code>indented block\n# not a heading</code></pre>"
+        )
+    );
+    assert!(compiled.contains("<code>make seed</code>"));
+}
+
+// generated documents reach field and unicode combinations the hand written fixtures don't
+#[test]
+fn stress_documents_round_trip() {
+    for seed in [stress::DEFAULT_SEED, 1, 2] {
+        let items = stress::generate(200, seed);
+        let serialized = embedding::serialize_items(&items).unwrap();
+        assert_eq!(embedding::deserialize_items(&serialized).unwrap(), items);
+        assert_eq!(embedding::extract(&compile(&items)).unwrap(), items);
+        for item in &items {
+            let parsed = TestStep::parse_markdown(&item.gen_markdown(true)).unwrap();
+            assert_eq!(parsed.instructions, item.instructions, "seed {}", seed);
+            assert_eq!(parsed.expected_results, item.expected_results);
+        }
+        // the whole document at once, as a bulk import reads it, and with windows endings
+        let document = TestStep::gen_document(&items, true);
+        let imported = TestStep::parse_document(&document).unwrap();
+        assert_eq!(imported.len(), items.len());
+        assert_eq!(
+            TestStep::parse_document(&document.replace('\n', "\r\n")).unwrap(),
+            imported
+        );
+        for (imported, item) in imported.iter().zip(&items) {
+            assert_eq!(imported.instructions, item.instructions, "seed {}", seed);
+            assert_eq!(imported.ac, item.ac);
+        }
+    }
+}
+
+#[test]
+fn load_file_reads_compiled_html() {
+    let items = fixture("markup");
+    let path =
+        std::env::temp_dir().join(format!("uat_editor_round_trip_{}.html", std::process::id()));
+    std::fs::write(&path, compile(&items)).unwrap();
+    let loaded = embedding::load_file(path.to_str().unwrap());
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(loaded.unwrap(), items);
+}
+
+#[test]
+fn compiled_html_matches_golden() {
+    for name in FIXTURES {
+        check_golden(name, &compile(&fixture(name)));
+    }
+}
+
+// gfm.html is the same fixture with the extensions off, where all of it stays text
+#[test]
+fn each_markdown_extension_matches_golden() {
+    let items = fixture("gfm");
+    let extensions = [
+        ("gfm_tables", MarkdownExtension::Tables, "<table>"),
+        (
+            "gfm_tasklists",
+            MarkdownExtension::Tasklists,
+            "type=\"checkbox\"",
+        ),
+        (
+            "gfm_strikethrough",
+            MarkdownExtension::Strikethrough,
+            "<del>",
+        ),
+    ];
+    let plain = compile(&items);
+    for (name, extension, marker) in extensions {
+        let compiled = compile_with(&items, &[extension]);
+        assert!(
+            !plain.contains(marker) && compiled.contains(marker),
+            "{}",
+            name
+        );
+        assert_eq!(embedding::extract(&compiled).unwrap(), items, "{}", name);
+        check_golden(name, &compiled);
+    }
+}
+
+#[test]
+fn print_html_is_standalone_and_embeds_on_request() {
+    let items = fixture("markup");
+    let columns = ColumnConfig::default();
+    let options = HtmlOptions {
+        columns: &columns,
+        use_ac: true,
+        show_ids: false,
+        max_image_bytes: 0,
+        locale: Locale::En,
+        markdown_extensions: &[],
+        numbering_start: 1,
+    };
+    let print = |embed| {
+        let print = PrintOptions {
+            source: Some("markup.json".to_string()),
+            embed,
+        };
+        html::gen_print_html(&items, &options, &print).unwrap()
+    };
+    let plain = print(false);
+    assert!(plain.starts_with("<!DOCTYPE html>"));
+    assert!(plain.contains("<thead>\n<tr>\n<td class=\"step-td\""));
+    assert!(plain.contains("<dt>Source</dt><dd>markup.json</dd>"));
+    assert!(plain.contains("page-break-inside: avoid"));
+    assert!(embedding::extract(&plain).is_err());
+    assert_eq!(embedding::extract(&print(true)).unwrap(), items);
+}
+
+#[test]
+fn saved_documents_are_byte_stable() {
+    for name in FIXTURES {
+        let path = std::env::temp_dir().join(format!(
+            "uat_editor_canonical_{}_{}.json",
+            std::process::id(),
+            name
+        ));
+        let path = path.to_str().unwrap();
+        let items = fixture(name);
+        embedding::save_file(path, &items).unwrap();
+        let first = std::fs::read_to_string(path).unwrap();
+        let loaded = embedding::load_file(path).unwrap();
+        assert_eq!(loaded, items, "{}", name);
+        embedding::save_file(path, &loaded).unwrap();
+        let second = std::fs::read_to_string(path).unwrap();
+        assert!(first == second, "saving {} again changed the file", name);
+        assert!(!embedding::format_file(path, false).unwrap(), "{}", name);
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn fmt_rewrites_compact_documents_once() {
+    let path = std::env::temp_dir().join(format!("uat_editor_fmt_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let compact = embedding::document_json(&fixture("markup")).unwrap();
+    std::fs::write(path, &compact).unwrap();
+    assert!(embedding::format_file(path, true).unwrap());
+    assert_eq!(std::fs::read_to_string(path).unwrap(), compact);
+    assert!(embedding::format_file(path, false).unwrap());
+    assert!(!embedding::format_file(path, false).unwrap());
+    let _ = std::fs::remove_file(path);
+}
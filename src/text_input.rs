@@ -0,0 +1,313 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// text typed into the tui itself: the command line and search, and a table cell
+// edited with `i`. the cursor is a byte offset that always sits on a char boundary
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextInput {
+    text: String,
+    cursor: usize,
+    multiline: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Editing,
+    Commit,
+    Cancel,
+}
+
+// pasted text as an input takes it: line endings made \n, the newline copied after a line
+// dropped and the rest joined with spaces unless the input is multiline. other control
+// characters are removed, the count is returned so the paste can be flagged
+pub fn clean_paste(text: &str, multiline: bool) -> (String, usize) {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut dropped = 0;
+    let mut out = String::new();
+    for c in text.trim_end_matches('\n').chars() {
+        match c {
+            '\n' if multiline => out.push(c),
+            '\n' | '\t' => out.push(' '),
+            c if c.is_control() => dropped += 1,
+            c => out.push(c),
+        }
+    }
+    (out, dropped)
+}
+
+impl TextInput {
+    // the cursor starts at the end
+    pub fn new(text: &str) -> TextInput {
+        TextInput {
+            text: text.to_string(),
+            cursor: text.len(),
+            multiline: false,
+        }
+    }
+
+    // Enter still commits, Alt+Enter starts a new line
+    pub fn multiline(text: &str) -> TextInput {
+        TextInput {
+            multiline: true,
+            ..TextInput::new(text)
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        self.multiline
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        if c == '\n' && !self.multiline {
+            return;
+        }
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    // inserted as typed text, never committing. returns the control characters dropped
+    pub fn paste(&mut self, text: &str) -> usize {
+        let (text, dropped) = clean_paste(text, self.multiline);
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+        dropped
+    }
+
+    // false when there was nothing before the cursor to delete
+    pub fn backspace(&mut self) -> bool {
+        let Some(c) = self.text[..self.cursor].chars().next_back() else {
+            return false;
+        };
+        self.cursor -= c.len_utf8();
+        self.text.remove(self.cursor);
+        true
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    fn line_start(&self) -> usize {
+        self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self) -> usize {
+        self.text[self.cursor..]
+            .find('\n')
+            .map_or(self.text.len(), |i| self.cursor + i)
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = self.line_start();
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.line_end();
+    }
+
+    // the display column the cursor is at within its line
+    fn column(&self) -> usize {
+        self.text[self.line_start()..self.cursor].width()
+    }
+
+    // the byte offset in the line starting at `start` closest to display column `column`
+    fn offset_at(&self, start: usize, column: usize) -> usize {
+        let line = self.text[start..].split('\n').next().unwrap_or("");
+        let mut width = 0;
+        for (idx, c) in line.char_indices() {
+            let next = width + c.width().unwrap_or(0);
+            if next > column {
+                return start + idx;
+            }
+            width = next;
+        }
+        start + line.len()
+    }
+
+    pub fn up(&mut self) {
+        let start = self.line_start();
+        if start == 0 {
+            return;
+        }
+        let column = self.column();
+        let previous = self.text[..start - 1].rfind('\n').map_or(0, |i| i + 1);
+        self.cursor = self.offset_at(previous, column);
+    }
+
+    pub fn down(&mut self) {
+        let end = self.line_end();
+        if end == self.text.len() {
+            return;
+        }
+        let column = self.column();
+        self.cursor = self.offset_at(end + 1, column);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Enter if alt && self.multiline => self.insert('\n'),
+            KeyCode::Enter => return Action::Commit,
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Up => self.up(),
+            KeyCode::Down => self.down(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            KeyCode::Char('a') if ctrl => self.home(),
+            KeyCode::Char('e') if ctrl => self.end(),
+            KeyCode::Backspace => {
+                self.backspace();
+            }
+            KeyCode::Delete => self.delete(),
+            KeyCode::Char(c) if !ctrl => self.insert(c),
+            _ => {}
+        }
+        Action::Editing
+    }
+
+    // the part of the text that fits in `width` by `height` cells, scrolled so the cursor
+    // shows, and where the cursor lands in it. every line scrolls sideways together
+    pub fn view(&self, width: u16, height: u16) -> (Vec<String>, (u16, u16)) {
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+        let row = self.text[..self.cursor].matches('\n').count();
+        let column = self.column();
+        let first_row = (row + 1).saturating_sub(height);
+        // one cell is kept free past the end for the cursor to sit in
+        let scroll = (column + 1).saturating_sub(width);
+        let lines = self
+            .text
+            .split('\n')
+            .skip(first_row)
+            .take(height)
+            .map(|line| {
+                let mut at = 0;
+                let mut out = String::new();
+                for c in line.chars() {
+                    let w = c.width().unwrap_or(0);
+                    if at >= scroll && at + w <= scroll + width {
+                        out.push(c);
+                    } else if at < scroll && at + w > scroll {
+                        // a wide char cut by the left edge
+                        out.push(' ');
+                    }
+                    at += w;
+                }
+                out
+            })
+            .collect();
+        (lines, ((column - scroll) as u16, (row - first_row) as u16))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn editing_moves_over_whole_chars() {
+        let mut input = TextInput::new("naïve");
+        input.left();
+        input.left();
+        assert!(input.backspace());
+        input.insert('i');
+        assert_eq!(input.text(), "naive");
+        input.home();
+        assert!(!input.backspace());
+        input.delete();
+        assert_eq!(input.text(), "aive");
+        input.insert('\n');
+        assert_eq!(input.text(), "aive", "single line inputs ignore newlines");
+    }
+
+    #[test]
+    fn keys_commit_cancel_and_edit() {
+        let mut input = TextInput::multiline("a");
+        assert_eq!(input.handle_key(key(KeyCode::Char('j'))), Action::Editing);
+        assert_eq!(
+            input.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)),
+            Action::Editing
+        );
+        assert_eq!(input.text(), "aj\n");
+        assert_eq!(input.handle_key(key(KeyCode::Enter)), Action::Commit);
+        assert_eq!(input.handle_key(key(KeyCode::Esc)), Action::Cancel);
+    }
+
+    #[test]
+    fn pastes_fit_the_input() {
+        let mut input = TextInput::new("search ");
+        assert_eq!(input.paste("status:fail\r\nLogin\tpage\r\n"), 0);
+        assert_eq!(input.text(), "search status:fail Login page");
+        input.home();
+        assert_eq!(input.paste("\u{1b}[2J\u{7}x"), 2);
+        assert_eq!(input.text(), "[2Jxsearch status:fail Login page");
+
+        let mut input = TextInput::multiline("");
+        input.paste("one\rtwo\n\nthree\n");
+        assert_eq!(input.text(), "one\ntwo\n\nthree");
+        assert_eq!(input.handle_key(key(KeyCode::Char('!'))), Action::Editing);
+        assert_eq!(input.text(), "one\ntwo\n\nthree!");
+    }
+
+    #[test]
+    fn up_and_down_keep_the_display_column() {
+        let mut input = TextInput::multiline("日本語\nab\nabcdef");
+        input.up();
+        assert_eq!(input.cursor, "日本語\n".len() + 2);
+        input.up();
+        // column 2 is the start of 本
+        assert_eq!(input.cursor, "日".len());
+        input.down();
+        input.down();
+        assert_eq!(input.cursor, "日本語\nab\n".len() + 2);
+    }
+
+    #[test]
+    fn long_lines_scroll_to_the_cursor() {
+        let mut input = TextInput::new("0123456789");
+        assert_eq!(input.view(4, 1), (vec!["789".to_string()], (3, 0)));
+        input.home();
+        assert_eq!(input.view(4, 1), (vec!["0123".to_string()], (0, 0)));
+
+        let input = TextInput::multiline("one\ntwo\nthree\nfour");
+        assert_eq!(
+            input.view(10, 3),
+            (
+                vec!["two".to_string(), "three".to_string(), "four".to_string()],
+                (4, 2)
+            )
+        );
+        let input = TextInput::new("日本語x");
+        assert_eq!(input.view(4, 1), (vec!["語x".to_string()], (3, 0)));
+        // the half of 語 left of the edge is blanked
+        assert_eq!(input.view(3, 1), (vec![" x".to_string()], (2, 0)));
+    }
+}
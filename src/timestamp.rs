@@ -0,0 +1,82 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+const MONTH: u64 = 30 * DAY;
+const YEAR: u64 = 365 * DAY;
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn format_age(secs: u64) -> String {
+    if secs < MINUTE {
+        "just now".to_string()
+    } else if secs < HOUR {
+        format!("{}m ago", secs / MINUTE)
+    } else if secs < DAY {
+        format!("{}h ago", secs / HOUR)
+    } else if secs < MONTH {
+        format!("{}d ago", secs / DAY)
+    } else if secs < YEAR {
+        format!("{}mo ago", secs / MONTH)
+    } else {
+        format!("{}y ago", secs / YEAR)
+    }
+}
+
+// formats an epoch timestamp as YYYY-MM-DD (UTC)
+pub fn format_date(timestamp: u64) -> String {
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = (timestamp / DAY) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// accepts durations like "90d", "2w", "3mo", "1y" or "12h"
+pub fn parse_duration(input: &str) -> Option<u64> {
+    let input = input.trim().to_lowercase();
+    let split = input.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = input.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let unit = match unit {
+        "m" | "min" => MINUTE,
+        "h" => HOUR,
+        "d" => DAY,
+        "w" => WEEK,
+        "mo" => MONTH,
+        "y" => YEAR,
+        _ => return None,
+    };
+    amount.checked_mul(unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_parse_or_are_rejected() {
+        assert_eq!(parse_duration("90d"), Some(90 * DAY));
+        assert_eq!(parse_duration(" 2W "), Some(2 * WEEK));
+        assert_eq!(parse_duration("3mo"), Some(3 * MONTH));
+        assert_eq!(parse_duration("12h"), Some(12 * HOUR));
+        assert_eq!(parse_duration("90"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("5s"), None);
+        // more seconds than a u64 holds
+        assert_eq!(parse_duration("99999999999999y"), None);
+    }
+}
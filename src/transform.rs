@@ -0,0 +1,185 @@
+use crate::{
+    config::TransformerConfig,
+    shell,
+    test_step::{DOCUMENT_SEPARATOR, TestStep},
+};
+use std::{ops::Range, time::Duration};
+
+// the rewritten steps, with the originals kept wherever a chunk failed
+pub struct Transformed {
+    pub items: Vec<TestStep>,
+    pub failures: Vec<String>,
+    pub chunks: usize,
+}
+
+// consecutive steps whose markdown fits in `max_chars`, a step longer than that on its own
+pub fn chunks(items: &[TestStep], use_ac: bool, max_chars: Option<usize>) -> Vec<Range<usize>> {
+    let max_chars = max_chars.unwrap_or(usize::MAX);
+    // the same join gen_document puts between steps
+    let separator = DOCUMENT_SEPARATOR.len() + 4;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    for (idx, item) in items.iter().enumerate() {
+        let step_len = item.gen_markdown(use_ac).len();
+        if idx > start && len + separator + step_len > max_chars {
+            chunks.push(start..idx);
+            start = idx;
+            len = 0;
+        }
+        len += if idx > start { separator } else { 0 } + step_len;
+    }
+    if start < items.len() {
+        chunks.push(start..items.len());
+    }
+    chunks
+}
+
+fn label(range: &Range<usize>) -> String {
+    if range.len() == 1 {
+        format!("Item {}", range.start + 1)
+    } else {
+        format!("Items {}-{}", range.start + 1, range.end)
+    }
+}
+
+// the headings carry the section and comment flags, so the same kinds have to come back
+// in the same order for each step to keep the id and history of the one it replaces
+fn transform_chunk(
+    transformer: &TransformerConfig,
+    originals: &[TestStep],
+    use_ac: bool,
+    author: Option<String>,
+) -> Result<Vec<TestStep>, String> {
+    let output = shell::run_timeout(
+        &transformer.command,
+        &TestStep::gen_document(originals, use_ac),
+        Duration::from_secs(transformer.timeout_secs),
+    )?;
+    if !output.status.success() {
+        return Err(
+            format!("failed ({}) {}", output.status, output.first_stderr_line())
+                .trim_end()
+                .to_string(),
+        );
+    }
+    let mut steps = TestStep::parse_document(&output.stdout)?;
+    if steps.len() != originals.len() {
+        return Err(format!(
+            "got {} steps back for {}",
+            steps.len(),
+            originals.len()
+        ));
+    }
+    for (step, original) in steps.iter_mut().zip(originals) {
+        if (step.is_new_section, step.is_stepless_comment)
+            != (original.is_new_section, original.is_stepless_comment)
+        {
+            return Err("a section or comment heading was changed".to_string());
+        }
+        step.inherit_metadata(original, author.clone());
+    }
+    Ok(steps)
+}
+
+pub fn run(
+    transformer: &TransformerConfig,
+    items: &[TestStep],
+    use_ac: bool,
+    author: Option<String>,
+) -> Transformed {
+    let ranges = chunks(items, use_ac, transformer.max_chars);
+    let mut transformed = Transformed {
+        items: Vec::with_capacity(items.len()),
+        failures: Vec::new(),
+        chunks: ranges.len(),
+    };
+    for range in ranges {
+        let originals = &items[range.clone()];
+        match transform_chunk(transformer, originals, use_ac, author.clone()) {
+            Ok(steps) => transformed.items.extend(steps),
+            Err(err) => {
+                transformed.items.extend_from_slice(originals);
+                transformed
+                    .failures
+                    .push(format!("{}: {}", label(&range), err));
+            }
+        }
+    }
+    transformed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transformer(command: &str, max_chars: Option<usize>) -> TransformerConfig {
+        TransformerConfig {
+            command: command.to_string(),
+            timeout_secs: 10,
+            max_chars,
+        }
+    }
+
+    fn items() -> Vec<TestStep> {
+        let mut items = vec![
+            TestStep::section("Login"),
+            TestStep::new().with_instructions("Open the app"),
+            TestStep::comment("Boom"),
+            TestStep::new().with_instructions("Open settings"),
+        ];
+        for (id, item) in items.iter_mut().enumerate() {
+            item.id = id as u64 + 1;
+        }
+        items
+    }
+
+    #[test]
+    fn chunks_hold_whole_steps() {
+        let items = items();
+        assert_eq!(chunks(&items, false, None).len(), 1);
+        assert!(chunks(&[], false, None).is_empty());
+        assert_eq!(chunks(&items, false, Some(1)), vec![0..1, 1..2, 2..3, 3..4]);
+        let two = TestStep::gen_document(&items[2..], false).len();
+        assert_eq!(chunks(&items, false, Some(two)), vec![0..2, 2..4]);
+    }
+
+    #[test]
+    fn failed_chunks_keep_their_originals() {
+        let cmd = "input=$(cat); case \"$input\" in *Boom*) echo no >&2; exit 3;; esac; \
+                   printf '%s\\n' \"$input\" | sed 's/Open/Öffnen/'";
+        let transformed = run(&transformer(cmd, Some(1)), &items(), false, None);
+        let texts: Vec<&str> = transformed
+            .items
+            .iter()
+            .map(|i| i.instructions.as_str())
+            .collect();
+        assert_eq!(
+            texts,
+            vec!["Login", "Öffnen the app", "Boom", "Öffnen settings"]
+        );
+        assert!(transformed.items[0].is_new_section);
+        assert!(transformed.items[2].is_stepless_comment);
+        let ids: Vec<u64> = transformed.items.iter().map(|i| i.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+        assert_eq!(
+            transformed.failures,
+            vec!["Item 3: failed (exit status: 3) no"]
+        );
+    }
+
+    #[test]
+    fn changed_headings_fail_the_chunk() {
+        let transformed = run(
+            &transformer("sed 's/# New Section/# Instructions/'", None),
+            &items(),
+            false,
+            None,
+        );
+        assert_eq!(transformed.items, items());
+        assert_eq!(
+            transformed.failures,
+            vec!["Items 1-4: a section or comment heading was changed"]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{config::Config, images, spellcheck, test_step::TestStep, words};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+pub struct Issue {
+    pub step: Option<String>,
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.step {
+            Some(step) => write!(f, "step {}: {}", step, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// only checks that a scheme is present, anything beyond that is up to the browser
+fn is_valid_url(url: &str) -> bool {
+    match url.split_once("://") {
+        Some((scheme, rest)) => {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+                && !rest.is_empty()
+        }
+        None => url.starts_with("mailto:"),
+    }
+}
+
+fn check_links(step: &str, item: &TestStep, issues: &mut Vec<Issue>) {
+    for link in &item.links {
+        let (_, url) = TestStep::link_parts(link);
+        if !is_valid_url(url) {
+            issues.push(Issue {
+                step: Some(step.to_string()),
+                message: format!("link is missing a scheme: {}", url),
+            });
+        }
+    }
+}
+
+fn check_images(step: &str, item: &TestStep, config: &Config, issues: &mut Vec<Issue>) {
+    let fields = [
+        &item.instructions,
+        &item.test_data,
+        &item.expected_results,
+        &item.ac,
+    ];
+    for field in fields {
+        for dest in images::local_images(field) {
+            if let Err(problem) = images::check(&dest, None, config.max_image_bytes) {
+                issues.push(Issue {
+                    step: Some(step.to_string()),
+                    message: problem.message(),
+                });
+            }
+        }
+    }
+}
+
+fn check_length(step: &str, item: &TestStep, config: &Config, issues: &mut Vec<Issue>) {
+    if config.max_step_words == 0 || !item.is_step() {
+        return;
+    }
+    let fields = [
+        ("instructions", &item.instructions),
+        ("test data", &item.test_data),
+        ("expected results", &item.expected_results),
+        ("ac", &item.ac),
+    ];
+    for (name, field) in fields {
+        let count = words::word_count(field);
+        if count > config.max_step_words {
+            issues.push(Issue {
+                step: Some(step.to_string()),
+                message: format!(
+                    "{} has {} words, over the limit of {} (:split can break it up)",
+                    name, count, config.max_step_words
+                ),
+            });
+        }
+    }
+}
+
+// instructions and expected results with whitespace runs collapsed, empty steps have no
+// key since they're reported on their own
+fn duplicate_key(item: &TestStep) -> Option<String> {
+    if !item.is_step() || item.is_blank() {
+        return None;
+    }
+    let normalize = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(format!(
+        "{}\n{}",
+        normalize(&item.instructions),
+        normalize(&item.expected_results)
+    ))
+}
+
+// indices of steps that are identical apart from whitespace, grouped in the order the
+// first copy of each appears
+pub fn duplicate_groups(items: &[TestStep]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    for (idx, item) in items.iter().enumerate() {
+        let Some(key) = duplicate_key(item) else {
+            continue;
+        };
+        match by_key.get(&key) {
+            Some(group) => groups[*group].push(idx),
+            None => {
+                by_key.insert(key, groups.len());
+                groups.push(vec![idx]);
+            }
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+// sections and comments alone make a table with nothing to test
+pub fn has_steps(items: &[TestStep]) -> bool {
+    items.iter().any(|item| item.is_step() && !item.is_blank())
+}
+
+// a section followed by the next section or the end, comments don't count as steps
+fn check_sections(items: &[TestStep], issues: &mut Vec<Issue>) {
+    for (idx, item) in items.iter().enumerate() {
+        if !item.is_new_section {
+            continue;
+        }
+        let empty = items[idx + 1..]
+            .iter()
+            .take_while(|next| !next.is_new_section)
+            .all(|next| !next.is_step());
+        if empty {
+            issues.push(Issue {
+                step: None,
+                message: format!("section \"{}\" has no steps", item.instructions.trim()),
+            });
+        }
+    }
+}
+
+pub fn validate(items: &[TestStep], config: &Config) -> Vec<Issue> {
+    let labels = TestStep::step_labels(items, config.numbering_start);
+    let mut issues = Vec::new();
+    for (item, label) in items.iter().zip(labels.iter()) {
+        if item.is_blank() {
+            issues.push(Issue {
+                step: Some(label.to_string()),
+                message: "step is empty".to_string(),
+            });
+        }
+        check_links(label, item, &mut issues);
+        check_images(label, item, config, &mut issues);
+        check_length(label, item, config, &mut issues);
+    }
+    check_sections(items, &mut issues);
+    for group in duplicate_groups(items) {
+        let copies: Vec<&str> = group[1..].iter().map(|idx| labels[*idx].as_str()).collect();
+        issues.push(Issue {
+            step: Some(labels[group[0]].to_string()),
+            message: format!(
+                "duplicated by step{} {} (:dedup keeps the first)",
+                if copies.len() == 1 { "" } else { "s" },
+                copies.join(", ")
+            ),
+        });
+    }
+    issues
+}
+
+// runs every step through the configured spell checker, a broken command is a single warning
+pub fn spellcheck(items: &[TestStep], config: &Config) -> Vec<Issue> {
+    let Some(cmd) = &config.spellcheck_cmd else {
+        return Vec::new();
+    };
+    let labels = TestStep::step_labels(items, config.numbering_start);
+    let mut issues = Vec::new();
+    for (item, label) in items.iter().zip(labels.iter()) {
+        let text = [
+            &item.instructions,
+            &item.test_data,
+            &item.expected_results,
+            &item.ac,
+        ]
+        .map(|f| f.as_str())
+        .join("\n");
+        let words = match spellcheck::flagged_words(cmd, &text) {
+            Ok(words) => words,
+            Err(msg) => {
+                return vec![Issue {
+                    step: None,
+                    message: format!("spell check skipped: {}", msg),
+                }];
+            }
+        };
+        let mut words: Vec<String> = words
+            .into_iter()
+            .filter(|w| {
+                !config
+                    .spellcheck_ignore
+                    .iter()
+                    .any(|i| i.eq_ignore_ascii_case(w))
+            })
+            .collect();
+        words.sort();
+        words.dedup();
+        if !words.is_empty() {
+            issues.push(Issue {
+                step: Some(label.to_string()),
+                message: format!("possible misspellings: {}", words.join(", ")),
+            });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates_match_after_whitespace_normalization() {
+        let step = |instructions: &str, expected: &str| TestStep {
+            expected_results: expected.to_string(),
+            ..TestStep::new().with_instructions(instructions)
+        };
+        let items = vec![
+            step("log in", "home page"),
+            step("open settings", "settings page"),
+            TestStep::section("log in"),
+            step("log  in\n", "home page "),
+            TestStep::new(),
+            step("log in", "home page"),
+            TestStep::new(),
+            step("open settings", "other page"),
+        ];
+        assert_eq!(duplicate_groups(&items), vec![vec![0, 3, 5]]);
+    }
+
+    #[test]
+    fn sections_without_steps_are_flagged() {
+        let items = vec![
+            TestStep::section("Login"),
+            TestStep::comment("needs a test account"),
+            TestStep::section("Checkout"),
+            TestStep::new().with_instructions("pay"),
+            TestStep::section("Reports"),
+        ];
+        let issues: Vec<String> = validate(&items, &Config::default())
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "section \"Login\" has no steps",
+                "section \"Reports\" has no steps"
+            ]
+        );
+        assert!(has_steps(&items));
+        assert!(!has_steps(&items[..3]));
+        assert!(!has_steps(&[TestStep::new()]));
+    }
+
+    #[test]
+    fn long_fields_are_flagged() {
+        let config = Config {
+            max_step_words: 5,
+            ..Config::default()
+        };
+        let items = vec![
+            TestStep::new().with_instructions("open the **settings** page"),
+            TestStep::new().with_instructions("open the settings page and then log out"),
+            TestStep::section("a section title that is long enough to flag"),
+            TestStep::new().with_instructions("log out"),
+        ];
+        let issues: Vec<String> = validate(&items, &config)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "step 1.2: instructions has 8 words, over the limit of 5 (:split can break it up)"
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use pulldown_cmark::{Event, Parser};
+
+// a comfortable pace for reading instructions while following along
+const WORDS_PER_MINUTE: usize = 200;
+
+fn count_tokens(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|token| token.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+// words as a reader sees them: markdown syntax (emphasis markers, bullets, link targets)
+// is parsed away first and tokens without a letter or digit, like a lone dash, don't
+// count. code is read too so it counts, a url is one word
+pub fn word_count(markdown: &str) -> usize {
+    Parser::new(markdown)
+        .map(|event| match event {
+            Event::Text(text) | Event::Code(text) => count_tokens(&text),
+            _ => 0,
+        })
+        .sum()
+}
+
+pub fn reading_time(words: usize) -> String {
+    match words.div_ceil(WORDS_PER_MINUTE) {
+        0 => "0 min".to_string(),
+        1 if words < WORDS_PER_MINUTE => "<1 min".to_string(),
+        minutes => format!("{} min", minutes),
+    }
+}
+
+// "12 words, <1 min"
+pub fn describe(words: usize) -> String {
+    format!(
+        "{} word{}, {}",
+        words,
+        if words == 1 { "" } else { "s" },
+        reading_time(words)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_syntax_is_not_counted() {
+        assert_eq!(word_count("**Log in** as an _admin_"), 5);
+        assert_eq!(word_count("- one\n- two\n\n1. three"), 3);
+        assert_eq!(word_count("# Heading\n\n> quoted text"), 3);
+        assert_eq!(word_count("a - b — c | d"), 4);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn links_code_and_urls() {
+        assert_eq!(word_count("open [the dashboard](https://x.io/a)"), 3);
+        assert_eq!(word_count("visit https://example.com/login now"), 3);
+        assert_eq!(word_count("run `cargo test --all`"), 4);
+        assert_eq!(word_count("```\nlet x = 1;\nprint(x)\n```"), 4);
+    }
+
+    #[test]
+    fn reading_time_rounds_up() {
+        assert_eq!(reading_time(0), "0 min");
+        assert_eq!(reading_time(40), "<1 min");
+        assert_eq!(reading_time(200), "1 min");
+        assert_eq!(reading_time(201), "2 min");
+    }
+}